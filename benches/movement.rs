@@ -0,0 +1,85 @@
+//! Benchmarks for the per-controller cost of the character controller systems.
+//!
+//! Run with `cargo bench --features test_utils`.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+use bevy_movement::{prelude::*, test_utils::*};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const CONTROLLER_COUNTS: [usize; 3] = [10, 100, 1000];
+
+/// Creates an app with `count` grounded controllers on a grid.
+fn crowd_app(count: usize, ground_probes: bool) -> (App, Vec<Entity>) {
+    let mut app = headless_movement_app();
+    app.spawn_test_ground();
+
+    let columns = (count as Scalar).sqrt().ceil() as usize;
+    let controllers = (0..count)
+        .map(|i| {
+            let x = (i % columns) as Scalar * 2.0;
+            let z = (i / columns) as Scalar * 2.0;
+            let mut entity = app.world_mut().spawn((
+                Transform::from_translation(Vector::new(x - 50.0, 1.0, z - 50.0).f32()),
+                NpcControllerBundle::new(Collider::capsule(0.4, 1.0), Vector::NEG_Y * 9.81 * 2.0),
+            ));
+            if ground_probes {
+                entity.insert(GroundProbes::default());
+            }
+            entity.id()
+        })
+        .collect();
+
+    // Let the controllers settle on the ground.
+    app.tick(30);
+    (app, controllers)
+}
+
+/// Idle controllers, dominated by grounded detection.
+fn grounded(c: &mut Criterion) {
+    let mut group = c.benchmark_group("grounded");
+    for count in CONTROLLER_COUNTS {
+        for (name, ground_probes) in [("shape_cast", false), ("ground_probes", true)] {
+            let (mut app, _) = crowd_app(count, ground_probes);
+            group.bench_with_input(BenchmarkId::new(name, count), &count, |b, _| {
+                b.iter(|| app.update())
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Controllers walking and jumping with directed actions.
+fn movement(c: &mut Criterion) {
+    let mut group = c.benchmark_group("movement");
+    for count in CONTROLLER_COUNTS {
+        let (mut app, controllers) = crowd_app(count, false);
+        let mut tick = 0_u32;
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| {
+                tick += 1;
+                let direction = Vector2::from_angle(tick as Scalar * 0.05);
+                let mut messages = app
+                    .world_mut()
+                    .resource_mut::<Messages<DirectedMovementAction>>();
+                for &entity in &controllers {
+                    messages.write(DirectedMovementAction {
+                        entity,
+                        action: MovementAction::Move(direction),
+                    });
+                    if tick % 60 == 0 {
+                        messages.write(DirectedMovementAction {
+                            entity,
+                            action: MovementAction::Jump,
+                        });
+                    }
+                }
+                app.update();
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, grounded, movement);
+criterion_main!(benches);
@@ -31,11 +31,44 @@ fn main() {
             DefaultPlugins,
             PhysicsPlugins::default(),
             CharacterControllerPlugin,
+            PlaygroundPlugin,
         ))
         .add_systems(Startup, setup)
+        .add_systems(Update, (toggle_noclip_on_key, possess_on_key))
         .run();
 }
 
+/// Toggles noclip mode for the player when N is pressed.
+fn toggle_noclip_on_key(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    players: Query<Entity, With<FpsController>>,
+    mut toggle_writer: MessageWriter<ToggleNoclip>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyN) {
+        for entity in &players {
+            toggle_writer.write(ToggleNoclip { entity });
+        }
+    }
+}
+
+/// Switches the player between character controllers when P is pressed.
+fn possess_on_key(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    characters: Query<(Entity, Has<FpsController>), With<CharacterController>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyP) {
+        return;
+    }
+
+    let entities: Vec<_> = characters.iter().collect();
+    let Some(current) = entities.iter().position(|&(_, possessed)| possessed) else {
+        return;
+    };
+    let (next, _) = entities[(current + 1) % entities.len()];
+    commands.queue(Possess(next));
+}
+
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -48,7 +81,19 @@ fn setup(
             MeshMaterial3d(materials.add(Color::srgb(0.8, 0.7, 0.6))),
             Transform::from_xyz(0.0, 1.5, 0.0),
             CharacterControllerBundle::new(Collider::capsule(0.4, 1.0), Vector::NEG_Y * 9.81 * 2.0)
-                .with_movement(30.0, 0.92, 7.0, (30.0 as Scalar).to_radians()),
+                .with_movement_config(MovementConfig {
+                    damping: 0.92,
+                    max_slope_angle: (30.0 as Scalar).to_radians(),
+                    ..default()
+                }),
+            StanceConfig::capsule(0.4, 1.0),
+            CeilingDetection::default(),
+            GroundProbes::default(),
+            JumpArcGizmo {
+                assumed_input: Vector2::Y,
+                ..default()
+            },
+            Carrier::default(),
             FpsController::default(),
         ))
         .id();
@@ -79,6 +124,13 @@ fn setup(
         Transform::from_xyz(0.0, -0.05, 0.0),
     ));
 
+    // A tuning playground with stairs, slopes, gaps, and moving platforms beyond the front wall,
+    // reachable with noclip
+    commands.queue(SpawnPlayground(PlaygroundConfig {
+        origin: Vector::new(0.0, 0.0, -50.0),
+        ..default()
+    }));
+
     // Some platforms to jump on
     for i in 0..5 {
         let x = (i as f32 - 2.0) * 4.0;
@@ -93,6 +145,31 @@ fn setup(
         ));
     }
 
+    // A one-way platform to jump up through and drop down from
+    commands.spawn((
+        RigidBody::Static,
+        Collider::cuboid(3.0, 0.1, 3.0),
+        OneWayPlatform::default(),
+        Mesh3d(meshes.add(Cuboid::new(3.0, 0.1, 3.0))),
+        MeshMaterial3d(materials.add(Color::srgb(0.3, 0.6, 0.8))),
+        Transform::from_xyz(5.0, 2.0, -5.0),
+    ));
+
+    // An NPC walking a short path with the same movement physics as the player
+    let mut npc_path = PathFollower::default();
+    npc_path.set_path([
+        Vector::new(-4.0, 0.0, -4.0),
+        Vector::new(4.0, 0.0, -4.0),
+        Vector::new(4.0, 0.0, 4.0),
+    ]);
+    commands.spawn((
+        Mesh3d(meshes.add(Capsule3d::new(0.4, 1.0))),
+        MeshMaterial3d(materials.add(Color::srgb(0.7, 0.3, 0.3))),
+        Transform::from_xyz(-4.0, 1.5, 4.0),
+        NpcControllerBundle::new(Collider::capsule(0.4, 1.0), Vector::NEG_Y * 9.81 * 2.0),
+        npc_path,
+    ));
+
     // A ramp to test slope climbing
     commands.spawn((
         RigidBody::Static,
@@ -137,11 +214,24 @@ fn setup(
     println!("  WASD / Arrow Keys - Move");
     println!("  Mouse - Look around (after grabbing cursor)");
     println!("  Space - Jump");
+    println!("  Left Ctrl / C - Crouch (hold)");
+    println!("  Z - Go prone (hold)");
+    println!("  Crouch + Space - Drop through a one-way platform");
+    println!("  E - Pick up / drop objects");
+    println!("  Left Click - Throw carried object");
+    println!("  Left Shift - Sprint (hold)");
+    println!("  N - Toggle noclip");
+    println!("  P - Possess the next character");
     println!("  Right Click - Grab cursor and enable FPS controls");
     println!("  Escape - Release cursor and disable FPS controls");
     println!("  Gamepad Left Stick - Move");
     println!("  Gamepad Right Stick - Look around");
     println!("  Gamepad South Button (A/X) - Jump");
+    println!("  Gamepad East Button (B/Circle) - Crouch (hold)");
+    println!("  Gamepad D-Pad Down - Go prone (hold)");
+    println!("  Gamepad West Button (X/Square) - Pick up / drop objects");
+    println!("  Gamepad Right Trigger - Throw carried object");
+    println!("  Gamepad Left Stick Press - Sprint (hold)");
     println!();
     println!("This uses a kinematic character controller with first-person camera.");
     println!("Right-click to grab the cursor and start playing!");
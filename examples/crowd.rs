@@ -0,0 +1,129 @@
+//! A stress test with a crowd of AI-driven character controllers.
+//!
+//! Spawns 1,000 NPC controllers that wander between random points using their
+//! `PathFollower`s, and logs the frame time to quantify the per-controller cost.
+//!
+//! Pass a different count as the first argument, for example `cargo run --release --example crowd -- 2000`.
+
+use avian3d::{math::*, prelude::*};
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    prelude::*,
+};
+use bevy_movement::prelude::*;
+
+/// The default number of controllers to spawn.
+const DEFAULT_COUNT: usize = 1000;
+/// The half size of the area the crowd wanders in.
+const ARENA_HALF_SIZE: Scalar = 50.0;
+
+fn main() {
+    let count = std::env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_COUNT);
+
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            PhysicsPlugins::default(),
+            CharacterControllerPlugin,
+            FrameTimeDiagnosticsPlugin::default(),
+            LogDiagnosticsPlugin::default(),
+        ))
+        .insert_resource(CrowdSize(count))
+        .insert_resource(Wander(0x2545_f491_4f6c_dd1d))
+        .add_systems(Startup, setup)
+        .add_systems(Update, pick_new_destinations)
+        .run();
+}
+
+#[derive(Resource)]
+struct CrowdSize(usize);
+
+/// The state of a xorshift random number generator for picking destinations.
+#[derive(Resource)]
+struct Wander(u64);
+
+impl Wander {
+    /// Returns a random point in the arena.
+    fn next_point(&mut self) -> Vector {
+        let mut next = || {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 >> 40) as Scalar / (1_u64 << 24) as Scalar * 2.0 - 1.0
+        };
+        Vector::new(next(), 0.0, next()) * ARENA_HALF_SIZE * 0.9
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut wander: ResMut<Wander>,
+    crowd_size: Res<CrowdSize>,
+) {
+    let ground_size = Vector::new(ARENA_HALF_SIZE * 2.0, 1.0, ARENA_HALF_SIZE * 2.0);
+    commands.spawn((
+        RigidBody::Static,
+        Collider::cuboid(ground_size.x, ground_size.y, ground_size.z),
+        Mesh3d(meshes.add(Cuboid::from_size(ground_size.f32()))),
+        MeshMaterial3d(materials.add(Color::srgb(0.3, 0.5, 0.3))),
+        Transform::from_xyz(0.0, -0.5, 0.0),
+    ));
+
+    // All controllers share a mesh and material to keep rendering cheap.
+    let mesh = meshes.add(Capsule3d::new(0.4, 1.0));
+    let material = materials.add(Color::srgb(0.7, 0.3, 0.3));
+
+    for _ in 0..crowd_size.0 {
+        let mut follower = PathFollower::default();
+        follower.give_up_time = Some(3.0);
+        let destination = wander.next_point();
+        follower.set_destination(destination);
+        follower.set_path([destination]);
+
+        commands.spawn((
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(wander.next_point().with_y(1.0).f32()),
+            NpcControllerBundle::new(Collider::capsule(0.4, 1.0), Vector::NEG_Y * 9.81 * 2.0),
+            follower,
+        ));
+    }
+
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 60.0, 70.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+    commands.spawn((
+        DirectionalLight {
+            shadows_enabled: false,
+            ..default()
+        },
+        Transform::from_xyz(10.0, 20.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
+
+/// Sends controllers that arrived or got stuck to a new random point.
+fn pick_new_destinations(
+    mut wander: ResMut<Wander>,
+    mut completed: MessageReader<PathCompleted>,
+    mut failed: MessageReader<PathFailed>,
+    mut followers: Query<&mut PathFollower>,
+) {
+    let finished = completed
+        .read()
+        .map(|message| message.entity)
+        .chain(failed.read().map(|message| message.entity));
+
+    for entity in finished {
+        if let Ok(mut follower) = followers.get_mut(entity) {
+            let destination = wander.next_point();
+            follower.set_destination(destination);
+            follower.set_path([destination]);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Accessibility presets for character controllers.
+//!
+//! An [`AccessibilityProfile`] bundles the settings that players commonly need to change
+//! together, like toggling sprint and crouch instead of holding them, jumping over
+//! obstacles automatically, reducing camera motion, and a longer grace period for
+//! jumping off ledges. Inserting the profile on a character controller applies all of
+//! them, and with the `serde` feature it can be saved with the rest of the user settings.
+
+use avian3d::math::*;
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::{AutoJump, JumpTiming};
+
+/// Accessibility settings for a character controller, applied by inserting the profile.
+///
+/// Sprint and crouch toggles apply to keyboard and gamepad input of the controller with
+/// input enabled. [`AutoJump`] is inserted or enabled when `auto_jump` is set.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+#[require(JumpTiming)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct AccessibilityProfile {
+    /// Whether pressing sprint toggles it on and off, instead of sprinting while held.
+    pub toggle_sprint: bool,
+    /// Whether the character crouches while crouch is held. If `false`, pressing crouch
+    /// toggles it on and off.
+    pub hold_to_crouch: bool,
+    /// Whether the character jumps over low obstacles automatically.
+    pub auto_jump: bool,
+    /// The scale of camera motion, like the lean of an [`UprightSpring`](crate::UprightSpring),
+    /// from 0 for none to 1 for full.
+    pub camera_motion: Scalar,
+    /// The minimum time the character can still jump after walking off a ledge, in seconds,
+    /// extending its [`CoyoteTime`](crate::CoyoteTime).
+    pub coyote_time: Scalar,
+}
+
+impl Default for AccessibilityProfile {
+    fn default() -> Self {
+        Self {
+            toggle_sprint: false,
+            hold_to_crouch: true,
+            auto_jump: false,
+            camera_motion: 1.0,
+            coyote_time: 0.0,
+        }
+    }
+}
+
+impl AccessibilityProfile {
+    /// A preset that removes camera motion, for players prone to motion sickness.
+    pub fn reduced_motion() -> Self {
+        Self {
+            camera_motion: 0.0,
+            ..default()
+        }
+    }
+
+    /// A preset for players who have difficulty holding buttons or timing jumps.
+    pub fn motor_assist() -> Self {
+        Self {
+            toggle_sprint: true,
+            hold_to_crouch: false,
+            auto_jump: true,
+            coyote_time: 0.25,
+            ..default()
+        }
+    }
+}
+
+/// The toggle states of sprint and crouch for [`AccessibilityProfile`]s, kept separately for
+/// the keyboard and each gamepad so that one device can't flip the toggles of another.
+#[derive(Resource, Default)]
+pub(crate) struct InputToggles {
+    keyboard: ToggleState,
+    gamepads: HashMap<Entity, ToggleState>,
+}
+
+impl InputToggles {
+    /// Returns the toggle state of the keyboard.
+    pub(crate) fn keyboard(&mut self) -> &mut ToggleState {
+        &mut self.keyboard
+    }
+
+    /// Returns the toggle state of a gamepad.
+    pub(crate) fn gamepad(&mut self, gamepad: Entity) -> &mut ToggleState {
+        self.gamepads.entry(gamepad).or_default()
+    }
+
+    /// Forgets the toggle states of gamepads that are no longer connected.
+    pub(crate) fn retain_gamepads(&mut self, connected: impl Fn(Entity) -> bool) {
+        self.gamepads.retain(|&gamepad, _| connected(gamepad));
+    }
+}
+
+/// The toggle states of sprint and crouch for a single input device.
+#[derive(Default)]
+pub(crate) struct ToggleState {
+    sprint: bool,
+    crouch: bool,
+}
+
+impl ToggleState {
+    /// Returns whether sprint is active for the given input.
+    pub(crate) fn sprint(
+        &mut self,
+        profile: Option<&AccessibilityProfile>,
+        held: bool,
+        pressed: bool,
+    ) -> bool {
+        let toggle = profile.is_some_and(|profile| profile.toggle_sprint);
+        resolve_toggle(&mut self.sprint, toggle, held, pressed)
+    }
+
+    /// Returns whether crouch is active for the given input.
+    pub(crate) fn crouch(
+        &mut self,
+        profile: Option<&AccessibilityProfile>,
+        held: bool,
+        pressed: bool,
+    ) -> bool {
+        let toggle = profile.is_some_and(|profile| !profile.hold_to_crouch);
+        resolve_toggle(&mut self.crouch, toggle, held, pressed)
+    }
+}
+
+/// Returns whether a held action is active, flipping it on presses if it is toggled.
+fn resolve_toggle(toggled: &mut bool, toggle: bool, held: bool, pressed: bool) -> bool {
+    if !toggle {
+        *toggled = false;
+        return held;
+    }
+    if pressed {
+        *toggled = !*toggled;
+    }
+    *toggled
+}
+
+/// Applies the auto-jump setting of an inserted [`AccessibilityProfile`].
+pub(crate) fn apply_accessibility_profile(
+    insert: On<Insert, AccessibilityProfile>,
+    mut commands: Commands,
+    mut profiles: Query<(&AccessibilityProfile, Option<&mut AutoJump>)>,
+) {
+    let Ok((profile, auto_jump)) = profiles.get_mut(insert.entity) else {
+        return;
+    };
+    match auto_jump {
+        Some(mut auto_jump) => auto_jump.enabled = profile.auto_jump,
+        None if profile.auto_jump => {
+            commands.entity(insert.entity).insert(AutoJump::default());
+        }
+        None => {}
+    }
+}
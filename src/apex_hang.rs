@@ -0,0 +1,102 @@
+//! Hang time at the apex of jumps.
+//!
+//! With [`ApexHang`], gravity is reduced for a short window while the vertical speed of an
+//! airborne character is close to zero, so it floats briefly at the top of its arc, which
+//! makes jumps easier to aim. [`AtApex`] is triggered when the vertical velocity crosses
+//! zero, for effects and animations.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    CharacterController, ControllerEnabled, ControllerGravity, ControllerUp, Grounded, WorldUp,
+};
+
+/// Reduces the gravity of an airborne character controller around the apex of its arc.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(ApexHangState)]
+pub struct ApexHang {
+    /// The vertical speed below which the character counts as near the apex, in m/s.
+    pub apex_speed: Scalar,
+    /// The fraction of gravity applied near the apex.
+    pub gravity_multiplier: Scalar,
+    /// The maximum duration of the reduced gravity per airtime, in seconds.
+    pub max_duration: Scalar,
+}
+
+impl Default for ApexHang {
+    fn default() -> Self {
+        Self {
+            apex_speed: 1.5,
+            gravity_multiplier: 0.4,
+            max_duration: 0.2,
+        }
+    }
+}
+
+/// The apex state tracked for [`ApexHang`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct ApexHangState {
+    /// Whether the character was moving up in the last frame.
+    was_rising: bool,
+    /// How long gravity has been reduced during the current airtime, in seconds.
+    hang_time: Scalar,
+}
+
+impl ApexHangState {
+    /// Returns how long gravity has been reduced during the current airtime, in seconds.
+    pub fn hang_time(&self) -> Scalar {
+        self.hang_time
+    }
+}
+
+/// Triggered on an [`ApexHang`] character controller when it reaches the apex of its arc.
+#[derive(EntityEvent, Clone, Copy, Debug)]
+pub struct AtApex {
+    pub entity: Entity,
+}
+
+/// Reduces gravity near the apex and triggers [`AtApex`].
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_apex_hang(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    mut controllers: Query<
+        (
+            Entity,
+            &ApexHang,
+            &mut ApexHangState,
+            &ControllerGravity,
+            &mut LinearVelocity,
+            Has<Grounded>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (entity, apex_hang, mut state, gravity, mut linear_velocity, is_grounded, controller_up) in
+        &mut controllers
+    {
+        let vertical_speed = linear_velocity.dot(world_up.for_controller(controller_up));
+
+        if is_grounded && vertical_speed <= 0.0 {
+            state.was_rising = false;
+            state.hang_time = 0.0;
+            continue;
+        }
+
+        if state.was_rising && vertical_speed <= 0.0 {
+            commands.trigger(AtApex { entity });
+        }
+        state.was_rising = vertical_speed > 0.0;
+
+        if vertical_speed.abs() < apex_hang.apex_speed && state.hang_time < apex_hang.max_duration {
+            state.hang_time += delta_time;
+            // Gravity was already applied in full, so take back the part that doesn't apply.
+            linear_velocity.0 -= gravity.0 * (1.0 - apex_hang.gravity_multiplier) * delta_time;
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Automatic jumping over obstacles, as an accessibility option.
+//!
+//! With [`AutoJump`] enabled, a grounded character that moves into an obstacle too tall
+//! to walk onto but low enough to jump over jumps automatically, like in Minecraft.
+//! The jump is written as a [`DirectedMovementAction`], so it behaves like a regular jump.
+
+use avian3d::{math::*, prelude::*};
+use bevy::{
+    ecs::message::{MessageCursor, Messages},
+    prelude::*,
+};
+
+use crate::{
+    input_rotation, CharacterController, ControllerEnabled, ControllerUp, DirectedMovementAction,
+    FpsController, Grounded, MaxSlopeAngle, MovementAction, ViewYaw, WorldUp,
+};
+
+/// Makes a character controller jump over obstacles it moves into.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct AutoJump {
+    /// Whether auto-jump is enabled, for example from an accessibility setting.
+    pub enabled: bool,
+    /// How far ahead of the edge of the collider obstacles are detected.
+    pub check_distance: Scalar,
+    /// The minimum obstacle height that triggers a jump. Lower steps are walked onto.
+    pub min_height: Scalar,
+    /// The maximum obstacle height that triggers a jump, usually what a jump can clear.
+    pub max_height: Scalar,
+}
+
+impl Default for AutoJump {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            check_distance: 0.3,
+            min_height: 0.3,
+            max_height: 1.2,
+        }
+    }
+}
+
+/// Jumps with [`AutoJump`] characters that move into a jumpable obstacle.
+#[allow(clippy::type_complexity)]
+pub(crate) fn auto_jump(
+    world_up: Res<WorldUp>,
+    spatial_query: SpatialQuery,
+    mut movement_reader: MessageReader<MovementAction>,
+    mut directed_cursor: Local<MessageCursor<DirectedMovementAction>>,
+    mut directed_messages: ResMut<Messages<DirectedMovementAction>>,
+    controllers: Query<
+        (
+            Entity,
+            &AutoJump,
+            &Position,
+            &Rotation,
+            &ColliderAabb,
+            Option<&MaxSlopeAngle>,
+            Option<&FpsController>,
+            Option<&ViewYaw>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, With<Grounded>, ControllerEnabled),
+    >,
+) {
+    let global_input: Vector2 = movement_reader
+        .read()
+        .filter_map(|action| match action {
+            MovementAction::Move(direction) => Some(*direction),
+            _ => None,
+        })
+        .sum();
+    let directed_input: Vec<(Entity, Vector2)> = directed_cursor
+        .read(&directed_messages)
+        .filter_map(|directed| match directed.action {
+            MovementAction::Move(direction) => Some((directed.entity, direction)),
+            _ => None,
+        })
+        .collect();
+
+    for (
+        entity,
+        auto_jump,
+        position,
+        rotation,
+        aabb,
+        max_slope_angle,
+        fps_controller,
+        view_yaw,
+        controller_up,
+    ) in &controllers
+    {
+        if !auto_jump.enabled {
+            continue;
+        }
+        let up = world_up.for_controller(controller_up);
+
+        let mut input: Vector2 = directed_input
+            .iter()
+            .filter(|(target, _)| *target == entity)
+            .map(|(_, direction)| *direction)
+            .sum();
+        if fps_controller.is_some_and(|controller| controller.enable_input) {
+            input += global_input;
+        }
+        let Ok(direction) = Dir3::new(
+            (input_rotation(rotation, view_yaw) * Vector::new(input.x, 0.0, -input.y))
+                .reject_from_normalized(up)
+                .f32(),
+        ) else {
+            continue;
+        };
+
+        let half_extents = (aabb.max - aabb.min) * 0.5;
+        let half_height = half_extents.dot(up.abs());
+        let radius = half_extents.reject_from_normalized(up.abs()).max_element();
+        let distance = radius + auto_jump.check_distance;
+        let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+        let cast_at_height = |height: Scalar| {
+            let origin = position.0 + up * (height - half_height);
+            spatial_query.cast_ray(origin, direction, distance, true, &filter)
+        };
+
+        // Something too steep to walk onto blocks the way at step height...
+        let max_slope = max_slope_angle.map_or(PI * 0.25, |angle| angle.0);
+        let blocked = cast_at_height(auto_jump.min_height)
+            .is_some_and(|hit| hit.normal.angle_between(up) > max_slope);
+        // ...but there is room above it at jump height.
+        let clear = cast_at_height(auto_jump.max_height).is_none();
+
+        if blocked && clear {
+            directed_messages.write(DirectedMovementAction {
+                entity,
+                action: MovementAction::Jump,
+            });
+        }
+    }
+}
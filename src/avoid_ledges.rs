@@ -0,0 +1,59 @@
+//! Ledge protection for character controllers.
+//!
+//! Navmesh paths and steering behaviors don't always account for drops, so AI-driven
+//! controllers can walk off ledges they can't climb back up. With [`AvoidLedges`], the
+//! movement system casts a ray ahead of the character and down, and refuses to move it
+//! towards a drop taller than [`AvoidLedges::max_drop`].
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+/// Stops a grounded character controller from walking off drops taller than a threshold.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct AvoidLedges {
+    /// How far ahead of the edge of the collider the ground is checked.
+    pub look_ahead: Scalar,
+    /// The tallest drop the character is allowed to walk off.
+    pub max_drop: Scalar,
+}
+
+impl Default for AvoidLedges {
+    fn default() -> Self {
+        Self {
+            look_ahead: 0.3,
+            max_drop: 1.0,
+        }
+    }
+}
+
+impl AvoidLedges {
+    /// Returns `true` if moving in the given direction would walk off a drop taller
+    /// than [`AvoidLedges::max_drop`], measured along the `up` direction of the character.
+    pub fn ledge_ahead(
+        &self,
+        spatial_query: &SpatialQuery,
+        entity: Entity,
+        position: &Position,
+        aabb: &ColliderAabb,
+        direction: Vector,
+        up: Vector,
+    ) -> bool {
+        let Some(direction) = direction.try_normalize() else {
+            return false;
+        };
+        let Ok(down) = Dir3::new(-up.f32()) else {
+            return false;
+        };
+
+        // Start slightly above the feet, just past the edge of the collider.
+        let half_extents = (aabb.max - aabb.min) * 0.5;
+        let half_height = half_extents.dot(up.abs());
+        let radius = half_extents.reject_from_normalized(up.abs()).max_element();
+        let origin = position.0 - up * (half_height - 0.1) + direction * (radius + self.look_ahead);
+        let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+
+        spatial_query
+            .cast_ray(origin, down, self.max_drop + 0.1, true, &filter)
+            .is_none()
+    }
+}
@@ -0,0 +1,174 @@
+//! Assisted balance on narrow surfaces, like beams and ledges.
+//!
+//! Walking along a beam narrower than the character takes precise input, since any
+//! sideways drift makes it fall off. With [`BalanceAssist`], rays are cast down across the
+//! character to measure the width of the support under it. On narrow supports, the
+//! character is gently pulled toward the center of the support and slowed down, and it is
+//! marked as [`OnNarrowSurface`].
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    CharacterController, ControllerEnabled, ControllerUp, Grounded, SpeedModifier, SpeedModifiers,
+    WorldUp,
+};
+
+/// The tag of the [`SpeedModifier`] that slows characters down on narrow surfaces.
+pub const BALANCE_ASSIST_MODIFIER: &str = "balance_assist";
+
+/// Helps a character controller keep its balance on narrow surfaces.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct BalanceAssist {
+    /// The support width below which the assist applies.
+    pub max_width: Scalar,
+    /// The width across the character that is probed for support.
+    pub probe_width: Scalar,
+    /// How far below the character a ray can hit and still count as support.
+    pub max_drop: Scalar,
+    /// The number of rays cast across the probe width.
+    pub samples: usize,
+    /// How strongly the character is pulled toward the center of the support, in 1/s.
+    pub centering_strength: Scalar,
+    /// The speed multiplier on narrow surfaces.
+    pub speed_multiplier: Scalar,
+}
+
+impl Default for BalanceAssist {
+    fn default() -> Self {
+        Self {
+            max_width: 0.5,
+            probe_width: 1.2,
+            max_drop: 0.3,
+            samples: 9,
+            centering_strength: 4.0,
+            speed_multiplier: 0.6,
+        }
+    }
+}
+
+/// A component on a [`BalanceAssist`] character controller that stands on a narrow surface.
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct OnNarrowSurface {
+    /// The measured width of the support.
+    pub width: Scalar,
+    /// The offset from the character to the center of the support.
+    pub center_offset: Vector,
+}
+
+/// Measures the support under [`BalanceAssist`] characters and centers them on narrow ones.
+#[allow(clippy::type_complexity)]
+pub(crate) fn assist_balance(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    spatial_query: SpatialQuery,
+    mut controllers: Query<
+        (
+            Entity,
+            &BalanceAssist,
+            &Position,
+            &Rotation,
+            &ColliderAabb,
+            &mut LinearVelocity,
+            Option<&mut SpeedModifiers>,
+            Has<Grounded>,
+            Has<OnNarrowSurface>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (
+        entity,
+        assist,
+        position,
+        rotation,
+        aabb,
+        mut linear_velocity,
+        speed_modifiers,
+        is_grounded,
+        was_narrow,
+        controller_up,
+    ) in &mut controllers
+    {
+        let up = world_up.for_controller(controller_up);
+        let Ok(down) = Dir3::new(-up.f32()) else {
+            continue;
+        };
+
+        // The support runs along the movement direction, so probe across it
+        let horizontal = linear_velocity.reject_from_normalized(up);
+        let along = horizontal.try_normalize().unwrap_or(
+            (rotation * Vector::NEG_Z)
+                .reject_from_normalized(up)
+                .normalize_or_zero(),
+        );
+        let across = along.cross(up).normalize_or_zero();
+
+        let support = (is_grounded && across != Vector::ZERO && assist.samples >= 2)
+            .then(|| {
+                // Rays start at the height of the center so that they can't begin inside the
+                // ground, like ground probes.
+                let half_extents = (aabb.max - aabb.min) * 0.5;
+                let max_distance = half_extents.dot(up.abs()) + assist.max_drop;
+                let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+                let step = assist.probe_width / (assist.samples - 1) as Scalar;
+
+                // Find the extent of the support from the rays that hit it
+                let hits = (0..assist.samples)
+                    .map(|i| -assist.probe_width * 0.5 + step * i as Scalar)
+                    .filter(|&offset| {
+                        spatial_query
+                            .cast_ray(
+                                position.0 + across * offset,
+                                down,
+                                max_distance,
+                                true,
+                                &filter,
+                            )
+                            .is_some()
+                    });
+                let (min, max) = hits.fold((Scalar::MAX, Scalar::MIN), |(min, max), offset| {
+                    (min.min(offset), max.max(offset))
+                });
+                (min <= max).then(|| (max - min + step, (min + max) * 0.5))
+            })
+            .flatten()
+            .filter(|(width, _)| *width <= assist.max_width);
+
+        let Some((width, center)) = support else {
+            if was_narrow {
+                commands.entity(entity).remove::<OnNarrowSurface>();
+                if let Some(mut modifiers) = speed_modifiers {
+                    modifiers.remove(BALANCE_ASSIST_MODIFIER);
+                }
+            }
+            continue;
+        };
+
+        // Gently pull the character toward the center, replacing drift away from it
+        let center_offset = across * center;
+        let lateral_speed = linear_velocity.dot(across);
+        let target_speed = center * assist.centering_strength;
+        let blend = (assist.centering_strength * delta_time).min(1.0);
+        linear_velocity.0 += across * (target_speed - lateral_speed) * blend;
+
+        commands.entity(entity).insert(OnNarrowSurface {
+            width,
+            center_offset,
+        });
+        let modifier = SpeedModifier::multiplier(assist.speed_multiplier);
+        match speed_modifiers {
+            Some(mut modifiers) => modifiers.insert(BALANCE_ASSIST_MODIFIER, modifier),
+            None => {
+                let mut modifiers = SpeedModifiers::default();
+                modifiers.insert(BALANCE_ASSIST_MODIFIER, modifier);
+                commands.entity(entity).insert(modifiers);
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Kill-Z and out-of-bounds handling.
+//!
+//! Character controllers that fall below [`WorldBounds::min_y`] or leave
+//! [`WorldBounds::bounding_box`] write an [`OutOfBounds`] message, and are optionally
+//! respawned at their [`RespawnPoint`] instead of falling forever with accumulating velocity.
+//! Respawned characters are placed at the nearest free position like [`SafeSpawn`](crate::SafeSpawn) ones.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{CharacterController, ControllerEnabled, PendingPlacement};
+
+/// The playable bounds of the world for character controllers.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct WorldBounds {
+    /// The height below which characters are out of bounds, if any.
+    pub min_y: Option<Scalar>,
+    /// The minimum and maximum corners of a box characters must stay inside, if any.
+    pub bounding_box: Option<(Vector, Vector)>,
+    /// Whether characters are respawned automatically when they go out of bounds.
+    pub auto_respawn: bool,
+    /// Where characters without a [`RespawnPoint`] are respawned.
+    pub respawn_position: Vector,
+}
+
+impl Default for WorldBounds {
+    fn default() -> Self {
+        Self {
+            min_y: Some(-100.0),
+            bounding_box: None,
+            auto_respawn: false,
+            respawn_position: Vector::new(0.0, 2.0, 0.0),
+        }
+    }
+}
+
+impl WorldBounds {
+    /// Returns `true` if the given position is inside the bounds.
+    pub fn contains(&self, position: Vector) -> bool {
+        let above_min_y = self.min_y.is_none_or(|min_y| position.y >= min_y);
+        let inside_box = self
+            .bounding_box
+            .is_none_or(|(min, max)| position.cmpge(min).all() && position.cmple(max).all());
+        above_min_y && inside_box
+    }
+}
+
+/// The position a character controller is respawned at when it goes out of bounds.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct RespawnPoint(pub Vector);
+
+/// A marker component for character controllers that are out of bounds
+/// and were not respawned automatically.
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct IsOutOfBounds;
+
+/// A [`Message`] written when a character controller leaves the [`WorldBounds`].
+#[derive(Message, Clone, Copy, Debug)]
+pub struct OutOfBounds {
+    pub entity: Entity,
+    /// The position at which the character left the bounds.
+    pub position: Vector,
+    /// Whether the character was respawned automatically.
+    pub respawned: bool,
+}
+
+/// Writes [`OutOfBounds`] messages for character controllers outside the [`WorldBounds`],
+/// and respawns them if [`WorldBounds::auto_respawn`] is enabled.
+#[allow(clippy::type_complexity)]
+pub(crate) fn handle_out_of_bounds(
+    mut commands: Commands,
+    bounds: Res<WorldBounds>,
+    mut out_of_bounds_writer: MessageWriter<OutOfBounds>,
+    mut query: Query<
+        (
+            Entity,
+            &mut Position,
+            &mut LinearVelocity,
+            Option<&RespawnPoint>,
+            Has<IsOutOfBounds>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    for (entity, mut position, mut linear_velocity, respawn_point, was_out_of_bounds) in &mut query
+    {
+        if bounds.contains(position.0) {
+            if was_out_of_bounds {
+                commands.entity(entity).remove::<IsOutOfBounds>();
+            }
+            continue;
+        }
+
+        // Characters that are left out of bounds only report it once.
+        if was_out_of_bounds {
+            continue;
+        }
+
+        out_of_bounds_writer.write(OutOfBounds {
+            entity,
+            position: position.0,
+            respawned: bounds.auto_respawn,
+        });
+
+        if bounds.auto_respawn {
+            position.0 = respawn_point.map_or(bounds.respawn_position, |point| point.0);
+            linear_velocity.0 = Vector::ZERO;
+            commands.entity(entity).insert(PendingPlacement);
+        } else {
+            commands.entity(entity).insert(IsOutOfBounds);
+        }
+    }
+}
@@ -0,0 +1,369 @@
+//! Picking up and carrying dynamic rigid bodies.
+//!
+//! A character with a [`Carrier`] component grabs the dynamic body it is looking at
+//! when [`MovementAction::Interact`] is pressed, and holds it in front of the camera
+//! using spring forces. The object is dropped when interact is pressed again,
+//! or when it gets obstructed and falls too far behind the hold point.
+//!
+//! Carried objects can also be thrown with [`MovementAction::Throw`], optionally charging
+//! the throw by holding the button. [`ObjectThrown`] and [`ObjectDropped`] messages are
+//! written so games can add sounds and achievements.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{CharacterController, ControllerEnabled, FpsController, MovementAction, Ragdoll};
+
+/// Enables picking up and carrying dynamic bodies for a character controller.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Carrier {
+    /// The maximum distance from the camera at which objects can be grabbed.
+    pub grab_range: Scalar,
+    /// The distance in front of the camera at which objects are held.
+    pub hold_distance: Scalar,
+    /// The stiffness of the spring pulling the object toward the hold point.
+    pub strength: Scalar,
+    /// The damping of the spring, relative to the velocity of the carrier.
+    pub damping: Scalar,
+    /// The heaviest object that can be picked up.
+    pub max_mass: Scalar,
+    /// The object is dropped if it gets further than this from the hold point,
+    /// for example when it is blocked by a wall.
+    pub break_distance: Scalar,
+    /// The impulse applied along the camera direction when throwing at full charge.
+    pub throw_impulse: Scalar,
+    /// How long the throw button must be held to reach full charge, in seconds.
+    /// If `None`, throws are instant and always use the full impulse.
+    pub throw_charge_time: Option<Scalar>,
+    /// The fraction of the impulse used for an uncharged throw.
+    pub min_throw_fraction: Scalar,
+}
+
+impl Default for Carrier {
+    fn default() -> Self {
+        Self {
+            grab_range: 3.0,
+            hold_distance: 1.5,
+            strength: 150.0,
+            damping: 20.0,
+            max_mass: 50.0,
+            break_distance: 1.5,
+            throw_impulse: 15.0,
+            throw_charge_time: None,
+            min_throw_fraction: 0.3,
+        }
+    }
+}
+
+/// A component on a [`Carrier`] that is currently carrying an object.
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct Carrying(pub Entity);
+
+/// The charge of a throw while the throw button is held, in seconds.
+#[derive(Component, Clone, Copy, Debug, Default)]
+#[component(storage = "SparseSet")]
+pub struct ThrowCharge(pub Scalar);
+
+/// A [`Message`] written when a carried object is thrown.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct ObjectThrown {
+    pub carrier: Entity,
+    pub object: Entity,
+    /// The impulse applied to the object.
+    pub impulse: Vector,
+    /// The charge of the throw, from 0 to 1.
+    pub charge: Scalar,
+}
+
+/// A [`Message`] written when a carried object is dropped without being thrown,
+/// either on purpose or because it got obstructed.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct ObjectDropped {
+    pub carrier: Entity,
+    pub object: Entity,
+}
+
+/// A component on an object that is currently being carried.
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct Carried {
+    /// The character carrying the object.
+    pub by: Entity,
+    /// The gravity scale of the object before it was picked up.
+    previous_gravity_scale: Option<GravityScale>,
+}
+
+/// Returns the origin and direction the character is aiming in.
+///
+/// Uses the first camera among the descendants of the character, or the
+/// character's own position and facing otherwise.
+pub(crate) fn aim_ray(
+    entity: Entity,
+    position: &Position,
+    rotation: &Rotation,
+    children: &Query<&Children>,
+    cameras: &Query<&GlobalTransform, With<Camera3d>>,
+) -> (Vector, Dir3) {
+    let camera = children
+        .iter_descendants(entity)
+        .find_map(|descendant| cameras.get(descendant).ok());
+
+    match camera {
+        Some(camera_transform) => (
+            camera_transform.translation().adjust_precision(),
+            camera_transform.forward(),
+        ),
+        None => (
+            position.0,
+            Dir3::new((rotation * Vector::NEG_Z).f32()).unwrap_or(Dir3::NEG_Z),
+        ),
+    }
+}
+
+/// Drops a carried object, restoring its gravity.
+pub(crate) fn release_carried(
+    commands: &mut Commands,
+    carrier: Entity,
+    object: Entity,
+    carried: Option<&Carried>,
+) {
+    commands.entity(carrier).remove::<Carrying>();
+
+    let Ok(mut object_commands) = commands.get_entity(object) else {
+        return;
+    };
+    object_commands.remove::<Carried>();
+    match carried.and_then(|carried| carried.previous_gravity_scale) {
+        Some(gravity_scale) => {
+            object_commands.insert(gravity_scale);
+        }
+        None => {
+            object_commands.remove::<GravityScale>();
+        }
+    }
+}
+
+/// Picks up and drops objects in response to [`MovementAction::Interact`].
+#[allow(clippy::type_complexity)]
+pub(crate) fn pick_up_objects(
+    mut commands: Commands,
+    mut movement_reader: MessageReader<MovementAction>,
+    mut dropped_writer: MessageWriter<ObjectDropped>,
+    spatial_query: SpatialQuery,
+    carriers: Query<
+        (
+            Entity,
+            &Carrier,
+            &Position,
+            &Rotation,
+            Option<&Carrying>,
+            Option<&FpsController>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+    objects: Query<(&RigidBody, &ComputedMass, Option<&GravityScale>), Without<Carried>>,
+    carried: Query<&Carried>,
+    collider_rbs: Query<&ColliderOf>,
+    children: Query<&Children>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+) {
+    let interact = movement_reader
+        .read()
+        .any(|event| matches!(event, MovementAction::Interact));
+    if !interact {
+        return;
+    }
+
+    for (entity, carrier, position, rotation, carrying, fps_controller) in &carriers {
+        if !fps_controller.is_some_and(|controller| controller.enable_input) {
+            continue;
+        }
+
+        // Pressing interact while carrying drops the object.
+        if let Some(&Carrying(object)) = carrying {
+            release_carried(&mut commands, entity, object, carried.get(object).ok());
+            dropped_writer.write(ObjectDropped {
+                carrier: entity,
+                object,
+            });
+            continue;
+        }
+
+        let (origin, direction) = aim_ray(entity, position, rotation, &children, &cameras);
+        let Some(hit) = spatial_query.cast_ray(
+            origin,
+            direction,
+            carrier.grab_range,
+            true,
+            &SpatialQueryFilter::from_excluded_entities([entity]),
+        ) else {
+            continue;
+        };
+
+        let object = collider_rbs
+            .get(hit.entity)
+            .map_or(hit.entity, |collider_of| collider_of.body);
+        let Ok((rb, mass, gravity_scale)) = objects.get(object) else {
+            continue;
+        };
+
+        // Only dynamic bodies that aren't too heavy can be picked up.
+        if !rb.is_dynamic() || mass.value() > carrier.max_mass {
+            continue;
+        }
+
+        commands.entity(entity).insert(Carrying(object));
+        commands.entity(object).insert((
+            Carried {
+                by: entity,
+                previous_gravity_scale: gravity_scale.copied(),
+            },
+            GravityScale(0.0),
+        ));
+    }
+}
+
+/// Pulls carried objects toward the hold point in front of the carrier,
+/// and drops them when they get obstructed.
+#[allow(clippy::type_complexity)]
+pub(crate) fn hold_carried_objects(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    mut dropped_writer: MessageWriter<ObjectDropped>,
+    carriers: Query<(
+        Entity,
+        &Carrier,
+        &Carrying,
+        &Position,
+        &Rotation,
+        &LinearVelocity,
+        Has<Ragdoll>,
+    )>,
+    mut objects: Query<
+        (
+            &Carried,
+            &Position,
+            &mut LinearVelocity,
+            &mut AngularVelocity,
+        ),
+        Without<Carrier>,
+    >,
+    children: Query<&Children>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (entity, carrier, &Carrying(object), position, rotation, carrier_velocity, is_ragdoll) in
+        &carriers
+    {
+        let Ok((carried, object_position, mut linear_velocity, mut angular_velocity)) =
+            objects.get_mut(object)
+        else {
+            // The object was despawned while being carried.
+            commands.entity(entity).remove::<Carrying>();
+            continue;
+        };
+
+        let (origin, direction) = aim_ray(entity, position, rotation, &children, &cameras);
+        let target = origin + direction.as_vec3().adjust_precision() * carrier.hold_distance;
+        let offset = target - object_position.0;
+
+        // Drop the object if the carrier went limp, or if the object got stuck behind something.
+        if is_ragdoll || offset.length() > carrier.break_distance {
+            release_carried(&mut commands, entity, object, Some(carried));
+            dropped_writer.write(ObjectDropped {
+                carrier: entity,
+                object,
+            });
+            continue;
+        }
+
+        // Damped spring toward the hold point, relative to the velocity of the carrier.
+        let relative_velocity = linear_velocity.0 - carrier_velocity.0;
+        let acceleration = offset * carrier.strength - relative_velocity * carrier.damping;
+        linear_velocity.0 += acceleration * delta_time;
+
+        // Keep the object from spinning wildly while held.
+        angular_velocity.0 /= 1.0 + carrier.damping * delta_time;
+    }
+}
+
+/// Charges and throws carried objects in response to [`MovementAction::Throw`].
+///
+/// The object is thrown when the throw button is released, with an impulse
+/// scaled by how long it was held.
+#[allow(clippy::type_complexity)]
+pub(crate) fn throw_carried_objects(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    mut movement_reader: MessageReader<MovementAction>,
+    mut thrown_writer: MessageWriter<ObjectThrown>,
+    mut carriers: Query<
+        (
+            Entity,
+            &Carrier,
+            &Carrying,
+            &Position,
+            &Rotation,
+            Option<&mut ThrowCharge>,
+            Option<&FpsController>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+    mut objects: Query<(&Carried, &ComputedMass, &mut LinearVelocity), Without<Carrier>>,
+    children: Query<&Children>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    // Throw input is held while charging.
+    let throw_held = movement_reader
+        .read()
+        .any(|event| matches!(event, MovementAction::Throw));
+
+    for (entity, carrier, &Carrying(object), position, rotation, charge, fps_controller) in
+        &mut carriers
+    {
+        if !fps_controller.is_some_and(|controller| controller.enable_input) {
+            continue;
+        }
+
+        let charge_fraction = match (charge, carrier.throw_charge_time) {
+            // Keep charging while the button is held.
+            (Some(mut charge), Some(_)) if throw_held => {
+                charge.0 += delta_time;
+                continue;
+            }
+            (None, Some(_)) if throw_held => {
+                commands.entity(entity).insert(ThrowCharge::default());
+                continue;
+            }
+            // Throw on release.
+            (Some(charge), Some(charge_time)) => {
+                commands.entity(entity).remove::<ThrowCharge>();
+                (charge.0 / charge_time.max(Scalar::EPSILON)).min(1.0)
+            }
+            (_, None) if throw_held => 1.0,
+            _ => continue,
+        };
+
+        let Ok((carried, mass, mut linear_velocity)) = objects.get_mut(object) else {
+            continue;
+        };
+
+        let (_, direction) = aim_ray(entity, position, rotation, &children, &cameras);
+        let fraction =
+            carrier.min_throw_fraction + (1.0 - carrier.min_throw_fraction) * charge_fraction;
+        let impulse = direction.as_vec3().adjust_precision() * carrier.throw_impulse * fraction;
+        linear_velocity.0 += impulse * mass.inverse();
+
+        release_carried(&mut commands, entity, object, Some(carried));
+        thrown_writer.write(ObjectThrown {
+            carrier: entity,
+            object,
+            impulse,
+            charge: charge_fraction,
+        });
+    }
+}
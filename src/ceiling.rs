@@ -0,0 +1,141 @@
+//! Ceiling detection for character controllers.
+//!
+//! An upward shape cast maintains a [`CeilingContact`] component, which is used to
+//! cancel upward velocity when the character bonks its head, cut jumps short,
+//! and block standing up from a crouch under low geometry.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{CharacterController, ControllerEnabled, StanceConfig, StanceHeight};
+
+/// Enables ceiling detection for a character controller.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct CeilingDetection {
+    /// The maximum distance above the head at which a ceiling is detected.
+    ///
+    /// Characters with a [`StanceConfig`] cast far enough to cover the height
+    /// needed to stand up, even if that is further than this distance.
+    pub max_distance: Scalar,
+    /// The distance at which the character is considered to be touching the ceiling.
+    pub contact_distance: Scalar,
+}
+
+impl Default for CeilingDetection {
+    fn default() -> Self {
+        Self {
+            max_distance: 0.2,
+            contact_distance: 0.05,
+        }
+    }
+}
+
+/// A component describing the ceiling above a character controller.
+///
+/// Present only while a ceiling is within [`CeilingDetection::max_distance`]
+/// (or within standing height, for crouching characters).
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct CeilingContact {
+    /// The entity of the ceiling collider.
+    pub entity: Entity,
+    /// The surface normal of the ceiling, pointing toward the character.
+    pub normal: Vector,
+    /// The distance between the top of the character and the ceiling.
+    pub distance: Scalar,
+}
+
+impl CeilingContact {
+    /// Returns `true` if the character is touching the ceiling.
+    pub fn is_touching(&self, detection: &CeilingDetection) -> bool {
+        self.distance <= detection.contact_distance
+    }
+}
+
+/// Updates the [`CeilingContact`] of character controllers.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_ceiling_contact(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    controllers: Query<
+        (
+            Entity,
+            &CeilingDetection,
+            &Collider,
+            &Position,
+            &Rotation,
+            Option<&StanceConfig>,
+            Option<&StanceHeight>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    for (entity, detection, collider, position, rotation, stance_config, stance_height) in
+        &controllers
+    {
+        // Cast far enough to know whether there is room to stand up.
+        let max_distance = match (stance_config, stance_height) {
+            (Some(config), Some(height)) => detection
+                .max_distance
+                .max(config.standing_height - height.0 + detection.contact_distance),
+            _ => detection.max_distance,
+        };
+
+        // Use a slightly smaller version of the collider, like the ground caster.
+        let mut caster_shape = collider.clone();
+        caster_shape.set_scale(Vector::ONE * 0.99, 10);
+
+        let Ok(up) = Dir3::new((rotation * Vector::Y).f32()) else {
+            continue;
+        };
+
+        let hit = spatial_query.cast_shape(
+            &caster_shape,
+            position.0,
+            rotation.0,
+            up,
+            &ShapeCastConfig::from_max_distance(max_distance),
+            &SpatialQueryFilter::from_excluded_entities([entity]),
+        );
+
+        match hit {
+            Some(hit) => {
+                commands.entity(entity).insert(CeilingContact {
+                    entity: hit.entity,
+                    normal: rotation * -hit.normal2,
+                    distance: hit.distance,
+                });
+            }
+            None => {
+                commands.entity(entity).remove::<CeilingContact>();
+            }
+        }
+    }
+}
+
+/// Cancels upward velocity when a character controller hits its head on a ceiling.
+///
+/// This also cuts jumps short when jumping under low geometry.
+pub(crate) fn ceiling_bonk(
+    mut controllers: Query<
+        (
+            &CeilingDetection,
+            &CeilingContact,
+            &Rotation,
+            &mut LinearVelocity,
+        ),
+        ControllerEnabled,
+    >,
+) {
+    for (detection, contact, rotation, mut linear_velocity) in &mut controllers {
+        if !contact.is_touching(detection) {
+            continue;
+        }
+
+        let up = rotation * Vector::Y;
+        let upward_speed = linear_velocity.dot(up);
+        if upward_speed > 0.0 {
+            linear_velocity.0 -= up * upward_speed;
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Configuration for how character controllers collide with each other.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::CharacterController;
+
+/// The collision layer reserved for character controllers when using
+/// [`CharacterCollisionPolicy::PassThrough`].
+///
+/// Controllers are moved from the default layer to this layer, and stop
+/// filtering it, so they don't generate contacts with each other.
+pub const CHARACTER_COLLISION_LAYER: LayerMask = LayerMask(1 << 31);
+
+/// A resource that configures how character controllers interact with each other.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub enum CharacterCollisionPolicy {
+    /// Characters block each other like walls.
+    /// Each character resolves half of the penetration, so neither of them gets shoved.
+    #[default]
+    Solid,
+    /// Overlapping characters are gently separated with a velocity proportional
+    /// to the penetration depth, instead of being blocked.
+    SoftPush {
+        /// The separation speed per unit of penetration.
+        strength: Scalar,
+    },
+    /// Characters walk through each other.
+    ///
+    /// This is implemented with collision layers using [`CHARACTER_COLLISION_LAYER`].
+    PassThrough,
+}
+
+/// The collision layers a character controller had before
+/// [`CharacterCollisionPolicy::PassThrough`] changed them.
+#[derive(Component)]
+struct PreviousCollisionLayers(CollisionLayers);
+
+/// Applies a soft separation velocity to a character overlapping another character.
+pub(crate) fn push_apart(
+    contacts: &ContactPair,
+    is_first: bool,
+    strength: Scalar,
+    up: Vector,
+    linear_velocity: &mut LinearVelocity,
+) {
+    for manifold in contacts.manifolds.iter() {
+        let normal = if is_first {
+            -manifold.normal
+        } else {
+            manifold.normal
+        };
+
+        let deepest_penetration = manifold
+            .points
+            .iter()
+            .map(|contact| contact.penetration)
+            .fold(0.0, Scalar::max);
+
+        // Only push sideways, so characters can't launch each other into the air.
+        let push_direction = normal.reject_from_normalized(up).normalize_or_zero();
+        linear_velocity.0 += push_direction * deepest_penetration * strength;
+    }
+}
+
+/// Moves character controllers to and from the [`CHARACTER_COLLISION_LAYER`]
+/// when the [`CharacterCollisionPolicy`] changes or new controllers are added.
+#[allow(clippy::type_complexity)]
+pub(crate) fn sync_character_collision_layers(
+    mut commands: Commands,
+    policy: Res<CharacterCollisionPolicy>,
+    mut controllers: Query<(
+        Entity,
+        Option<&mut CollisionLayers>,
+        Option<&PreviousCollisionLayers>,
+        Ref<CharacterController>,
+    )>,
+) {
+    let pass_through = *policy == CharacterCollisionPolicy::PassThrough;
+
+    for (entity, layers, previous, controller) in &mut controllers {
+        if !policy.is_changed() && !controller.is_added() {
+            continue;
+        }
+
+        match (pass_through, previous) {
+            (true, None) => {
+                let current = layers.as_deref().copied().unwrap_or_default();
+                let pass_through_layers = CollisionLayers::new(
+                    (current.memberships & !LayerMask::DEFAULT) | CHARACTER_COLLISION_LAYER,
+                    current.filters & !CHARACTER_COLLISION_LAYER,
+                );
+                commands
+                    .entity(entity)
+                    .insert((pass_through_layers, PreviousCollisionLayers(current)));
+            }
+            (false, Some(previous)) => {
+                if let Some(mut layers) = layers {
+                    *layers = previous.0;
+                }
+                commands.entity(entity).remove::<PreviousCollisionLayers>();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Uniform scaling of character controllers and the parameters that depend on their size.
+//!
+//! Giant or tiny characters need a bigger or smaller collider, but also a matching
+//! ground cast, step and ledge heights, jump impulse, and eye height. [`CharacterScale`]
+//! derives all of them from a single factor, relative to the values the controller was
+//! spawned with, and can be changed at runtime.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    AutoJump, AvoidLedges, CameraPivot, CharacterController, EyeHeight, GroundCastConfig,
+    JumpImpulse, StanceConfig,
+};
+
+/// Scales a character controller and its size-dependent parameters by a single factor.
+///
+/// The parameters of the controller are treated as the values for a factor of 1. When the
+/// factor changes, these are rescaled proportionally:
+///
+/// - The collider, or the [`StanceConfig`] dimensions if the character has one.
+/// - The origin, distance, and sphere radius of the [`GroundCastConfig`].
+/// - The step and ledge heights of [`AutoJump`] and [`AvoidLedges`].
+/// - The [`JumpImpulse`], so that the jump height scales with the character.
+/// - The offset of the camera or [`CameraPivot`], which sets the eye height, or the
+///   offsets of the [`EyeHeight`] if the character has one.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct CharacterScale {
+    /// The scale factor, where 1 is the size the controller was configured for.
+    pub factor: Scalar,
+    /// The factor the parameters are currently scaled by.
+    applied: Scalar,
+}
+
+impl CharacterScale {
+    /// Creates a scale with the given factor.
+    pub fn new(factor: Scalar) -> Self {
+        Self {
+            factor,
+            applied: 1.0,
+        }
+    }
+}
+
+impl Default for CharacterScale {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+/// Rescales the parameters of character controllers whose [`CharacterScale`] changed.
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_character_scale(
+    mut controllers: Query<
+        (
+            Entity,
+            &mut CharacterScale,
+            &mut Collider,
+            Option<&mut StanceConfig>,
+            Option<&mut GroundCastConfig>,
+            Option<&mut ShapeCaster>,
+            Option<&mut JumpImpulse>,
+            Option<&mut AutoJump>,
+            Option<&mut AvoidLedges>,
+            Option<&mut EyeHeight>,
+        ),
+        (With<CharacterController>, Changed<CharacterScale>),
+    >,
+    children: Query<&Children>,
+    mut eyes: Query<
+        &mut Transform,
+        (
+            Or<(With<Camera3d>, With<CameraPivot>)>,
+            Without<CharacterController>,
+        ),
+    >,
+) {
+    for (
+        entity,
+        mut scale,
+        mut collider,
+        stance_config,
+        ground_cast_config,
+        ground_caster,
+        jump_impulse,
+        auto_jump,
+        avoid_ledges,
+        eye_height,
+    ) in &mut controllers
+    {
+        if scale.factor <= 0.0 || scale.factor == scale.applied {
+            continue;
+        }
+        let factor = scale.factor;
+        let ratio = factor / scale.applied;
+        // Don't retrigger change detection for the bookkeeping.
+        scale.bypass_change_detection().applied = factor;
+
+        // The stance system resizes the collider to match its config.
+        if let Some(mut config) = stance_config {
+            config.radius *= ratio;
+            config.standing_height *= ratio;
+            config.crouching_height *= ratio;
+            config.prone_height *= ratio;
+            config.transition_speed *= ratio;
+        } else {
+            let collider_scale = collider.scale() * ratio;
+            collider.set_scale(collider_scale, 10);
+        }
+
+        // The ground caster is rebuilt from its config, or scaled directly without one.
+        if let Some(mut config) = ground_cast_config {
+            config.origin *= ratio;
+            config.max_distance *= ratio;
+            if let Some(radius) = &mut config.sphere_radius {
+                *radius *= ratio;
+            }
+        } else if let Some(mut caster) = ground_caster {
+            let shape_scale = caster.shape.scale() * ratio;
+            caster.shape.set_scale(shape_scale, 10);
+            caster.origin *= ratio;
+            caster.max_distance *= ratio;
+        }
+        if let Some(mut auto_jump) = auto_jump {
+            auto_jump.check_distance *= ratio;
+            auto_jump.min_height *= ratio;
+            auto_jump.max_height *= ratio;
+        }
+        if let Some(mut avoid_ledges) = avoid_ledges {
+            avoid_ledges.look_ahead *= ratio;
+            avoid_ledges.max_drop *= ratio;
+        }
+
+        if let Some(mut eye_height) = eye_height {
+            eye_height.offset_from_top *= ratio;
+            eye_height.clearance *= ratio;
+            eye_height.landing_dip *= ratio;
+        }
+
+        // The jump height is proportional to the square of the impulse.
+        if let Some(mut jump_impulse) = jump_impulse {
+            jump_impulse.0 *= ratio.sqrt();
+        }
+
+        for descendant in children.iter_descendants(entity) {
+            if let Ok(mut transform) = eyes.get_mut(descendant) {
+                transform.translation = (transform.translation.adjust_precision() * ratio).f32();
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! The weight of character controllers on the dynamic bodies they stand on.
+//!
+//! Character controllers are kinematic, so the physics engine doesn't push down on what
+//! they stand on. With [`CharacterWeight`], the weight of the character is applied to the
+//! dynamic bodies supporting it at the contact points, so see-saws tip, rafts sink, and
+//! pressure plates are pushed down by the character.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    CharacterController, ControllerEnabled, ControllerGravity, DropThrough, Grounded,
+    MaxSlopeAngle, WalkableSurfaces,
+};
+
+/// The mass of a character controller in kilograms, applied as weight to the dynamic bodies
+/// it stands on.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct CharacterWeight(pub Scalar);
+
+impl Default for CharacterWeight {
+    fn default() -> Self {
+        Self(75.0)
+    }
+}
+
+/// Applies the weight of grounded [`CharacterWeight`] characters to the dynamic bodies they
+/// stand on, split evenly between the supporting contacts.
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_character_weight(
+    time: Res<Time<Virtual>>,
+    controllers: Query<
+        (
+            &CharacterWeight,
+            &ControllerGravity,
+            &ShapeHits,
+            &Rotation,
+            Option<&MaxSlopeAngle>,
+            Option<&DropThrough>,
+        ),
+        (With<CharacterController>, With<Grounded>, ControllerEnabled),
+    >,
+    colliders: Query<&ColliderOf>,
+    mut bodies: Query<(&RigidBody, Forces), Without<CharacterController>>,
+    walkable: WalkableSurfaces,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (weight, gravity, hits, rotation, max_slope_angle, drop_through) in &controllers {
+        let supports = hits
+            .iter()
+            .filter(|hit| {
+                walkable.is_walkable(
+                    hit.entity,
+                    rotation * -hit.normal2,
+                    max_slope_angle,
+                    drop_through,
+                )
+            })
+            .collect::<Vec<_>>();
+        if supports.is_empty() {
+            continue;
+        }
+
+        // Static supports take their share too, so a character standing between a static
+        // ledge and a raft only pushes the raft with part of its weight.
+        let force = gravity.0 * weight.0 / supports.len() as Scalar;
+
+        for hit in supports {
+            let body = colliders
+                .get(hit.entity)
+                .map_or(hit.entity, |collider_of| collider_of.body);
+            let Ok((rb, mut forces)) = bodies.get_mut(body) else {
+                continue;
+            };
+            if !rb.is_dynamic() {
+                continue;
+            }
+
+            // Impulses keep the push consistent with the frame rate, unlike per-step forces.
+            forces.apply_linear_impulse_at_point(force * delta_time, hit.point1);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! Charged jumps, where holding jump builds up power and releasing it jumps.
+//!
+//! Character controllers with [`ChargedJump`] don't jump when
+//! [`MovementAction::Jump`](crate::MovementAction::Jump) is pressed. Instead, a [`JumpCharge`]
+//! builds up while [`MovementAction::JumpHeld`](crate::MovementAction::JumpHeld) is written,
+//! and releasing jump while grounded or within the [`CoyoteTime`] jumps with an impulse scaled
+//! by the charge.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    ground_normal, jump_blocked, jump_velocity, AccessibilityProfile, CharacterController,
+    ControllerEnabled, ControllerUp, CoyoteTime, FpsController, GatheredMovementInput, Grounded,
+    InCrawlVolume, JumpCooldown, JumpImpulse, JumpLiftoff, JumpTiming, Jumped,
+    RecoveringFromLanding, SlopeJump, SpeedModifier, SpeedModifiers, WadingState, WorldUp,
+};
+
+/// The tag of the [`SpeedModifier`] that slows characters down while charging a jump.
+pub const CHARGED_JUMP_MODIFIER: &str = "charged_jump";
+
+/// Makes a character controller charge its jumps while jump is held.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ChargedJump {
+    /// The time it takes to fully charge a jump, in seconds.
+    pub max_charge_time: Scalar,
+    /// The [`JumpImpulse`] multiplier of a jump released without any charge.
+    pub min_multiplier: Scalar,
+    /// The [`JumpImpulse`] multiplier of a fully charged jump.
+    pub max_multiplier: Scalar,
+    /// The movement acceleration multiplier while charging, or `None` to move at full speed.
+    pub charging_speed_multiplier: Option<Scalar>,
+}
+
+impl Default for ChargedJump {
+    fn default() -> Self {
+        Self {
+            max_charge_time: 1.0,
+            min_multiplier: 0.5,
+            max_multiplier: 1.5,
+            charging_speed_multiplier: Some(0.3),
+        }
+    }
+}
+
+impl ChargedJump {
+    /// Returns the [`JumpImpulse`] multiplier for the given charge fraction.
+    pub fn multiplier(&self, fraction: Scalar) -> Scalar {
+        self.min_multiplier + (self.max_multiplier - self.min_multiplier) * fraction.clamp(0.0, 1.0)
+    }
+}
+
+/// The charge of a jump while jump is held, present only while charging.
+#[derive(Component, Clone, Copy, Debug, Default)]
+#[component(storage = "SparseSet")]
+pub struct JumpCharge {
+    /// How long jump has been held, in seconds.
+    pub time: Scalar,
+    /// The charge from 0 to 1, for example for a charge bar.
+    pub fraction: Scalar,
+}
+
+/// Charges and releases the jumps of [`ChargedJump`] character controllers.
+#[allow(clippy::type_complexity)]
+pub(crate) fn charge_jumps(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    gathered: Res<GatheredMovementInput>,
+    mut controllers: Query<
+        (
+            Entity,
+            &ChargedJump,
+            Option<&mut JumpCharge>,
+            &JumpImpulse,
+            &mut LinearVelocity,
+            Has<Grounded>,
+            Option<&FpsController>,
+            Option<&mut SpeedModifiers>,
+            Option<&WadingState>,
+            &Rotation,
+            Option<&ShapeHits>,
+            Option<&SlopeJump>,
+            Option<&RecoveringFromLanding>,
+            Has<InCrawlVolume>,
+            (
+                (Has<JumpLiftoff>, Option<&JumpCooldown>),
+                (
+                    Option<&CoyoteTime>,
+                    Option<&JumpTiming>,
+                    Option<&AccessibilityProfile>,
+                ),
+                Option<&ControllerUp>,
+            ),
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (
+        entity,
+        charged_jump,
+        charge,
+        jump_impulse,
+        mut linear_velocity,
+        is_grounded,
+        fps_controller,
+        speed_modifiers,
+        wading,
+        rotation,
+        ground_hits,
+        slope_jump,
+        recovering,
+        in_crawl_volume,
+        ((lifting_off, jump_cooldown), (coyote_time, jump_timing, profile), controller_up),
+    ) in &mut controllers
+    {
+        // Jump input is held while charging.
+        let held = gathered.get(entity, fps_controller).jump_held;
+
+        match (charge, held) {
+            (Some(mut charge), true) => {
+                charge.time += delta_time;
+                charge.fraction = if charged_jump.max_charge_time > 0.0 {
+                    (charge.time / charged_jump.max_charge_time).min(1.0)
+                } else {
+                    1.0
+                };
+            }
+            (None, true) => {
+                commands.entity(entity).insert(JumpCharge::default());
+                if let Some(multiplier) = charged_jump.charging_speed_multiplier {
+                    let modifier = SpeedModifier::multiplier(multiplier);
+                    match speed_modifiers {
+                        Some(mut modifiers) => modifiers.insert(CHARGED_JUMP_MODIFIER, modifier),
+                        None => {
+                            let mut modifiers = SpeedModifiers::default();
+                            modifiers.insert(CHARGED_JUMP_MODIFIER, modifier);
+                            commands.entity(entity).insert(modifiers);
+                        }
+                    }
+                }
+            }
+            (Some(charge), false) => {
+                commands.entity(entity).remove::<JumpCharge>();
+                if let Some(mut modifiers) = speed_modifiers {
+                    modifiers.remove(CHARGED_JUMP_MODIFIER);
+                }
+
+                // Releasing jump in the air after the coyote time, while recovering from a
+                // landing, in a crawl volume, or right after another jump discards the charge.
+                let jump_locked = in_crawl_volume
+                    || recovering.is_some_and(|recovering| recovering.jump_locked)
+                    || jump_blocked(lifting_off, jump_cooldown);
+                let can_jump = is_grounded
+                    || jump_timing
+                        .is_some_and(|timing| timing.in_coyote_time(coyote_time, profile));
+                if can_jump && !jump_locked {
+                    // Wading through shallow water weakens jumps.
+                    let jump_multiplier = wading.map_or(1.0, |wading| wading.jump_multiplier);
+                    let impulse =
+                        jump_impulse.0 * charged_jump.multiplier(charge.fraction) * jump_multiplier;
+                    let up = world_up.for_controller(controller_up);
+                    let direction = slope_jump.map_or(up, |slope_jump| {
+                        let normal = ground_hits.and_then(|hits| ground_normal(hits, rotation, up));
+                        slope_jump.direction(up, normal)
+                    });
+                    linear_velocity.0 = jump_velocity(linear_velocity.0, up, direction, impulse);
+                    commands.trigger(Jumped { entity });
+                }
+            }
+            (None, false) => {}
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Click-to-move for point-and-click and ARPG style games.
+//!
+//! Clicking the ground with a [`ClickToMove`] character casts a ray from the camera through
+//! the cursor, and the [`PathFollower`] of the character walks to the hit point using the
+//! regular movement system. If the character is blocked by an obstacle for too long,
+//! it gives up and [`PathFailed`](crate::PathFailed) is written.
+
+use avian3d::{math::*, prelude::*};
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{CharacterController, PathFollower};
+
+/// Moves a character controller to the point clicked on the ground.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(PathFollower)]
+pub struct ClickToMove {
+    /// The mouse button used to pick a destination.
+    pub button: MouseButton,
+    /// The camera used for picking. If `None`, the first active 3D camera is used.
+    pub camera: Option<Entity>,
+    /// The maximum distance of the picking ray.
+    pub max_distance: Scalar,
+    /// Whether to request a path with [`RepathRequested`](crate::RepathRequested),
+    /// for example from a navmesh, instead of walking straight to the destination.
+    pub use_pathfinding: bool,
+    /// How long the character can be blocked by an obstacle before giving up, in seconds.
+    pub give_up_time: Scalar,
+}
+
+impl Default for ClickToMove {
+    fn default() -> Self {
+        Self {
+            button: MouseButton::Left,
+            camera: None,
+            max_distance: 100.0,
+            use_pathfinding: false,
+            give_up_time: 2.0,
+        }
+    }
+}
+
+/// Sets the destination of [`ClickToMove`] characters to the clicked point.
+#[allow(clippy::type_complexity)]
+pub(crate) fn click_to_move(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    spatial_query: SpatialQuery,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform), With<Camera3d>>,
+    mut characters: Query<(Entity, &ClickToMove, &mut PathFollower), With<CharacterController>>,
+) {
+    let Some(cursor_position) = windows
+        .single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+
+    for (entity, click_to_move, mut follower) in &mut characters {
+        if !mouse_input.just_pressed(click_to_move.button) {
+            continue;
+        }
+
+        let camera = match click_to_move.camera {
+            Some(camera_entity) => cameras.get(camera_entity).ok(),
+            None => cameras.iter().find(|(_, camera, _)| camera.is_active),
+        };
+        let Some((_, camera, camera_transform)) = camera else {
+            continue;
+        };
+        let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_position) else {
+            continue;
+        };
+
+        let origin = ray.origin.adjust_precision();
+        let Some(hit) = spatial_query.cast_ray(
+            origin,
+            ray.direction,
+            click_to_move.max_distance,
+            true,
+            &SpatialQueryFilter::from_excluded_entities([entity]),
+        ) else {
+            continue;
+        };
+        let destination = origin + ray.direction.as_vec3().adjust_precision() * hit.distance;
+
+        follower.give_up_time = Some(click_to_move.give_up_time);
+        follower.set_destination(destination);
+        if !click_to_move.use_pathfinding {
+            follower.set_path([destination]);
+        }
+    }
+}
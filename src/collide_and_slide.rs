@@ -0,0 +1,247 @@
+//! A collide-and-slide mode for the collision response of character controllers.
+//!
+//! Character controllers have always been kinematic bodies, so there is no separate kinematic
+//! mode, only two ways of resolving their collisions. By default they respond to the contacts
+//! found by the narrow phase: penetration is pushed out, and velocity into walls and slopes is
+//! corrected. This works well in most cases, but the response lags a step behind the contacts,
+//! which can make characters jitter against corners and bounce off steps at high speeds.
+//!
+//! With [`ControllerMode::CollideAndSlide`], the movement of each physics step is resolved up
+//! front instead. The collider is shape cast along the velocity, stops just short of the first
+//! hit, and the rest of the movement is projected onto the hit surface and cast again. Walkable
+//! slopes are followed, steep slopes and walls are slid along without climbing them, and
+//! grounded characters are snapped down to the ground when walking down slopes and steps. The
+//! result is deterministic and never moves the character into geometry, so contacts are only
+//! used to push characters out of overlaps they didn't cause, like moving platforms.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    CharacterCollisionPolicy, CharacterController, ControllerEnabled, ControllerUp, DropThrough,
+    Grounded, MaxSlopeAngle, NotWalkable, OneWayPlatform, WorldUp,
+};
+
+/// The gap kept between the collider and the surfaces it slides along.
+const SKIN_WIDTH: Scalar = 0.02;
+
+/// The maximum number of surfaces the movement of a step is slid along.
+const MAX_SLIDES: usize = 4;
+
+/// The maximum number of hits that are skipped in one step, like sensors and one-way platforms.
+const MAX_SKIPPED_HITS: usize = 8;
+
+/// How far grounded characters are snapped down to keep them on slopes and steps.
+const GROUND_SNAP_DISTANCE: Scalar = 0.3;
+
+/// How the collisions of a character controller are resolved.
+///
+/// Both modes move a kinematic body. The default [`ControllerMode::ContactResponse`] is the
+/// collision response the controller has always used.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[require(SlideVelocity)]
+pub enum ControllerMode {
+    /// Pushes the character out of contacts found by the narrow phase, and corrects its
+    /// velocity into walls and slopes.
+    #[default]
+    ContactResponse,
+    /// Resolves the movement of each step with shape casts before it happens, sliding along
+    /// the surfaces that are hit.
+    CollideAndSlide,
+}
+
+/// The velocity of a [`ControllerMode::CollideAndSlide`] character after sliding.
+///
+/// The solver sets the [`LinearVelocity`] to the resolved displacement of the step so that
+/// integration moves the character exactly that far, and restores this velocity afterward.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub(crate) struct SlideVelocity {
+    velocity: Vector,
+    /// Whether the movement of the current step was resolved and the velocity needs restoring.
+    resolved: bool,
+}
+
+/// Resolves the movement of [`ControllerMode::CollideAndSlide`] characters for this step.
+#[allow(clippy::type_complexity)]
+pub(crate) fn collide_and_slide(
+    spatial_query: SpatialQuery,
+    world_up: Res<WorldUp>,
+    time: Res<Time>,
+    policy: Res<CharacterCollisionPolicy>,
+    mut controllers: Query<
+        (
+            Entity,
+            &ControllerMode,
+            &mut SlideVelocity,
+            &mut LinearVelocity,
+            &Position,
+            &Rotation,
+            &Collider,
+            Has<Grounded>,
+            Option<&MaxSlopeAngle>,
+            Option<&DropThrough>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+    collider_rbs: Query<&ColliderOf>,
+    bodies: Query<&RigidBody>,
+    characters: Query<(), With<CharacterController>>,
+    sensors: Query<(), With<Sensor>>,
+    one_way_platforms: Query<(), With<OneWayPlatform>>,
+    not_walkable: Query<(), With<NotWalkable>>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+    if delta_time <= 0.0 {
+        return;
+    }
+
+    for (
+        entity,
+        mode,
+        mut slide_velocity,
+        mut linear_velocity,
+        position,
+        rotation,
+        collider,
+        is_grounded,
+        max_slope_angle,
+        drop_through,
+        controller_up,
+    ) in &mut controllers
+    {
+        if *mode != ControllerMode::CollideAndSlide {
+            continue;
+        }
+
+        let up = world_up.for_controller(controller_up);
+        let max_slope = max_slope_angle.map_or(PI * 0.25, |angle| angle.0);
+        let cast_config = |max_distance: Scalar| ShapeCastConfig {
+            ignore_origin_penetration: true,
+            ..ShapeCastConfig::from_max_distance(max_distance)
+        };
+        let mut filter = SpatialQueryFilter::from_excluded_entities([entity]);
+
+        // Returns `true` if a hit collider doesn't block the character.
+        let is_ignored = |collider_entity: Entity, normal: Vector, motion: Vector| {
+            let body = collider_rbs
+                .get(collider_entity)
+                .map_or(collider_entity, |c| c.body);
+            let is_one_way =
+                one_way_platforms.contains(collider_entity) || one_way_platforms.contains(body);
+            sensors.contains(collider_entity)
+                // Dynamic bodies are pushed by the contacts instead
+                || bodies.get(body).is_ok_and(|rb| rb.is_dynamic())
+                || (characters.contains(body)
+                    && !matches!(*policy, CharacterCollisionPolicy::Solid))
+                || (is_one_way
+                    && (drop_through.is_some_and(|drop| drop.platform == collider_entity)
+                        || normal.dot(up) <= 0.0
+                        || motion.dot(up) > 0.0))
+        };
+
+        let mut velocity = linear_velocity.0;
+        let mut remaining = velocity * delta_time;
+        let mut displacement = Vector::ZERO;
+        let (mut slides, mut skipped) = (0, 0);
+
+        while slides < MAX_SLIDES && skipped < MAX_SKIPPED_HITS {
+            let Ok((direction, length)) = Dir3::new_and_length(remaining.f32()) else {
+                break;
+            };
+            let length = length.adjust_precision();
+
+            let Some(hit) = spatial_query.cast_shape(
+                collider,
+                position.0 + displacement,
+                rotation.0,
+                direction,
+                &cast_config(length + SKIN_WIDTH),
+                &filter,
+            ) else {
+                displacement += remaining;
+                break;
+            };
+
+            let normal = rotation * -hit.normal2;
+            if is_ignored(hit.entity, normal, remaining) {
+                filter.excluded_entities.insert(hit.entity);
+                skipped += 1;
+                continue;
+            }
+            slides += 1;
+
+            // Move up to the surface, keeping the skin width
+            let travel = (hit.distance - SKIN_WIDTH).clamp(0.0, length);
+            let step = remaining / length * travel;
+            displacement += step;
+            remaining -= step;
+
+            let walkable = normal.angle_between(up) <= max_slope
+                && !not_walkable.contains(hit.entity)
+                && !collider_rbs
+                    .get(hit.entity)
+                    .is_ok_and(|c| not_walkable.contains(c.body));
+
+            remaining = slide(remaining, normal, up, walkable);
+            if velocity.dot(normal) < 0.0 {
+                velocity = slide(velocity, normal, up, walkable);
+            }
+        }
+
+        // Keep grounded characters on slopes and steps when moving down them
+        let snap_direction = Dir3::new(-up.f32())
+            .ok()
+            .filter(|_| is_grounded && velocity.dot(up) <= 0.0);
+        if let Some(down) = snap_direction {
+            let snap = spatial_query.cast_shape(
+                collider,
+                position.0 + displacement,
+                rotation.0,
+                down,
+                &cast_config(GROUND_SNAP_DISTANCE + SKIN_WIDTH),
+                &filter,
+            );
+            if let Some(hit) = snap {
+                let normal = rotation * -hit.normal2;
+                if normal.angle_between(up) <= max_slope && !is_ignored(hit.entity, normal, -up) {
+                    displacement -= up * (hit.distance - SKIN_WIDTH).max(0.0);
+                }
+            }
+        }
+
+        // Integration moves the character by exactly the resolved displacement
+        slide_velocity.velocity = velocity;
+        slide_velocity.resolved = true;
+        linear_velocity.0 = displacement / delta_time;
+    }
+}
+
+/// Restores the velocity of [`ControllerMode::CollideAndSlide`] characters after integration.
+pub(crate) fn restore_slide_velocity(
+    mut controllers: Query<(&mut SlideVelocity, &mut LinearVelocity)>,
+) {
+    for (mut slide_velocity, mut linear_velocity) in &mut controllers {
+        if slide_velocity.resolved {
+            linear_velocity.0 = slide_velocity.velocity;
+            slide_velocity.resolved = false;
+        }
+    }
+}
+
+/// Projects a motion onto a hit surface.
+///
+/// Walkable slopes are followed. Steep slopes and walls are slid along without gaining height,
+/// so they can't be climbed.
+fn slide(motion: Vector, normal: Vector, up: Vector, walkable: bool) -> Vector {
+    let projected = motion.reject_from_normalized(normal);
+    if walkable {
+        return projected;
+    }
+    let rise = projected.dot(up);
+    if rise > 0.0 && motion.dot(up) <= 0.0 {
+        projected - up * rise
+    } else {
+        projected
+    }
+}
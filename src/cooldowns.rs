@@ -0,0 +1,230 @@
+//! Shared cooldown and charge bookkeeping for movement abilities.
+//!
+//! Abilities like dashes, double jumps, wall jumps, and blinks register a tagged
+//! [`AbilityCooldown`] in the [`AbilityCooldowns`] of a character, and call
+//! [`AbilityCooldowns::try_use`] when activated. Charges recharge over time,
+//! on landing, or both, so each ability doesn't need its own timer plumbing.
+//!
+//! ```ignore
+//! commands.entity(player).insert(
+//!     AbilityCooldowns::default()
+//!         .with("dash", AbilityCooldown::new(2, Some(1.5)).with_cooldown(0.3))
+//!         .with("double_jump", AbilityCooldown::new(1, None).with_reset_on_land(true)),
+//! );
+//!
+//! // In the dash system:
+//! if cooldowns.try_use("dash") {
+//!     // Dash!
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use avian3d::math::*;
+use bevy::prelude::*;
+
+use crate::{CharacterController, Grounded};
+
+/// The charges and timers of a single ability.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AbilityCooldown {
+    /// The maximum number of charges that can be stored.
+    pub max_charges: u32,
+    /// The time to recharge one charge in seconds, or `None` if charges don't recharge over time.
+    pub recharge_time: Option<Scalar>,
+    /// The minimum time between two uses in seconds, even with charges left.
+    pub cooldown: Scalar,
+    /// Whether all charges are restored when the character lands.
+    pub reset_on_land: bool,
+    charges: u32,
+    recharge_progress: Scalar,
+    cooldown_remaining: Scalar,
+}
+
+impl AbilityCooldown {
+    /// Creates a fully charged ability with the given number of charges and recharge time.
+    pub const fn new(max_charges: u32, recharge_time: Option<Scalar>) -> Self {
+        Self {
+            max_charges,
+            recharge_time,
+            cooldown: 0.0,
+            reset_on_land: false,
+            charges: max_charges,
+            recharge_progress: 0.0,
+            cooldown_remaining: 0.0,
+        }
+    }
+
+    /// Sets the minimum time between two uses in seconds.
+    pub const fn with_cooldown(mut self, cooldown: Scalar) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Sets whether all charges are restored when the character lands.
+    pub const fn with_reset_on_land(mut self, reset_on_land: bool) -> Self {
+        self.reset_on_land = reset_on_land;
+        self
+    }
+
+    /// Returns the number of charges available.
+    pub fn charges(&self) -> u32 {
+        self.charges
+    }
+
+    /// Returns the remaining time before the ability can be used again after a use, in seconds.
+    pub fn cooldown_remaining(&self) -> Scalar {
+        self.cooldown_remaining
+    }
+
+    /// Returns how far the next charge has recharged, from 0 to 1.
+    ///
+    /// Full abilities and abilities that don't recharge over time return 1.
+    pub fn recharge_fraction(&self) -> Scalar {
+        match self.recharge_time {
+            Some(recharge_time) if self.charges < self.max_charges && recharge_time > 0.0 => {
+                (self.recharge_progress / recharge_time).clamp(0.0, 1.0)
+            }
+            _ => 1.0,
+        }
+    }
+
+    /// Returns `true` if the ability has a charge and is not cooling down.
+    pub fn is_ready(&self) -> bool {
+        self.charges > 0 && self.cooldown_remaining <= 0.0
+    }
+
+    /// Uses a charge if the ability is ready, returning whether it was used.
+    pub fn try_use(&mut self) -> bool {
+        if !self.is_ready() {
+            return false;
+        }
+        self.charges -= 1;
+        self.cooldown_remaining = self.cooldown;
+        true
+    }
+
+    /// Restores all charges and clears the cooldown.
+    pub fn reset(&mut self) {
+        self.charges = self.max_charges;
+        self.recharge_progress = 0.0;
+        self.cooldown_remaining = 0.0;
+    }
+
+    /// Returns the time the next charge has been recharging for, in seconds.
+    pub(crate) fn recharge_progress(&self) -> Scalar {
+        self.recharge_progress
+    }
+
+    /// Restores the charges and timers, like from a saved game.
+    pub(crate) fn restore(
+        &mut self,
+        charges: u32,
+        recharge_progress: Scalar,
+        cooldown_remaining: Scalar,
+    ) {
+        self.charges = charges.min(self.max_charges);
+        self.recharge_progress = recharge_progress.max(0.0);
+        self.cooldown_remaining = cooldown_remaining.max(0.0);
+    }
+
+    /// Advances the cooldown and recharge timers.
+    fn tick(&mut self, delta_time: Scalar) {
+        self.cooldown_remaining = (self.cooldown_remaining - delta_time).max(0.0);
+
+        let Some(recharge_time) = self.recharge_time else {
+            return;
+        };
+        if self.charges >= self.max_charges {
+            self.recharge_progress = 0.0;
+            return;
+        }
+        self.recharge_progress += delta_time;
+        while self.recharge_progress >= recharge_time && self.charges < self.max_charges {
+            self.charges += 1;
+            self.recharge_progress -= recharge_time.max(Scalar::EPSILON);
+        }
+    }
+}
+
+/// A component holding tagged [`AbilityCooldown`]s for a character controller.
+#[derive(Component, Clone, Debug, Default)]
+pub struct AbilityCooldowns {
+    // A sorted map keeps the iteration order deterministic.
+    abilities: BTreeMap<String, AbilityCooldown>,
+    /// Whether the character was grounded last frame.
+    was_grounded: bool,
+}
+
+impl AbilityCooldowns {
+    /// Returns the cooldowns with an ability added, for use when spawning.
+    pub fn with(mut self, tag: impl Into<String>, cooldown: AbilityCooldown) -> Self {
+        self.insert(tag, cooldown);
+        self
+    }
+
+    /// Inserts an ability with the given tag, replacing any existing ability with the same tag.
+    pub fn insert(&mut self, tag: impl Into<String>, cooldown: AbilityCooldown) {
+        self.abilities.insert(tag.into(), cooldown);
+    }
+
+    /// Removes the ability with the given tag, returning it if it existed.
+    pub fn remove(&mut self, tag: &str) -> Option<AbilityCooldown> {
+        self.abilities.remove(tag)
+    }
+
+    /// Returns the ability with the given tag.
+    pub fn get(&self, tag: &str) -> Option<&AbilityCooldown> {
+        self.abilities.get(tag)
+    }
+
+    /// Returns the ability with the given tag mutably.
+    pub fn get_mut(&mut self, tag: &str) -> Option<&mut AbilityCooldown> {
+        self.abilities.get_mut(tag)
+    }
+
+    /// Returns `true` if the ability with the given tag exists and is ready.
+    pub fn is_ready(&self, tag: &str) -> bool {
+        self.get(tag).is_some_and(AbilityCooldown::is_ready)
+    }
+
+    /// Uses a charge of the ability with the given tag if it is ready, returning whether it was used.
+    ///
+    /// Abilities that don't exist can't be used.
+    pub fn try_use(&mut self, tag: &str) -> bool {
+        self.get_mut(tag).is_some_and(AbilityCooldown::try_use)
+    }
+
+    /// Restores the charges of all abilities and clears their cooldowns.
+    pub fn reset_all(&mut self) {
+        self.abilities.values_mut().for_each(AbilityCooldown::reset);
+    }
+
+    /// Returns an iterator over the tags and abilities, sorted by tag.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &AbilityCooldown)> {
+        self.abilities
+            .iter()
+            .map(|(tag, cooldown)| (tag.as_str(), cooldown))
+    }
+}
+
+/// Advances [`AbilityCooldowns`] and restores charges of abilities that reset on landing.
+pub(crate) fn tick_ability_cooldowns(
+    time: Res<Time<Virtual>>,
+    mut query: Query<(&mut AbilityCooldowns, Has<Grounded>), With<CharacterController>>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (mut cooldowns, is_grounded) in &mut query {
+        let landed = is_grounded && !cooldowns.was_grounded;
+        cooldowns.was_grounded = is_grounded;
+
+        for cooldown in cooldowns.abilities.values_mut() {
+            cooldown.tick(delta_time);
+            if landed && cooldown.reset_on_land {
+                cooldown.charges = cooldown.max_charges;
+                cooldown.recharge_progress = 0.0;
+            }
+        }
+    }
+}
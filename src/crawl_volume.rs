@@ -0,0 +1,148 @@
+//! Volumes that force character controllers to crouch or crawl, like vents and tunnels.
+//!
+//! A character controller that enters a [`CrawlVolume`] is forced into the stance of the
+//! volume and can't stand up or jump while inside. When it leaves, its previous stance is
+//! restored once there is room to stand up, so it never stands up inside low geometry.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{CharacterController, ControllerEnabled, Stance, StanceConfig, StanceHeight};
+
+/// A sensor volume that forces character controllers inside it into a lower [`Stance`].
+#[derive(Component, Clone, Copy, Debug)]
+#[require(Sensor)]
+pub struct CrawlVolume {
+    /// The stance characters are forced into, [`Stance::Crouching`] or [`Stance::Prone`].
+    pub stance: Stance,
+}
+
+impl Default for CrawlVolume {
+    fn default() -> Self {
+        Self {
+            stance: Stance::Crouching,
+        }
+    }
+}
+
+/// A component on a character controller inside a [`CrawlVolume`].
+///
+/// Jumping is blocked while this is present.
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct InCrawlVolume {
+    /// The crawl volume entity.
+    pub volume: Entity,
+    /// The stance the character is forced into.
+    pub stance: Stance,
+    /// The stance the character had before entering, restored when it leaves.
+    pub previous: Stance,
+}
+
+impl InCrawlVolume {
+    /// Returns the stance allowed for the requested one, which is never higher than the
+    /// forced stance.
+    pub fn limit(&self, requested: Stance) -> Stance {
+        match (self.stance, requested) {
+            (Stance::Prone, _) | (_, Stance::Prone) => Stance::Prone,
+            (Stance::Crouching, _) | (_, Stance::Crouching) => Stance::Crouching,
+            _ => Stance::Standing,
+        }
+    }
+}
+
+/// Forces character controllers inside [`CrawlVolume`]s into their stance, and restores
+/// the previous stance when they leave and have room to stand up.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_crawl_volumes(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    volumes: Query<(Entity, &CrawlVolume, &Collider, &Position, &Rotation)>,
+    colliders: Query<&ColliderOf>,
+    mut controllers: Query<
+        (
+            Entity,
+            &mut Stance,
+            Option<&InCrawlVolume>,
+            &StanceConfig,
+            Option<&StanceHeight>,
+            &Collider,
+            &Position,
+            &Rotation,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    let mut inside = Vec::<(Entity, Entity, Stance)>::new();
+    for (volume_entity, volume, collider, position, rotation) in &volumes {
+        let intersections = spatial_query.shape_intersections(
+            collider,
+            position.0,
+            rotation.0,
+            &SpatialQueryFilter::from_excluded_entities([volume_entity]),
+        );
+        for collider_entity in intersections {
+            let Ok(&ColliderOf { body }) = colliders.get(collider_entity) else {
+                continue;
+            };
+            if controllers.contains(body) && !inside.iter().any(|(entity, ..)| *entity == body) {
+                inside.push((body, volume_entity, volume.stance));
+            }
+        }
+    }
+
+    for &(entity, volume, stance) in &inside {
+        let Ok((_, mut current, in_volume, ..)) = controllers.get_mut(entity) else {
+            continue;
+        };
+        let previous = in_volume.map_or(*current, |in_volume| in_volume.previous);
+        let in_volume = InCrawlVolume {
+            volume,
+            stance,
+            previous,
+        };
+        let limited = in_volume.limit(*current);
+        if *current != limited {
+            *current = limited;
+        }
+        commands.entity(entity).insert(in_volume);
+    }
+
+    // Characters that left a volume get their previous stance back once there is room.
+    for (entity, mut stance, in_volume, config, height, collider, position, rotation) in
+        &mut controllers
+    {
+        let Some(in_volume) = in_volume else {
+            continue;
+        };
+        if inside.iter().any(|(inside, ..)| *inside == entity) {
+            continue;
+        }
+
+        let growth = config.height(in_volume.previous) - height.map_or(0.0, |height| height.0);
+        if growth > 0.0 {
+            // Use a slightly smaller version of the collider, like the ceiling detection.
+            let mut caster_shape = collider.clone();
+            caster_shape.set_scale(Vector::ONE * 0.99, 10);
+            let Ok(up) = Dir3::new((rotation * Vector::Y).f32()) else {
+                continue;
+            };
+            let blocked = spatial_query
+                .cast_shape(
+                    &caster_shape,
+                    position.0,
+                    rotation.0,
+                    up,
+                    &ShapeCastConfig::from_max_distance(growth),
+                    &SpatialQueryFilter::from_excluded_entities([entity, in_volume.volume]),
+                )
+                .is_some();
+            if blocked {
+                continue;
+            }
+        }
+
+        *stance = in_volume.previous;
+        commands.entity(entity).remove::<InCrawlVolume>();
+    }
+}
@@ -0,0 +1,188 @@
+//! An on-screen text overlay for diagnosing how a character controller moves.
+//!
+//! Shows the speed, velocity, grounded and slope state, movement mode, and last input
+//! of a character controller. Toggled with [`DebugOverlay::toggle_key`].
+
+use std::fmt::Write as _;
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    CharacterController, ControllerUp, FpsController, Grounded, MovementAction, Noclip, Parked,
+    Ragdoll, Seated, Stance, Submerged, WallContact, WorldUp,
+};
+
+/// Configures the debug overlay.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct DebugOverlay {
+    /// Whether the overlay is shown.
+    pub visible: bool,
+    /// The key that toggles the overlay.
+    pub toggle_key: KeyCode,
+    /// The character controller to inspect. If `None`, the possessed [`FpsController`]
+    /// is used, or the first character controller if there is none.
+    pub target: Option<Entity>,
+}
+
+impl Default for DebugOverlay {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            toggle_key: KeyCode::F3,
+            target: None,
+        }
+    }
+}
+
+/// A marker component for the text entity of the debug overlay.
+#[derive(Component)]
+pub struct DebugOverlayText;
+
+/// Toggles the [`DebugOverlay`] with its toggle key.
+pub(crate) fn toggle_debug_overlay(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+) {
+    if keyboard_input.just_pressed(overlay.toggle_key) {
+        overlay.visible = !overlay.visible;
+    }
+}
+
+/// Spawns, hides, and updates the debug overlay text.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_debug_overlay(
+    mut commands: Commands,
+    overlay: Res<DebugOverlay>,
+    world_up: Res<WorldUp>,
+    mut movement_reader: MessageReader<MovementAction>,
+    mut last_input: Local<Option<MovementAction>>,
+    mut texts: Query<(&mut Text, &mut Visibility), With<DebugOverlayText>>,
+    controllers: Query<
+        (
+            Entity,
+            &LinearVelocity,
+            &Rotation,
+            Option<&ShapeHits>,
+            Has<Grounded>,
+            Has<FpsController>,
+            Option<&Stance>,
+            Option<&Submerged>,
+            Option<&WallContact>,
+            (Has<Noclip>, Has<Ragdoll>, Has<Parked>, Has<Seated>),
+            Option<&ControllerUp>,
+        ),
+        With<CharacterController>,
+    >,
+) {
+    if let Some(action) = movement_reader.read().last() {
+        *last_input = Some(*action);
+    }
+
+    let Ok((mut text, mut visibility)) = texts.single_mut() else {
+        if overlay.visible {
+            commands.spawn((
+                DebugOverlayText,
+                Text::default(),
+                TextFont::from_font_size(14.0),
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(8.0),
+                    left: Val::Px(8.0),
+                    padding: UiRect::all(Val::Px(6.0)),
+                    ..default()
+                },
+                BackgroundColor(Color::BLACK.with_alpha(0.6)),
+            ));
+        }
+        return;
+    };
+
+    *visibility = if overlay.visible {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    if !overlay.visible {
+        return;
+    }
+
+    let controller = match overlay.target {
+        Some(target) => controllers.get(target).ok(),
+        None => controllers
+            .iter()
+            .find(|(.., is_possessed, _, _, _, _, _)| *is_possessed)
+            .or_else(|| controllers.iter().next()),
+    };
+    let Some((
+        entity,
+        linear_velocity,
+        rotation,
+        hits,
+        is_grounded,
+        _,
+        stance,
+        submerged,
+        wall_contact,
+        (noclip, ragdoll, parked, seated),
+        controller_up,
+    )) = controller
+    else {
+        text.0 = "No character controller".to_string();
+        return;
+    };
+
+    let velocity = linear_velocity.0;
+    let up = world_up.for_controller(controller_up);
+    let horizontal_speed = velocity.reject_from_normalized(up).length();
+    let slope = hits
+        .and_then(|hits| hits.iter().next())
+        .map(|hit| (rotation * -hit.normal2).angle_between(up).to_degrees());
+
+    let mode = if noclip {
+        "noclip"
+    } else if ragdoll {
+        "ragdoll"
+    } else if parked {
+        "vehicle"
+    } else if seated {
+        "seated"
+    } else if submerged.is_some_and(|submerged| submerged.fraction > 0.5) {
+        "swimming"
+    } else if wall_contact.is_some() && !is_grounded {
+        "wall"
+    } else if is_grounded {
+        "grounded"
+    } else {
+        "airborne"
+    };
+
+    let mut info = String::new();
+    let _ = writeln!(info, "Controller {entity}");
+    let _ = writeln!(
+        info,
+        "Speed: {:.2} (horizontal {horizontal_speed:.2})",
+        velocity.length()
+    );
+    let _ = writeln!(
+        info,
+        "Velocity: ({:.2}, {:.2}, {:.2})",
+        velocity.x, velocity.y, velocity.z
+    );
+    let _ = writeln!(info, "Grounded: {is_grounded}");
+    let _ = writeln!(
+        info,
+        "Slope: {}",
+        slope.map_or("-".to_string(), |slope| format!("{slope:.1}°"))
+    );
+    let _ = writeln!(info, "Mode: {mode}");
+    if let Some(stance) = stance {
+        let _ = writeln!(info, "Stance: {stance:?}");
+    }
+    let _ = write!(
+        info,
+        "Last input: {}",
+        last_input.map_or("-".to_string(), |action| format!("{action:?}"))
+    );
+    text.0 = info;
+}
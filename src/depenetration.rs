@@ -0,0 +1,236 @@
+//! Depenetration and stuck recovery for character controllers.
+//!
+//! The kinematic collision response only resolves shallow contacts. When a controller
+//! ends up deep inside geometry (crushed by a moving platform, teleported into a wall),
+//! this recovery pass searches for the nearest free space and nudges the character there
+//! over a few frames. If no free space is found in time, a [`CharacterStuck`] message is written.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{CharacterController, ControllerEnabled, ControllerUp, WorldUp};
+
+/// Enables stuck recovery for a character controller.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct StuckRecovery {
+    /// The scale of the collider used to detect deep penetration.
+    /// Overlaps of this shrunken shape mean the character is stuck,
+    /// while shallower contacts are left to the collision response.
+    pub detection_scale: Scalar,
+    /// How far from the current position free space is searched for.
+    pub search_radius: Scalar,
+    /// The number of distance steps tried within the search radius.
+    pub search_steps: u32,
+    /// The maximum speed at which the character is moved toward free space.
+    pub max_speed: Scalar,
+    /// The number of frames after which [`CharacterStuck`] is written
+    /// if the character is still stuck.
+    pub max_frames: u32,
+}
+
+impl Default for StuckRecovery {
+    fn default() -> Self {
+        Self {
+            detection_scale: 0.8,
+            search_radius: 1.5,
+            search_steps: 6,
+            max_speed: 6.0,
+            max_frames: 30,
+        }
+    }
+}
+
+/// A component indicating that a character controller is stuck inside geometry
+/// and is being moved toward free space.
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct Stuck {
+    /// The number of frames the character has been stuck for.
+    pub frames: u32,
+    /// The free position the character is moving toward, if one was found.
+    pub target: Option<Vector>,
+}
+
+/// A [`Message`] written when a character controller could not be freed
+/// within [`StuckRecovery::max_frames`].
+#[derive(Message, Clone, Copy, Debug)]
+pub struct CharacterStuck {
+    pub entity: Entity,
+    pub position: Vector,
+}
+
+/// Returns `true` if the collider doesn't overlap any non-sensor colliders
+/// other than those of the given entity.
+pub(crate) fn is_free_position(
+    spatial_query: &SpatialQuery,
+    sensors: &Query<(), With<Sensor>>,
+    collider: &Collider,
+    position: Vector,
+    rotation: Quaternion,
+    entity: Entity,
+) -> bool {
+    spatial_query
+        .shape_intersections(
+            collider,
+            position,
+            rotation,
+            &SpatialQueryFilter::from_excluded_entities([entity]),
+        )
+        .into_iter()
+        .all(|hit| sensors.contains(hit))
+}
+
+/// Searches for the nearest position within `search_radius` where the collider
+/// doesn't overlap any geometry. Positions along `up` are preferred at each distance.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn find_free_position(
+    spatial_query: &SpatialQuery,
+    sensors: &Query<(), With<Sensor>>,
+    collider: &Collider,
+    position: Vector,
+    rotation: Quaternion,
+    entity: Entity,
+    up: Vector,
+    search_radius: Scalar,
+    search_steps: u32,
+) -> Option<Vector> {
+    const HORIZONTAL_DIRECTIONS: usize = 8;
+
+    let (tangent, bitangent) = up.any_orthonormal_pair();
+    let steps = search_steps.max(1);
+    for step in 1..=steps {
+        let distance = search_radius * step as Scalar / steps as Scalar;
+
+        let above = std::iter::once(up);
+        let around = (0..HORIZONTAL_DIRECTIONS).flat_map(|i| {
+            let angle = TAU * i as Scalar / HORIZONTAL_DIRECTIONS as Scalar;
+            let horizontal = tangent * angle.cos() + bitangent * angle.sin();
+            [(horizontal + up).normalize(), horizontal]
+        });
+        let below = std::iter::once(-up);
+
+        for direction in above.chain(around).chain(below) {
+            let candidate = position + direction * distance;
+            if is_free_position(
+                spatial_query,
+                sensors,
+                collider,
+                candidate,
+                rotation,
+                entity,
+            ) {
+                return Some(candidate);
+            }
+        }
+    }
+
+    None
+}
+
+/// Detects character controllers stuck deep inside geometry and moves them toward free space.
+#[allow(clippy::type_complexity)]
+pub(crate) fn recover_stuck_controllers(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    spatial_query: SpatialQuery,
+    sensors: Query<(), With<Sensor>>,
+    world_up: Res<WorldUp>,
+    mut controllers: Query<
+        (
+            Entity,
+            &StuckRecovery,
+            &Collider,
+            &mut Position,
+            &Rotation,
+            &mut LinearVelocity,
+            Option<&mut Stuck>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+    mut stuck_writer: MessageWriter<CharacterStuck>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (
+        entity,
+        recovery,
+        collider,
+        mut position,
+        rotation,
+        mut linear_velocity,
+        stuck,
+        controller_up,
+    ) in &mut controllers
+    {
+        let mut detection_shape = collider.clone();
+        detection_shape.set_scale(Vector::ONE * recovery.detection_scale, 10);
+
+        let is_stuck = !is_free_position(
+            &spatial_query,
+            &sensors,
+            &detection_shape,
+            position.0,
+            rotation.0,
+            entity,
+        );
+
+        let Some(mut stuck) = stuck else {
+            if is_stuck {
+                commands.entity(entity).insert(Stuck {
+                    frames: 0,
+                    target: None,
+                });
+            }
+            continue;
+        };
+
+        // Shallow contacts that remain are resolved by the collision response.
+        if !is_stuck {
+            commands.entity(entity).remove::<Stuck>();
+            continue;
+        }
+
+        stuck.frames += 1;
+        if stuck.frames == recovery.max_frames {
+            stuck_writer.write(CharacterStuck {
+                entity,
+                position: position.0,
+            });
+        }
+
+        // The target may become blocked by moving geometry, so keep it up to date.
+        let target_is_free = stuck.target.is_some_and(|target| {
+            is_free_position(
+                &spatial_query,
+                &sensors,
+                collider,
+                target,
+                rotation.0,
+                entity,
+            )
+        });
+        if !target_is_free {
+            stuck.target = find_free_position(
+                &spatial_query,
+                &sensors,
+                collider,
+                position.0,
+                rotation.0,
+                entity,
+                world_up.for_controller(controller_up),
+                recovery.search_radius,
+                recovery.search_steps,
+            );
+        }
+
+        // Don't let the character accumulate velocity while stuck,
+        // which would make it explode out of the geometry or tunnel through it.
+        linear_velocity.0 = Vector::ZERO;
+
+        if let Some(target) = stuck.target {
+            let offset = target - position.0;
+            position.0 += offset.clamp_length_max(recovery.max_speed * delta_time);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Developer commands for the local character controller, enabled with the `dev_commands` feature.
+//!
+//! Any console can forward lines of text as [`DevCommandInput`] messages, for example from
+//! `bevy_console` or an egui text field. The commands manipulate the components of the local
+//! [`FpsController`] characters, and replies are written as [`DevCommandOutput`] messages.
+//!
+//! The crate doesn't integrate with any console itself, so the game needs a system that
+//! forwards submitted lines and shows the replies:
+//!
+//! ```ignore
+//! fn forward_console(
+//!     mut submitted: MessageReader<ConsoleLine>,
+//!     mut input_writer: MessageWriter<DevCommandInput>,
+//!     mut output_reader: MessageReader<DevCommandOutput>,
+//!     mut console: ResMut<Console>,
+//! ) {
+//!     for line in submitted.read() {
+//!         input_writer.write(DevCommandInput(line.text.clone()));
+//!     }
+//!     for DevCommandOutput(reply) in output_reader.read() {
+//!         console.print(reply);
+//!     }
+//! }
+//! ```
+//!
+//! Supported commands:
+//!
+//! - `setspeed <acceleration>`
+//! - `setgravity <y>` or `setgravity <x> <y> <z>`
+//! - `noclip`
+//! - `teleport <x> <y> <z>`
+//! - `god`
+
+use avian3d::math::*;
+use bevy::prelude::*;
+
+use crate::{
+    ControllerGravity, FpsController, MovementAcceleration, TeleportCharacter, ToggleNoclip,
+};
+
+/// A parsed developer command.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DevCommand {
+    /// Sets the [`MovementAcceleration`].
+    SetSpeed(Scalar),
+    /// Sets the [`ControllerGravity`].
+    SetGravity(Vector),
+    /// Toggles noclip mode.
+    Noclip,
+    /// Teleports to the nearest free position around a point.
+    Teleport(Vector),
+    /// Toggles [`GodMode`].
+    God,
+}
+
+impl DevCommand {
+    /// Parses a command from a line of text.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut parts = line.split_whitespace();
+        let Some(name) = parts.next() else {
+            return Err("empty command".to_string());
+        };
+        let args = parts
+            .map(|part| {
+                part.parse::<Scalar>()
+                    .map_err(|_| format!("invalid number `{part}`"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match (name.to_lowercase().as_str(), args.as_slice()) {
+            ("setspeed", &[speed]) => Ok(Self::SetSpeed(speed)),
+            ("setgravity", &[y]) => Ok(Self::SetGravity(Vector::new(0.0, y, 0.0))),
+            ("setgravity", &[x, y, z]) => Ok(Self::SetGravity(Vector::new(x, y, z))),
+            ("noclip", &[]) => Ok(Self::Noclip),
+            ("teleport", &[x, y, z]) => Ok(Self::Teleport(Vector::new(x, y, z))),
+            ("god", &[]) => Ok(Self::God),
+            ("setspeed", _) => Err("usage: setspeed <acceleration>".to_string()),
+            ("setgravity", _) => Err("usage: setgravity <y> | setgravity <x> <y> <z>".to_string()),
+            ("teleport", _) => Err("usage: teleport <x> <y> <z>".to_string()),
+            ("noclip" | "god", _) => Err(format!("`{name}` takes no arguments")),
+            _ => Err(format!("unknown command `{name}`")),
+        }
+    }
+}
+
+/// A marker component for characters in god mode, toggled with the `god` command.
+///
+/// Characters in god mode don't write [`HardImpact`](crate::HardImpact)s, so they take no
+/// crash damage. Other damage systems of the game can skip characters with it too.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct GodMode;
+
+/// A [`Message`] with a line of text to run as a [`DevCommand`].
+#[derive(Message, Clone, Debug)]
+pub struct DevCommandInput(pub String);
+
+/// A [`Message`] with the reply to a [`DevCommandInput`].
+#[derive(Message, Clone, Debug)]
+pub struct DevCommandOutput(pub String);
+
+/// Runs [`DevCommandInput`]s on the local [`FpsController`] characters.
+#[allow(clippy::type_complexity)]
+pub(crate) fn run_dev_commands(
+    mut commands: Commands,
+    mut input_reader: MessageReader<DevCommandInput>,
+    mut output_writer: MessageWriter<DevCommandOutput>,
+    mut noclip_writer: MessageWriter<ToggleNoclip>,
+    mut teleport_writer: MessageWriter<TeleportCharacter>,
+    mut characters: Query<
+        (
+            Entity,
+            &mut MovementAcceleration,
+            &mut ControllerGravity,
+            Has<GodMode>,
+        ),
+        With<FpsController>,
+    >,
+) {
+    for DevCommandInput(line) in input_reader.read() {
+        let command = match DevCommand::parse(line) {
+            Ok(command) => command,
+            Err(error) => {
+                output_writer.write(DevCommandOutput(error));
+                continue;
+            }
+        };
+
+        if characters.is_empty() {
+            output_writer.write(DevCommandOutput("no local character".to_string()));
+            continue;
+        }
+
+        for (entity, mut acceleration, mut gravity, is_god) in &mut characters {
+            let reply = match command {
+                DevCommand::SetSpeed(speed) => {
+                    acceleration.0 = speed;
+                    format!("speed set to {speed}")
+                }
+                DevCommand::SetGravity(new_gravity) => {
+                    gravity.0 = new_gravity;
+                    format!("gravity set to {new_gravity}")
+                }
+                DevCommand::Noclip => {
+                    noclip_writer.write(ToggleNoclip { entity });
+                    "noclip toggled".to_string()
+                }
+                DevCommand::Teleport(position) => {
+                    teleport_writer.write(TeleportCharacter { entity, position });
+                    format!("teleported to {position}")
+                }
+                DevCommand::God if is_god => {
+                    commands.entity(entity).remove::<GodMode>();
+                    "god mode off".to_string()
+                }
+                DevCommand::God => {
+                    commands.entity(entity).insert(GodMode);
+                    "god mode on".to_string()
+                }
+            };
+            output_writer.write(DevCommandOutput(reply));
+        }
+    }
+}
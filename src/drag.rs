@@ -0,0 +1,97 @@
+//! Terminal velocity and aerodynamic drag for falling character controllers.
+//!
+//! Movement damping only slows characters down perpendicular to their up direction, so falls
+//! accelerate indefinitely by default. [`MaxFallSpeed`] caps the fall speed, and
+//! [`AirDrag`] slows airborne characters down with quadratic drag, which reaches a
+//! terminal velocity smoothly. Raising the drag coefficient at runtime makes gliders
+//! and parachutes.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{CharacterController, ControllerEnabled, ControllerUp, Grounded, WorldUp};
+
+/// Caps the speed at which a character controller falls, in m/s.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct MaxFallSpeed(pub Scalar);
+
+impl Default for MaxFallSpeed {
+    fn default() -> Self {
+        Self(50.0)
+    }
+}
+
+/// Quadratic aerodynamic drag for airborne character controllers.
+///
+/// The drag deceleration is `coefficient * speed²`, opposite to the velocity.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct AirDrag {
+    /// The drag coefficient, in 1/m.
+    pub coefficient: Scalar,
+}
+
+impl AirDrag {
+    /// Creates drag that makes the character reach the given terminal speed under
+    /// gravity of the given strength.
+    pub fn from_terminal_speed(terminal_speed: Scalar, gravity: Scalar) -> Self {
+        Self {
+            coefficient: gravity / (terminal_speed * terminal_speed).max(Scalar::EPSILON),
+        }
+    }
+
+    /// Returns the terminal speed under gravity of the given strength.
+    pub fn terminal_speed(&self, gravity: Scalar) -> Scalar {
+        if self.coefficient > 0.0 {
+            (gravity / self.coefficient).sqrt()
+        } else {
+            Scalar::INFINITY
+        }
+    }
+}
+
+impl Default for AirDrag {
+    fn default() -> Self {
+        // A terminal speed of roughly 55 m/s under standard gravity, like a skydiver.
+        Self {
+            coefficient: 0.0032,
+        }
+    }
+}
+
+/// Slows down airborne character controllers with [`AirDrag`].
+pub(crate) fn apply_air_drag(
+    time: Res<Time<Virtual>>,
+    mut controllers: Query<
+        (&AirDrag, &mut LinearVelocity),
+        (
+            With<CharacterController>,
+            Without<Grounded>,
+            ControllerEnabled,
+        ),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (drag, mut linear_velocity) in &mut controllers {
+        // Integrated implicitly, so high speeds and coefficients can't overshoot.
+        let speed = linear_velocity.length();
+        linear_velocity.0 /= 1.0 + drag.coefficient.max(0.0) * speed * delta_time;
+    }
+}
+
+/// Caps the fall speed of character controllers with [`MaxFallSpeed`].
+pub(crate) fn limit_fall_speed(
+    world_up: Res<WorldUp>,
+    mut controllers: Query<
+        (&MaxFallSpeed, &mut LinearVelocity, Option<&ControllerUp>),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    for (max_fall_speed, mut linear_velocity, controller_up) in &mut controllers {
+        let up = world_up.for_controller(controller_up);
+        let fall_speed = -linear_velocity.dot(up);
+        if fall_speed > max_fall_speed.0 {
+            linear_velocity.0 += up * (fall_speed - max_fall_speed.0);
+        }
+    }
+}
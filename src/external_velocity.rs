@@ -0,0 +1,144 @@
+//! Velocity from external sources, kept separate from the velocity driven by input.
+//!
+//! Knockbacks, explosions, and conveyors that write to [`LinearVelocity`] directly are
+//! erased within a few frames by movement damping, which can't tell them apart from the
+//! movement of the character. Writing them to [`ExternalVelocity`] instead keeps them out
+//! of the damping: the external part decays at its own rate, and the rest of the velocity
+//! is exposed as [`InputVelocity`]. Both are summed into [`LinearVelocity`].
+//!
+//! The vertical part of external velocity is handed over to the controller immediately,
+//! so upward launches are slowed down by gravity like jumps. External velocity into the
+//! surfaces the character touches is removed, so a knockback into a wall stops at the wall.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{CharacterController, ControllerEnabled, ControllerUp, WorldUp};
+
+/// Velocity applied to a character controller from external sources, like knockbacks,
+/// explosions, moving platforms, and conveyors.
+///
+/// Add to [`ExternalVelocity::velocity`] for impulses, or set it every frame for
+/// constant pushes like conveyors, with a decay rate of 0.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(InputVelocity)]
+pub struct ExternalVelocity {
+    /// The external velocity, in m/s.
+    pub velocity: Vector,
+    /// The rate at which the external velocity decays exponentially, in 1/s.
+    pub decay_rate: Scalar,
+    /// The external velocity that is currently included in the [`LinearVelocity`].
+    applied: Vector,
+}
+
+impl ExternalVelocity {
+    /// Creates an external velocity that decays at the given rate, in 1/s.
+    pub fn new(decay_rate: Scalar) -> Self {
+        Self {
+            velocity: Vector::ZERO,
+            decay_rate,
+            applied: Vector::ZERO,
+        }
+    }
+
+    /// Adds an impulse to the external velocity, in m/s.
+    pub fn add(&mut self, velocity: Vector) {
+        self.velocity += velocity;
+    }
+
+    /// Returns the external velocity that is currently included in the [`LinearVelocity`].
+    pub fn applied(&self) -> Vector {
+        self.applied
+    }
+}
+
+impl Default for ExternalVelocity {
+    fn default() -> Self {
+        Self::new(3.0)
+    }
+}
+
+/// The part of the [`LinearVelocity`] of a character controller that isn't from its
+/// [`ExternalVelocity`], including movement, jumps, and gravity.
+///
+/// Updated by the plugin every frame. Changing it has no effect.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct InputVelocity(pub Vector);
+
+/// Decays the [`ExternalVelocity`] of character controllers and sums it with the
+/// [`InputVelocity`] into the [`LinearVelocity`].
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_external_velocity(
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    collisions: Collisions,
+    collider_rbs: Query<&ColliderOf, Without<Sensor>>,
+    mut controllers: Query<
+        (
+            &mut ExternalVelocity,
+            &mut InputVelocity,
+            &mut LinearVelocity,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    // The collision response stops the character at surfaces it is pushed into. Stop the
+    // external velocity there too, or taking it out of the stopped velocity would bounce
+    // the character back.
+    for contacts in collisions.iter() {
+        let Ok([&ColliderOf { body: rb1 }, &ColliderOf { body: rb2 }]) =
+            collider_rbs.get_many([contacts.collider1, contacts.collider2])
+        else {
+            continue;
+        };
+
+        for (character_entity, is_first) in [(rb1, true), (rb2, false)] {
+            let Ok((mut external, ..)) = controllers.get_mut(character_entity) else {
+                continue;
+            };
+
+            for manifold in contacts.manifolds.iter() {
+                let normal = if is_first {
+                    -manifold.normal
+                } else {
+                    manifold.normal
+                };
+                external.applied = clip_into_surface(external.applied, normal);
+                external.velocity = clip_into_surface(external.velocity, normal);
+            }
+        }
+    }
+
+    for (mut external, mut input_velocity, mut linear_velocity, controller_up) in &mut controllers {
+        let up = world_up.for_controller(controller_up);
+        let mut input = linear_velocity.0 - external.applied;
+
+        // Gravity already slows down vertical motion, so the controller takes it over.
+        let vertical = up * external.velocity.dot(up);
+        external.velocity -= vertical;
+        input += vertical;
+
+        let decay = (-external.decay_rate.max(0.0) * delta_time).exp();
+        external.velocity *= decay;
+        if external.velocity.length_squared() < 1e-6 {
+            external.velocity = Vector::ZERO;
+        }
+
+        external.applied = external.velocity;
+        input_velocity.0 = input;
+        linear_velocity.0 = input + external.velocity;
+    }
+}
+
+/// Removes the part of a velocity that points into a surface with the given normal.
+fn clip_into_surface(velocity: Vector, normal: Vector) -> Vector {
+    let into_surface = velocity.dot(normal);
+    if into_surface < 0.0 {
+        velocity - normal * into_surface
+    } else {
+        velocity
+    }
+}
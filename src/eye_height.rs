@@ -0,0 +1,196 @@
+//! Smooth eye height transitions for stance changes and landings.
+//!
+//! Without [`EyeHeight`], the camera is a fixed child of the controller, so it moves with the
+//! collider as the [`StanceHeight`] changes. With it, the camera or [`CameraPivot`] eases
+//! toward the eye height of the current [`Stance`] along a configurable curve, and dips
+//! briefly on landings. The eye height is measured from the feet and kept inside the current
+//! collider, so the view never clips through the ground or a low ceiling while the collider
+//! resizes.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    find_camera_pivot, CameraPivot, CharacterController, ControllerEnabled, ControllerUp, Grounded,
+    Stance, StanceConfig, StanceHeight, WorldUp,
+};
+
+/// Eases the camera of a character controller toward the eye height of its [`Stance`].
+///
+/// Requires a [`StanceConfig`] for the heights of the stances.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(EyeHeightState)]
+pub struct EyeHeight {
+    /// The distance of the eyes below the top of the collider in every stance.
+    pub offset_from_top: Scalar,
+    /// The curve of the transitions between stances.
+    pub ease: EaseFunction,
+    /// The duration of the transitions between stances, in seconds.
+    pub duration: Scalar,
+    /// The minimum distance between the eyes and the top and bottom of the collider.
+    pub clearance: Scalar,
+    /// How far the eyes dip on the hardest landings.
+    pub landing_dip: Scalar,
+    /// The fall speed at which landings dip the eyes by the full `landing_dip`, in m/s.
+    pub landing_dip_speed: Scalar,
+    /// How long the dip of a landing lasts, in seconds.
+    pub landing_dip_duration: Scalar,
+}
+
+impl Default for EyeHeight {
+    fn default() -> Self {
+        Self {
+            offset_from_top: 0.15,
+            ease: EaseFunction::CubicOut,
+            duration: 0.2,
+            clearance: 0.1,
+            landing_dip: 0.2,
+            landing_dip_speed: 15.0,
+            landing_dip_duration: 0.3,
+        }
+    }
+}
+
+impl EyeHeight {
+    /// Returns the eye height above the feet for the given stance.
+    pub fn for_stance(&self, config: &StanceConfig, stance: Stance) -> Scalar {
+        (config.height(stance) - self.offset_from_top).max(0.0)
+    }
+
+    /// Samples the easing curve at the given progress, from 0 to 1.
+    fn sample(&self, progress: Scalar) -> Scalar {
+        self.ease
+            .sample_clamped(progress.clamp(0.0, 1.0).f32())
+            .adjust_precision()
+    }
+}
+
+/// The transition and landing state of an [`EyeHeight`] character controller.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct EyeHeightState {
+    /// The current eye height above the feet, without the landing dip.
+    current: Option<Scalar>,
+    /// The eye height the current transition started from, and the target it eases to.
+    from: Scalar,
+    to: Scalar,
+    /// The time since the current transition started, in seconds.
+    elapsed: Scalar,
+    /// The full depth, the elapsed time, and the current offset of the landing dip.
+    dip_depth: Scalar,
+    dip_elapsed: Scalar,
+    dip: Scalar,
+    /// Whether the character was grounded last frame, and its fall speed while airborne.
+    was_grounded: bool,
+    fall_speed: Scalar,
+}
+
+impl EyeHeightState {
+    /// Returns the current eye height above the feet, including the landing dip.
+    pub fn eye_height(&self) -> Option<Scalar> {
+        self.current.map(|current| current - self.dip)
+    }
+}
+
+/// Eases the camera of [`EyeHeight`] characters toward the eye height of their stance, and
+/// dips it on landings.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_eye_height(
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    mut controllers: Query<
+        (
+            Entity,
+            &EyeHeight,
+            &mut EyeHeightState,
+            &StanceConfig,
+            &Stance,
+            Option<&StanceHeight>,
+            &LinearVelocity,
+            Has<Grounded>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+    children: Query<&Children>,
+    mut pivots: Query<
+        (&mut Transform, Has<CameraPivot>),
+        (
+            Or<(With<CameraPivot>, With<Camera3d>)>,
+            Without<CharacterController>,
+        ),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (
+        entity,
+        eye_height,
+        mut state,
+        config,
+        stance,
+        stance_height,
+        linear_velocity,
+        is_grounded,
+        controller_up,
+    ) in &mut controllers
+    {
+        let target = eye_height.for_stance(config, *stance);
+        let current = match state.current {
+            Some(current) => current,
+            None => {
+                state.from = target;
+                state.to = target;
+                state.elapsed = eye_height.duration;
+                target
+            }
+        };
+
+        // A new target restarts the transition from where the eyes are now
+        if state.to != target {
+            state.from = current;
+            state.to = target;
+            state.elapsed = 0.0;
+        }
+        state.elapsed += delta_time;
+        let progress = state.elapsed / eye_height.duration.max(Scalar::EPSILON);
+        let eased = state.from + (state.to - state.from) * eye_height.sample(progress);
+        state.current = Some(eased);
+
+        // Landing dips the eyes quickly, then eases them back up
+        let up = world_up.for_controller(controller_up);
+        if !is_grounded {
+            state.fall_speed = (-linear_velocity.dot(up)).max(0.0);
+        } else if !state.was_grounded && eye_height.landing_dip_speed > 0.0 {
+            let severity = (state.fall_speed / eye_height.landing_dip_speed).min(1.0);
+            state.dip_depth = severity * eye_height.landing_dip;
+            state.dip_elapsed = 0.0;
+            state.fall_speed = 0.0;
+        }
+        state.was_grounded = is_grounded;
+
+        if state.dip_elapsed < eye_height.landing_dip_duration && is_grounded {
+            state.dip_elapsed += delta_time;
+            let progress = state.dip_elapsed / eye_height.landing_dip_duration;
+            state.dip = if progress < 0.25 {
+                state.dip_depth * eye_height.sample(progress / 0.25)
+            } else {
+                state.dip_depth * (1.0 - eye_height.sample((progress - 0.25) / 0.75))
+            };
+        } else {
+            state.dip = 0.0;
+        }
+
+        // Keep the eyes inside the collider as it resizes, so the view doesn't clip
+        let height = stance_height.map_or(config.height(*stance), |height| height.0);
+        let clearance = eye_height.clearance.min(height * 0.5);
+        let eye = (eased - state.dip).clamp(clearance, height - clearance);
+
+        let Some(pivot) = find_camera_pivot(entity, &children, &pivots) else {
+            continue;
+        };
+        if let Ok((mut transform, _)) = pivots.get_mut(pivot) {
+            // The camera is a child of the controller, whose origin is the collider center
+            transform.translation.y = (eye - height * 0.5).f32();
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Third-person rotation that faces the movement direction.
+//!
+//! By default, look input turns the character, and movement is relative to where it
+//! faces. With [`FaceMovementDirection`], look input orbits the camera around the
+//! character instead, movement is relative to the camera through [`ViewYaw`], and the
+//! character smoothly turns toward the direction it moves in, like in most third-person
+//! games.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    find_camera_pivot, CameraPivot, CharacterController, ControllerEnabled, ControllerUp,
+    LockOnTarget, Pitch, WorldUp,
+};
+
+/// Turns a character controller toward its movement direction, while look input orbits
+/// the camera around it.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(ViewYaw)]
+pub struct FaceMovementDirection {
+    /// The maximum turn speed, in radians per second.
+    pub turn_speed: Scalar,
+    /// The minimum horizontal speed at which the character turns, in m/s.
+    pub min_speed: Scalar,
+}
+
+impl Default for FaceMovementDirection {
+    fn default() -> Self {
+        Self {
+            turn_speed: 10.0,
+            min_speed: 0.5,
+        }
+    }
+}
+
+/// The yaw of the view of a character controller whose facing isn't driven by look input,
+/// like with [`FaceMovementDirection`].
+///
+/// Look input rotates it instead of the character, and movement input is relative to it.
+/// Initialized from the rotation of the character when added.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct ViewYaw(pub Quaternion);
+
+impl Default for ViewYaw {
+    fn default() -> Self {
+        Self(Quaternion::IDENTITY)
+    }
+}
+
+/// Returns the rotation that movement input is relative to.
+pub(crate) fn input_rotation(rotation: &Rotation, view_yaw: Option<&ViewYaw>) -> Quaternion {
+    view_yaw.map_or(rotation.0, |view_yaw| view_yaw.0)
+}
+
+/// Returns the local rotation of the camera pivot of a character whose view is
+/// independent of its facing.
+pub(crate) fn orbit_rotation(
+    rotation: Quaternion,
+    view_yaw: Quaternion,
+    pitch: Scalar,
+) -> Quaternion {
+    rotation.inverse() * view_yaw * Quaternion::from_rotation_x(pitch)
+}
+
+/// Initializes the [`ViewYaw`] of a character controller from its rotation.
+pub(crate) fn init_view_yaw(add: On<Add, ViewYaw>, mut query: Query<(&Rotation, &mut ViewYaw)>) {
+    if let Ok((rotation, mut view_yaw)) = query.get_mut(add.entity) {
+        view_yaw.0 = rotation.0;
+    }
+}
+
+/// Turns [`FaceMovementDirection`] characters toward their movement direction, keeping
+/// their camera aligned with the view.
+#[allow(clippy::type_complexity)]
+pub(crate) fn face_movement_direction(
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    mut controllers: Query<
+        (
+            Entity,
+            &FaceMovementDirection,
+            &ViewYaw,
+            &Pitch,
+            &mut Rotation,
+            &LinearVelocity,
+            Has<LockOnTarget>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+    mut pivots: Query<
+        (&mut Transform, Has<CameraPivot>),
+        (
+            Or<(With<CameraPivot>, With<Camera3d>)>,
+            Without<CharacterController>,
+        ),
+    >,
+    children: Query<&Children>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (
+        entity,
+        face,
+        view_yaw,
+        pitch,
+        mut rotation,
+        linear_velocity,
+        is_locked_on,
+        controller_up,
+    ) in &mut controllers
+    {
+        let up = world_up.for_controller(controller_up);
+
+        // Locked-on characters face their target instead
+        let horizontal = linear_velocity.reject_from_normalized(up);
+        if horizontal.length() > face.min_speed && !is_locked_on {
+            let forward = (rotation.0 * Vector::NEG_Z).reject_from_normalized(up);
+            let direction = horizontal.normalize();
+
+            // Turn by the signed angle around the up axis, limited by the turn speed
+            let angle = forward
+                .cross(direction)
+                .dot(up)
+                .atan2(forward.dot(direction));
+            let max_turn = face.turn_speed * delta_time;
+            let turn = angle.clamp(-max_turn, max_turn);
+            rotation.0 = (Quaternion::from_axis_angle(up, turn) * rotation.0).normalize();
+        }
+
+        // The camera is a child of the character, so counter its rotation
+        if let Some(target) = find_camera_pivot(entity, &children, &pivots) {
+            if let Ok((mut pivot_transform, _)) = pivots.get_mut(target) {
+                pivot_transform.rotation =
+                    orbit_rotation(rotation.0, view_yaw.0, pitch.angle).f32();
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Helpers for rendering the body of a first-person character.
+//!
+//! A visible body makes first-person characters feel grounded, but the head and upper body
+//! clip into the camera. Parts marked [`HiddenInFirstPerson`] are moved, with all their
+//! mesh descendants, to the [`FIRST_PERSON_HIDDEN_LAYER`] render layer, which first-person
+//! cameras don't render. Lights with [`first_person_shadow_layers`] still cast shadows for
+//! them, so the full body shows up in shadows.
+//!
+//! [`FirstPersonBody`] keeps the body mesh facing the view of the controller, and bends a
+//! spine bone with the pitch, so the arms follow where the player looks.
+
+use avian3d::{math::*, prelude::*};
+use bevy::{camera::visibility::RenderLayers, prelude::*};
+
+use crate::{CharacterController, Pitch, ViewYaw};
+
+/// The render layer of body parts that are hidden from first-person cameras.
+pub const FIRST_PERSON_HIDDEN_LAYER: usize = 1;
+
+/// Returns the render layers for lights that cast shadows for hidden first-person body parts,
+/// the default layer and [`FIRST_PERSON_HIDDEN_LAYER`].
+pub fn first_person_shadow_layers() -> RenderLayers {
+    RenderLayers::from_layers(&[0, FIRST_PERSON_HIDDEN_LAYER])
+}
+
+/// A marker component for body parts, like the head, that are hidden from first-person
+/// cameras but still cast shadows.
+///
+/// Mesh descendants, like the meshes of a glTF scene, are hidden as they are spawned.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct HiddenInFirstPerson;
+
+/// Syncs a visible body mesh to the view of a first-person character controller.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(FirstPersonBodyState)]
+pub struct FirstPersonBody {
+    /// The root entity of the body mesh, a child of the controller, turned to face the view.
+    pub body: Entity,
+    /// The local rotation of the body mesh when it faces forward, for models that don't face
+    /// the negative Z axis.
+    pub body_rotation: Quat,
+    /// A spine bone that is bent with the pitch of the view, if any.
+    pub spine: Option<Entity>,
+    /// The fraction of the pitch applied to the spine bone.
+    pub spine_pitch_weight: Scalar,
+}
+
+impl FirstPersonBody {
+    /// Creates a first-person body with the given body mesh root and no spine bone.
+    pub fn new(body: Entity) -> Self {
+        Self {
+            body,
+            body_rotation: Quat::IDENTITY,
+            spine: None,
+            spine_pitch_weight: 0.5,
+        }
+    }
+
+    /// Sets the spine bone that is bent with the pitch of the view.
+    pub fn with_spine(mut self, spine: Entity) -> Self {
+        self.spine = Some(spine);
+        self
+    }
+}
+
+/// Tracks the pose of the spine bone of a [`FirstPersonBody`], so the pitch can be layered on
+/// top of animations.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct FirstPersonBodyState {
+    /// The spine rotation without the pitch.
+    spine_base: Quat,
+    /// The spine rotation written in the last update.
+    spine_written: Option<Quat>,
+}
+
+/// Moves the meshes of [`HiddenInFirstPerson`] body parts to the hidden render layer.
+pub(crate) fn hide_first_person_parts(
+    mut commands: Commands,
+    parts: Query<Entity, With<HiddenInFirstPerson>>,
+    children: Query<&Children>,
+    meshes: Query<Option<&RenderLayers>, With<Mesh3d>>,
+) {
+    let hidden = RenderLayers::layer(FIRST_PERSON_HIDDEN_LAYER);
+    for part in &parts {
+        for entity in std::iter::once(part).chain(children.iter_descendants(part)) {
+            if let Ok(layers) = meshes.get(entity) {
+                if layers != Some(&hidden) {
+                    commands.entity(entity).insert(hidden.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Turns [`FirstPersonBody`] meshes to the view yaw and bends their spine with the pitch.
+///
+/// Runs after animations, and layers the pitch on top of the current pose of the spine, so
+/// it also works with animated bones.
+#[allow(clippy::type_complexity)]
+pub(crate) fn sync_first_person_body(
+    mut controllers: Query<
+        (
+            &FirstPersonBody,
+            &mut FirstPersonBodyState,
+            &Rotation,
+            Option<&ViewYaw>,
+            Option<&Pitch>,
+        ),
+        With<CharacterController>,
+    >,
+    mut transforms: Query<&mut Transform, Without<CharacterController>>,
+) {
+    for (body, mut state, rotation, view_yaw, pitch) in &mut controllers {
+        // The body is a child of the controller, so turn it by the view relative to the body
+        if let Ok(mut transform) = transforms.get_mut(body.body) {
+            let yaw = view_yaw.map_or(Quaternion::IDENTITY, |view_yaw| {
+                rotation.0.inverse() * view_yaw.0
+            });
+            transform.rotation = yaw.f32() * body.body_rotation;
+        }
+
+        let (Some(spine), Some(pitch)) = (body.spine, pitch) else {
+            continue;
+        };
+        let Ok(mut transform) = transforms.get_mut(spine) else {
+            continue;
+        };
+
+        // Anything else writing the spine, like an animation, sets a new base pose
+        if state.spine_written != Some(transform.rotation) {
+            state.spine_base = transform.rotation;
+        }
+        let bend = Quaternion::from_rotation_x(pitch.angle * body.spine_pitch_weight);
+        transform.rotation = state.spine_base * bend.f32();
+        state.spine_written = Some(transform.rotation);
+    }
+}
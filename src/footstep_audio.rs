@@ -0,0 +1,151 @@
+//! Footstep and landing sounds, enabled with the `bevy_audio` feature.
+//!
+//! Sounds are looked up by [`SurfaceMaterial`] from a [`SurfaceSoundMap`] asset
+//! referenced by the [`FootstepSounds`] component of the character, and played
+//! as spatial audio at the position of the footstep. The
+//! [`SurfaceProfile`](crate::SurfaceProfile) of the ground can pick a different set of sounds.
+
+use avian3d::math::*;
+use bevy::{audio::Volume, platform::collections::HashMap, prelude::*};
+
+use crate::{CurrentSurface, Footstep, Landed, SurfaceMaterial};
+
+/// The sounds played for a single surface material.
+#[derive(Clone, Debug, Default, Reflect)]
+pub struct SurfaceSounds {
+    /// Footstep sounds, cycled through in order.
+    pub footsteps: Vec<Handle<AudioSource>>,
+    /// The sound played when landing. Falls back to a footstep sound if `None`.
+    pub landing: Option<Handle<AudioSource>>,
+}
+
+/// An asset mapping [`SurfaceMaterial`]s to [`SurfaceSounds`].
+#[derive(Asset, Clone, Debug, Default, Reflect)]
+pub struct SurfaceSoundMap {
+    /// Sounds keyed by the name of the [`SurfaceMaterial`].
+    pub surfaces: HashMap<String, SurfaceSounds>,
+    /// Sounds used for surfaces without a material, or with a material that isn't in the map.
+    pub fallback: SurfaceSounds,
+}
+
+impl SurfaceSoundMap {
+    /// Returns the sounds for the given material.
+    pub fn get(&self, material: Option<&SurfaceMaterial>) -> &SurfaceSounds {
+        material
+            .and_then(|material| self.surfaces.get(&material.0))
+            .unwrap_or(&self.fallback)
+    }
+
+    /// Returns the sounds for the given material, or for the footstep set of the
+    /// [`CurrentSurface`] profile if it has one.
+    pub fn get_for_surface(
+        &self,
+        material: Option<&SurfaceMaterial>,
+        surface: Option<&CurrentSurface>,
+    ) -> &SurfaceSounds {
+        match surface.and_then(|surface| surface.profile.footsteps.as_deref()) {
+            Some(footsteps) => self.surfaces.get(footsteps).unwrap_or(&self.fallback),
+            None => self.get(material),
+        }
+    }
+}
+
+/// Plays footstep and landing sounds for a character from a [`SurfaceSoundMap`].
+#[derive(Component, Clone, Debug)]
+pub struct FootstepSounds {
+    pub map: Handle<SurfaceSoundMap>,
+    /// The base volume of footstep sounds.
+    pub volume: Scalar,
+    /// The horizontal speed at which footsteps are played at the base volume.
+    pub reference_speed: Scalar,
+    /// The volume multiplier while crouching or prone.
+    pub crouch_volume: Scalar,
+    /// The fall speed at which landing sounds are played at the base volume.
+    pub reference_landing_speed: Scalar,
+    /// The index of the next footstep sound to play.
+    next: usize,
+}
+
+impl FootstepSounds {
+    /// Creates footstep sounds using the given [`SurfaceSoundMap`].
+    pub fn new(map: Handle<SurfaceSoundMap>) -> Self {
+        Self {
+            map,
+            volume: 1.0,
+            reference_speed: 7.0,
+            crouch_volume: 0.4,
+            reference_landing_speed: 10.0,
+            next: 0,
+        }
+    }
+
+    /// Returns the volume for a sound at the given speed relative to the reference speed.
+    fn volume_for(&self, speed: Scalar, reference_speed: Scalar, crouching: bool) -> f32 {
+        let crouch_volume = if crouching { self.crouch_volume } else { 1.0 };
+        let speed_volume = (speed / reference_speed.max(Scalar::EPSILON)).clamp(0.2, 1.5);
+        (self.volume * speed_volume * crouch_volume).f32()
+    }
+}
+
+/// Spawns a one-shot spatial sound at the given position.
+fn play_at(commands: &mut Commands, sound: Handle<AudioSource>, position: Vector, volume: f32) {
+    commands.spawn((
+        AudioPlayer::new(sound),
+        PlaybackSettings::DESPAWN
+            .with_volume(Volume::Linear(volume))
+            .with_spatial(true),
+        Transform::from_translation(position.f32()),
+    ));
+}
+
+/// Plays sounds for [`Footstep`] and [`Landed`] messages.
+pub(crate) fn play_footstep_sounds(
+    mut commands: Commands,
+    mut footstep_reader: MessageReader<Footstep>,
+    mut landed_reader: MessageReader<Landed>,
+    mut characters: Query<(&mut FootstepSounds, Option<&CurrentSurface>)>,
+    sound_maps: Res<Assets<SurfaceSoundMap>>,
+) {
+    for footstep in footstep_reader.read() {
+        let Ok((mut sounds, current_surface)) = characters.get_mut(footstep.entity) else {
+            continue;
+        };
+        let Some(map) = sound_maps.get(&sounds.map) else {
+            continue;
+        };
+        let surface = map.get_for_surface(footstep.material.as_ref(), current_surface);
+        if surface.footsteps.is_empty() {
+            continue;
+        }
+
+        let sound = surface.footsteps[sounds.next % surface.footsteps.len()].clone();
+        sounds.next = sounds.next.wrapping_add(1);
+
+        let volume = sounds.volume_for(footstep.speed, sounds.reference_speed, footstep.crouching);
+        play_at(&mut commands, sound, footstep.position, volume);
+    }
+
+    for landed in landed_reader.read() {
+        let Ok((sounds, current_surface)) = characters.get(landed.entity) else {
+            continue;
+        };
+        let Some(map) = sound_maps.get(&sounds.map) else {
+            continue;
+        };
+        let surface = map.get_for_surface(landed.material.as_ref(), current_surface);
+        let Some(sound) = surface
+            .landing
+            .clone()
+            .or_else(|| surface.footsteps.first().cloned())
+        else {
+            continue;
+        };
+
+        let volume = sounds.volume_for(
+            landed.impact_speed,
+            sounds.reference_landing_speed,
+            landed.crouching,
+        );
+        play_at(&mut commands, sound, landed.position, volume);
+    }
+}
@@ -0,0 +1,185 @@
+//! Footstep and landing events.
+//!
+//! Character controllers with a [`Footsteps`] component write a [`Footstep`] message
+//! every stride while walking on the ground, and a [`Landed`] message when they touch
+//! down after being airborne. The [`SurfaceMaterial`] of the ground is included so games
+//! can pick sounds and effects per surface.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{CharacterController, ControllerEnabled, ControllerUp, Grounded, Stance, WorldUp};
+
+/// Enables [`Footstep`] and [`Landed`] messages for a character controller.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(FootstepState)]
+pub struct Footsteps {
+    /// The horizontal distance traveled on the ground between footsteps.
+    pub stride_length: Scalar,
+    /// The minimum horizontal speed for footsteps to be counted.
+    pub min_speed: Scalar,
+    /// The minimum fall speed for a [`Landed`] message to be written.
+    pub min_landing_speed: Scalar,
+}
+
+impl Default for Footsteps {
+    fn default() -> Self {
+        Self {
+            stride_length: 1.6,
+            min_speed: 0.5,
+            min_landing_speed: 1.0,
+        }
+    }
+}
+
+/// Tracks the stride and airborne state of a [`Footsteps`] character.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct FootstepState {
+    /// The distance traveled since the last footstep.
+    distance: Scalar,
+    /// Whether the character was grounded last frame.
+    was_grounded: bool,
+    /// The downward speed of the character while it was airborne.
+    fall_speed: Scalar,
+}
+
+/// The material of a surface, used to pick footstep sounds and effects.
+///
+/// Can be added to either the collider or its rigid body.
+#[derive(Component, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SurfaceMaterial(pub String);
+
+/// A [`Message`] written for every stride a [`Footsteps`] character takes on the ground.
+#[derive(Message, Clone, Debug)]
+pub struct Footstep {
+    pub entity: Entity,
+    /// The collider entity of the ground.
+    pub surface: Entity,
+    /// The material of the ground, if it has a [`SurfaceMaterial`].
+    pub material: Option<SurfaceMaterial>,
+    pub position: Vector,
+    /// The horizontal speed of the character.
+    pub speed: Scalar,
+    /// Whether the character is crouching or prone.
+    pub crouching: bool,
+}
+
+/// A [`Message`] written when a [`Footsteps`] character lands after being airborne.
+#[derive(Message, Clone, Debug)]
+pub struct Landed {
+    pub entity: Entity,
+    /// The collider entity of the ground.
+    pub surface: Entity,
+    /// The material of the ground, if it has a [`SurfaceMaterial`].
+    pub material: Option<SurfaceMaterial>,
+    pub position: Vector,
+    /// The downward speed of the character just before landing.
+    pub impact_speed: Scalar,
+    /// Whether the character is crouching or prone.
+    pub crouching: bool,
+}
+
+/// Writes [`Footstep`] and [`Landed`] messages for [`Footsteps`] characters.
+#[allow(clippy::type_complexity)]
+pub(crate) fn detect_footsteps(
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    mut footstep_writer: MessageWriter<Footstep>,
+    mut landed_writer: MessageWriter<Landed>,
+    mut query: Query<
+        (
+            Entity,
+            &Footsteps,
+            &mut FootstepState,
+            &ShapeHits,
+            &Position,
+            &LinearVelocity,
+            Has<Grounded>,
+            Option<&Stance>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+    materials: Query<&SurfaceMaterial>,
+    collider_rbs: Query<&ColliderOf>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    // Materials can be set on the collider or on its rigid body.
+    let get_material = |entity: Entity| {
+        materials
+            .get(entity)
+            .ok()
+            .or_else(|| {
+                collider_rbs
+                    .get(entity)
+                    .ok()
+                    .and_then(|collider_of| materials.get(collider_of.body).ok())
+            })
+            .cloned()
+    };
+
+    for (
+        entity,
+        footsteps,
+        mut state,
+        hits,
+        position,
+        linear_velocity,
+        is_grounded,
+        stance,
+        controller_up,
+    ) in &mut query
+    {
+        let up = world_up.for_controller(controller_up);
+        let was_grounded = state.was_grounded;
+        state.was_grounded = is_grounded;
+
+        if !is_grounded {
+            state.fall_speed = (-linear_velocity.dot(up)).max(0.0);
+            continue;
+        }
+
+        let Some(hit) = hits.iter().next() else {
+            continue;
+        };
+        let crouching = stance.is_some_and(|stance| *stance != Stance::Standing);
+
+        if !was_grounded {
+            // Start a new stride when touching down.
+            state.distance = 0.0;
+            if state.fall_speed >= footsteps.min_landing_speed {
+                landed_writer.write(Landed {
+                    entity,
+                    surface: hit.entity,
+                    material: get_material(hit.entity),
+                    position: position.0,
+                    impact_speed: state.fall_speed,
+                    crouching,
+                });
+            }
+            state.fall_speed = 0.0;
+            continue;
+        }
+
+        let speed = linear_velocity.reject_from_normalized(up).length();
+        if speed < footsteps.min_speed {
+            continue;
+        }
+
+        state.distance += speed * delta_time;
+        if state.distance < footsteps.stride_length {
+            continue;
+        }
+        state.distance -= footsteps.stride_length;
+
+        footstep_writer.write(Footstep {
+            entity,
+            surface: hit.entity,
+            material: get_material(hit.entity),
+            position: position.0,
+            speed,
+            crouching,
+        });
+    }
+}
@@ -0,0 +1,200 @@
+//! Recording and replaying ghosts for time trials and tutorial demonstrations.
+//!
+//! A [`GhostRecorder`] on a character controller samples its position and rotation into a
+//! [`GhostTrack`], and a [`GhostPlayback`] replays a track onto a ghost entity by moving its
+//! [`Transform`]. Ghosts should not have colliders, so they never interact with the player.
+//! Recording and playback are synchronized by [`RaceStarted`] and [`RaceFinished`] messages.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+/// A single sample of a [`GhostTrack`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GhostSample {
+    /// The time since the start of the race, in seconds.
+    pub time: Scalar,
+    pub position: Vector,
+    pub rotation: Quaternion,
+}
+
+/// A recorded sequence of [`GhostSample`]s, sorted by time.
+#[derive(Clone, Debug, Default)]
+pub struct GhostTrack {
+    pub samples: Vec<GhostSample>,
+}
+
+impl GhostTrack {
+    /// Returns the duration of the track in seconds.
+    pub fn duration(&self) -> Scalar {
+        self.samples.last().map_or(0.0, |sample| sample.time)
+    }
+
+    /// Returns the interpolated position and rotation at the given time,
+    /// or `None` if the track is empty.
+    pub fn sample(&self, time: Scalar) -> Option<(Vector, Quaternion)> {
+        let first = self.samples.first()?;
+        if time <= first.time {
+            return Some((first.position, first.rotation));
+        }
+
+        // Find the first sample after the given time.
+        let next_index = self.samples.partition_point(|sample| sample.time <= time);
+        let Some(next) = self.samples.get(next_index) else {
+            let last = self.samples.last()?;
+            return Some((last.position, last.rotation));
+        };
+        let previous = &self.samples[next_index - 1];
+
+        let span = (next.time - previous.time).max(Scalar::EPSILON);
+        let t = (time - previous.time) / span;
+        Some((
+            previous.position.lerp(next.position, t),
+            previous.rotation.slerp(next.rotation, t),
+        ))
+    }
+}
+
+/// Records the position and rotation of a character controller into a [`GhostTrack`]
+/// between [`RaceStarted`] and [`RaceFinished`].
+#[derive(Component, Clone, Debug)]
+pub struct GhostRecorder {
+    /// The recorded track. Cleared when a new race starts.
+    pub track: GhostTrack,
+    /// The time between samples, in seconds.
+    pub sample_interval: Scalar,
+    /// The time since the start of the race, or `None` if not recording.
+    elapsed: Option<Scalar>,
+}
+
+impl Default for GhostRecorder {
+    fn default() -> Self {
+        Self {
+            track: GhostTrack::default(),
+            sample_interval: 1.0 / 30.0,
+            elapsed: None,
+        }
+    }
+}
+
+impl GhostRecorder {
+    /// Returns `true` if the recorder is currently recording.
+    pub fn is_recording(&self) -> bool {
+        self.elapsed.is_some()
+    }
+}
+
+/// Replays a [`GhostTrack`] onto the [`Transform`] of a ghost entity, starting at [`RaceStarted`].
+#[derive(Component, Clone, Debug)]
+pub struct GhostPlayback {
+    pub track: GhostTrack,
+    /// Whether the track restarts from the beginning when it ends.
+    pub looping: bool,
+    /// The time since the start of the race, or `None` if waiting for the race to start.
+    elapsed: Option<Scalar>,
+}
+
+impl GhostPlayback {
+    pub fn new(track: GhostTrack) -> Self {
+        Self {
+            track,
+            looping: false,
+            elapsed: None,
+        }
+    }
+
+    /// Makes the playback restart from the beginning when the track ends.
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    /// Returns `true` if the playback is running.
+    pub fn is_playing(&self) -> bool {
+        self.elapsed.is_some()
+    }
+}
+
+/// A [`Message`] that starts all [`GhostRecorder`]s and [`GhostPlayback`]s.
+#[derive(Message, Clone, Copy, Debug, Default)]
+pub struct RaceStarted;
+
+/// A [`Message`] that stops all [`GhostRecorder`]s and [`GhostPlayback`]s.
+#[derive(Message, Clone, Copy, Debug, Default)]
+pub struct RaceFinished;
+
+/// Records ghost tracks for [`GhostRecorder`] characters.
+pub(crate) fn record_ghosts(
+    time: Res<Time<Virtual>>,
+    mut started_reader: MessageReader<RaceStarted>,
+    mut finished_reader: MessageReader<RaceFinished>,
+    mut recorders: Query<(&mut GhostRecorder, &Position, &Rotation)>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    let started = started_reader.read().count() > 0;
+    let finished = finished_reader.read().count() > 0;
+
+    for (mut recorder, position, rotation) in &mut recorders {
+        if started {
+            recorder.track.samples.clear();
+            recorder.elapsed = Some(0.0);
+        } else if let Some(elapsed) = &mut recorder.elapsed {
+            *elapsed += delta_time;
+        }
+
+        let Some(elapsed) = recorder.elapsed else {
+            continue;
+        };
+
+        let last_time = recorder.track.samples.last().map(|sample| sample.time);
+        if finished || last_time.is_none_or(|last| elapsed - last >= recorder.sample_interval) {
+            recorder.track.samples.push(GhostSample {
+                time: elapsed,
+                position: position.0,
+                rotation: rotation.0,
+            });
+        }
+
+        if finished {
+            recorder.elapsed = None;
+        }
+    }
+}
+
+/// Moves [`GhostPlayback`] entities along their tracks.
+pub(crate) fn play_ghosts(
+    time: Res<Time<Virtual>>,
+    mut started_reader: MessageReader<RaceStarted>,
+    mut finished_reader: MessageReader<RaceFinished>,
+    mut ghosts: Query<(&mut GhostPlayback, &mut Transform)>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    let started = started_reader.read().count() > 0;
+    let finished = finished_reader.read().count() > 0;
+
+    for (mut playback, mut transform) in &mut ghosts {
+        if started {
+            playback.elapsed = Some(0.0);
+        } else if finished {
+            playback.elapsed = None;
+        } else if let Some(elapsed) = &mut playback.elapsed {
+            *elapsed += delta_time;
+        }
+
+        let Some(mut elapsed) = playback.elapsed else {
+            continue;
+        };
+
+        let duration = playback.track.duration();
+        if playback.looping && duration > 0.0 && elapsed > duration {
+            elapsed = elapsed.rem_euclid(duration);
+            playback.elapsed = Some(elapsed);
+        }
+
+        if let Some((position, rotation)) = playback.track.sample(elapsed) {
+            transform.translation = position.f32();
+            transform.rotation = rotation.f32();
+        }
+    }
+}
@@ -0,0 +1,351 @@
+//! Gravity fields that pull character controllers toward points and axes.
+//!
+//! A [`GravityField`] is a sensor volume that overrides the [`ControllerGravity`] of
+//! [`AffectedByGravityFields`] characters inside it. A point field pulls toward its
+//! center, like a tiny planet, and a cylinder field pulls toward its axis, or pushes away
+//! from it with a negative strength, like the floor of a rotating station. The character
+//! smoothly turns its feet toward the gravity, and its [`ControllerUp`] follows, so walking,
+//! jumping, and looking around work on any side of the field.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    CharacterController, ControllerEnabled, ControllerGravity, ControllerUp, ViewYaw, WorldUp,
+};
+
+/// The shape of the pull of a [`GravityField`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GravityFieldShape {
+    /// Pulls toward the center of the field.
+    Point,
+    /// Pulls toward the axis through the center of the field, in the local space of the field.
+    Cylinder { axis: Vector },
+}
+
+/// A sensor volume that overrides the gravity of [`AffectedByGravityFields`] character
+/// controllers inside it.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(Sensor)]
+pub struct GravityField {
+    pub shape: GravityFieldShape,
+    /// The gravitational acceleration at the reference radius, in m/s².
+    ///
+    /// Negative values push away from the center or axis.
+    pub strength: Scalar,
+    /// The distance from the center or axis at which the full strength applies.
+    pub radius: Scalar,
+    /// How quickly the strength falls off with distance beyond the radius.
+    ///
+    /// At 0, the strength is constant, and at 2 it falls off with the inverse square of the
+    /// distance, like real gravity.
+    pub falloff: Scalar,
+    /// Fields with a higher priority take precedence where fields overlap.
+    pub priority: i32,
+}
+
+impl GravityField {
+    /// Creates a field pulling toward its center with a constant strength.
+    pub fn point(strength: Scalar) -> Self {
+        Self {
+            shape: GravityFieldShape::Point,
+            strength,
+            radius: 1.0,
+            falloff: 0.0,
+            priority: 0,
+        }
+    }
+
+    /// Creates a field pulling toward its local axis with a constant strength.
+    pub fn cylinder(axis: Vector, strength: Scalar) -> Self {
+        Self {
+            shape: GravityFieldShape::Cylinder {
+                axis: axis.normalize_or_zero(),
+            },
+            ..Self::point(strength)
+        }
+    }
+
+    /// Sets the falloff exponent of the strength beyond the given radius.
+    pub fn with_falloff(mut self, radius: Scalar, falloff: Scalar) -> Self {
+        self.radius = radius;
+        self.falloff = falloff;
+        self
+    }
+
+    /// Sets the priority of the field where fields overlap.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Returns the gravitational acceleration at a point, given the transform of the field.
+    pub fn acceleration(&self, position: Vector, rotation: Quaternion, point: Vector) -> Vector {
+        let offset = point - position;
+        let offset = match self.shape {
+            GravityFieldShape::Point => offset,
+            GravityFieldShape::Cylinder { axis } => offset.reject_from_normalized(rotation * axis),
+        };
+        let distance = offset.length();
+        if distance <= Scalar::EPSILON {
+            return Vector::ZERO;
+        }
+
+        let scale = if distance > self.radius && self.radius > 0.0 {
+            (self.radius / distance).powf(self.falloff)
+        } else {
+            1.0
+        };
+        -offset / distance * self.strength * scale
+    }
+}
+
+/// Makes a character controller follow the [`GravityField`]s it is inside.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct AffectedByGravityFields {
+    /// How quickly the character turns its feet toward the gravity, in radians per second.
+    pub align_speed: Scalar,
+}
+
+impl Default for AffectedByGravityFields {
+    fn default() -> Self {
+        Self { align_speed: TAU }
+    }
+}
+
+/// A component on a character controller inside a [`GravityField`].
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct InGravityField {
+    /// The gravity field entity.
+    pub field: Entity,
+    /// The gravity of the character before it entered the field, restored when it leaves.
+    pub default_gravity: Vector,
+}
+
+/// Applies [`GravityField`]s to the gravity and up direction of character controllers.
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_gravity_fields(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    spatial_query: SpatialQuery,
+    fields: Query<(Entity, &GravityField, &Collider, &Position, &Rotation)>,
+    colliders: Query<&ColliderOf>,
+    mut controllers: Query<
+        (
+            Entity,
+            &AffectedByGravityFields,
+            &Position,
+            &mut Rotation,
+            &mut ControllerGravity,
+            Option<&InGravityField>,
+            Option<&mut ControllerUp>,
+            Option<&mut ViewYaw>,
+        ),
+        (
+            With<CharacterController>,
+            Without<GravityField>,
+            ControllerEnabled,
+        ),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    // The field with the highest priority for each controller inside one
+    let mut inside = Vec::<(Entity, Entity, i32)>::new();
+    for (field_entity, field, collider, position, rotation) in &fields {
+        let intersections = spatial_query.shape_intersections(
+            collider,
+            position.0,
+            rotation.0,
+            &SpatialQueryFilter::from_excluded_entities([field_entity]),
+        );
+        for collider_entity in intersections {
+            let Ok(&ColliderOf { body }) = colliders.get(collider_entity) else {
+                continue;
+            };
+            if !controllers.contains(body) {
+                continue;
+            }
+            match inside.iter_mut().find(|(entity, ..)| *entity == body) {
+                Some(entry) if entry.2 < field.priority => {
+                    *entry = (body, field_entity, field.priority)
+                }
+                Some(_) => {}
+                None => inside.push((body, field_entity, field.priority)),
+            }
+        }
+    }
+
+    for (
+        entity,
+        affected,
+        position,
+        mut rotation,
+        mut gravity,
+        in_field,
+        controller_up,
+        mut view_yaw,
+    ) in &mut controllers
+    {
+        let field = inside
+            .iter()
+            .find(|(body, ..)| *body == entity)
+            .and_then(|(_, field, _)| fields.get(*field).ok());
+
+        let target_up = match field {
+            Some((field_entity, field, _, field_position, field_rotation)) => {
+                let default_gravity =
+                    in_field.map_or(gravity.0, |in_field| in_field.default_gravity);
+                commands.entity(entity).insert(InGravityField {
+                    field: field_entity,
+                    default_gravity,
+                });
+                let acceleration =
+                    field.acceleration(field_position.0, field_rotation.0, position.0);
+                gravity.0 = acceleration;
+                (-acceleration).try_normalize()
+            }
+            None => {
+                // Leaving a field restores the gravity and turns back to the world up
+                if let Some(in_field) = in_field {
+                    gravity.0 = in_field.default_gravity;
+                    commands.entity(entity).remove::<InGravityField>();
+                }
+                if controller_up.is_none() {
+                    continue;
+                }
+                Some(world_up.0)
+            }
+        };
+        let Some(target_up) = target_up else {
+            continue;
+        };
+
+        // Turn the feet toward the gravity, limited by the align speed
+        turn_toward_up(
+            &mut rotation,
+            view_yaw.as_deref_mut(),
+            target_up,
+            affected.align_speed * delta_time,
+        );
+        sync_controller_up(
+            &mut commands,
+            entity,
+            controller_up,
+            rotation.0 * Vector::Y,
+            world_up.0,
+            field.is_some(),
+        );
+    }
+}
+
+/// Turns the body up of a character controller toward the target up by at most `max_angle`,
+/// turning the view yaw along with it.
+pub(crate) fn turn_toward_up(
+    rotation: &mut Rotation,
+    view_yaw: Option<&mut ViewYaw>,
+    target_up: Vector,
+    max_angle: Scalar,
+) {
+    let body_up = rotation.0 * Vector::Y;
+    let angle = body_up.angle_between(target_up);
+    if angle <= Scalar::EPSILON {
+        return;
+    }
+    let arc = Quaternion::from_rotation_arc(body_up, target_up);
+    let turn = Quaternion::IDENTITY.slerp(arc, (max_angle / angle).min(1.0));
+    rotation.0 = (turn * rotation.0).normalize();
+    if let Some(view_yaw) = view_yaw {
+        view_yaw.0 = (turn * view_yaw.0).normalize();
+    }
+}
+
+/// Sets the [`ControllerUp`] of a character controller to its body up while `active`, and
+/// removes it once the body is back on the world up afterwards.
+pub(crate) fn sync_controller_up(
+    commands: &mut Commands,
+    entity: Entity,
+    controller_up: Option<Mut<ControllerUp>>,
+    up: Vector,
+    world_up: Vector,
+    active: bool,
+) {
+    match controller_up {
+        None if !active => {}
+        Some(_) if !active && up.angle_between(world_up) <= 1.0e-3 => {
+            commands.entity(entity).remove::<ControllerUp>();
+        }
+        Some(mut controller_up) => controller_up.0 = up,
+        None => {
+            commands.entity(entity).insert(ControllerUp(up));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: Vector, expected: Vector) {
+        assert!(
+            actual.abs_diff_eq(expected, 1e-4),
+            "expected {expected}, but got {actual}"
+        );
+    }
+
+    #[test]
+    fn point_fields_pull_toward_their_center() {
+        let field = GravityField::point(10.0);
+        let position = Vector::new(1.0, 2.0, 3.0);
+
+        let acceleration =
+            field.acceleration(position, Quaternion::IDENTITY, position + Vector::Y * 3.0);
+        assert_near(acceleration, Vector::NEG_Y * 10.0);
+
+        // Negative strengths push away, and the center itself has no pull.
+        let repelling = GravityField::point(-10.0);
+        let acceleration =
+            repelling.acceleration(position, Quaternion::IDENTITY, position + Vector::X);
+        assert_near(acceleration, Vector::X * 10.0);
+        assert_near(
+            field.acceleration(position, Quaternion::IDENTITY, position),
+            Vector::ZERO,
+        );
+    }
+
+    #[test]
+    fn strength_falls_off_beyond_the_radius() {
+        let field = GravityField::point(10.0).with_falloff(2.0, 2.0);
+
+        // Inside the radius the full strength applies.
+        let inside = field.acceleration(Vector::ZERO, Quaternion::IDENTITY, Vector::Y);
+        assert_near(inside, Vector::NEG_Y * 10.0);
+
+        // At twice the radius, the inverse square falloff leaves a quarter.
+        let outside = field.acceleration(Vector::ZERO, Quaternion::IDENTITY, Vector::Y * 4.0);
+        assert_near(outside, Vector::NEG_Y * 2.5);
+    }
+
+    #[test]
+    fn cylinder_fields_ignore_offsets_along_their_axis() {
+        let field = GravityField::cylinder(Vector::X, 10.0);
+
+        let acceleration = field.acceleration(
+            Vector::ZERO,
+            Quaternion::IDENTITY,
+            Vector::new(5.0, 0.0, 3.0),
+        );
+        assert_near(acceleration, Vector::NEG_Z * 10.0);
+
+        // The axis is rotated with the field, so here it points along Y.
+        let rotation = Quaternion::from_rotation_z(PI * 0.5);
+        let acceleration = field.acceleration(Vector::ZERO, rotation, Vector::new(0.0, 5.0, 3.0));
+        assert_near(acceleration, Vector::NEG_Z * 10.0);
+
+        // Points on the axis have no pull.
+        let acceleration = field.acceleration(Vector::ZERO, Quaternion::IDENTITY, Vector::X * 5.0);
+        assert_near(acceleration, Vector::ZERO);
+    }
+}
@@ -0,0 +1,371 @@
+//! Rail grinding for character controllers.
+//!
+//! Characters with a [`RailGrinder`] lock onto a [`GrindRail`] when they fall onto it.
+//! While [`Grinding`], the regular movement systems are skipped: the character slides
+//! along the rail with its momentum, sped up and slowed down by gravity and rail friction,
+//! and optionally has to keep its balance with sideways movement input. Jumping detaches
+//! the character with a push in the direction of the movement input, and so does reaching
+//! the end of the rail or losing balance.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    input_rotation, CharacterController, ControllerEnabled, ControllerGravity, ControllerUp,
+    FpsController, GatheredMovementInput, JumpImpulse, Jumped, MovementInput, Noclip, Ragdoll,
+    ViewYaw, WorldUp,
+};
+
+/// A rail that [`RailGrinder`] characters can grind on, as a polyline in local space.
+#[derive(Component, Clone, Debug)]
+pub struct GrindRail {
+    points: Vec<Vector>,
+    /// The distance along the rail at each point.
+    distances: Vec<Scalar>,
+    /// How close the feet of a falling character must get to the rail to lock on.
+    pub snap_distance: Scalar,
+    /// The deceleration from friction while grinding, in m/s².
+    pub friction: Scalar,
+}
+
+impl GrindRail {
+    /// Creates a rail through the given local points.
+    pub fn new(points: impl IntoIterator<Item = Vector>) -> Self {
+        let points: Vec<Vector> = points.into_iter().collect();
+        let mut distances = Vec::with_capacity(points.len());
+        let mut distance = 0.0;
+        for (i, point) in points.iter().enumerate() {
+            if i > 0 {
+                distance += point.distance(points[i - 1]);
+            }
+            distances.push(distance);
+        }
+        Self {
+            points,
+            distances,
+            snap_distance: 0.3,
+            friction: 1.0,
+        }
+    }
+
+    /// Creates a smooth rail through the given local control points with a Catmull-Rom
+    /// spline, sampled with the given number of segments between each pair of points.
+    pub fn catmull_rom(control_points: &[Vector], segments: usize) -> Self {
+        let segments = segments.max(1);
+        let count = control_points.len();
+        let point = |i: isize| control_points[i.clamp(0, count as isize - 1) as usize];
+
+        let mut points = Vec::new();
+        for i in 0..count.saturating_sub(1) as isize {
+            let (p0, p1, p2, p3) = (point(i - 1), point(i), point(i + 1), point(i + 2));
+            for step in 0..segments {
+                let t = step as Scalar / segments as Scalar;
+                let (t2, t3) = (t * t, t * t * t);
+                points.push(
+                    0.5 * (2.0 * p1
+                        + (p2 - p0) * t
+                        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+                        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3),
+                );
+            }
+        }
+        points.extend(control_points.last());
+        Self::new(points)
+    }
+
+    /// Sets how close the feet of a falling character must get to the rail to lock on.
+    pub fn with_snap_distance(mut self, snap_distance: Scalar) -> Self {
+        self.snap_distance = snap_distance;
+        self
+    }
+
+    /// Sets the deceleration from friction while grinding, in m/s².
+    pub fn with_friction(mut self, friction: Scalar) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    /// Returns the local points of the rail.
+    pub fn points(&self) -> &[Vector] {
+        &self.points
+    }
+
+    /// Returns the total length of the rail.
+    pub fn length(&self) -> Scalar {
+        self.distances.last().copied().unwrap_or(0.0)
+    }
+
+    /// Returns the local position and direction at the given distance along the rail.
+    pub fn sample(&self, distance: Scalar) -> Option<(Vector, Vector)> {
+        let segment = self
+            .distances
+            .windows(2)
+            .position(|window| distance <= window[1])
+            .unwrap_or(self.distances.len().checked_sub(2)?);
+        let (start, end) = (self.points[segment], self.points[segment + 1]);
+        let length = self.distances[segment + 1] - self.distances[segment];
+        let t = if length > 0.0 {
+            ((distance - self.distances[segment]) / length).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        Some((start.lerp(end, t), (end - start).normalize_or_zero()))
+    }
+
+    /// Returns the distance along the rail of the point closest to the given local point,
+    /// and the distance between the two.
+    pub fn closest(&self, point: Vector) -> Option<(Scalar, Scalar)> {
+        self.points
+            .windows(2)
+            .enumerate()
+            .map(|(i, window)| {
+                let segment = window[1] - window[0];
+                let t = ((point - window[0]).dot(segment) / segment.length_squared().max(1e-6))
+                    .clamp(0.0, 1.0);
+                let closest = window[0] + segment * t;
+                (
+                    self.distances[i] + segment.length() * t,
+                    closest.distance(point),
+                )
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+}
+
+/// Lets a character controller lock onto [`GrindRail`]s.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct RailGrinder {
+    /// The horizontal speed added in the direction of the movement input when jumping off a rail.
+    pub jump_side_speed: Scalar,
+    /// Makes grinding require balance, or `None` to stay balanced automatically.
+    pub balance: Option<RailBalance>,
+}
+
+impl Default for RailGrinder {
+    fn default() -> Self {
+        Self {
+            jump_side_speed: 4.0,
+            balance: None,
+        }
+    }
+}
+
+/// Balance settings for a [`RailGrinder`].
+///
+/// The balance tips over faster and faster unless it is corrected with sideways movement
+/// input, and the character falls off the rail when it reaches 1 in either direction.
+#[derive(Clone, Copy, Debug)]
+pub struct RailBalance {
+    /// How quickly the balance tips over, in 1/s.
+    pub instability: Scalar,
+    /// How quickly full sideways input corrects the balance, in 1/s.
+    pub correction: Scalar,
+}
+
+impl Default for RailBalance {
+    fn default() -> Self {
+        Self {
+            instability: 1.5,
+            correction: 3.0,
+        }
+    }
+}
+
+/// A component on a character controller that is grinding on a rail.
+///
+/// The regular movement systems skip grinding characters.
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct Grinding {
+    /// The [`GrindRail`] entity.
+    pub rail: Entity,
+    /// The distance along the rail.
+    pub distance: Scalar,
+    /// The speed along the rail. Negative values move towards its start.
+    pub speed: Scalar,
+    /// The balance from -1 to 1, where 0 is balanced. Always 0 without [`RailBalance`].
+    pub balance: Scalar,
+    /// The height of the character origin above its feet.
+    feet_offset: Scalar,
+}
+
+/// Locks falling [`RailGrinder`] characters onto [`GrindRail`]s.
+#[allow(clippy::type_complexity)]
+pub(crate) fn attach_to_rails(
+    mut commands: Commands,
+    world_up: Res<WorldUp>,
+    characters: Query<
+        (
+            Entity,
+            &Position,
+            &ColliderAabb,
+            &LinearVelocity,
+            Option<&ControllerUp>,
+        ),
+        (
+            With<RailGrinder>,
+            With<CharacterController>,
+            ControllerEnabled,
+        ),
+    >,
+    rails: Query<(Entity, &GrindRail, &GlobalTransform)>,
+) {
+    for (entity, position, aabb, linear_velocity, controller_up) in &characters {
+        let up = world_up.for_controller(controller_up);
+
+        // Only lock on when falling onto a rail, not when jumping off of it.
+        if linear_velocity.dot(up) > 0.0 {
+            continue;
+        }
+
+        let feet_offset = ((aabb.max - aabb.min) * 0.5).dot(up.abs());
+        let feet = position.0 - up * feet_offset;
+
+        for (rail_entity, rail, transform) in &rails {
+            let local_feet = transform
+                .affine()
+                .inverse()
+                .transform_point3(feet.f32())
+                .adjust_precision();
+            let Some((distance, offset)) = rail.closest(local_feet) else {
+                continue;
+            };
+            // Falling past the ends of a rail doesn't lock on.
+            if offset > rail.snap_distance || distance <= 0.0 || distance >= rail.length() {
+                continue;
+            }
+            let Some((_, local_direction)) = rail.sample(distance) else {
+                continue;
+            };
+
+            // Keep the momentum along the rail.
+            let direction = transform
+                .affine()
+                .transform_vector3(local_direction.f32())
+                .adjust_precision()
+                .normalize_or_zero();
+            commands.entity(entity).insert(Grinding {
+                rail: rail_entity,
+                distance,
+                speed: linear_velocity.dot(direction),
+                balance: 0.0,
+                feet_offset,
+            });
+            break;
+        }
+    }
+}
+
+/// Moves [`Grinding`] characters along their rails and detaches them.
+#[allow(clippy::type_complexity)]
+pub(crate) fn grind_rails(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    gathered: Res<GatheredMovementInput>,
+    mut characters: Query<
+        (
+            Entity,
+            &RailGrinder,
+            &mut Grinding,
+            &mut Position,
+            &mut LinearVelocity,
+            &Rotation,
+            &ControllerGravity,
+            &JumpImpulse,
+            Option<&FpsController>,
+            Option<&ViewYaw>,
+            Option<&ControllerUp>,
+        ),
+        (Without<Ragdoll>, Without<Noclip>),
+    >,
+    rails: Query<(&GrindRail, &GlobalTransform)>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (
+        entity,
+        grinder,
+        mut grinding,
+        mut position,
+        mut linear_velocity,
+        rotation,
+        gravity,
+        jump_impulse,
+        fps_controller,
+        view_yaw,
+        controller_up,
+    ) in &mut characters
+    {
+        let up = world_up.for_controller(controller_up);
+        let Ok((rail, transform)) = rails.get(grinding.rail) else {
+            // The rail was despawned.
+            commands.entity(entity).remove::<Grinding>();
+            continue;
+        };
+
+        let MovementInput {
+            direction: input,
+            jump,
+            ..
+        } = gathered.get(entity, fps_controller);
+
+        let Some((_, local_direction)) = rail.sample(grinding.distance) else {
+            commands.entity(entity).remove::<Grinding>();
+            continue;
+        };
+        let direction = transform
+            .affine()
+            .transform_vector3(local_direction.f32())
+            .adjust_precision()
+            .normalize_or_zero();
+
+        if jump {
+            // Jump off with a push in the direction of the movement input.
+            let side = (input_rotation(rotation, view_yaw) * Vector::new(input.x, 0.0, -input.y))
+                .reject_from_normalized(up);
+            linear_velocity.0 =
+                direction * grinding.speed + up * jump_impulse.0 + side * grinder.jump_side_speed;
+            commands.entity(entity).remove::<Grinding>();
+            commands.trigger(Jumped { entity });
+            continue;
+        }
+
+        // Gravity speeds up the character downhill, and friction slows it down.
+        grinding.speed += gravity.0.dot(direction) * delta_time;
+        let friction = rail.friction * delta_time;
+        grinding.speed = grinding.speed.signum() * (grinding.speed.abs() - friction).max(0.0);
+        grinding.distance += grinding.speed * delta_time;
+
+        // The balance tips over unless corrected with sideways input.
+        let mut fell_off = false;
+        if let Some(balance) = grinder.balance {
+            let wobble = (grinding.distance * 1.7).sin() * 0.1;
+            grinding.balance += (grinding.balance + wobble) * balance.instability * delta_time;
+            grinding.balance -= input.x * balance.correction * delta_time;
+            fell_off = grinding.balance.abs() >= 1.0;
+        }
+
+        // Leave the rail at its ends, or when falling off.
+        if fell_off || grinding.distance <= 0.0 || grinding.distance >= rail.length() {
+            linear_velocity.0 = direction * grinding.speed;
+            if fell_off {
+                // Tip over to the side the balance was lost to.
+                let side = direction.cross(up).normalize_or_zero() * grinding.balance.signum();
+                linear_velocity.0 += side * 2.0;
+            }
+            commands.entity(entity).remove::<Grinding>();
+            continue;
+        }
+
+        // Follow the rail exactly, so errors don't accumulate.
+        let Some((local_point, _)) = rail.sample(grinding.distance) else {
+            continue;
+        };
+        let point = transform
+            .transform_point(local_point.f32())
+            .adjust_precision();
+        position.0 = point + up * grinding.feet_offset;
+        linear_velocity.0 = direction * grinding.speed;
+    }
+}
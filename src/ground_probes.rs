@@ -0,0 +1,181 @@
+//! Multi-sample grounded detection with hysteresis.
+//!
+//! The default grounded check uses a single shape cast, which can flicker between grounded
+//! and airborne on ledges, ramp transitions, and seams between meshes. [`GroundProbes`]
+//! adds a ring of downward rays around the center of the character, any of which can
+//! support it, and keeps the character [`Grounded`] for a short time after losing support.
+
+use avian3d::{math::*, prelude::*};
+use bevy::{log::tracing::field::Empty, prelude::*};
+
+use crate::{
+    CharacterController, ControllerEnabled, ControllerUp, DropThrough, Grounded, MaxSlopeAngle,
+    WalkableSurfaces, WorldUp,
+};
+
+/// Configures multi-sample grounded detection for a character controller.
+///
+/// The controller is grounded if its ground [`ShapeCaster`] or any of the probe rays
+/// hits walkable ground.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(GroundProbeState)]
+pub struct GroundProbes {
+    /// The number of rays in the ring around the center ray.
+    pub ring_count: u32,
+    /// The radius of the ring relative to the horizontal half extents of the collider.
+    pub ring_scale: Scalar,
+    /// How far below the bottom of the collider the ground is detected.
+    pub max_distance: Scalar,
+    /// How long the character stays grounded after losing support, in seconds.
+    ///
+    /// Moving upwards, like when jumping, ends the grace period immediately. This only bridges
+    /// brief gaps in support. Use [`CoyoteTime`](crate::CoyoteTime) for jumping off ledges.
+    pub ungrounded_delay: Scalar,
+}
+
+impl Default for GroundProbes {
+    fn default() -> Self {
+        Self {
+            ring_count: 8,
+            ring_scale: 0.9,
+            max_distance: 0.2,
+            ungrounded_delay: 0.08,
+        }
+    }
+}
+
+impl GroundProbes {
+    /// Returns the probe ray origins in the plane perpendicular to `up`, relative to the center.
+    fn offsets(&self, half_extents: Vector, up: Vector) -> impl Iterator<Item = Vector> + '_ {
+        let (tangent, bitangent) = up.any_orthonormal_pair();
+        let radius = half_extents
+            .dot(tangent.abs())
+            .min(half_extents.dot(bitangent.abs()))
+            * self.ring_scale;
+        let step = TAU / self.ring_count.max(1) as Scalar;
+
+        std::iter::once(Vector::ZERO).chain((0..self.ring_count).map(move |i| {
+            let angle = i as Scalar * step;
+            (tangent * angle.cos() + bitangent * angle.sin()) * radius
+        }))
+    }
+}
+
+/// The hysteresis state of [`GroundProbes`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct GroundProbeState {
+    /// How long the character has been without support, in seconds.
+    pub unsupported_time: Scalar,
+    /// The number of probe rays that hit walkable ground in the last update.
+    pub supporting_probes: u32,
+}
+
+/// Updates the [`Grounded`] status of character controllers with [`GroundProbes`].
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_probed_grounded(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    spatial_query: SpatialQuery,
+    walkable: WalkableSurfaces,
+    world_up: Res<WorldUp>,
+    mut query: Query<
+        (
+            Entity,
+            &GroundProbes,
+            &mut GroundProbeState,
+            &ShapeHits,
+            &Position,
+            &Rotation,
+            &ColliderAabb,
+            &LinearVelocity,
+            Has<Grounded>,
+            Option<&MaxSlopeAngle>,
+            Option<&DropThrough>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    let span = info_span!("update_probed_grounded", controllers = Empty, rays = Empty).entered();
+    let (mut controllers, mut rays) = (0, 0);
+
+    for (
+        entity,
+        probes,
+        mut state,
+        hits,
+        position,
+        rotation,
+        aabb,
+        linear_velocity,
+        was_grounded,
+        max_slope_angle,
+        drop_through,
+        controller_up,
+    ) in &mut query
+    {
+        let up = world_up.for_controller(controller_up);
+        let Ok(down) = Dir3::new(-up.f32()) else {
+            continue;
+        };
+        // Slopes are measured against the up direction of the controller
+        let align = controller_up.map_or(Quaternion::IDENTITY, |controller_up| {
+            Quaternion::from_rotation_arc(controller_up.0, world_up.0)
+        });
+
+        let cast_supported = hits.iter().any(|hit| {
+            walkable.is_walkable(
+                hit.entity,
+                align * (rotation * -hit.normal2),
+                max_slope_angle,
+                drop_through,
+            )
+        });
+
+        // Rays start at the height of the center so that they can't begin inside the ground.
+        let half_extents = (aabb.max - aabb.min) * 0.5;
+        let max_distance = half_extents.dot(up.abs()) + probes.max_distance;
+        let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+        controllers += 1;
+        rays += probes.ring_count + 1;
+
+        state.supporting_probes = probes
+            .offsets(half_extents, up)
+            .filter(|offset| {
+                spatial_query
+                    .cast_ray(position.0 + *offset, down, max_distance, true, &filter)
+                    .is_some_and(|hit| {
+                        walkable.is_walkable(
+                            hit.entity,
+                            align * hit.normal,
+                            max_slope_angle,
+                            drop_through,
+                        )
+                    })
+            })
+            .count() as u32;
+
+        let supported = cast_supported || state.supporting_probes > 0;
+        if supported {
+            state.unsupported_time = 0.0;
+        } else {
+            state.unsupported_time += delta_time;
+        }
+
+        // Stay grounded briefly after losing support, unless moving upwards.
+        let in_grace_period = was_grounded
+            && linear_velocity.dot(up) <= 0.0
+            && state.unsupported_time < probes.ungrounded_delay;
+
+        if supported || in_grace_period {
+            commands.entity(entity).insert(Grounded);
+        } else {
+            commands.entity(entity).remove::<Grounded>();
+        }
+    }
+
+    span.record("controllers", controllers);
+    span.record("rays", rays);
+}
@@ -0,0 +1,154 @@
+//! Time-sliced ground checks for large crowds of character controllers.
+//!
+//! Every character controller casts its ground shape every physics step by default. In
+//! worlds with hundreds of NPCs, these spatial queries add up. With more than one bucket in
+//! the [`GroundCheckSlicing`] resource, controllers without an input-enabled
+//! [`FpsController`] are spread round-robin across the buckets, and only one bucket casts
+//! each physics step. Between casts, the grounded state is estimated from the last cast and
+//! how far the character has moved along the up direction since, so jumping off the ground is
+//! still noticed right away. The local player is always checked every step.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{CharacterController, ControllerUp, FpsController, GroundProbes, WorldUp};
+
+/// Spreads the ground checks of NPC character controllers across frames.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct GroundCheckSlicing {
+    /// The number of buckets. Each NPC is checked every `buckets` physics steps, and 1 checks
+    /// every controller every step.
+    pub buckets: u32,
+    /// The number of physics steps since slicing started, used to pick the bucket to check.
+    step: u32,
+}
+
+impl Default for GroundCheckSlicing {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl GroundCheckSlicing {
+    /// Creates slicing with the given number of buckets.
+    pub const fn new(buckets: u32) -> Self {
+        Self { buckets, step: 0 }
+    }
+}
+
+/// The time-sliced ground check state of an NPC character controller.
+///
+/// Inserted automatically while [`GroundCheckSlicing`] has more than one bucket.
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct SlicedGroundCheck {
+    /// The bucket the controller is checked in.
+    bucket: u32,
+    /// Whether the ground was cast since the grounded state was last updated.
+    fresh: bool,
+    /// Whether the controller was grounded at the last cast.
+    grounded: bool,
+    /// The distance to the walkable ground at the last cast.
+    ground_distance: Scalar,
+    /// How far the controller has moved along the up direction since the last cast.
+    rise: Scalar,
+    /// The maximum distance of the ground cast.
+    max_distance: Scalar,
+}
+
+impl SlicedGroundCheck {
+    /// Returns `true` if the ground was cast since the grounded state was last updated.
+    pub fn is_fresh(&self) -> bool {
+        self.fresh
+    }
+
+    /// Records the result of a fresh ground cast.
+    pub(crate) fn record(&mut self, ground_distance: Option<Scalar>) {
+        self.grounded = ground_distance.is_some();
+        self.ground_distance = ground_distance.unwrap_or(0.0);
+        self.fresh = false;
+    }
+
+    /// Estimates whether the controller is grounded from the last cast.
+    ///
+    /// Controllers that weren't grounded stay airborne until the next cast, since the cast
+    /// didn't find any ground within reach to land on.
+    pub(crate) fn estimate_grounded(&self) -> bool {
+        self.grounded && self.ground_distance + self.rise <= self.max_distance
+    }
+}
+
+/// Picks the controllers whose ground is cast in this physics step, and tracks the movement
+/// of sliced controllers between casts.
+///
+/// Runs at the start of the step, before Avian casts the enabled ground casters.
+#[allow(clippy::type_complexity)]
+pub(crate) fn slice_ground_checks(
+    mut commands: Commands,
+    time: Res<Time>,
+    world_up: Res<WorldUp>,
+    mut slicing: ResMut<GroundCheckSlicing>,
+    mut controllers: Query<
+        (
+            Entity,
+            &mut ShapeCaster,
+            &LinearVelocity,
+            Option<&mut SlicedGroundCheck>,
+            Option<&FpsController>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, Without<GroundProbes>),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    let buckets = slicing.buckets.max(1);
+    slicing.step = slicing.step.wrapping_add(1);
+    let bucket = slicing.step % buckets;
+
+    for (entity, mut ground_caster, linear_velocity, slice, fps_controller, controller_up) in
+        &mut controllers
+    {
+        // The local player is always checked every step
+        let is_player = fps_controller.is_some_and(|controller| controller.enable_input);
+        if buckets <= 1 || is_player {
+            if slice.is_some() {
+                ground_caster.enabled = true;
+                commands.entity(entity).remove::<SlicedGroundCheck>();
+            }
+            continue;
+        }
+
+        let Some(mut slice) = slice else {
+            // The caster is still enabled, so the first estimate starts from a real cast
+            commands.entity(entity).insert(SlicedGroundCheck {
+                bucket: entity.index() % buckets,
+                fresh: true,
+                grounded: false,
+                ground_distance: 0.0,
+                rise: 0.0,
+                max_distance: ground_caster.max_distance,
+            });
+            continue;
+        };
+
+        slice.bucket %= buckets;
+        slice.max_distance = ground_caster.max_distance;
+        let up = world_up.for_controller(controller_up);
+        slice.rise += linear_velocity.dot(up) * delta_time;
+
+        ground_caster.enabled = slice.bucket == bucket;
+    }
+}
+
+/// Marks the sliced controllers whose ground was cast in this physics step as fresh.
+pub(crate) fn mark_sliced_ground_casts(
+    mut controllers: Query<(&ShapeCaster, &mut SlicedGroundCheck)>,
+) {
+    for (ground_caster, mut slice) in &mut controllers {
+        if ground_caster.enabled {
+            slice.fresh = true;
+            slice.rise = 0.0;
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Events for high-speed impacts into floors and walls.
+//!
+//! Character controllers with [`HardImpacts`] write a [`HardImpact`] message when they hit a
+//! surface faster than a threshold, measured along the surface normal, so a glancing slide
+//! along a wall doesn't count. Games can use them for crash damage, screen shake, and stuns.
+//! Impacts are detected in the physics schedule before the collision response removes the
+//! velocity into the surface. Characters in god mode, from the `dev_commands` feature, don't
+//! have impacts.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    passes_through_one_way, CharacterController, ControllerEnabled, ControllerUp, DropThrough,
+    MaxSlopeAngle, OneWayPlatform, WorldUp,
+};
+
+/// Whether a [`HardImpact`] was into a floor, a wall, or a ceiling.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HardImpactKind {
+    /// A surface that isn't steeper than the [`MaxSlopeAngle`] of the character.
+    Floor,
+    Wall,
+    /// A surface facing down.
+    Ceiling,
+}
+
+/// A [`Message`] written when a [`HardImpacts`] character hits a surface at high speed.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct HardImpact {
+    pub entity: Entity,
+    /// The speed into the surface along its normal, in m/s.
+    pub speed: Scalar,
+    /// The normal of the surface, pointing toward the character.
+    pub normal: Vector,
+    pub kind: HardImpactKind,
+    /// The collider entity of the surface.
+    pub surface: Entity,
+}
+
+/// Enables [`HardImpact`] messages for a character controller.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct HardImpacts {
+    /// The minimum speed into floors for an impact, in m/s.
+    pub floor_speed: Scalar,
+    /// The minimum speed into walls and ceilings for an impact, in m/s.
+    pub wall_speed: Scalar,
+    /// Whether impacts remove all velocity into the surface right away, instead of leaving
+    /// it to the collision response, which only removes what would penetrate the surface
+    /// within the next step.
+    pub stop_on_impact: bool,
+}
+
+impl Default for HardImpacts {
+    fn default() -> Self {
+        Self {
+            floor_speed: 15.0,
+            wall_speed: 10.0,
+            stop_on_impact: false,
+        }
+    }
+}
+
+/// Writes [`HardImpact`] messages for [`HardImpacts`] characters that hit surfaces too fast.
+///
+/// Only the strongest impact of a character is written each physics step.
+#[allow(clippy::type_complexity)]
+pub(crate) fn detect_hard_impacts(
+    collisions: Collisions,
+    world_up: Res<WorldUp>,
+    time: Res<Time>,
+    mut impact_writer: MessageWriter<HardImpact>,
+    collider_rbs: Query<&ColliderOf, Without<Sensor>>,
+    mut characters: Query<
+        (
+            &HardImpacts,
+            &mut LinearVelocity,
+            Option<&MaxSlopeAngle>,
+            Option<&DropThrough>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+    one_way_platforms: Query<(), With<OneWayPlatform>>,
+    #[cfg(feature = "dev_commands")] god_mode: Query<(), With<crate::GodMode>>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+    let mut impacts: Vec<HardImpact> = Vec::new();
+
+    for contacts in collisions.iter() {
+        let Ok([&ColliderOf { body: rb1 }, &ColliderOf { body: rb2 }]) =
+            collider_rbs.get_many([contacts.collider1, contacts.collider2])
+        else {
+            continue;
+        };
+
+        for (character_entity, other_entity, other_collider, is_first) in [
+            (rb1, rb2, contacts.collider2, true),
+            (rb2, rb1, contacts.collider1, false),
+        ] {
+            let Ok((impacts_config, mut linear_velocity, max_slope_angle, drop_through, up)) =
+                characters.get_mut(character_entity)
+            else {
+                continue;
+            };
+            let up = world_up.for_controller(up);
+
+            #[cfg(feature = "dev_commands")]
+            if god_mode.contains(character_entity) {
+                continue;
+            }
+
+            // One-way platforms that the character passes through aren't hit
+            let is_one_way = one_way_platforms.contains(other_collider)
+                || one_way_platforms.contains(other_entity);
+            if is_one_way
+                && (drop_through
+                    .is_some_and(|drop_through| drop_through.platform == other_collider)
+                    || passes_through_one_way(contacts, is_first, &linear_velocity, up))
+            {
+                continue;
+            }
+
+            let max_slope = max_slope_angle.map_or(PI * 0.25, |angle| angle.0);
+
+            for manifold in contacts.manifolds.iter() {
+                let normal = if is_first {
+                    -manifold.normal
+                } else {
+                    manifold.normal
+                };
+                let speed = -linear_velocity.dot(normal);
+                if speed <= 0.0 {
+                    continue;
+                }
+
+                // Speculative contacts only count if the surface is reached within this step
+                let deepest_penetration = manifold
+                    .points
+                    .iter()
+                    .map(|contact| contact.penetration)
+                    .fold(Scalar::MIN, Scalar::max);
+                if deepest_penetration + speed * delta_time < 0.0 {
+                    continue;
+                }
+
+                let kind = if normal.angle_between(up) <= max_slope {
+                    HardImpactKind::Floor
+                } else if normal.dot(up) < -0.5 {
+                    HardImpactKind::Ceiling
+                } else {
+                    HardImpactKind::Wall
+                };
+                let threshold = match kind {
+                    HardImpactKind::Floor => impacts_config.floor_speed,
+                    HardImpactKind::Wall | HardImpactKind::Ceiling => impacts_config.wall_speed,
+                };
+                if speed < threshold {
+                    continue;
+                }
+
+                if impacts_config.stop_on_impact {
+                    linear_velocity.0 += normal * speed;
+                }
+
+                let impact = HardImpact {
+                    entity: character_entity,
+                    speed,
+                    normal,
+                    kind,
+                    surface: other_collider,
+                };
+                match impacts
+                    .iter_mut()
+                    .find(|impact| impact.entity == character_entity)
+                {
+                    Some(strongest) if strongest.speed < speed => *strongest = impact,
+                    Some(_) => {}
+                    None => impacts.push(impact),
+                }
+            }
+        }
+    }
+
+    impact_writer.write_batch(impacts);
+}
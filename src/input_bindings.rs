@@ -0,0 +1,237 @@
+//! Rebindable keyboard, mouse, and gamepad input.
+//!
+//! The [`InputBindings`] resource maps each [`InputAction`] to the keys, mouse buttons, and
+//! gamepad buttons that trigger it. The keyboard and gamepad input systems read it every
+//! frame, so changing it at runtime, like from a key-remapping menu, takes effect right away.
+//! With the `serde` feature, the bindings can be saved with the rest of the user settings.
+//!
+//! ```ignore
+//! fn remap_jump(mut bindings: ResMut<InputBindings>) {
+//!     bindings.rebind(InputAction::Jump, KeyCode::KeyF);
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use avian3d::math::*;
+use bevy::prelude::*;
+
+/// A logical action that can be bound to input in [`InputBindings`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InputAction {
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    /// Jumps when pressed, and charges jumps while held.
+    Jump,
+    Sprint,
+    Crouch,
+    Prone,
+    /// Interacts when pressed, and keeps interacting while held.
+    Interact,
+    Throw,
+}
+
+/// A key, mouse button, or gamepad button that an [`InputAction`] is bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+}
+
+impl InputBinding {
+    /// Returns `true` if both bindings are on the same kind of device.
+    fn same_device(&self, other: &InputBinding) -> bool {
+        core::mem::discriminant(self) == core::mem::discriminant(other)
+    }
+}
+
+impl From<KeyCode> for InputBinding {
+    fn from(key: KeyCode) -> Self {
+        Self::Key(key)
+    }
+}
+
+impl From<MouseButton> for InputBinding {
+    fn from(button: MouseButton) -> Self {
+        Self::Mouse(button)
+    }
+}
+
+impl From<GamepadButton> for InputBinding {
+    fn from(button: GamepadButton) -> Self {
+        Self::Gamepad(button)
+    }
+}
+
+/// The input bindings of the [`FpsController`](crate::FpsController) with input enabled.
+///
+/// Mouse bindings only apply while the controller has input enabled, since the cursor is
+/// free otherwise. Movement from the left stick of gamepads is always applied, and gamepad
+/// buttons bound to movement add to it.
+#[derive(Resource, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputBindings {
+    bindings: BTreeMap<InputAction, Vec<InputBinding>>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use InputAction::*;
+        use InputBinding::{Gamepad as Pad, Key, Mouse};
+
+        Self::empty()
+            .with(MoveForward, [Key(KeyCode::KeyW), Key(KeyCode::ArrowUp)])
+            .with(MoveBack, [Key(KeyCode::KeyS), Key(KeyCode::ArrowDown)])
+            .with(MoveLeft, [Key(KeyCode::KeyA), Key(KeyCode::ArrowLeft)])
+            .with(MoveRight, [Key(KeyCode::KeyD), Key(KeyCode::ArrowRight)])
+            .with(Jump, [Key(KeyCode::Space), Pad(GamepadButton::South)])
+            .with(
+                Sprint,
+                [Key(KeyCode::ShiftLeft), Pad(GamepadButton::LeftThumb)],
+            )
+            .with(
+                Crouch,
+                [
+                    Key(KeyCode::ControlLeft),
+                    Key(KeyCode::KeyC),
+                    Pad(GamepadButton::East),
+                ],
+            )
+            .with(Prone, [Key(KeyCode::KeyZ), Pad(GamepadButton::DPadDown)])
+            .with(Interact, [Key(KeyCode::KeyE), Pad(GamepadButton::West)])
+            .with(
+                Throw,
+                [Mouse(MouseButton::Left), Pad(GamepadButton::RightTrigger2)],
+            )
+    }
+}
+
+impl InputBindings {
+    /// Creates bindings without any bound input.
+    pub fn empty() -> Self {
+        Self {
+            bindings: BTreeMap::new(),
+        }
+    }
+
+    /// Adds bindings for an action.
+    pub fn with(
+        mut self,
+        action: InputAction,
+        bindings: impl IntoIterator<Item = InputBinding>,
+    ) -> Self {
+        for binding in bindings {
+            self.bind(action, binding);
+        }
+        self
+    }
+
+    /// Returns the bindings of an action.
+    pub fn get(&self, action: InputAction) -> &[InputBinding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Adds a binding to an action, keeping its other bindings.
+    pub fn bind(&mut self, action: InputAction, binding: impl Into<InputBinding>) {
+        let binding = binding.into();
+        let bindings = self.bindings.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    /// Replaces the bindings of an action on the same kind of device as the given binding.
+    ///
+    /// Rebinding the jump key to F keeps the gamepad button for jumping.
+    pub fn rebind(&mut self, action: InputAction, binding: impl Into<InputBinding>) {
+        let binding = binding.into();
+        if let Some(bindings) = self.bindings.get_mut(&action) {
+            bindings.retain(|bound| !bound.same_device(&binding));
+        }
+        self.bind(action, binding);
+    }
+
+    /// Removes a binding from an action.
+    pub fn unbind(&mut self, action: InputAction, binding: impl Into<InputBinding>) {
+        let binding = binding.into();
+        if let Some(bindings) = self.bindings.get_mut(&action) {
+            bindings.retain(|bound| *bound != binding);
+        }
+    }
+
+    /// Removes all bindings of an action.
+    pub fn clear(&mut self, action: InputAction) {
+        self.bindings.remove(&action);
+    }
+
+    /// Returns the actions bound to a binding, for finding conflicts when remapping.
+    pub fn actions_for(&self, binding: impl Into<InputBinding>) -> Vec<InputAction> {
+        let binding = binding.into();
+        self.bindings
+            .iter()
+            .filter(|(_, bindings)| bindings.contains(&binding))
+            .map(|(action, _)| *action)
+            .collect()
+    }
+
+    /// Returns `true` if a key or mouse button bound to the action is pressed.
+    ///
+    /// Mouse bindings are ignored if `mouse` is `None`.
+    pub fn pressed(
+        &self,
+        action: InputAction,
+        keys: &ButtonInput<KeyCode>,
+        mouse: Option<&ButtonInput<MouseButton>>,
+    ) -> bool {
+        self.get(action).iter().any(|binding| match binding {
+            InputBinding::Key(key) => keys.pressed(*key),
+            InputBinding::Mouse(button) => mouse.is_some_and(|mouse| mouse.pressed(*button)),
+            InputBinding::Gamepad(_) => false,
+        })
+    }
+
+    /// Returns `true` if a key or mouse button bound to the action was pressed this frame.
+    ///
+    /// Mouse bindings are ignored if `mouse` is `None`.
+    pub fn just_pressed(
+        &self,
+        action: InputAction,
+        keys: &ButtonInput<KeyCode>,
+        mouse: Option<&ButtonInput<MouseButton>>,
+    ) -> bool {
+        self.get(action).iter().any(|binding| match binding {
+            InputBinding::Key(key) => keys.just_pressed(*key),
+            InputBinding::Mouse(button) => mouse.is_some_and(|mouse| mouse.just_pressed(*button)),
+            InputBinding::Gamepad(_) => false,
+        })
+    }
+
+    /// Returns `true` if a gamepad button bound to the action is pressed.
+    pub fn gamepad_pressed(&self, action: InputAction, gamepad: &Gamepad) -> bool {
+        self.get(action).iter().any(|binding| match binding {
+            InputBinding::Gamepad(button) => gamepad.pressed(*button),
+            _ => false,
+        })
+    }
+
+    /// Returns `true` if a gamepad button bound to the action was pressed this frame.
+    pub fn gamepad_just_pressed(&self, action: InputAction, gamepad: &Gamepad) -> bool {
+        self.get(action).iter().any(|binding| match binding {
+            InputBinding::Gamepad(button) => gamepad.just_pressed(*button),
+            _ => false,
+        })
+    }
+}
+
+/// Returns the movement direction from the pressed movement actions, with a maximum length of 1.
+pub(crate) fn bound_direction(pressed: impl Fn(InputAction) -> bool) -> Vector2 {
+    let axis = |positive, negative| pressed(positive) as i8 - pressed(negative) as i8;
+    let horizontal = axis(InputAction::MoveRight, InputAction::MoveLeft);
+    let vertical = axis(InputAction::MoveForward, InputAction::MoveBack);
+    Vector2::new(horizontal as Scalar, vertical as Scalar).clamp_length_max(1.0)
+}
@@ -0,0 +1,157 @@
+//! Tracking of the input device the local player used last.
+//!
+//! The [`ActiveInputDevice`] resource switches between the keyboard and mouse and a specific
+//! gamepad whenever another device produces input, and an [`InputDeviceChanged`] message is
+//! written, for example to swap button prompt icons. The FPS controller with input enabled
+//! also gets the device as a component.
+//!
+//! [`InputDeviceSettings`] on the controller tune the look sensitivity, stick response, and
+//! deadzone per device, so a specific gamepad model can feel different from the mouse.
+
+use avian3d::math::*;
+use bevy::{input::mouse::AccumulatedMouseMotion, prelude::*};
+
+use crate::{FpsController, GamepadLookSensitivity, StickResponse};
+
+/// An input device of the local player.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum InputDevice {
+    #[default]
+    KeyboardMouse,
+    /// The gamepad entity.
+    Gamepad(Entity),
+}
+
+/// The input device that most recently produced input.
+///
+/// Available as a resource, and as a component on the FPS controller with input enabled.
+#[derive(Resource, Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ActiveInputDevice(pub InputDevice);
+
+/// A [`Message`] written when the [`ActiveInputDevice`] changes.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct InputDeviceChanged {
+    pub previous: InputDevice,
+    pub current: InputDevice,
+}
+
+/// Input settings of an FPS controller for a gamepad model.
+#[derive(Clone, Debug, Default)]
+pub struct GamepadDeviceSettings {
+    /// The USB vendor ID of the gamepads the settings apply to, or `None` for any.
+    pub vendor_id: Option<u16>,
+    /// The USB product ID of the gamepads the settings apply to, or `None` for any.
+    pub product_id: Option<u16>,
+    /// Replaces the [`GamepadLookSensitivity`] of the controller.
+    pub look_sensitivity: Option<GamepadLookSensitivity>,
+    /// Replaces the [`StickResponse`] of the controller.
+    pub stick_response: Option<StickResponse>,
+    /// The stick deflection below which stick input is ignored, for worn sticks that drift.
+    pub deadzone: Scalar,
+}
+
+impl GamepadDeviceSettings {
+    /// Returns whether the settings apply to the given gamepad.
+    pub fn matches(&self, gamepad: &Gamepad) -> bool {
+        self.vendor_id
+            .is_none_or(|id| gamepad.vendor_id() == Some(id))
+            && self
+                .product_id
+                .is_none_or(|id| gamepad.product_id() == Some(id))
+    }
+
+    /// Applies the deadzone to a stick position, rescaling the rest of the range so input
+    /// still starts at zero.
+    pub fn apply_deadzone(&self, stick: Vector2) -> Vector2 {
+        let deflection = stick.length();
+        if deflection <= self.deadzone || deflection <= 0.0 {
+            return Vector2::ZERO;
+        }
+        let scaled = (deflection - self.deadzone) / (1.0 - self.deadzone).max(Scalar::EPSILON);
+        stick / deflection * scaled.min(1.0)
+    }
+}
+
+/// Per-device input settings of an FPS controller.
+#[derive(Component, Clone, Debug)]
+pub struct InputDeviceSettings {
+    /// The multiplier of mouse look, on top of the [`MouseSensitivity`](crate::MouseSensitivity).
+    pub mouse_look_scale: Scalar,
+    /// Settings for gamepad models. The first entry that matches a gamepad is used.
+    pub gamepads: Vec<GamepadDeviceSettings>,
+}
+
+impl Default for InputDeviceSettings {
+    fn default() -> Self {
+        Self {
+            mouse_look_scale: 1.0,
+            gamepads: Vec::new(),
+        }
+    }
+}
+
+impl InputDeviceSettings {
+    /// Returns the settings for the given gamepad, if any match it.
+    pub fn for_gamepad(&self, gamepad: &Gamepad) -> Option<&GamepadDeviceSettings> {
+        self.gamepads
+            .iter()
+            .find(|settings| settings.matches(gamepad))
+    }
+}
+
+/// The stick deflection that counts as gamepad activity.
+const GAMEPAD_ACTIVITY_THRESHOLD: f32 = 0.5;
+
+/// Updates the [`ActiveInputDevice`] from the devices that produced input this frame.
+pub(crate) fn track_input_device(
+    mut commands: Commands,
+    mut active: ResMut<ActiveInputDevice>,
+    mut changed_writer: MessageWriter<InputDeviceChanged>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    gamepads: Query<(Entity, &Gamepad)>,
+    controllers: Query<(Entity, &FpsController, Option<&ActiveInputDevice>)>,
+) {
+    let keyboard_mouse = keyboard_input.get_just_pressed().len() > 0
+        || mouse_input.get_just_pressed().len() > 0
+        || mouse_motion.delta != Vec2::ZERO;
+    let gamepad = gamepads.iter().find_map(|(entity, gamepad)| {
+        let active = gamepad.get_just_pressed().next().is_some()
+            || gamepad.left_stick().length() > GAMEPAD_ACTIVITY_THRESHOLD
+            || gamepad.right_stick().length() > GAMEPAD_ACTIVITY_THRESHOLD;
+        active.then_some(InputDevice::Gamepad(entity))
+    });
+
+    // The current device wins ties, so using both at once doesn't flicker
+    let current = match (keyboard_mouse, gamepad) {
+        (true, Some(_)) if active.0 == InputDevice::KeyboardMouse => InputDevice::KeyboardMouse,
+        (_, Some(gamepad)) => gamepad,
+        (true, None) => InputDevice::KeyboardMouse,
+        (false, None) => active.0,
+    };
+
+    // A disconnected gamepad falls back to the keyboard and mouse
+    let current = match current {
+        InputDevice::Gamepad(entity) if !gamepads.contains(entity) => InputDevice::KeyboardMouse,
+        current => current,
+    };
+
+    let previous = active.0;
+    if active.set_if_neq(ActiveInputDevice(current)) {
+        changed_writer.write(InputDeviceChanged { previous, current });
+    }
+
+    for (entity, controller, device) in &controllers {
+        match device {
+            Some(_) if !controller.enable_input => {
+                commands.entity(entity).remove::<ActiveInputDevice>();
+            }
+            Some(device) if device.0 == current => {}
+            None if !controller.enable_input => {}
+            _ => {
+                commands.entity(entity).insert(ActiveInputDevice(current));
+            }
+        }
+    }
+}
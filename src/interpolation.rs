@@ -0,0 +1,47 @@
+//! Smooth rendering of character controllers between physics ticks.
+//!
+//! Physics runs at a fixed tick rate, while movement input and rendering run every frame.
+//! At low tick rates, or with frame rates that aren't a multiple of the tick rate, the
+//! character and its attached camera visibly step from tick to tick. [`RenderInterpolation`]
+//! enables Avian's translation interpolation, so the rendered [`Transform`] eases between the
+//! last two ticks instead.
+//!
+//! Only the translation is interpolated. The rotation is driven by look input every frame,
+//! and interpolating it would make looking around lag behind by a tick.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+/// Whether the rendered position of a character controller is interpolated between physics
+/// ticks.
+///
+/// Enabled by default in [`CharacterControllerBundle`](crate::CharacterControllerBundle).
+/// Requires Avian's `PhysicsInterpolationPlugin`, which is part of the default `PhysicsPlugins`.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RenderInterpolation(pub bool);
+
+impl Default for RenderInterpolation {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Adds or removes [`TranslationInterpolation`] when [`RenderInterpolation`] is inserted.
+pub(crate) fn apply_render_interpolation(
+    insert: On<Insert, RenderInterpolation>,
+    mut commands: Commands,
+    query: Query<&RenderInterpolation>,
+) {
+    let Ok(interpolation) = query.get(insert.entity) else {
+        return;
+    };
+    if interpolation.0 {
+        commands
+            .entity(insert.entity)
+            .insert(TranslationInterpolation);
+    } else {
+        commands
+            .entity(insert.entity)
+            .remove::<TranslationInterpolation>();
+    }
+}
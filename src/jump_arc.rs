@@ -0,0 +1,153 @@
+//! A debug gizmo showing the predicted jump trajectory of a character controller.
+//!
+//! [`JumpArcGizmo`] integrates the movement parameters and gravity of the character forward
+//! in time and draws the path its feet would take, ending where it would land. Grounded
+//! characters show the arc of a jump from their current velocity, and airborne characters
+//! show the rest of their current arc, which makes it easy to tune [`JumpImpulse`] and
+//! [`ControllerGravity`] against level geometry.
+
+use avian3d::{math::*, prelude::*};
+use bevy::{color::palettes::css::LIME, prelude::*};
+
+use crate::{
+    CharacterController, ControllerEnabled, ControllerGravity, ControllerUp, Grounded, JumpImpulse,
+    MovementAcceleration, MovementDampingFactor, SpeedModifiers, WadingState, WorldUp,
+    DAMPING_REFERENCE_RATE,
+};
+
+/// Draws the predicted jump trajectory of a character controller as a gizmo.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct JumpArcGizmo {
+    pub color: Color,
+    /// How far ahead the trajectory is predicted, in seconds.
+    pub duration: Scalar,
+    /// The time step of the prediction, in seconds.
+    pub time_step: Scalar,
+    /// The local movement direction assumed to be held during the jump.
+    ///
+    /// `Vector2::Y` predicts a jump while holding forward. Zero only uses the current velocity.
+    pub assumed_input: Vector2,
+}
+
+impl Default for JumpArcGizmo {
+    fn default() -> Self {
+        Self {
+            color: LIME.into(),
+            duration: 3.0,
+            time_step: 1.0 / 60.0,
+            assumed_input: Vector2::ZERO,
+        }
+    }
+}
+
+/// Draws the [`JumpArcGizmo`]s of character controllers.
+///
+/// Only runs when gizmos are available, so headless apps are unaffected.
+#[allow(clippy::type_complexity)]
+pub(crate) fn draw_jump_arcs(
+    mut gizmos: Gizmos,
+    world_up: Res<WorldUp>,
+    spatial_query: SpatialQuery,
+    query: Query<
+        (
+            Entity,
+            &JumpArcGizmo,
+            &Position,
+            &Rotation,
+            &ColliderAabb,
+            &LinearVelocity,
+            &ControllerGravity,
+            &JumpImpulse,
+            &MovementAcceleration,
+            &MovementDampingFactor,
+            Has<Grounded>,
+            Option<&SpeedModifiers>,
+            Option<&WadingState>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    for (
+        entity,
+        arc,
+        position,
+        rotation,
+        aabb,
+        linear_velocity,
+        gravity,
+        jump_impulse,
+        acceleration,
+        damping,
+        is_grounded,
+        speed_modifiers,
+        wading,
+        controller_up,
+    ) in &query
+    {
+        let up = world_up.for_controller(controller_up);
+        let mut velocity = linear_velocity.0;
+        if is_grounded {
+            let jump_multiplier = wading.map_or(1.0, |wading| wading.jump_multiplier);
+            velocity = velocity.reject_from_normalized(up) + up * jump_impulse.0 * jump_multiplier;
+        }
+
+        // Mirror the per-frame integration of the movement systems.
+        let acceleration =
+            speed_modifiers.map_or(acceleration.0, |modifiers| modifiers.apply(acceleration.0));
+        let input_direction = rotation * Vector::X * arc.assumed_input.x
+            + rotation * Vector::NEG_Z * arc.assumed_input.y;
+        let input_acceleration = input_direction.reject_from_normalized(up) * acceleration;
+        let step_damping = damping.0.powf(arc.time_step * DAMPING_REFERENCE_RATE);
+
+        let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+        let half_height = ((aabb.max - aabb.min) * 0.5).dot(up.abs());
+        let mut feet = position.0 - up * half_height;
+        let mut points = vec![feet.f32()];
+        let mut landing = None;
+
+        let steps = (arc.duration / arc.time_step.max(0.001)).ceil() as usize;
+        for _ in 0..steps {
+            velocity += (gravity.0 + input_acceleration) * arc.time_step;
+            let vertical = velocity.project_onto_normalized(up);
+            velocity = vertical + (velocity - vertical) * step_damping;
+
+            let step = velocity * arc.time_step;
+            let Ok((direction, distance)) = Dir3::new_and_length(step.f32()) else {
+                continue;
+            };
+
+            // Ignore hits while rising, so the ground being jumped from doesn't end the arc.
+            if velocity.dot(up) <= 0.0 {
+                if let Some(hit) = spatial_query.cast_ray(
+                    feet,
+                    direction,
+                    distance.adjust_precision(),
+                    true,
+                    &filter,
+                ) {
+                    feet += step.normalize() * hit.distance;
+                    points.push(feet.f32());
+                    landing = Some((feet, hit.normal));
+                    break;
+                }
+            }
+
+            feet += step;
+            points.push(feet.f32());
+        }
+
+        gizmos.linestrip(points, arc.color);
+        if let Some((point, normal)) = landing {
+            let normal = Dir3::new(normal.f32()).unwrap_or(Dir3::Y);
+            gizmos.circle(
+                Isometry3d::new(
+                    point.f32() + normal * 0.01,
+                    Quat::from_rotation_arc(Vec3::Z, *normal),
+                ),
+                0.25,
+                arc.color,
+            );
+        }
+    }
+}
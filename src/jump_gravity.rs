@@ -0,0 +1,126 @@
+//! Asymmetric gravity and variable jump height for character controllers.
+//!
+//! A single jump impulse with uniform gravity gives a symmetric, floaty arc. With
+//! [`JumpGravity`], gravity is scaled separately while rising and falling, and releasing
+//! the jump button early cuts the jump short by increasing gravity for the rest of the
+//! rise, giving the snappy arc of platformers.
+//!
+//! Jump cuts rely on [`MovementAction::JumpHeld`](crate::MovementAction::JumpHeld), so jumps
+//! of characters that never send it, like AI-driven ones, always reach their full height.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    CharacterController, ControllerEnabled, ControllerGravity, ControllerUp, FpsController,
+    GatheredMovementInput, Grounded, Jumped, WorldUp,
+};
+
+/// Scales the gravity of a character controller while rising and falling, and cuts jumps
+/// short when the jump button is released early.
+///
+/// The multipliers are relative to [`ControllerGravity`].
+#[derive(Component, Clone, Copy, Debug)]
+#[require(JumpGravityState)]
+pub struct JumpGravity {
+    /// The gravity multiplier while moving up.
+    pub rising_multiplier: Scalar,
+    /// The gravity multiplier while moving down.
+    pub falling_multiplier: Scalar,
+    /// The gravity multiplier while moving up after the jump button was released.
+    pub jump_cut_multiplier: Scalar,
+}
+
+impl Default for JumpGravity {
+    fn default() -> Self {
+        Self {
+            rising_multiplier: 1.0,
+            falling_multiplier: 2.0,
+            jump_cut_multiplier: 3.0,
+        }
+    }
+}
+
+/// The jump state tracked for [`JumpGravity`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct JumpGravityState {
+    /// Whether the jump button was held in the last frame.
+    held: bool,
+    /// Whether the character is in the air after a jump that can still be cut.
+    cuttable: bool,
+    /// Whether the current jump was cut.
+    cut: bool,
+}
+
+impl JumpGravityState {
+    /// Returns `true` if the current jump was cut short by releasing the jump button.
+    pub fn is_cut(&self) -> bool {
+        self.cut
+    }
+}
+
+/// Starts tracking a jump of a [`JumpGravity`] character.
+pub(crate) fn start_jump_cut(jumped: On<Jumped>, mut states: Query<&mut JumpGravityState>) {
+    if let Ok(mut state) = states.get_mut(jumped.entity) {
+        // Only jumps that started with the button held can be cut by releasing it.
+        state.cuttable = state.held;
+        state.cut = false;
+    }
+}
+
+/// Applies the [`JumpGravity`] multipliers on top of the gravity that was already applied.
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_jump_gravity(
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    gathered: Res<GatheredMovementInput>,
+    mut controllers: Query<
+        (
+            Entity,
+            &JumpGravity,
+            &mut JumpGravityState,
+            &ControllerGravity,
+            &mut LinearVelocity,
+            Has<Grounded>,
+            Option<&FpsController>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (
+        entity,
+        jump_gravity,
+        mut state,
+        gravity,
+        mut linear_velocity,
+        is_grounded,
+        fps_controller,
+        controller_up,
+    ) in &mut controllers
+    {
+        let held = gathered.get(entity, fps_controller).jump_held;
+        state.held = held;
+
+        let rising = linear_velocity.dot(world_up.for_controller(controller_up)) > 0.0;
+        if is_grounded && !rising {
+            state.cuttable = false;
+            state.cut = false;
+            continue;
+        }
+        if state.cuttable && rising && !held {
+            state.cut = true;
+        }
+
+        let multiplier = match (rising, state.cut) {
+            (true, true) => jump_gravity.jump_cut_multiplier,
+            (true, false) => jump_gravity.rising_multiplier,
+            (false, _) => jump_gravity.falling_multiplier,
+        };
+
+        // Gravity was already applied in full, so only add the difference.
+        linear_velocity.0 += gravity.0 * (multiplier - 1.0) * delta_time;
+    }
+}
@@ -0,0 +1,123 @@
+//! Guards against jumps retriggering right after a jump.
+//!
+//! The ground caster of a character keeps reporting the ground for a few frames after a jump,
+//! until the character has risen past the cast distance. Without a guard, a second jump
+//! during that liftoff, like from a buffered input or an auto jump, stacks another impulse
+//! on top of the first and launches the character much higher. Every jump marks the
+//! character with [`JumpLiftoff`] until it actually leaves the ground, and grounded jumps are
+//! ignored while it is there.
+//!
+//! [`JumpCooldown`] additionally enforces a minimum time between two jumps.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{CharacterController, ControllerUp, Grounded, Jumped, WorldUp};
+
+/// The longest time a character is kept in [`JumpLiftoff`], in seconds, in case something
+/// holds it on the ground.
+const MAX_LIFTOFF_TIME: Scalar = 0.25;
+
+/// A minimum time between two jumps of a character controller.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct JumpCooldown {
+    /// The minimum time between two jumps, in seconds.
+    pub min_interval: Scalar,
+    /// The remaining time before the character can jump again.
+    remaining: Scalar,
+}
+
+impl Default for JumpCooldown {
+    fn default() -> Self {
+        Self::new(0.2)
+    }
+}
+
+impl JumpCooldown {
+    /// Creates a jump cooldown with the given minimum time between jumps, in seconds.
+    pub const fn new(min_interval: Scalar) -> Self {
+        Self {
+            min_interval,
+            remaining: 0.0,
+        }
+    }
+
+    /// Returns the remaining time before the character can jump again, in seconds.
+    pub fn remaining(&self) -> Scalar {
+        self.remaining
+    }
+
+    /// Returns `true` if the character can jump again.
+    pub fn is_ready(&self) -> bool {
+        self.remaining <= 0.0
+    }
+}
+
+/// Marks a character controller that just jumped and hasn't left the ground yet.
+///
+/// Grounded jumps are ignored while it is present.
+#[derive(Component, Clone, Copy, Debug, Default)]
+#[component(storage = "SparseSet")]
+pub struct JumpLiftoff {
+    /// The time since the jump, in seconds.
+    elapsed: Scalar,
+}
+
+/// Returns `true` if a grounded jump is blocked by a [`JumpLiftoff`] or [`JumpCooldown`].
+pub(crate) fn jump_blocked(lifting_off: bool, cooldown: Option<&JumpCooldown>) -> bool {
+    lifting_off || cooldown.is_some_and(|cooldown| !cooldown.is_ready())
+}
+
+/// Starts the liftoff and the [`JumpCooldown`] of a character controller that jumped.
+pub(crate) fn start_jump_liftoff(
+    jumped: On<Jumped>,
+    mut commands: Commands,
+    mut controllers: Query<Option<&mut JumpCooldown>, With<CharacterController>>,
+) {
+    let Ok(cooldown) = controllers.get_mut(jumped.entity) else {
+        return;
+    };
+    if let Some(mut cooldown) = cooldown {
+        cooldown.remaining = cooldown.min_interval;
+    }
+    commands
+        .entity(jumped.entity)
+        .insert(JumpLiftoff::default());
+}
+
+/// Advances [`JumpCooldown`]s, and ends the [`JumpLiftoff`] of characters that left the
+/// ground or stopped rising.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_jump_guards(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    mut cooldowns: Query<&mut JumpCooldown, With<CharacterController>>,
+    mut lifting_off: Query<
+        (
+            Entity,
+            &mut JumpLiftoff,
+            &LinearVelocity,
+            Has<Grounded>,
+            Option<&ControllerUp>,
+        ),
+        With<CharacterController>,
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for mut cooldown in &mut cooldowns {
+        if cooldown.remaining > 0.0 {
+            cooldown.remaining = (cooldown.remaining - delta_time).max(0.0);
+        }
+    }
+
+    for (entity, mut liftoff, linear_velocity, is_grounded, controller_up) in &mut lifting_off {
+        liftoff.elapsed += delta_time;
+        let up = world_up.for_controller(controller_up);
+        let rising = linear_velocity.dot(up) > 0.0;
+        if !is_grounded || !rising || liftoff.elapsed >= MAX_LIFTOFF_TIME {
+            commands.entity(entity).remove::<JumpLiftoff>();
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Coyote time and jump buffering.
+//!
+//! Jumps are easy to mistime by a few frames. With [`CoyoteTime`], a character that walks off
+//! a ledge can still jump for a short time after leaving the ground, as long as it didn't
+//! leave the ground by jumping. With [`JumpBuffer`], a jump pressed shortly before landing is
+//! remembered and performed as soon as the character can jump again.
+//!
+//! Both are tracked across frames in [`JumpTiming`], and respect the [`JumpLiftoff`] and
+//! [`JumpCooldown`](crate::JumpCooldown) guards of the movement system.
+//!
+//! Dropping through a [`OneWayPlatform`](crate::OneWayPlatform) with crouch and jump uses up
+//! the jump, so it neither starts coyote time nor buffers a jump for the landing below.
+//!
+//! [`CoyoteTime`] is the only grace period for jumping off ledges, and the `coyote_time` of an
+//! [`AccessibilityProfile`] extends it. The `ungrounded_delay` of
+//! [`GroundProbes`](crate::GroundProbes) only keeps the character [`Grounded`] over brief gaps
+//! in support, like seams between meshes.
+
+use core::time::Duration;
+
+use avian3d::math::*;
+use bevy::prelude::*;
+
+use crate::{AccessibilityProfile, CharacterController, Grounded, JumpLiftoff, Jumped};
+
+/// The time a character controller can still jump after walking off a ledge.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(JumpTiming)]
+pub struct CoyoteTime(pub Duration);
+
+impl Default for CoyoteTime {
+    fn default() -> Self {
+        Self(Duration::from_millis(100))
+    }
+}
+
+/// The time a jump pressed while a character controller can't jump is remembered.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(JumpTiming)]
+pub struct JumpBuffer(pub Duration);
+
+impl Default for JumpBuffer {
+    fn default() -> Self {
+        Self(Duration::from_millis(100))
+    }
+}
+
+/// The jump state of a character controller with [`CoyoteTime`] or a [`JumpBuffer`], tracked
+/// across frames.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct JumpTiming {
+    /// The time since the character was last grounded, in seconds.
+    airborne_time: Scalar,
+    /// Whether the character jumped since it was last grounded.
+    jumped: bool,
+    /// The remaining time of the buffered jump, in seconds.
+    buffered: Scalar,
+}
+
+impl JumpTiming {
+    /// Returns the time since the character was last grounded, in seconds.
+    pub fn airborne_time(&self) -> Scalar {
+        self.airborne_time
+    }
+
+    /// Returns `true` if a jump is buffered.
+    pub fn is_buffered(&self) -> bool {
+        self.buffered > 0.0
+    }
+
+    /// Returns `true` if the character walked off a ledge within the coyote time.
+    ///
+    /// The coyote time of an [`AccessibilityProfile`] extends the [`CoyoteTime`].
+    pub(crate) fn in_coyote_time(
+        &self,
+        coyote_time: Option<&CoyoteTime>,
+        profile: Option<&AccessibilityProfile>,
+    ) -> bool {
+        let coyote_time = coyote_time
+            .map_or(0.0, |coyote_time| {
+                coyote_time.0.as_secs_f64().adjust_precision()
+            })
+            .max(profile.map_or(0.0, |profile| profile.coyote_time));
+        !self.jumped && self.airborne_time > 0.0 && self.airborne_time <= coyote_time
+    }
+
+    /// Remembers a jump that couldn't be performed.
+    pub(crate) fn buffer(&mut self, jump_buffer: Option<&JumpBuffer>) {
+        if let Some(jump_buffer) = jump_buffer {
+            self.buffered = jump_buffer.0.as_secs_f64().adjust_precision();
+        }
+    }
+
+    /// Ends the coyote time and clears the buffered jump, like a performed jump does.
+    pub(crate) fn consume(&mut self) {
+        self.jumped = true;
+        self.buffered = 0.0;
+    }
+}
+
+/// Ends the coyote time and clears the buffered jump of a character controller that jumped.
+pub(crate) fn consume_jump_timing(
+    jumped: On<Jumped>,
+    mut controllers: Query<&mut JumpTiming, With<CharacterController>>,
+) {
+    if let Ok(mut timing) = controllers.get_mut(jumped.entity) {
+        timing.consume();
+    }
+}
+
+/// Advances the [`JumpTiming`] of character controllers.
+///
+/// Characters still lifting off the ground from a jump count as airborne.
+pub(crate) fn update_jump_timing(
+    time: Res<Time<Virtual>>,
+    mut controllers: Query<(&mut JumpTiming, Has<Grounded>, Has<JumpLiftoff>)>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (mut timing, is_grounded, lifting_off) in &mut controllers {
+        if is_grounded && !lifting_off {
+            timing.airborne_time = 0.0;
+            timing.jumped = false;
+        } else {
+            timing.airborne_time += delta_time;
+        }
+        timing.buffered = (timing.buffered - delta_time).max(0.0);
+    }
+}
@@ -0,0 +1,170 @@
+//! Prediction of where an airborne character controller will land.
+//!
+//! [`LandingPrediction`] integrates the gravity, movement damping, [`AirDrag`], and
+//! [`MaxFallSpeed`] of a character from a given velocity, the same way the movement systems
+//! do, and casts along the resulting arc to find where its feet touch down. This is useful
+//! for AI jump planning, landing indicators, and aiming jump pads.
+
+use avian3d::{math::*, prelude::*};
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    AirDrag, CharacterController, ControllerGravity, ControllerUp, MaxFallSpeed, MaxSlopeAngle,
+    MovementDampingFactor, NotWalkable, WorldUp, DAMPING_REFERENCE_RATE,
+};
+
+/// How far ahead landings are predicted by [`LandingPrediction::predict_landing`], in seconds.
+pub const DEFAULT_PREDICTION_DURATION: Scalar = 5.0;
+
+/// The time step of [`LandingPrediction::predict_landing`], in seconds.
+pub const DEFAULT_PREDICTION_TIME_STEP: Scalar = 1.0 / 60.0;
+
+/// A landing predicted by [`LandingPrediction`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PredictedLanding {
+    /// The point where the feet of the character touch down.
+    pub point: Vector,
+    /// The normal of the surface at the landing point.
+    pub normal: Vector,
+    /// The collider entity of the surface.
+    pub surface: Entity,
+    /// Whether the surface is walkable ground for the character.
+    pub walkable: bool,
+    /// The time until the landing, in seconds.
+    pub time: Scalar,
+    /// The velocity of the character when it lands.
+    pub velocity: Vector,
+}
+
+/// A [`SystemParam`] for predicting where character controllers land.
+#[derive(SystemParam)]
+pub struct LandingPrediction<'w, 's> {
+    spatial_query: SpatialQuery<'w, 's>,
+    world_up: Res<'w, WorldUp>,
+    #[allow(clippy::type_complexity)]
+    controllers: Query<
+        'w,
+        's,
+        (
+            &'static Position,
+            &'static ColliderAabb,
+            &'static ControllerGravity,
+            Option<&'static MovementDampingFactor>,
+            Option<&'static AirDrag>,
+            Option<&'static MaxFallSpeed>,
+            Option<&'static MaxSlopeAngle>,
+            Option<&'static ControllerUp>,
+        ),
+        With<CharacterController>,
+    >,
+    not_walkable: Query<'w, 's, (), With<NotWalkable>>,
+    collider_rbs: Query<'w, 's, &'static ColliderOf>,
+}
+
+impl LandingPrediction<'_, '_> {
+    /// Predicts where a character controller lands when it leaves the ground with the given
+    /// velocity, looking [`DEFAULT_PREDICTION_DURATION`] ahead.
+    ///
+    /// Returns `None` if the entity isn't a character controller, or if it doesn't land in
+    /// time.
+    pub fn predict_landing(
+        &self,
+        entity: Entity,
+        initial_velocity: Vector,
+    ) -> Option<PredictedLanding> {
+        self.predict_landing_with(
+            entity,
+            initial_velocity,
+            DEFAULT_PREDICTION_DURATION,
+            DEFAULT_PREDICTION_TIME_STEP,
+        )
+    }
+
+    /// Predicts where a character controller lands, looking `duration` seconds ahead with
+    /// the given time step.
+    pub fn predict_landing_with(
+        &self,
+        entity: Entity,
+        initial_velocity: Vector,
+        duration: Scalar,
+        time_step: Scalar,
+    ) -> Option<PredictedLanding> {
+        let (position, aabb, gravity, damping, air_drag, max_fall_speed, max_slope, controller_up) =
+            self.controllers.get(entity).ok()?;
+        let up = self.world_up.for_controller(controller_up);
+        let time_step = time_step.max(0.001);
+
+        // Mirror the per-frame integration of the movement systems
+        let step_damping = damping.map_or(1.0, |damping| {
+            damping.0.powf(time_step * DAMPING_REFERENCE_RATE)
+        });
+        let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+        let half_height = ((aabb.max - aabb.min) * 0.5).dot(up.abs());
+        let mut feet = position.0 - up * half_height;
+        let mut velocity = initial_velocity;
+        let mut time = 0.0;
+
+        let steps = (duration / time_step).ceil() as usize;
+        for _ in 0..steps {
+            velocity += gravity.0 * time_step;
+            if let Some(air_drag) = air_drag {
+                let speed = velocity.length();
+                velocity /= 1.0 + air_drag.coefficient.max(0.0) * speed * time_step;
+            }
+            let vertical = up * velocity.dot(up);
+            velocity = vertical + (velocity - vertical) * step_damping;
+            if let Some(max_fall_speed) = max_fall_speed {
+                let fall_speed = -velocity.dot(up);
+                if fall_speed > max_fall_speed.0 {
+                    velocity += up * (fall_speed - max_fall_speed.0);
+                }
+            }
+            time += time_step;
+
+            let step = velocity * time_step;
+            let Ok((direction, distance)) = Dir3::new_and_length(step.f32()) else {
+                continue;
+            };
+
+            // Ignore hits while rising, so the ground being jumped from doesn't end the arc.
+            if velocity.dot(up) <= 0.0 {
+                if let Some(hit) = self.spatial_query.cast_ray(
+                    feet,
+                    direction,
+                    distance.adjust_precision(),
+                    true,
+                    &filter,
+                ) {
+                    let fraction = hit.distance / distance.adjust_precision();
+                    return Some(PredictedLanding {
+                        point: feet + step * fraction,
+                        normal: hit.normal,
+                        surface: hit.entity,
+                        walkable: self.is_walkable(hit.entity, hit.normal, up, max_slope),
+                        time: time - time_step * (1.0 - fraction),
+                        velocity,
+                    });
+                }
+            }
+
+            feet += step;
+        }
+        None
+    }
+
+    /// Returns whether a surface is walkable, like the grounded checks.
+    fn is_walkable(
+        &self,
+        entity: Entity,
+        normal: Vector,
+        up: Vector,
+        max_slope: Option<&MaxSlopeAngle>,
+    ) -> bool {
+        let not_walkable = self.not_walkable.contains(entity)
+            || self
+                .collider_rbs
+                .get(entity)
+                .is_ok_and(|collider_of| self.not_walkable.contains(collider_of.body));
+        !not_walkable && max_slope.is_none_or(|angle| normal.angle_between(up) <= angle.0)
+    }
+}
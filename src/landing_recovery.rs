@@ -0,0 +1,198 @@
+//! Recovery slowdown after hard landings.
+//!
+//! A [`LandingRecovery`] character that lands faster than a threshold is slowed down and
+//! can't jump for a short while, both scaled by how hard it landed. The recovery is
+//! exposed as [`RecoveringFromLanding`] with the remaining time, and announced with
+//! [`LandingRecoveryStarted`], so animations can sync a landing roll to it.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    CharacterController, ControllerEnabled, ControllerUp, Grounded, SpeedModifier, SpeedModifiers,
+    WorldUp,
+};
+
+/// The tag of the [`SpeedModifier`] that slows characters down while recovering.
+pub const LANDING_RECOVERY_MODIFIER: &str = "landing_recovery";
+
+/// Slows a character controller down and locks jumping after hard landings.
+///
+/// The severity of a landing goes from 0 at [`LandingRecovery::min_impact_speed`] to 1 at
+/// [`LandingRecovery::max_impact_speed`], and scales the duration and the slowdown.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(LandingRecoveryState)]
+pub struct LandingRecovery {
+    /// The fall speed above which landings need recovery, in m/s.
+    pub min_impact_speed: Scalar,
+    /// The fall speed at which landings are most severe, in m/s.
+    pub max_impact_speed: Scalar,
+    /// The recovery duration of the most severe landings, in seconds.
+    pub max_duration: Scalar,
+    /// The speed multiplier of the most severe landings.
+    pub speed_multiplier: Scalar,
+    /// Whether jumping is locked while recovering.
+    pub lock_jump: bool,
+}
+
+impl Default for LandingRecovery {
+    fn default() -> Self {
+        Self {
+            min_impact_speed: 10.0,
+            max_impact_speed: 25.0,
+            max_duration: 0.6,
+            speed_multiplier: 0.3,
+            lock_jump: true,
+        }
+    }
+}
+
+impl LandingRecovery {
+    /// Returns the severity of a landing at the given fall speed, from 0 to 1, or `None`
+    /// if the landing doesn't need recovery.
+    pub fn severity(&self, impact_speed: Scalar) -> Option<Scalar> {
+        if impact_speed < self.min_impact_speed {
+            return None;
+        }
+        let range = self.max_impact_speed - self.min_impact_speed;
+        if range <= 0.0 {
+            return Some(1.0);
+        }
+        Some(((impact_speed - self.min_impact_speed) / range).clamp(0.0, 1.0))
+    }
+}
+
+/// The airborne state tracked for [`LandingRecovery`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct LandingRecoveryState {
+    /// Whether the character was grounded last frame.
+    was_grounded: bool,
+    /// The downward speed of the character while it was airborne.
+    fall_speed: Scalar,
+}
+
+/// A component on a character controller that is recovering from a hard landing.
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct RecoveringFromLanding {
+    /// The remaining recovery time, in seconds.
+    pub remaining: Scalar,
+    /// The total recovery time, in seconds.
+    pub duration: Scalar,
+    /// The severity of the landing, from 0 to 1.
+    pub severity: Scalar,
+    /// Whether jumping is locked until the recovery ends.
+    pub jump_locked: bool,
+}
+
+impl RecoveringFromLanding {
+    /// Returns the fraction of the recovery that has elapsed, from 0 to 1.
+    pub fn progress(&self) -> Scalar {
+        if self.duration > 0.0 {
+            (1.0 - self.remaining / self.duration).clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Triggered on a [`LandingRecovery`] character controller when it lands hard enough to
+/// need recovery.
+#[derive(EntityEvent, Clone, Copy, Debug)]
+pub struct LandingRecoveryStarted {
+    pub entity: Entity,
+    /// The downward speed of the character just before landing.
+    pub impact_speed: Scalar,
+    /// The severity of the landing, from 0 to 1.
+    pub severity: Scalar,
+    /// The recovery duration, in seconds.
+    pub duration: Scalar,
+}
+
+/// Starts and ends the recovery of [`LandingRecovery`] characters after hard landings.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_landing_recovery(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    mut controllers: Query<
+        (
+            Entity,
+            &LandingRecovery,
+            &mut LandingRecoveryState,
+            Option<&mut RecoveringFromLanding>,
+            Option<&mut SpeedModifiers>,
+            &LinearVelocity,
+            Has<Grounded>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (
+        entity,
+        recovery,
+        mut state,
+        recovering,
+        speed_modifiers,
+        linear_velocity,
+        is_grounded,
+        controller_up,
+    ) in &mut controllers
+    {
+        if let Some(mut recovering) = recovering {
+            recovering.remaining -= delta_time;
+            if recovering.remaining <= 0.0 {
+                commands.entity(entity).remove::<RecoveringFromLanding>();
+            }
+        }
+
+        let was_grounded = state.was_grounded;
+        state.was_grounded = is_grounded;
+
+        if !is_grounded {
+            state.fall_speed =
+                (-linear_velocity.dot(world_up.for_controller(controller_up))).max(0.0);
+            continue;
+        }
+        if was_grounded {
+            continue;
+        }
+
+        let impact_speed = std::mem::take(&mut state.fall_speed);
+        let Some(severity) = recovery.severity(impact_speed) else {
+            continue;
+        };
+        let duration = recovery.max_duration * severity;
+        if duration <= 0.0 {
+            continue;
+        }
+
+        // The slowdown expires on its own when the recovery ends.
+        let multiplier = 1.0 - (1.0 - recovery.speed_multiplier) * severity;
+        let modifier = SpeedModifier::multiplier(multiplier).with_duration(duration);
+        match speed_modifiers {
+            Some(mut modifiers) => modifiers.insert(LANDING_RECOVERY_MODIFIER, modifier),
+            None => {
+                let mut modifiers = SpeedModifiers::default();
+                modifiers.insert(LANDING_RECOVERY_MODIFIER, modifier);
+                commands.entity(entity).insert(modifiers);
+            }
+        }
+
+        commands.entity(entity).insert(RecoveringFromLanding {
+            remaining: duration,
+            duration,
+            severity,
+            jump_locked: recovery.lock_jump,
+        });
+        commands.trigger(LandingRecoveryStarted {
+            entity,
+            impact_speed,
+            severity,
+            duration,
+        });
+    }
+}
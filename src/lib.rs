@@ -32,13 +32,195 @@
 //!             Collider::capsule(0.4, 1.0),
 //!             Vec3::NEG_Y * 9.81 * 2.0
 //!         )
-//!         .with_movement(30.0, 0.92, 7.0, std::f32::consts::PI * 0.25)
+//!         .with_movement_config(MovementConfig {
+//!             damping: 0.92,
+//!             max_slope_angle: std::f32::consts::PI * 0.25,
+//!             ..default()
+//!         })
 //!     );
 //! }
 //! ```
 
 use avian3d::{math::*, prelude::*};
-use bevy::{ecs::query::Has, prelude::*};
+use bevy::{
+    ecs::{query::Has, system::SystemParam},
+    log::tracing::field::Empty,
+    prelude::*,
+};
+
+mod accessibility;
+mod apex_hang;
+mod auto_jump;
+mod avoid_ledges;
+mod balance_assist;
+mod bounds;
+mod carry;
+mod ceiling;
+mod character_collisions;
+mod character_scale;
+mod character_weight;
+mod charged_jump;
+mod click_to_move;
+mod collide_and_slide;
+mod cooldowns;
+mod crawl_volume;
+#[cfg(feature = "debug_overlay")]
+mod debug_overlay;
+mod depenetration;
+#[cfg(feature = "dev_commands")]
+mod dev_commands;
+mod drag;
+mod external_velocity;
+mod eye_height;
+mod face_movement;
+mod first_person_body;
+#[cfg(feature = "bevy_audio")]
+mod footstep_audio;
+mod footsteps;
+mod ghost;
+mod gravity_field;
+mod grind_rail;
+mod ground_probes;
+mod ground_slicing;
+mod hard_impact;
+mod input_bindings;
+mod input_device;
+mod interpolation;
+mod jump_arc;
+mod jump_gravity;
+mod jump_guard;
+mod jump_timing;
+mod landing_prediction;
+mod landing_recovery;
+mod lock_on;
+mod locomotion_blend;
+mod magnet_boots;
+mod movement_basis;
+mod movement_noise;
+mod movement_reference;
+mod movement_script;
+mod noclip;
+mod one_way_platform;
+mod path_following;
+mod placement;
+mod playground;
+mod possession;
+mod push_pull;
+mod ragdoll;
+mod save_state;
+mod seat;
+mod speed_modifiers;
+mod sprint;
+mod stance;
+mod stats;
+mod surface_effects;
+mod surface_profile;
+mod swing_rope;
+mod target_velocity;
+#[cfg(feature = "test_utils")]
+pub mod test_utils;
+#[cfg(feature = "tnua_compat")]
+mod tnua_compat;
+mod triggers;
+mod tuning;
+mod turn_in_place;
+mod upright;
+mod vehicle;
+mod velocity_hooks;
+#[cfg(feature = "vleue_navigator")]
+mod vleue_navigation;
+mod wading;
+mod wall;
+mod wall_slide;
+mod water;
+mod water_climb;
+mod xr;
+
+pub use accessibility::*;
+pub use apex_hang::*;
+pub use auto_jump::*;
+pub use avoid_ledges::*;
+pub use balance_assist::*;
+pub use bounds::*;
+pub use carry::*;
+pub use ceiling::*;
+pub use character_collisions::*;
+pub use character_scale::*;
+pub use character_weight::*;
+pub use charged_jump::*;
+pub use click_to_move::*;
+pub use collide_and_slide::*;
+pub use cooldowns::*;
+pub use crawl_volume::*;
+#[cfg(feature = "debug_overlay")]
+pub use debug_overlay::*;
+pub use depenetration::*;
+#[cfg(feature = "dev_commands")]
+pub use dev_commands::*;
+pub use drag::*;
+pub use external_velocity::*;
+pub use eye_height::*;
+pub use face_movement::*;
+pub use first_person_body::*;
+#[cfg(feature = "bevy_audio")]
+pub use footstep_audio::*;
+pub use footsteps::*;
+pub use ghost::*;
+pub use gravity_field::*;
+pub use grind_rail::*;
+pub use ground_probes::*;
+pub use ground_slicing::*;
+pub use hard_impact::*;
+pub use input_bindings::*;
+pub use input_device::*;
+pub use interpolation::*;
+pub use jump_arc::*;
+pub use jump_gravity::*;
+pub use jump_guard::*;
+pub use jump_timing::*;
+pub use landing_prediction::*;
+pub use landing_recovery::*;
+pub use lock_on::*;
+pub use locomotion_blend::*;
+pub use magnet_boots::*;
+pub use movement_basis::*;
+pub use movement_noise::*;
+pub use movement_reference::*;
+pub use movement_script::*;
+pub use noclip::*;
+pub use one_way_platform::*;
+pub use path_following::*;
+pub use placement::*;
+pub use playground::*;
+pub use possession::*;
+pub use push_pull::*;
+pub use ragdoll::*;
+pub use save_state::*;
+pub use seat::*;
+pub use speed_modifiers::*;
+pub use sprint::*;
+pub use stance::*;
+pub use stats::*;
+pub use surface_effects::*;
+pub use surface_profile::*;
+pub use swing_rope::*;
+pub use target_velocity::*;
+#[cfg(feature = "tnua_compat")]
+pub use tnua_compat::*;
+pub use triggers::*;
+pub use tuning::*;
+pub use turn_in_place::*;
+pub use upright::*;
+pub use vehicle::*;
+pub use velocity_hooks::*;
+#[cfg(feature = "vleue_navigator")]
+pub use vleue_navigation::*;
+pub use wading::*;
+pub use wall::*;
+pub use wall_slide::*;
+pub use water::*;
+pub use water_climb::*;
+pub use xr::*;
 
 /// Common imports for the character controller crate.
 pub mod prelude {
@@ -49,38 +231,410 @@ pub struct CharacterControllerPlugin;
 
 impl Plugin for CharacterControllerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_message::<MovementAction>()
+        app.init_resource::<WorldUp>()
+            .init_resource::<CursorWindow>()
+            .init_resource::<PossessedController>()
+            .init_resource::<InputToggles>()
+            .init_resource::<InputBindings>()
+            .init_resource::<ActiveInputDevice>()
+            .add_message::<MovementAction>()
+            .add_message::<LookAction>()
+            .add_message::<InputDeviceChanged>()
+            .add_message::<DirectedMovementAction>()
+            .add_message::<CharacterStuck>()
+            .add_message::<ObjectThrown>()
+            .add_message::<ObjectDropped>()
+            .add_message::<MountVehicle>()
+            .add_message::<DismountVehicle>()
+            .add_message::<DismountBlocked>()
+            .add_message::<SitDown>()
+            .add_message::<StandUp>()
+            .add_message::<Footstep>()
+            .add_message::<Landed>()
+            .add_message::<SurfaceEffect>()
+            .add_message::<HardImpact>()
+            .add_message::<MovementNoise>()
+            .add_message::<WadingSplash>()
+            .add_message::<OutOfBounds>()
+            .add_message::<TeleportCharacter>()
+            .add_message::<XrInput>()
+            .add_message::<PlacementFailed>()
+            .add_message::<ToggleNoclip>()
+            .add_message::<RaceStarted>()
+            .add_message::<RaceFinished>()
+            .add_message::<RepathRequested>()
+            .add_message::<PathCompleted>()
+            .add_message::<PathFailed>()
+            .add_message::<MovementScriptFinished>()
+            .add_message::<Possessed>()
+            .init_resource::<CharacterCollisionPolicy>()
+            .init_resource::<WorldBounds>()
+            .init_resource::<GroundCheckSlicing>()
+            .init_resource::<GatheredMovementInput>()
+            .init_asset::<MovementScript>()
+            .init_asset_loader::<MovementScriptLoader>()
+            .init_asset::<SurfaceProfileMap>()
+            .configure_sets(
+                Update,
+                (
+                    CharacterControllerSystems::Input,
+                    CharacterControllerSystems::Detection,
+                    CharacterControllerSystems::Movement,
+                    CharacterControllerSystems::PostMovement,
+                )
+                    .chain(),
+            )
+            .configure_sets(
+                Update,
+                (
+                    CharacterControllerSystems::Detection,
+                    VelocitySync::Load(VelocityHooks::PreMovement),
+                    VelocityHooks::PreMovement,
+                    VelocitySync::Store(VelocityHooks::PreMovement),
+                    CharacterControllerSystems::Movement,
+                    VelocitySync::Load(VelocityHooks::PostMovement),
+                    VelocityHooks::PostMovement,
+                    VelocitySync::Store(VelocityHooks::PostMovement),
+                    VelocitySync::Load(VelocityHooks::PreDamping),
+                    VelocityHooks::PreDamping,
+                    VelocitySync::Store(VelocityHooks::PreDamping),
+                    CharacterControllerSystems::PostMovement,
+                    VelocitySync::Load(VelocityHooks::Finalize),
+                    VelocityHooks::Finalize,
+                    VelocitySync::Store(VelocityHooks::Finalize),
+                )
+                    .chain(),
+            )
+            .configure_sets(
+                Update,
+                // Bases and abilities build on the velocity of the built-in walking, so they
+                // run after it and after the target velocity basis.
+                (MovementBasisSystems::Basis, MovementBasisSystems::Abilities)
+                    .chain()
+                    .after(gather_movement_input)
+                    .after(movement)
+                    .after(move_toward_target_velocity)
+                    .before(charge_jumps)
+                    .in_set(CharacterControllerSystems::Movement),
+            )
+            .add_observer(enter_ragdoll)
+            .add_observer(exit_ragdoll)
+            .add_observer(place_safe_spawn)
+            .add_observer(init_view_yaw)
+            .add_observer(forward_move_triggers)
+            .add_observer(forward_jump_triggers)
+            .add_observer(start_jump_cut)
+            .add_observer(start_jump_liftoff)
+            .add_observer(consume_jump_timing)
+            .add_observer(apply_accessibility_profile)
+            .add_observer(apply_render_interpolation)
             .add_systems(
                 Update,
                 (
                     manage_cursor,
+                    track_input_device,
                     keyboard_input,
                     gamepad_input,
-                    update_grounded,
-                    apply_gravity,
-                    movement,
-                    mouse_look,
+                    mount_vehicles,
+                    dismount_vehicles,
+                    stand_up_from_missing_seats,
+                    sit_and_stand,
+                    toggle_noclip,
+                    click_to_move,
+                    play_movement_scripts,
+                    follow_paths,
+                    xr_locomotion,
+                )
+                    .chain()
+                    .in_set(CharacterControllerSystems::Input),
+            )
+            .add_systems(
+                Update,
+                (
+                    (
+                        sync_character_collision_layers,
+                        handle_out_of_bounds,
+                        teleport_characters,
+                        resolve_placement,
+                        recover_stuck_controllers,
+                        update_ceiling_contact,
+                        apply_character_scale,
+                        update_crawl_volumes,
+                        update_stance,
+                        apply_gravity_fields,
+                        apply_magnet_boots,
+                    )
+                        .chain(),
+                    (
+                        sync_ground_caster,
+                        drop_through_platforms,
+                        update_grounded,
+                        update_probed_grounded,
+                        update_jump_guards,
+                        update_current_surface,
+                        update_wall_contact,
+                        update_submersion,
+                        update_wading,
+                    )
+                        .chain(),
+                )
+                    .chain()
+                    .in_set(CharacterControllerSystems::Detection),
+            )
+            .add_systems(
+                Update,
+                (
+                    (
+                        redirect_vehicle_input,
+                        sync_movement_reference,
+                        tick_speed_modifiers,
+                        tick_ability_cooldowns,
+                        update_landing_recovery,
+                        apply_gravity,
+                        apply_jump_gravity,
+                        apply_apex_hang,
+                        apply_buoyancy,
+                        start_water_climb,
+                        climb_out_of_water,
+                        apply_air_drag,
+                        limit_fall_speed,
+                        attach_to_rails,
+                        grind_rails,
+                        grab_swing_ropes,
+                        swing_on_ropes,
+                        auto_jump,
+                        assist_balance,
+                    )
+                        .chain(),
+                    (
+                        update_sprinting,
+                        update_jump_timing,
+                        movement,
+                        move_toward_target_velocity,
+                        charge_jumps,
+                        wall_slide,
+                        fly_noclip,
+                        ceiling_bonk,
+                        anchor_seated,
+                    )
+                        .chain(),
+                    (
+                        mouse_look,
+                        smooth_yaw,
+                        face_movement_direction,
+                        face_lock_on_target,
+                        turn_in_place,
+                        grab_pushables,
+                        push_pull_objects,
+                        pick_up_objects,
+                        throw_carried_objects,
+                        hold_carried_objects,
+                    )
+                        .chain(),
+                )
+                    .chain()
+                    .in_set(CharacterControllerSystems::Movement),
+            )
+            .add_systems(
+                Update,
+                (
                     apply_movement_damping,
+                    apply_external_velocity,
+                    apply_character_weight,
+                    update_locomotion_blend,
+                    stabilize_upright,
+                    detect_footsteps,
+                    emit_surface_effects,
+                    emit_movement_noise,
+                    track_movement_stats,
+                    record_ghosts,
+                    play_ghosts,
+                    hide_first_person_parts,
+                    update_eye_height,
                 )
-                    .chain(),
+                    .chain()
+                    .in_set(CharacterControllerSystems::PostMovement),
+            )
+            .add_systems(
+                Update,
+                draw_jump_arcs
+                    .after(apply_movement_damping)
+                    .in_set(CharacterControllerSystems::PostMovement)
+                    .run_if(resource_exists::<bevy::gizmos::config::GizmoConfigStore>),
+            )
+            .add_systems(
+                // The spine is bent on top of the animated pose.
+                PostUpdate,
+                sync_first_person_body
+                    .after(bevy::animation::AnimationSystems)
+                    .before(TransformSystems::Propagate),
             )
             .add_systems(
                 // Run collision handling after collision detection.
                 //
-                // NOTE: The collision implementation here is very basic and a bit buggy.
-                //       A collide-and-slide algorithm would likely work better.
+                // NOTE: The contact response here is very basic and a bit buggy.
+                //       Controllers in `ControllerMode::CollideAndSlide` resolve their
+                //       movement with shape casts instead.
+                PhysicsSchedule,
+                (
+                    detect_hard_impacts,
+                    kinematic_controller_collisions,
+                    collide_and_slide,
+                )
+                    .chain()
+                    .in_set(NarrowPhaseSystems::Last),
+            )
+            .add_systems(
                 PhysicsSchedule,
-                kinematic_controller_collisions.in_set(NarrowPhaseSystems::Last),
+                slice_ground_checks.in_set(PhysicsStepSystems::First),
+            )
+            .add_systems(
+                PhysicsSchedule,
+                (restore_slide_velocity, mark_sliced_ground_casts).in_set(PhysicsStepSystems::Last),
+            );
+
+        #[cfg(feature = "debug_overlay")]
+        app.init_resource::<DebugOverlay>()
+            .add_systems(
+                Update,
+                toggle_debug_overlay.in_set(CharacterControllerSystems::Input),
+            )
+            .add_systems(
+                Update,
+                update_debug_overlay.in_set(CharacterControllerSystems::PostMovement),
+            );
+
+        #[cfg(feature = "dev_commands")]
+        app.add_message::<DevCommandInput>()
+            .add_message::<DevCommandOutput>()
+            .add_systems(
+                Update,
+                run_dev_commands
+                    .before(toggle_noclip)
+                    .in_set(CharacterControllerSystems::Input),
+            );
+
+        #[cfg(feature = "tnua_compat")]
+        app.add_systems(
+            Update,
+            apply_tnua_controllers
+                .before(follow_paths)
+                .in_set(CharacterControllerSystems::Input),
+        );
+
+        #[cfg(feature = "vleue_navigator")]
+        app.add_systems(
+            Update,
+            compute_navmesh_paths
+                .after(follow_paths)
+                .in_set(CharacterControllerSystems::Input),
+        );
+
+        app.add_systems(
+            Update,
+            gather_movement_input
+                .before(redirect_vehicle_input)
+                .in_set(CharacterControllerSystems::Movement),
+        );
+
+        // Load and store the desired velocity around each velocity hook set.
+        for hook in VelocityHooks::ALL {
+            app.add_systems(
+                Update,
+                (
+                    load_desired_velocity.in_set(VelocitySync::Load(hook)),
+                    store_desired_velocity.in_set(VelocitySync::Store(hook)),
+                ),
             );
+        }
+
+        #[cfg(feature = "bevy_audio")]
+        app.init_asset::<SurfaceSoundMap>().add_systems(
+            Update,
+            play_footstep_sounds
+                .after(detect_footsteps)
+                .in_set(CharacterControllerSystems::PostMovement),
+        );
+
+        #[cfg(feature = "serde")]
+        app.init_asset_loader::<SurfaceProfileMapLoader>();
     }
 }
 
+/// System sets for the character controller systems in [`Update`], run in order.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CharacterControllerSystems {
+    /// Reads device input and writes [`MovementAction`] messages.
+    Input,
+    /// Updates contact and state components like [`Grounded`], [`CeilingContact`],
+    /// and [`WallContact`].
+    Detection,
+    /// Applies gravity, movement, and look.
+    Movement,
+    /// Applies damping and other final velocity adjustments.
+    PostMovement,
+}
+
+/// A query filter for character controllers whose controller systems are active.
+///
+/// Controllers in [`Ragdoll`] mode, [`Parked`] in a vehicle, [`Seated`],
+/// waiting for [`PendingPlacement`], in [`Noclip`] mode, [`Grinding`] on a rail,
+/// [`Swinging`] on a rope, or [`ClimbingOutOfWater`] are skipped.
+pub(crate) type ControllerEnabled = (
+    Without<Ragdoll>,
+    Without<Parked>,
+    Without<Seated>,
+    Without<PendingPlacement>,
+    Without<Noclip>,
+    Without<Grinding>,
+    Without<Swinging>,
+    Without<ClimbingOutOfWater>,
+);
+
+/// A query filter for character controllers that can look around.
+///
+/// Unlike [`ControllerEnabled`], this includes [`Seated`] characters.
+pub(crate) type LookEnabled = (Without<Ragdoll>, Without<Parked>);
+
 /// A [`Message`] written for a movement input action.
-#[derive(Message)]
+#[derive(Message, Clone, Copy, Debug)]
 pub enum MovementAction {
     Move(Vector2),
     Jump,
-    Look(Vector2),
+    /// Written every frame while jump is held, for example to charge a [`ChargedJump`].
+    JumpHeld,
+    /// Written every frame while crouch is held.
+    Crouch,
+    /// Written every frame while prone is held.
+    Prone,
+    /// Written when interact is pressed, for example to pick up or drop an object.
+    Interact,
+    /// Written every frame while interact is held, for example to push a [`Pushable`].
+    InteractHeld,
+    /// Written every frame while throw is held.
+    Throw,
+    /// Written every frame while sprint is held.
+    Sprint,
+}
+
+/// A [`Message`] written for look input, with the look delta in mouse-motion units.
+///
+/// Kept separate from [`MovementAction`] so that look-only consumers, like camera rigs
+/// or turrets, don't depend on the movement input stream.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct LookAction(pub Vector2);
+
+/// A [`Message`] with a [`MovementAction`] for a single character controller.
+///
+/// Unlike the global [`MovementAction`] stream, which drives the controllers of local
+/// players, directed actions move their target regardless of [`FpsController`] input,
+/// so AI and scripted characters share the same movement physics.
+///
+/// Currently only [`MovementAction::Move`] and [`MovementAction::Jump`] are supported.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct DirectedMovementAction {
+    pub entity: Entity,
+    pub action: MovementAction,
 }
 
 /// A marker component indicating that an entity is using a character controller.
@@ -97,6 +651,8 @@ pub struct Grounded;
 pub struct MovementAcceleration(pub Scalar);
 
 /// The damping factor used for slowing down movement.
+///
+/// Horizontal velocity is multiplied by this factor every 1/60th of a second.
 #[derive(Component)]
 pub struct MovementDampingFactor(pub Scalar);
 
@@ -104,10 +660,153 @@ pub struct MovementDampingFactor(pub Scalar);
 #[derive(Component)]
 pub struct JumpImpulse(pub Scalar);
 
+/// Makes a character controller jump along the ground normal instead of straight up,
+/// so jumping on steep ramps pushes away from the surface.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct SlopeJump {
+    /// The blend between jumping along [`WorldUp`] at 0 and along the ground normal at 1.
+    pub normal_blend: Scalar,
+}
+
+impl Default for SlopeJump {
+    fn default() -> Self {
+        Self { normal_blend: 1.0 }
+    }
+}
+
+impl SlopeJump {
+    /// Returns the jump direction for the given up axis and ground normal.
+    pub fn direction(&self, up: Vector, ground_normal: Option<Vector>) -> Vector {
+        ground_normal.map_or(up, |normal| {
+            up.lerp(normal, self.normal_blend.clamp(0.0, 1.0))
+                .try_normalize()
+                .unwrap_or(up)
+        })
+    }
+}
+
+/// Makes a character controller's jumps carry it further the faster it is running.
+///
+/// The forward speed added by a jump scales with the horizontal speed at takeoff, and
+/// jumping while sprinting can add a dedicated boost with a flatter arc, so sprinting
+/// characters clear wider gaps. The resulting horizontal speed is capped by
+/// [`LongJump::max_speed`], so chained jumps can't build up unbounded speed.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct LongJump {
+    /// The forward speed added per m/s of horizontal speed at takeoff.
+    pub speed_scale: Scalar,
+    /// The forward speed added when jumping while sprinting, in m/s.
+    pub sprint_boost: Scalar,
+    /// The multiplier of the jump impulse when jumping while sprinting.
+    ///
+    /// Values below 1 make sprint jumps longer and flatter.
+    pub sprint_impulse_multiplier: Scalar,
+    /// The maximum horizontal speed a long jump can reach, in m/s.
+    pub max_speed: Scalar,
+}
+
+impl Default for LongJump {
+    fn default() -> Self {
+        Self {
+            speed_scale: 0.2,
+            sprint_boost: 2.0,
+            sprint_impulse_multiplier: 0.85,
+            max_speed: 15.0,
+        }
+    }
+}
+
+impl LongJump {
+    /// Returns the horizontal velocity after a long jump from the given horizontal
+    /// velocity. Standing jumps are boosted along `facing`.
+    pub fn horizontal_velocity(
+        &self,
+        horizontal: Vector,
+        facing: Vector,
+        sprinting: bool,
+    ) -> Vector {
+        let speed = horizontal.length();
+        let direction = horizontal.try_normalize().unwrap_or(facing);
+        let boost = speed * self.speed_scale + if sprinting { self.sprint_boost } else { 0.0 };
+        let boosted = horizontal + direction * boost.max(0.0);
+
+        // Never slow down characters that are already faster than the cap.
+        boosted.clamp_length_max(self.max_speed.max(speed))
+    }
+
+    /// Returns the multiplier of the jump impulse.
+    pub fn impulse_multiplier(&self, sprinting: bool) -> Scalar {
+        if sprinting {
+            self.sprint_impulse_multiplier
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Returns the velocity of a character jumping in the given direction, keeping the
+/// momentum perpendicular to the up axis.
+pub(crate) fn jump_velocity(
+    velocity: Vector,
+    up: Vector,
+    direction: Vector,
+    impulse: Scalar,
+) -> Vector {
+    velocity.reject_from_normalized(up) + direction * impulse
+}
+
 /// The gravitational acceleration used for a character controller.
 #[derive(Component)]
 pub struct ControllerGravity(pub Vector);
 
+/// The up direction of the world, for projects that aren't Y-up.
+///
+/// Movement is projected onto the plane perpendicular to it, damping only affects
+/// velocity along that plane, jumps push along it, slopes are measured against it,
+/// and looking around yaws around it. Must be normalized.
+///
+/// Character controllers are expected to have their local Y axis pointing up,
+/// so in a Z-up world, spawn them rotated to map Y onto Z.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct WorldUp(pub Vector);
+
+impl Default for WorldUp {
+    fn default() -> Self {
+        Self(Vector::Y)
+    }
+}
+
+impl WorldUp {
+    /// Returns the up direction of a character controller, its [`ControllerUp`] if it has one.
+    pub fn for_controller(&self, controller_up: Option<&ControllerUp>) -> Vector {
+        controller_up.map_or(self.0, |controller_up| controller_up.0)
+    }
+}
+
+/// The up direction of a single character controller, overriding [`WorldUp`] for it.
+///
+/// [`GravityField`]s set this so characters can walk around tiny planets and inside
+/// rotating stations. Every system that needs the up direction of a character controller,
+/// like movement, jumps, falls, ground slopes, and water depth, uses it through
+/// [`WorldUp::for_controller`]. Must be normalized.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct ControllerUp(pub Vector);
+
+/// The window whose cursor is grabbed and released by the controller.
+///
+/// If `None`, the [`PrimaryWindow`](bevy::window::PrimaryWindow) is used. Cursor management
+/// does nothing while the window doesn't exist, like in headless apps or before a window is
+/// spawned.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CursorWindow(pub Option<Entity>);
+
+/// A marker component for colliders that never count as ground, regardless of their slope.
+///
+/// Useful for invisible blockers, the heads of other characters, or slippery surfaces.
+/// Can be added to either the collider or its rigid body.
+#[derive(Component)]
+pub struct NotWalkable;
+
 /// The maximum angle a slope can have for a character controller
 /// to be able to climb and jump. If the slope is steeper than this angle,
 /// the character will slide down.
@@ -118,6 +817,147 @@ pub struct MaxSlopeAngle(pub Scalar);
 #[derive(Component)]
 pub struct MouseSensitivity(pub Scalar);
 
+/// Gamepad right stick sensitivity for look around.
+///
+/// The values are look deltas per second at full stick deflection, in the same units
+/// as mouse motion, so [`MouseSensitivity`] still applies on top of them.
+/// Controllers without this component use the default values.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct GamepadLookSensitivity {
+    /// The sensitivity for turning left and right.
+    pub horizontal: Scalar,
+    /// The sensitivity for looking up and down.
+    pub vertical: Scalar,
+}
+
+impl Default for GamepadLookSensitivity {
+    fn default() -> Self {
+        Self {
+            horizontal: 120.0,
+            vertical: 120.0,
+        }
+    }
+}
+
+/// The response curve of the movement stick, from deflection to movement input.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ResponseCurve {
+    /// Movement input is proportional to the deflection.
+    #[default]
+    Linear,
+    /// Movement input is the square of the deflection, for precise control at low speeds.
+    Squared,
+    /// Movement input is the deflection raised to the given power.
+    Power(Scalar),
+    /// A piecewise linear curve through `(deflection, input)` points sorted by deflection,
+    /// for example authored in a settings file.
+    Custom(Vec<Vector2>),
+}
+
+impl ResponseCurve {
+    /// Returns the movement input magnitude for a stick deflection from 0 to 1.
+    pub fn apply(&self, deflection: Scalar) -> Scalar {
+        let deflection = deflection.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => deflection,
+            Self::Squared => deflection * deflection,
+            Self::Power(exponent) => deflection.powf(exponent.max(0.0)),
+            Self::Custom(points) => {
+                let Some(end) = points.iter().position(|point| point.x >= deflection) else {
+                    return points.last().map_or(deflection, |point| point.y);
+                };
+                let Some(start) = end.checked_sub(1).map(|start| points[start]) else {
+                    return points[end].y;
+                };
+                let end = points[end];
+                let t = (deflection - start.x) / (end.x - start.x).max(Scalar::EPSILON);
+                start.y + (end.y - start.y) * t
+            }
+        }
+    }
+}
+
+/// Shapes the gamepad movement stick of an FPS controller.
+///
+/// Controllers without this component use the stick deflection linearly.
+#[derive(Component, Clone, Debug, Default)]
+pub struct StickResponse {
+    /// The response curve, applied before the input is clamped to a length of 1.
+    pub curve: ResponseCurve,
+    /// The curved deflection below which the character walks, or `None` to move analog
+    /// across the whole range. Below the threshold, movement scales up to `walk_input`,
+    /// and above it, the character runs at full input.
+    pub walk_threshold: Option<Scalar>,
+    /// The movement input at the walk threshold.
+    pub walk_input: Scalar,
+}
+
+impl StickResponse {
+    /// Returns the movement input for a stick position.
+    pub fn apply(&self, stick: Vector2) -> Vector2 {
+        let deflection = stick.length();
+        if deflection <= 0.0 {
+            return Vector2::ZERO;
+        }
+        let mut magnitude = self.curve.apply(deflection);
+        if let Some(threshold) = self.walk_threshold.filter(|&threshold| threshold > 0.0) {
+            magnitude = if magnitude < threshold {
+                magnitude / threshold * self.walk_input
+            } else {
+                1.0
+            };
+        }
+        stick / deflection * magnitude
+    }
+}
+
+/// Limits how fast look input can turn an FPS controller, separately for mouse and gamepad.
+///
+/// The limits apply to the look angles after [`MouseSensitivity`], so they stay the same
+/// when the sensitivity changes. This is useful for heavy characters, vehicles, and players
+/// who find fast turns disorienting. Controllers without this component turn freely.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct MaxTurnRate {
+    /// The limits for mouse look.
+    pub mouse: TurnRateLimit,
+    /// The limits for gamepad stick look.
+    pub gamepad: TurnRateLimit,
+}
+
+/// Maximum yaw and pitch speeds in radians per second. `None` leaves the axis unlimited.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TurnRateLimit {
+    pub yaw: Option<Scalar>,
+    pub pitch: Option<Scalar>,
+}
+
+impl TurnRateLimit {
+    /// Creates a limit with the same speed for yaw and pitch, in radians per second.
+    pub fn uniform(max_speed: Scalar) -> Self {
+        Self {
+            yaw: Some(max_speed),
+            pitch: Some(max_speed),
+        }
+    }
+
+    /// Returns how large the look deltas of a frame may be in total, in look input units.
+    fn look_budget(&self, sensitivity: Scalar, delta_time: Scalar) -> Vector2 {
+        let budget = |limit: Option<Scalar>| {
+            limit.map_or(Scalar::INFINITY, |limit| {
+                limit * delta_time / sensitivity.abs()
+            })
+        };
+        Vector2::new(budget(self.yaw), budget(self.pitch))
+    }
+}
+
+/// Clamps a look delta to the remaining budget of the frame and uses up that part of it.
+fn limit_look_delta(delta: Vector2, budget: &mut Vector2) -> Vector2 {
+    let limited = delta.clamp(-*budget, *budget);
+    *budget -= limited.abs();
+    limited
+}
+
 /// Pitch angle for camera (up/down rotation).
 #[derive(Component)]
 pub struct Pitch {
@@ -125,6 +965,48 @@ pub struct Pitch {
     pub max: Scalar,
 }
 
+/// A marker component for the entity that [`Pitch`] is applied to.
+///
+/// Use this for camera rigs where the camera is not a direct child of the controller,
+/// like a pivot entity between an offset entity and the camera. The pivot can be any
+/// descendant of the controller. Without a pivot, the first [`Camera3d`] descendant is pitched.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct CameraPivot;
+
+/// Smooths the yaw rotation of an FPS controller instead of applying look input 1:1.
+///
+/// Look input rotates a target yaw, and the [`Rotation`] of the controller eases towards it,
+/// which avoids visible snaps from large mouse deltas. Controllers without this component
+/// apply the raw input directly, which is the most responsive option for competitive play.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct YawSmoothing {
+    /// The time it takes to cover half of the remaining rotation to the target, in seconds.
+    pub half_life: Scalar,
+    /// The rotation the look input is aiming for, or `None` once it has been reached.
+    target: Option<Quaternion>,
+}
+
+impl YawSmoothing {
+    /// Creates a yaw smoothing with the given half-life in seconds.
+    pub fn new(half_life: Scalar) -> Self {
+        Self {
+            half_life,
+            target: None,
+        }
+    }
+
+    /// Returns the rotation the controller is easing towards, if it is still turning.
+    pub fn target(&self) -> Option<Quaternion> {
+        self.target
+    }
+}
+
+impl Default for YawSmoothing {
+    fn default() -> Self {
+        Self::new(0.03)
+    }
+}
+
 /// A marker component indicating that an entity is using FPS controls.
 #[derive(Component)]
 pub struct FpsController {
@@ -139,6 +1021,63 @@ impl Default for FpsController {
     }
 }
 
+/// Parameters of the downward shape cast used for grounded detection.
+///
+/// Changing this component at runtime updates the ground caster.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct GroundCastConfig {
+    /// The origin of the cast relative to the center of the character.
+    pub origin: Vector,
+    /// The scale of the cast shape relative to the collider.
+    /// Slightly smaller than the collider so that walls aren't detected as ground.
+    pub shrink_factor: Scalar,
+    /// The maximum number of hits the ground caster can report.
+    pub max_hits: u32,
+    /// How far below the character the ground is detected.
+    pub max_distance: Scalar,
+    /// The radius of a sphere to cast instead of the shrunken collider, which is cheaper.
+    ///
+    /// Used by [`NpcControllerBundle`]. Without it, the cast shape follows the collider.
+    pub sphere_radius: Option<Scalar>,
+}
+
+impl Default for GroundCastConfig {
+    fn default() -> Self {
+        Self {
+            origin: Vector::ZERO,
+            shrink_factor: 0.99,
+            max_hits: 1,
+            max_distance: 0.2,
+            sphere_radius: None,
+        }
+    }
+}
+
+impl GroundCastConfig {
+    /// Creates the cast shape as a slightly smaller version of the given collider,
+    /// or as a sphere if [`sphere_radius`](Self::sphere_radius) is set.
+    pub fn caster_shape(&self, collider: &Collider) -> Collider {
+        if let Some(radius) = self.sphere_radius {
+            return Collider::sphere(radius);
+        }
+        let mut caster_shape = collider.clone();
+        caster_shape.set_scale(Vector::ONE * self.shrink_factor, 10);
+        caster_shape
+    }
+
+    /// Creates the ground [`ShapeCaster`] for the given collider.
+    pub fn shape_caster(&self, collider: &Collider) -> ShapeCaster {
+        ShapeCaster::new(
+            self.caster_shape(collider),
+            self.origin,
+            Quaternion::default(),
+            Dir3::NEG_Y,
+        )
+        .with_max_distance(self.max_distance)
+        .with_max_hits(self.max_hits)
+    }
+}
+
 /// A bundle that contains the components needed for a basic
 /// kinematic character controller.
 #[derive(Bundle)]
@@ -147,8 +1086,38 @@ pub struct CharacterControllerBundle {
     body: RigidBody,
     collider: Collider,
     ground_caster: ShapeCaster,
+    ground_cast_config: GroundCastConfig,
     gravity: ControllerGravity,
     movement: MovementBundle,
+    interpolation: RenderInterpolation,
+    mode: ControllerMode,
+}
+
+/// Movement parameters for [`CharacterControllerBundle::with_movement_config`]
+/// and [`NpcControllerBundle::with_movement_config`].
+///
+/// Fields that are not set can be filled in with `..default()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MovementConfig {
+    /// See [`MovementAcceleration`].
+    pub acceleration: Scalar,
+    /// See [`MovementDampingFactor`].
+    pub damping: Scalar,
+    /// See [`JumpImpulse`].
+    pub jump_impulse: Scalar,
+    /// See [`MaxSlopeAngle`].
+    pub max_slope_angle: Scalar,
+}
+
+impl Default for MovementConfig {
+    fn default() -> Self {
+        Self {
+            acceleration: 30.0,
+            damping: 0.9,
+            jump_impulse: 7.0,
+            max_slope_angle: PI * 0.45,
+        }
+    }
 }
 
 /// A bundle that contains components for character movement.
@@ -185,40 +1154,234 @@ impl MovementBundle {
 
 impl Default for MovementBundle {
     fn default() -> Self {
-        Self::new(30.0, 0.9, 7.0, PI * 0.45)
+        MovementConfig::default().into()
+    }
+}
+
+impl From<MovementConfig> for MovementBundle {
+    fn from(config: MovementConfig) -> Self {
+        Self::new(
+            config.acceleration,
+            config.damping,
+            config.jump_impulse,
+            config.max_slope_angle,
+        )
     }
 }
 
 impl CharacterControllerBundle {
     pub fn new(collider: Collider, gravity: Vector) -> Self {
-        // Create shape caster as a slightly smaller version of collider
-        let mut caster_shape = collider.clone();
-        caster_shape.set_scale(Vector::ONE * 0.99, 10);
+        let ground_cast_config = GroundCastConfig::default();
 
         Self {
             character_controller: CharacterController,
             body: RigidBody::Kinematic,
+            ground_caster: ground_cast_config.shape_caster(&collider),
+            ground_cast_config,
             collider,
-            ground_caster: ShapeCaster::new(
-                caster_shape,
-                Vector::ZERO,
-                Quaternion::default(),
-                Dir3::NEG_Y,
-            )
-            .with_max_distance(0.2),
             gravity: ControllerGravity(gravity),
             movement: MovementBundle::default(),
+            interpolation: RenderInterpolation::default(),
+            mode: ControllerMode::default(),
+        }
+    }
+
+    /// Sets how the collisions of the character are resolved.
+    ///
+    /// See [`ControllerMode`].
+    pub fn with_mode(mut self, mode: ControllerMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets whether the rendered position is interpolated between physics ticks.
+    ///
+    /// Enabled by default. See [`RenderInterpolation`].
+    pub fn with_interpolation(mut self, enabled: bool) -> Self {
+        self.interpolation = RenderInterpolation(enabled);
+        self
+    }
+
+    /// Configures the shape cast used for grounded detection.
+    pub fn with_ground_cast(mut self, config: GroundCastConfig) -> Self {
+        self.ground_caster = config.shape_caster(&self.collider);
+        self.ground_cast_config = config;
+        self
+    }
+
+    /// Sets how far below the character the ground is detected.
+    pub fn with_ground_cast_distance(self, max_distance: Scalar) -> Self {
+        let config = GroundCastConfig {
+            max_distance,
+            ..self.ground_cast_config
+        };
+        self.with_ground_cast(config)
+    }
+
+    #[deprecated(since = "0.1.0", note = "use `with_movement_config` instead")]
+    pub fn with_movement(
+        self,
+        acceleration: Scalar,
+        damping: Scalar,
+        jump_impulse: Scalar,
+        max_slope_angle: Scalar,
+    ) -> Self {
+        self.with_movement_config(MovementConfig {
+            acceleration,
+            damping,
+            jump_impulse,
+            max_slope_angle,
+        })
+    }
+
+    /// Sets the movement parameters of the controller.
+    pub fn with_movement_config(mut self, config: MovementConfig) -> Self {
+        self.movement = config.into();
+        self
+    }
+
+    /// Sets acceleration, damping, jump impulse, and gravity solved from a [`MovementFeel`],
+    /// keeping the direction of gravity.
+    pub fn with_feel(mut self, feel: MovementFeel) -> Self {
+        let tuned = feel.solve();
+        self.movement.acceleration = tuned.acceleration();
+        self.movement.damping = tuned.damping();
+        self.movement.jump_impulse = tuned.jump_impulse();
+        self.gravity = tuned.gravity(self.gravity.0);
+        self
+    }
+}
+
+/// A lightweight bundle for AI-driven character controllers.
+///
+/// Unlike [`CharacterControllerBundle`], it has no camera pitch or mouse sensitivity, and
+/// grounded detection uses a small sphere cast instead of a shrunken copy of the collider.
+/// NPCs are driven with [`DirectedMovementAction`]s, for example by a [`PathFollower`].
+#[derive(Bundle)]
+pub struct NpcControllerBundle {
+    character_controller: CharacterController,
+    body: RigidBody,
+    collider: Collider,
+    ground_caster: ShapeCaster,
+    ground_cast_config: GroundCastConfig,
+    gravity: ControllerGravity,
+    acceleration: MovementAcceleration,
+    damping: MovementDampingFactor,
+    jump_impulse: JumpImpulse,
+    max_slope_angle: MaxSlopeAngle,
+    interpolation: RenderInterpolation,
+    mode: ControllerMode,
+}
+
+impl NpcControllerBundle {
+    pub fn new(collider: Collider, gravity: Vector) -> Self {
+        let MovementBundle {
+            acceleration,
+            damping,
+            jump_impulse,
+            max_slope_angle,
+            ..
+        } = MovementBundle::default();
+        let ground_cast_config = Self::ground_cast_config(&collider);
+
+        Self {
+            character_controller: CharacterController,
+            body: RigidBody::Kinematic,
+            ground_caster: ground_cast_config.shape_caster(&collider),
+            ground_cast_config,
+            collider,
+            gravity: ControllerGravity(gravity),
+            acceleration,
+            damping,
+            jump_impulse,
+            max_slope_angle,
+            interpolation: RenderInterpolation::default(),
+            mode: ControllerMode::default(),
+        }
+    }
+
+    /// Configures a sphere cast at the bottom of the collider, which is cheaper
+    /// than casting the whole collider shape.
+    fn ground_cast_config(collider: &Collider) -> GroundCastConfig {
+        let aabb = collider.aabb(Vector::ZERO, Quaternion::IDENTITY);
+        let half_extents = (aabb.max - aabb.min) * 0.5;
+        let radius = half_extents.x.min(half_extents.z) * 0.5;
+
+        GroundCastConfig {
+            origin: Vector::new(0.0, aabb.min.y + radius, 0.0),
+            sphere_radius: Some(radius),
+            ..default()
         }
     }
 
+    /// Sets how the collisions of the character are resolved.
+    ///
+    /// See [`ControllerMode`].
+    pub fn with_mode(mut self, mode: ControllerMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets whether the rendered position is interpolated between physics ticks.
+    ///
+    /// Enabled by default. See [`RenderInterpolation`].
+    pub fn with_interpolation(mut self, enabled: bool) -> Self {
+        self.interpolation = RenderInterpolation(enabled);
+        self
+    }
+
+    /// Configures the shape cast used for grounded detection.
+    ///
+    /// Leave [`GroundCastConfig::sphere_radius`] unset to cast the shrunken collider instead
+    /// of the default sphere.
+    pub fn with_ground_cast(mut self, config: GroundCastConfig) -> Self {
+        self.ground_caster = config.shape_caster(&self.collider);
+        self.ground_cast_config = config;
+        self
+    }
+
+    /// Sets how far below the character the ground is detected.
+    pub fn with_ground_cast_distance(self, max_distance: Scalar) -> Self {
+        let config = GroundCastConfig {
+            max_distance,
+            ..self.ground_cast_config
+        };
+        self.with_ground_cast(config)
+    }
+
+    #[deprecated(since = "0.1.0", note = "use `with_movement_config` instead")]
     pub fn with_movement(
-        mut self,
+        self,
         acceleration: Scalar,
         damping: Scalar,
         jump_impulse: Scalar,
         max_slope_angle: Scalar,
     ) -> Self {
-        self.movement = MovementBundle::new(acceleration, damping, jump_impulse, max_slope_angle);
+        self.with_movement_config(MovementConfig {
+            acceleration,
+            damping,
+            jump_impulse,
+            max_slope_angle,
+        })
+    }
+
+    /// Sets the movement parameters of the controller.
+    pub fn with_movement_config(mut self, config: MovementConfig) -> Self {
+        self.acceleration = MovementAcceleration(config.acceleration);
+        self.damping = MovementDampingFactor(config.damping);
+        self.jump_impulse = JumpImpulse(config.jump_impulse);
+        self.max_slope_angle = MaxSlopeAngle(config.max_slope_angle);
+        self
+    }
+
+    /// Sets acceleration, damping, jump impulse, and gravity solved from a [`MovementFeel`],
+    /// keeping the direction of gravity.
+    pub fn with_feel(mut self, feel: MovementFeel) -> Self {
+        let tuned = feel.solve();
+        self.acceleration = tuned.acceleration();
+        self.damping = tuned.damping();
+        self.jump_impulse = tuned.jump_impulse();
+        self.gravity = tuned.gravity(self.gravity.0);
         self
     }
 }
@@ -226,68 +1389,233 @@ impl CharacterControllerBundle {
 /// Sends [`MovementAction`] events based on keyboard input.
 fn keyboard_input(
     mut movement_writer: MessageWriter<MovementAction>,
+    mut look_writer: MessageWriter<LookAction>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
     mut mouse_motion: MessageReader<bevy::input::mouse::MouseMotion>,
-    fps_controllers: Query<&FpsController>,
+    real_time: Res<Time<Real>>,
+    bindings: Res<InputBindings>,
+    mut toggles: ResMut<InputToggles>,
+    fps_controllers: Query<(
+        &FpsController,
+        Option<&MouseSensitivity>,
+        Option<&MaxTurnRate>,
+        Option<&AccessibilityProfile>,
+        Option<&InputDeviceSettings>,
+    )>,
 ) {
+    let span = info_span!("keyboard_input", actions = Empty, looks = Empty).entered();
+    let mut looks = 0;
+    let mut actions = 0;
+    let mut write = |action| {
+        actions += 1;
+        movement_writer.write(action);
+    };
+
     // Check if any FPS controller has input enabled
-    let input_enabled = fps_controllers
+    let enabled_controller = fps_controllers
         .iter()
-        .any(|controller| controller.enable_input);
-    let up = keyboard_input.any_pressed([KeyCode::KeyW, KeyCode::ArrowUp]);
-    let down = keyboard_input.any_pressed([KeyCode::KeyS, KeyCode::ArrowDown]);
-    let left = keyboard_input.any_pressed([KeyCode::KeyA, KeyCode::ArrowLeft]);
-    let right = keyboard_input.any_pressed([KeyCode::KeyD, KeyCode::ArrowRight]);
+        .find(|(controller, ..)| controller.enable_input);
+    let input_enabled = enabled_controller.is_some();
+    let profile = enabled_controller.and_then(|(.., profile, _)| profile);
 
-    let horizontal = right as i8 - left as i8;
-    let vertical = up as i8 - down as i8;
-    let direction = Vector2::new(horizontal as Scalar, vertical as Scalar).clamp_length_max(1.0);
+    // Handle mouse buttons and look only if input is enabled
+    let mouse_input = input_enabled.then_some(&*mouse_input);
+    let pressed = |action| bindings.pressed(action, &keyboard_input, mouse_input);
+    let just_pressed = |action| bindings.just_pressed(action, &keyboard_input, mouse_input);
 
+    let direction = bound_direction(pressed);
     if direction != Vector2::ZERO {
-        movement_writer.write(MovementAction::Move(direction));
+        write(MovementAction::Move(direction));
+    }
+
+    if just_pressed(InputAction::Jump) {
+        write(MovementAction::Jump);
+    }
+
+    if pressed(InputAction::Jump) {
+        write(MovementAction::JumpHeld);
+    }
+
+    let toggles = toggles.keyboard();
+    if toggles.crouch(
+        profile,
+        pressed(InputAction::Crouch),
+        just_pressed(InputAction::Crouch),
+    ) {
+        write(MovementAction::Crouch);
+    }
+
+    if pressed(InputAction::Prone) {
+        write(MovementAction::Prone);
+    }
+
+    if just_pressed(InputAction::Interact) {
+        write(MovementAction::Interact);
+    }
+
+    if pressed(InputAction::Interact) {
+        write(MovementAction::InteractHeld);
+    }
+
+    if toggles.sprint(
+        profile,
+        pressed(InputAction::Sprint),
+        just_pressed(InputAction::Sprint),
+    ) {
+        write(MovementAction::Sprint);
     }
 
-    if keyboard_input.just_pressed(KeyCode::Space) {
-        movement_writer.write(MovementAction::Jump);
+    if pressed(InputAction::Throw) {
+        write(MovementAction::Throw);
     }
 
-    // Handle mouse look only if input is enabled
-    if input_enabled {
+    if let Some((_, sensitivity, max_turn_rate, _, device_settings)) = enabled_controller {
+        let look_scale = device_settings.map_or(1.0, |settings| settings.mouse_look_scale);
+
+        // The turn rate limit is shared by all mouse motion of the frame
+        let delta_time = real_time.delta_secs_f64().adjust_precision();
+        let mut budget = max_turn_rate.map_or(Vector2::INFINITY, |max_turn_rate| {
+            let sensitivity = sensitivity.map_or(1.0, |sensitivity| sensitivity.0);
+            max_turn_rate.mouse.look_budget(sensitivity, delta_time)
+        });
+
         for mouse_event in mouse_motion.read() {
-            movement_writer.write(MovementAction::Look(Vector2::new(
-                mouse_event.delta.x as Scalar,
-                mouse_event.delta.y as Scalar,
-            )));
+            looks += 1;
+            let delta = Vector2::new(mouse_event.delta.x as Scalar, mouse_event.delta.y as Scalar)
+                * look_scale;
+            look_writer.write(LookAction(limit_look_delta(delta, &mut budget)));
         }
     }
+    span.record("actions", actions);
+    span.record("looks", looks);
 }
 
 /// Sends [`MovementAction`] events based on gamepad input.
+///
+/// Stick look uses real time, so it keeps the same feel during slow motion.
 fn gamepad_input(
     mut movement_writer: MessageWriter<MovementAction>,
-    gamepads: Query<&Gamepad>,
-    fps_controllers: Query<&FpsController>,
+    mut look_writer: MessageWriter<LookAction>,
+    real_time: Res<Time<Real>>,
+    bindings: Res<InputBindings>,
+    mut toggles: ResMut<InputToggles>,
+    gamepads: Query<(Entity, &Gamepad)>,
+    fps_controllers: Query<(
+        &FpsController,
+        Option<&GamepadLookSensitivity>,
+        Option<&MouseSensitivity>,
+        Option<&MaxTurnRate>,
+        Option<&StickResponse>,
+        Option<&AccessibilityProfile>,
+        Option<&InputDeviceSettings>,
+    )>,
 ) {
-    // Check if any FPS controller has input enabled
-    let input_enabled = fps_controllers
+    let span = info_span!("gamepad_input", actions = Empty, looks = Empty).entered();
+    let mut looks = 0;
+    let mut actions = 0;
+    let mut write = |action| {
+        actions += 1;
+        movement_writer.write(action);
+    };
+
+    // Use the look sensitivity of the FPS controller with input enabled, if any
+    let Some((
+        _,
+        look_sensitivity,
+        mouse_sensitivity,
+        max_turn_rate,
+        stick_response,
+        profile,
+        device_settings,
+    )) = fps_controllers
         .iter()
-        .any(|controller| controller.enable_input);
-
-    if !input_enabled {
+        .find(|(controller, ..)| controller.enable_input)
+    else {
         return;
-    }
-    for gamepad in gamepads.iter() {
+    };
+
+    // Scaled by the real delta time to be independent of frame rate and time dilation
+    let delta_time = real_time.delta_secs_f64().adjust_precision();
+    // The turn rate limit is shared by all gamepads
+    let mut budget = max_turn_rate.map_or(Vector2::INFINITY, |max_turn_rate| {
+        let sensitivity = mouse_sensitivity.map_or(1.0, |sensitivity| sensitivity.0);
+        max_turn_rate.gamepad.look_budget(sensitivity, delta_time)
+    });
+
+    toggles.retain_gamepads(|gamepad| gamepads.contains(gamepad));
+
+    for (gamepad_entity, gamepad) in gamepads.iter() {
+        // Settings for the model of the gamepad replace the settings of the controller
+        let gamepad_settings = device_settings.and_then(|settings| settings.for_gamepad(gamepad));
+        let deadzone = |stick: Vector2| {
+            gamepad_settings.map_or(stick, |settings| settings.apply_deadzone(stick))
+        };
+        let look_sensitivity = gamepad_settings
+            .and_then(|settings| settings.look_sensitivity)
+            .or(look_sensitivity.copied())
+            .unwrap_or_default();
+        let stick_response = gamepad_settings
+            .and_then(|settings| settings.stick_response.as_ref())
+            .or(stick_response);
+        let pressed = |action| bindings.gamepad_pressed(action, gamepad);
+        let just_pressed = |action| bindings.gamepad_just_pressed(action, gamepad);
+
+        // Buttons bound to movement add to the stick
+        let buttons = bound_direction(pressed);
         if let (Some(x), Some(y)) = (
             gamepad.get(GamepadAxis::LeftStickX),
             gamepad.get(GamepadAxis::LeftStickY),
         ) {
-            movement_writer.write(MovementAction::Move(
-                Vector2::new(x as Scalar, y as Scalar).clamp_length_max(1.0),
+            let stick = deadzone(Vector2::new(x as Scalar, y as Scalar));
+            let input = stick_response.map_or(stick, |response| response.apply(stick));
+            write(MovementAction::Move(
+                (input + buttons).clamp_length_max(1.0),
             ));
+        } else if buttons != Vector2::ZERO {
+            write(MovementAction::Move(buttons));
+        }
+
+        if just_pressed(InputAction::Jump) {
+            write(MovementAction::Jump);
+        }
+
+        if pressed(InputAction::Jump) {
+            write(MovementAction::JumpHeld);
+        }
+
+        // Each gamepad toggles sprint and crouch on its own
+        let toggles = toggles.gamepad(gamepad_entity);
+        if toggles.crouch(
+            profile,
+            pressed(InputAction::Crouch),
+            just_pressed(InputAction::Crouch),
+        ) {
+            write(MovementAction::Crouch);
+        }
+
+        if pressed(InputAction::Prone) {
+            write(MovementAction::Prone);
+        }
+
+        if just_pressed(InputAction::Interact) {
+            write(MovementAction::Interact);
+        }
+
+        if pressed(InputAction::Interact) {
+            write(MovementAction::InteractHeld);
+        }
+
+        if pressed(InputAction::Throw) {
+            write(MovementAction::Throw);
         }
 
-        if gamepad.just_pressed(GamepadButton::South) {
-            movement_writer.write(MovementAction::Jump);
+        if toggles.sprint(
+            profile,
+            pressed(InputAction::Sprint),
+            just_pressed(InputAction::Sprint),
+        ) {
+            write(MovementAction::Sprint);
         }
 
         // Handle gamepad look input
@@ -295,104 +1623,424 @@ fn gamepad_input(
             gamepad.get(GamepadAxis::RightStickX),
             gamepad.get(GamepadAxis::RightStickY),
         ) {
-            let look_sensitivity = 2.0;
-            movement_writer.write(MovementAction::Look(Vector2::new(
-                x as Scalar * look_sensitivity,
-                -y as Scalar * look_sensitivity,
-            )));
+            looks += 1;
+            let stick = deadzone(Vector2::new(x as Scalar, y as Scalar));
+            let delta = Vector2::new(
+                stick.x * look_sensitivity.horizontal * delta_time,
+                -stick.y * look_sensitivity.vertical * delta_time,
+            );
+            look_writer.write(LookAction(limit_look_delta(delta, &mut budget)));
+        }
+    }
+    span.record("actions", actions);
+    span.record("looks", looks);
+}
+
+/// Applies changes to [`GroundCastConfig`] to the ground [`ShapeCaster`].
+fn sync_ground_caster(
+    mut query: Query<(&GroundCastConfig, &Collider, &mut ShapeCaster), Changed<GroundCastConfig>>,
+) {
+    for (config, collider, mut ground_caster) in &mut query {
+        ground_caster.shape = config.caster_shape(collider);
+        ground_caster.origin = config.origin;
+        ground_caster.max_hits = config.max_hits;
+        ground_caster.max_distance = config.max_distance;
+    }
+}
+
+/// Decides which ground hits count as walkable for a character controller.
+#[derive(SystemParam)]
+pub(crate) struct WalkableSurfaces<'w, 's> {
+    world_up: Res<'w, WorldUp>,
+    not_walkable: Query<'w, 's, (), With<NotWalkable>>,
+    collider_rbs: Query<'w, 's, &'static ColliderOf>,
+}
+
+impl WalkableSurfaces<'_, '_> {
+    /// Returns `true` if a hit on `entity` with the given world-space surface normal
+    /// is walkable ground.
+    pub(crate) fn is_walkable(
+        &self,
+        entity: Entity,
+        normal: Vector,
+        max_slope_angle: Option<&MaxSlopeAngle>,
+        drop_through: Option<&DropThrough>,
+    ) -> bool {
+        // Ignore the platform the character is dropping through.
+        if drop_through.is_some_and(|drop_through| drop_through.platform == entity) {
+            return false;
+        }
+
+        // Surfaces marked as not walkable never count as ground.
+        if self.not_walkable.contains(entity)
+            || self
+                .collider_rbs
+                .get(entity)
+                .is_ok_and(|collider_of| self.not_walkable.contains(collider_of.body))
+        {
+            return false;
+        }
+
+        if let Some(angle) = max_slope_angle {
+            normal.angle_between(self.world_up.0).abs() <= angle.0
+        } else {
+            true
         }
     }
 }
 
 /// Updates the [`Grounded`] status for character controllers.
+///
+/// Controllers with [`GroundProbes`] are handled by [`update_probed_grounded`] instead.
+#[allow(clippy::type_complexity)]
 fn update_grounded(
     mut commands: Commands,
+    world_up: Res<WorldUp>,
     mut query: Query<
-        (Entity, &ShapeHits, &Rotation, Option<&MaxSlopeAngle>),
-        With<CharacterController>,
+        (
+            Entity,
+            &ShapeHits,
+            &Rotation,
+            Option<&MaxSlopeAngle>,
+            Option<&DropThrough>,
+            Option<&ControllerUp>,
+            Option<&mut SlicedGroundCheck>,
+        ),
+        (
+            With<CharacterController>,
+            Without<GroundProbes>,
+            ControllerEnabled,
+        ),
     >,
+    walkable: WalkableSurfaces,
 ) {
-    for (entity, hits, rotation, max_slope_angle) in &mut query {
-        // The character is grounded if the shape caster has a hit with a normal
-        // that isn't too steep.
-        let is_grounded = hits.iter().any(|hit| {
-            if let Some(angle) = max_slope_angle {
-                (rotation * -hit.normal2).angle_between(Vector::Y).abs() <= angle.0
-            } else {
-                true
+    let span = info_span!("update_grounded", controllers = Empty, grounded = Empty).entered();
+    let (mut controllers, mut grounded) = (0, 0);
+
+    for (entity, hits, rotation, max_slope_angle, drop_through, controller_up, slice) in &mut query
+    {
+        controllers += 1;
+
+        let is_grounded = match slice {
+            // Time-sliced controllers estimate the ground between casts
+            Some(slice) if !slice.is_fresh() => slice.estimate_grounded(),
+            slice => {
+                // Slopes are measured against the up direction of the controller
+                let align = controller_up.map_or(Quaternion::IDENTITY, |controller_up| {
+                    Quaternion::from_rotation_arc(controller_up.0, world_up.0)
+                });
+
+                // The character is grounded if the shape caster has a hit with a normal
+                // that isn't too steep.
+                let ground_distance = hits
+                    .iter()
+                    .filter(|hit| {
+                        walkable.is_walkable(
+                            hit.entity,
+                            align * (rotation * -hit.normal2),
+                            max_slope_angle,
+                            drop_through,
+                        )
+                    })
+                    .map(|hit| hit.distance)
+                    .min_by(Scalar::total_cmp);
+                if let Some(mut slice) = slice {
+                    slice.record(ground_distance);
+                }
+                ground_distance.is_some()
             }
-        });
+        };
 
         if is_grounded {
+            grounded += 1;
             commands.entity(entity).insert(Grounded);
         } else {
             commands.entity(entity).remove::<Grounded>();
         }
     }
+
+    span.record("controllers", controllers);
+    span.record("grounded", grounded);
 }
 
 /// Responds to [`MovementAction`] events and moves character controllers accordingly.
+///
+/// Global [`MovementAction`]s move controllers with an input-enabled [`FpsController`],
+/// while [`DirectedMovementAction`]s only move the controller they are directed at.
+#[allow(clippy::type_complexity)]
 fn movement(
-    time: Res<Time>,
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    spatial_query: SpatialQuery,
     mut movement_reader: MessageReader<MovementAction>,
-    mut controllers: Query<(
-        &MovementAcceleration,
-        &JumpImpulse,
-        &mut LinearVelocity,
-        &Rotation,
-        Has<Grounded>,
-        &FpsController,
-    )>,
+    mut directed_reader: MessageReader<DirectedMovementAction>,
+    mut controllers: Query<
+        (
+            Entity,
+            &MovementAcceleration,
+            &JumpImpulse,
+            &mut LinearVelocity,
+            &Rotation,
+            Has<Grounded>,
+            Has<ChargedJump>,
+            Has<CustomMovementBasis>,
+            Option<&ShapeHits>,
+            Option<&FpsController>,
+            Option<&SpeedModifiers>,
+            Option<&WadingState>,
+            Option<(&AvoidLedges, &Position, &ColliderAabb)>,
+            (
+                Option<&SlopeJump>,
+                Option<&LongJump>,
+                Option<&RecoveringFromLanding>,
+                Option<&ViewYaw>,
+                Has<InCrawlVolume>,
+                Option<&ControllerUp>,
+                Option<&CurrentSurface>,
+                (Has<JumpLiftoff>, Option<&JumpCooldown>),
+                (Has<Sprinting>, Has<MovementSpeeds>),
+                (
+                    Option<&CoyoteTime>,
+                    Option<&JumpBuffer>,
+                    Option<&mut JumpTiming>,
+                    Option<&AccessibilityProfile>,
+                    Option<Ref<DropThrough>>,
+                ),
+            ),
+        ),
+        ControllerEnabled,
+    >,
 ) {
     // Precision is adjusted so that the example works with
     // both the `f32` and `f64` features. Otherwise you don't need this.
     let delta_time = time.delta_secs_f64().adjust_precision();
 
-    for event in movement_reader.read() {
+    let span = info_span!("movement", actions = Empty, applied = Empty).entered();
+    let (mut actions, mut applied) = (0, 0);
+
+    let global: Vec<&MovementAction> = movement_reader.read().collect();
+    let directed: Vec<&DirectedMovementAction> = directed_reader.read().collect();
+
+    // Whether the character is sprinting this frame, for long jumps.
+    let sprinting = |entity: Entity, input_enabled: bool| {
+        let is_sprint = |action: &MovementAction| matches!(action, MovementAction::Sprint);
+        (input_enabled && global.iter().any(|action| is_sprint(action)))
+            || directed
+                .iter()
+                .any(|directed| directed.entity == entity && is_sprint(&directed.action))
+    };
+
+    // Characters that jumped this frame, so repeated jump actions don't stack impulses
+    let mut jumped: Vec<Entity> = Vec::new();
+
+    // Buffered jumps are retried every frame until they are performed or expire
+    let buffered: Vec<Entity> = controllers
+        .iter()
+        .filter(|(.., (.., (_, _, timing, ..)))| timing.is_some_and(|timing| timing.is_buffered()))
+        .map(|(entity, ..)| entity)
+        .collect();
+
+    let global_actions = global.iter().map(|event| (None, *event, false));
+    let directed_actions = directed
+        .iter()
+        .map(|directed| (Some(directed.entity), &directed.action, false));
+    let buffered_actions = buffered
+        .iter()
+        .map(|&entity| (Some(entity), &MovementAction::Jump, true));
+
+    for (target, event, is_buffered) in global_actions
+        .chain(directed_actions)
+        .chain(buffered_actions)
+    {
+        actions += 1;
         for (
+            entity,
             movement_acceleration,
             jump_impulse,
             mut linear_velocity,
             rotation,
             is_grounded,
+            is_charged_jump,
+            has_custom_basis,
+            ground_hits,
             fps_controller,
+            speed_modifiers,
+            wading,
+            ledge_avoidance,
+            (
+                slope_jump,
+                long_jump,
+                recovering,
+                view_yaw,
+                in_crawl_volume,
+                controller_up,
+                surface,
+                (lifting_off, jump_cooldown),
+                (is_sprinting, has_speeds),
+                (coyote_time, jump_buffer, mut jump_timing, profile, drop_through),
+            ),
         ) in &mut controllers
         {
-            // Skip processing if input is disabled
-            if !fps_controller.enable_input {
-                continue;
+            match target {
+                // Directed actions only move their target
+                Some(target) if target != entity => continue,
+                // Skip processing if input is disabled
+                None if !fps_controller.is_some_and(|controller| controller.enable_input) => {
+                    continue
+                }
+                _ => {}
             }
+            applied += 1;
+            let up = world_up.for_controller(controller_up);
             match event {
                 MovementAction::Move(direction) => {
-                    // Convert local movement direction to world space based on character rotation
-                    let forward = rotation * Vector::NEG_Z;
-                    let right = rotation * Vector::X;
+                    // Controllers with a custom movement basis, like TargetVelocity, are
+                    // moved by their basis instead
+                    if has_custom_basis {
+                        continue;
+                    }
+
+                    // Convert local movement direction to world space based on character rotation,
+                    // or the view of characters that don't face it
+                    let input_rotation = input_rotation(rotation, view_yaw);
+                    let forward = input_rotation * Vector::NEG_Z;
+                    let right = input_rotation * Vector::X;
+
+                    // Speed modifiers are composed before the acceleration is applied.
+                    let acceleration = speed_modifiers
+                        .map_or(movement_acceleration.0, |modifiers| {
+                            modifiers.apply(movement_acceleration.0)
+                        });
 
                     // Calculate movement in world space
-                    let movement_vector = (right * direction.x + forward * direction.y)
-                        * movement_acceleration.0
-                        * delta_time;
+                    let movement_vector =
+                        (right * direction.x + forward * direction.y) * acceleration * delta_time;
+
+                    // Only move along the ground plane
+                    let mut movement_vector = movement_vector.reject_from_normalized(up);
+
+                    // Stop at the edge of drops that are too tall, instead of walking off
+                    if let Some((avoid_ledges, position, aabb)) =
+                        ledge_avoidance.filter(|_| is_grounded)
+                    {
+                        if avoid_ledges.ledge_ahead(
+                            &spatial_query,
+                            entity,
+                            position,
+                            aabb,
+                            movement_vector,
+                            up,
+                        ) {
+                            let direction = movement_vector.normalize_or_zero();
+                            let toward_ledge = linear_velocity.dot(direction).max(0.0);
+                            linear_velocity.0 -= direction * toward_ledge;
+                            continue;
+                        }
+                    }
+
+                    // Follow the slope of the ground, so running down ramps doesn't lose contact
+                    let ground_normal = ground_hits
+                        .filter(|_| is_grounded)
+                        .and_then(|hits| ground_normal(hits, rotation, up));
+                    if let Some(normal) = ground_normal {
+                        let length = movement_vector.length();
+                        movement_vector = movement_vector
+                            .reject_from_normalized(normal)
+                            .normalize_or_zero()
+                            * length;
+                    }
 
-                    linear_velocity.x += movement_vector.x;
-                    linear_velocity.z += movement_vector.z;
+                    linear_velocity.0 += movement_vector;
                 }
                 MovementAction::Jump => {
-                    if is_grounded {
-                        linear_velocity.y = jump_impulse.0;
+                    // Charged jumps are released by the charge_jumps system instead,
+                    // and hard landings and crawl volumes can lock jumping. The ground is
+                    // still detected right after a jump, so jumps during liftoff are ignored.
+                    let jump_locked = in_crawl_volume
+                        || recovering.is_some_and(|recovering| recovering.jump_locked)
+                        || jump_blocked(lifting_off, jump_cooldown)
+                        || jumped.contains(&entity);
+                    // Characters that walked off a ledge can still jump during coyote time
+                    let can_jump = is_grounded
+                        || jump_timing
+                            .as_ref()
+                            .is_some_and(|timing| timing.in_coyote_time(coyote_time, profile));
+                    if can_jump && !is_charged_jump && !jump_locked {
+                        // Wading through shallow water weakens jumps.
+                        let mut jump_multiplier =
+                            wading.map_or(1.0, |wading| wading.jump_multiplier);
+                        let direction = slope_jump.map_or(up, |slope_jump| {
+                            let normal =
+                                ground_hits.and_then(|hits| ground_normal(hits, rotation, up));
+                            slope_jump.direction(up, normal)
+                        });
+
+                        // Running jumps carry further, and sprint jumps are flatter
+                        if let Some(long_jump) = long_jump {
+                            let input_enabled =
+                                fps_controller.is_some_and(|controller| controller.enable_input);
+                            // Characters with movement speeds sprint with stamina and
+                            // stance rules, and surfaces like mud don't allow sprinting
+                            let sprinting = if has_speeds {
+                                is_sprinting
+                            } else {
+                                sprinting(entity, input_enabled)
+                                    && surface.is_none_or(CurrentSurface::can_sprint)
+                            };
+                            let facing = (rotation * Vector::NEG_Z)
+                                .reject_from_normalized(up)
+                                .normalize_or_zero();
+                            let horizontal = linear_velocity.reject_from_normalized(up);
+                            linear_velocity.0 = linear_velocity.0 - horizontal
+                                + long_jump.horizontal_velocity(horizontal, facing, sprinting);
+                            jump_multiplier *= long_jump.impulse_multiplier(sprinting);
+                        }
+
+                        linear_velocity.0 = jump_velocity(
+                            linear_velocity.0,
+                            up,
+                            direction,
+                            jump_impulse.0 * jump_multiplier,
+                        );
+                        commands.trigger(Jumped { entity });
+                        jumped.push(entity);
+                    } else if !is_charged_jump
+                        && !is_buffered
+                        && !jumped.contains(&entity)
+                        && !drop_through.is_some_and(|drop_through| drop_through.is_added())
+                    {
+                        // Remember the jump, so it is performed once the character can jump.
+                        // Jumps that started dropping through a platform are used up instead.
+                        if let Some(timing) = jump_timing.as_mut() {
+                            timing.buffer(jump_buffer);
+                        }
                     }
                 }
-                MovementAction::Look(_) => {
-                    // Look actions are handled by the mouse_look system
+                MovementAction::JumpHeld => {
+                    // Held jumps are handled by the systems that support them, like charge_jumps
+                }
+                MovementAction::Crouch | MovementAction::Prone => {
+                    // Stance actions are handled by the update_stance system
+                }
+                MovementAction::Interact | MovementAction::InteractHeld | MovementAction::Throw => {
+                    // Interactions are handled by their own systems, like pick_up_objects
+                }
+                MovementAction::Sprint => {
+                    // Sprinting is handled by the systems that support it, like fly_noclip
                 }
             }
         }
     }
+
+    span.record("actions", actions);
+    span.record("applied", applied);
 }
 
 /// Applies [`ControllerGravity`] to character controllers.
 fn apply_gravity(
-    time: Res<Time>,
-    mut controllers: Query<(&ControllerGravity, &mut LinearVelocity)>,
+    time: Res<Time<Virtual>>,
+    mut controllers: Query<(&ControllerGravity, &mut LinearVelocity), ControllerEnabled>,
 ) {
     // Precision is adjusted so that the example works with
     // both the `f32` and `f64` features. Otherwise you don't need this.
@@ -403,12 +2051,81 @@ fn apply_gravity(
     }
 }
 
-/// Slows down movement in the XZ plane.
-fn apply_movement_damping(mut query: Query<(&MovementDampingFactor, &mut LinearVelocity)>) {
-    for (damping_factor, mut linear_velocity) in &mut query {
-        // We could use `LinearDamping`, but we don't want to dampen movement along the Y axis
-        linear_velocity.x *= damping_factor.0;
-        linear_velocity.z *= damping_factor.0;
+/// The frame rate at which [`MovementDampingFactor`] is applied once per frame.
+pub(crate) const DAMPING_REFERENCE_RATE: Scalar = 60.0;
+
+/// Returns the normal of the ground cast hit that faces up the most, if any faces up at all.
+pub(crate) fn ground_normal(hits: &ShapeHits, rotation: &Rotation, up: Vector) -> Option<Vector> {
+    hits.iter()
+        .map(|hit| rotation * -hit.normal2)
+        .filter(|normal| normal.dot(up) > 0.0)
+        .max_by(|a, b| a.dot(up).total_cmp(&b.dot(up)))
+}
+
+/// Slows down movement perpendicular to [`WorldUp`], or the [`ControllerUp`] of the controller.
+///
+/// Grounded characters are slowed down along the ground plane instead, matching the
+/// movement that follows the slope of the ground.
+///
+/// The damping factor is scaled by the virtual delta time, so slow motion
+/// and pausing affect damping the same way they affect acceleration.
+///
+/// [`TargetVelocity`] controllers decelerate on their own and aren't damped.
+/// [`ExternalVelocity`] is excluded, since it decays at its own rate.
+#[allow(clippy::type_complexity)]
+fn apply_movement_damping(
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    mut query: Query<
+        (
+            &MovementDampingFactor,
+            &mut LinearVelocity,
+            &Rotation,
+            Has<Grounded>,
+            Option<&ShapeHits>,
+            Option<&ExternalVelocity>,
+            Option<&ControllerUp>,
+            Option<&CurrentSurface>,
+        ),
+        (ControllerEnabled, Without<TargetVelocity>),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (
+        damping_factor,
+        mut linear_velocity,
+        rotation,
+        is_grounded,
+        ground_hits,
+        external,
+        controller_up,
+        surface,
+    ) in &mut query
+    {
+        let up = world_up.for_controller(controller_up);
+
+        // External velocity decays at its own rate, so only the rest is damped
+        let external = external.map_or(Vector::ZERO, ExternalVelocity::applied);
+        let velocity = linear_velocity.0 - external;
+
+        // The surface under grounded characters can override their damping
+        let damping_factor = surface
+            .and_then(|surface| surface.profile.damping)
+            .filter(|_| is_grounded)
+            .unwrap_or(damping_factor.0);
+        let damping = damping_factor.powf(delta_time * DAMPING_REFERENCE_RATE);
+
+        // Characters leaving the ground, like when jumping, are damped like airborne ones
+        let axis = ground_hits
+            .filter(|_| is_grounded)
+            .and_then(|hits| ground_normal(hits, rotation, up))
+            .filter(|&normal| velocity.dot(normal) <= 0.0)
+            .unwrap_or(up);
+
+        // We could use `LinearDamping`, but we don't want to dampen movement along the up axis
+        let vertical = axis * velocity.dot(axis);
+        linear_velocity.0 = vertical + (velocity - vertical) * damping + external;
     }
 }
 
@@ -425,9 +2142,21 @@ fn kinematic_controller_collisions(
     bodies: Query<&RigidBody>,
     collider_rbs: Query<&ColliderOf, Without<Sensor>>,
     mut character_controllers: Query<
-        (&mut Position, &mut LinearVelocity, Option<&MaxSlopeAngle>),
+        (
+            &mut Position,
+            &mut LinearVelocity,
+            Option<&MaxSlopeAngle>,
+            Option<&DropThrough>,
+            Option<&ControllerMode>,
+            Option<&ControllerUp>,
+        ),
         (With<RigidBody>, With<CharacterController>),
     >,
+    characters: Query<(), With<CharacterController>>,
+    one_way_platforms: Query<(), With<OneWayPlatform>>,
+    not_walkable: Query<(), With<NotWalkable>>,
+    policy: Res<CharacterCollisionPolicy>,
+    world_up: Res<WorldUp>,
     time: Res<Time>,
 ) {
     // Iterate through collisions and move the kinematic body to resolve penetration
@@ -439,129 +2168,161 @@ fn kinematic_controller_collisions(
             continue;
         };
 
-        // Get the body of the character controller and whether it is the first
-        // or second entity in the collision.
-        let is_first: bool;
-
-        let character_rb: RigidBody;
-        let is_other_dynamic: bool;
-
-        let (mut position, mut linear_velocity, max_slope_angle) =
-            if let Ok(character) = character_controllers.get_mut(rb1) {
-                is_first = true;
-                character_rb = *bodies.get(rb1).unwrap();
-                is_other_dynamic = bodies.get(rb2).is_ok_and(|rb| rb.is_dynamic());
-                character
-            } else if let Ok(character) = character_controllers.get_mut(rb2) {
-                is_first = false;
-                character_rb = *bodies.get(rb2).unwrap();
-                is_other_dynamic = bodies.get(rb1).is_ok_and(|rb| rb.is_dynamic());
-                character
-            } else {
+        // Resolve the collision for each character controller in the pair.
+        // Both bodies can be character controllers, in which case the
+        // configured [`CharacterCollisionPolicy`] is used.
+        for (character_entity, other_entity, other_collider, is_first) in [
+            (rb1, rb2, contacts.collider2, true),
+            (rb2, rb1, contacts.collider1, false),
+        ] {
+            let Ok((
+                mut position,
+                mut linear_velocity,
+                max_slope_angle,
+                drop_through,
+                mode,
+                controller_up,
+            )) = character_controllers.get_mut(character_entity)
+            else {
                 continue;
             };
+            let up = world_up.for_controller(controller_up);
 
-        // This system only handles collision response for kinematic character controllers.
-        if !character_rb.is_kinematic() {
-            continue;
-        }
-
-        // Iterate through contact manifolds and their contacts.
-        // Each contact in a single manifold shares the same contact normal.
-        for manifold in contacts.manifolds.iter() {
-            let normal = if is_first {
-                -manifold.normal
-            } else {
-                manifold.normal
-            };
-
-            let mut deepest_penetration: Scalar = Scalar::MIN;
-
-            // Solve each penetrating contact in the manifold.
-            for contact in manifold.points.iter() {
-                if contact.penetration > 0.0 {
-                    position.0 += normal * contact.penetration;
-                }
-                deepest_penetration = deepest_penetration.max(contact.penetration);
+            // This system only handles collision response for kinematic character controllers.
+            let character_rb = *bodies.get(character_entity).unwrap();
+            if !character_rb.is_kinematic() {
+                continue;
             }
 
-            // For now, this system only handles velocity corrections for collisions against static geometry.
-            if is_other_dynamic {
+            let is_other_dynamic = bodies.get(other_entity).is_ok_and(|rb| rb.is_dynamic());
+
+            // One-way platforms only block characters landing on them from above,
+            // and are ignored entirely while dropping through them.
+            let is_one_way = one_way_platforms.contains(other_collider)
+                || one_way_platforms.contains(other_entity);
+            if is_one_way
+                && (drop_through
+                    .is_some_and(|drop_through| drop_through.platform == other_collider)
+                    || passes_through_one_way(contacts, is_first, &linear_velocity, up))
+            {
                 continue;
             }
 
-            // Determine if the slope is climbable or if it's too steep to walk on.
-            let slope_angle = normal.angle_between(Vector::Y);
-            let climbable = max_slope_angle.is_some_and(|angle| slope_angle.abs() <= angle.0);
-
-            if deepest_penetration > 0.0 {
-                // If the slope is climbable, snap the velocity so that the character
-                // up and down the surface smoothly.
-                if climbable {
-                    // Points in the normal's direction in the XZ plane.
-                    let normal_direction_xz =
-                        normal.reject_from_normalized(Vector::Y).normalize_or_zero();
-
-                    // The movement speed along the direction above.
-                    let linear_velocity_xz = linear_velocity.dot(normal_direction_xz);
+            // Solid characters share the penetration so that neither of them gets shoved.
+            let mut penetration_share = 1.0;
+            if characters.contains(other_entity) {
+                match *policy {
+                    CharacterCollisionPolicy::Solid => penetration_share = 0.5,
+                    CharacterCollisionPolicy::SoftPush { strength } => {
+                        push_apart(contacts, is_first, strength, up, &mut linear_velocity);
+                        continue;
+                    }
+                    CharacterCollisionPolicy::PassThrough => continue,
+                }
+            }
 
-                    // Snap the Y speed based on the speed at which the character is moving
-                    // up or down the slope, and how steep the slope is.
-                    //
-                    // A 2D visualization of the slope, the contact normal, and the velocity components:
-                    //
-                    //             ╱
-                    //     normal ╱
-                    // *         ╱
-                    // │   *    ╱   velocity_x
-                    // │       * - - - - - -
-                    // │           *       | velocity_y
-                    // │               *   |
-                    // *───────────────────*
-
-                    let max_y_speed = -linear_velocity_xz * slope_angle.tan();
-                    linear_velocity.y = linear_velocity.y.max(max_y_speed);
+            // Iterate through contact manifolds and their contacts.
+            // Each contact in a single manifold shares the same contact normal.
+            for manifold in contacts.manifolds.iter() {
+                let normal = if is_first {
+                    -manifold.normal
                 } else {
-                    // The character is intersecting an unclimbable object, like a wall.
-                    // We want the character to slide along the surface, similarly to
-                    // a collide-and-slide algorithm.
+                    manifold.normal
+                };
 
-                    // Don't apply an impulse if the character is moving away from the surface.
-                    if linear_velocity.dot(normal) > 0.0 {
-                        continue;
-                    }
+                let mut deepest_penetration: Scalar = Scalar::MIN;
 
-                    // Slide along the surface, rejecting the velocity along the contact normal.
-                    let impulse = linear_velocity.reject_from_normalized(normal);
-                    linear_velocity.0 = impulse;
+                // Solve each penetrating contact in the manifold.
+                for contact in manifold.points.iter() {
+                    if contact.penetration > 0.0 {
+                        position.0 += normal * contact.penetration * penetration_share;
+                    }
+                    deepest_penetration = deepest_penetration.max(contact.penetration);
                 }
-            } else {
-                // The character is not yet intersecting the other object,
-                // but the narrow phase detected a speculative collision.
-                //
-                // We need to push back the part of the velocity
-                // that would cause penetration within the next frame.
 
-                let normal_speed = linear_velocity.dot(normal);
-
-                // Don't apply an impulse if the character is moving away from the surface.
-                if normal_speed > 0.0 {
+                // For now, this system only handles velocity corrections for collisions against static geometry.
+                // Collide-and-slide characters have their velocity resolved by the solver.
+                if is_other_dynamic || mode == Some(&ControllerMode::CollideAndSlide) {
                     continue;
                 }
 
-                // Compute the impulse to apply.
-                let impulse_magnitude =
-                    normal_speed - (deepest_penetration / time.delta_secs_f64().adjust_precision());
-                let mut impulse = impulse_magnitude * normal;
+                // Determine if the slope is climbable or if it's too steep to walk on.
+                let slope_angle = normal.angle_between(up);
+                let climbable = max_slope_angle.is_some_and(|angle| slope_angle.abs() <= angle.0)
+                    && !not_walkable.contains(other_collider)
+                    && !not_walkable.contains(other_entity);
+
+                if deepest_penetration > 0.0 {
+                    // If the slope is climbable, snap the velocity so that the character
+                    // up and down the surface smoothly.
+                    if climbable {
+                        // Points in the normal's direction in the plane perpendicular to up.
+                        let normal_direction_xz =
+                            normal.reject_from_normalized(up).normalize_or_zero();
+
+                        // The movement speed along the direction above.
+                        let linear_velocity_xz = linear_velocity.dot(normal_direction_xz);
+
+                        // Snap the upward speed based on the speed at which the character is moving
+                        // up or down the slope, and how steep the slope is.
+                        //
+                        // A 2D visualization of the slope, the contact normal, and the velocity components:
+                        //
+                        //             ╱
+                        //     normal ╱
+                        // *         ╱
+                        // │   *    ╱   velocity_x
+                        // │       * - - - - - -
+                        // │           *       | velocity_y
+                        // │               *   |
+                        // *───────────────────*
+
+                        let max_y_speed = -linear_velocity_xz * slope_angle.tan();
+                        let y_speed = linear_velocity.dot(up);
+                        linear_velocity.0 += up * (max_y_speed - y_speed).max(0.0);
+                    } else {
+                        // The character is intersecting an unclimbable object, like a wall.
+                        // We want the character to slide along the surface, similarly to
+                        // a collide-and-slide algorithm.
+
+                        // Don't apply an impulse if the character is moving away from the surface.
+                        if linear_velocity.dot(normal) > 0.0 {
+                            continue;
+                        }
 
-                // Apply the impulse differently depending on the slope angle.
-                if climbable {
-                    // Avoid sliding down slopes.
-                    linear_velocity.y -= impulse.y.min(0.0);
+                        // Slide along the surface, rejecting the velocity along the contact normal.
+                        let impulse = linear_velocity.reject_from_normalized(normal);
+                        linear_velocity.0 = impulse;
+                    }
                 } else {
-                    // Avoid climbing up walls.
-                    impulse.y = impulse.y.max(0.0);
-                    linear_velocity.0 -= impulse;
+                    // The character is not yet intersecting the other object,
+                    // but the narrow phase detected a speculative collision.
+                    //
+                    // We need to push back the part of the velocity
+                    // that would cause penetration within the next frame.
+
+                    let normal_speed = linear_velocity.dot(normal);
+
+                    // Don't apply an impulse if the character is moving away from the surface.
+                    if normal_speed > 0.0 {
+                        continue;
+                    }
+
+                    // Compute the impulse to apply.
+                    let impulse_magnitude = normal_speed
+                        - (deepest_penetration / time.delta_secs_f64().adjust_precision());
+                    let mut impulse = impulse_magnitude * normal;
+                    let impulse_up = impulse.dot(up);
+
+                    // Apply the impulse differently depending on the slope angle.
+                    if climbable {
+                        // Avoid sliding down slopes.
+                        linear_velocity.0 -= up * impulse_up.min(0.0);
+                    } else {
+                        // Avoid climbing up walls.
+                        impulse -= up * impulse_up.min(0.0);
+                        linear_velocity.0 -= impulse;
+                    }
                 }
             }
         }
@@ -569,8 +2330,12 @@ fn kinematic_controller_collisions(
 }
 
 /// Handles mouse look input for character rotation and camera pitch.
+///
+/// Look deltas are applied directly without any delta time,
+/// so looking around stays real-time when [`Time<Virtual>`] is slowed down.
 fn mouse_look(
-    mut movement_reader: MessageReader<MovementAction>,
+    world_up: Res<WorldUp>,
+    mut look_reader: MessageReader<LookAction>,
     mut controllers: Query<
         (
             Entity,
@@ -578,50 +2343,167 @@ fn mouse_look(
             &mut Rotation,
             &mut Pitch,
             &FpsController,
+            Option<&Seated>,
+            Option<&mut YawSmoothing>,
+            Option<&mut ViewYaw>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, LookEnabled),
+    >,
+    mut pivots: Query<
+        (&mut Transform, Has<CameraPivot>),
+        (
+            Or<(With<CameraPivot>, With<Camera3d>)>,
+            Without<CharacterController>,
         ),
-        With<CharacterController>,
     >,
-    mut cameras: Query<&mut Transform, (With<Camera3d>, Without<CharacterController>)>,
     children: Query<&Children>,
+    seats: Query<(&Seat, &GlobalTransform)>,
 ) {
-    for event in movement_reader.read() {
-        if let MovementAction::Look(delta) = event {
-            for (entity, sensitivity, mut rotation, mut pitch, fps_controller) in &mut controllers {
-                // Skip processing if input is disabled
-                if !fps_controller.enable_input {
-                    continue;
-                }
+    let span = info_span!("mouse_look", look_actions = Empty).entered();
+    let mut look_actions = 0;
 
-                // Rotate around Y axis (yaw) based on mouse X movement
-                let yaw_delta = -delta.x * sensitivity.0;
-                let yaw_rotation = Quaternion::from_rotation_y(yaw_delta);
-                rotation.0 = yaw_rotation * rotation.0;
-
-                // Update pitch based on mouse Y movement
-                let pitch_delta = -delta.y * sensitivity.0;
-                pitch.angle += pitch_delta;
-                pitch.angle = pitch.angle.clamp(-pitch.max, pitch.max);
-
-                // Apply pitch to camera (if it's a child of the controller)
-                if let Ok(children) = children.get(entity) {
-                    for child in children.iter() {
-                        if let Ok(mut camera_transform) = cameras.get_mut(child) {
-                            camera_transform.rotation = Quaternion::from_rotation_x(pitch.angle);
-                        }
+    for LookAction(delta) in look_reader.read() {
+        look_actions += 1;
+        for (
+            entity,
+            sensitivity,
+            mut rotation,
+            mut pitch,
+            fps_controller,
+            seated,
+            mut smoothing,
+            mut view_yaw,
+            controller_up,
+        ) in &mut controllers
+        {
+            // Skip processing if input is disabled
+            if !fps_controller.enable_input {
+                continue;
+            }
+
+            // Rotate around the up axis (yaw) based on mouse X movement
+            let yaw_delta = -delta.x * sensitivity.0;
+            let up = world_up.for_controller(controller_up);
+            let yaw_rotation = Quaternion::from_axis_angle(up, yaw_delta);
+            let seat = seated.and_then(|seated| seats.get(seated.seat).ok());
+
+            match view_yaw.as_mut() {
+                // Controllers that don't face their view orbit the view around them instead
+                Some(view_yaw) => view_yaw.0 = (yaw_rotation * view_yaw.0).normalize(),
+                None => {
+                    // Smoothed controllers rotate their target yaw instead of the rotation itself
+                    let current = smoothing
+                        .as_ref()
+                        .and_then(|smoothing| smoothing.target)
+                        .unwrap_or(rotation.0);
+                    let mut yawed = yaw_rotation * current;
+
+                    // Seated characters can only look around within the limits of the seat
+                    if let Some((seat, seat_transform)) = seat {
+                        let (_, seat_rotation, _) = seat_transform.to_scale_rotation_translation();
+                        let seat_yaw = yaw_of(seat_rotation.adjust_precision(), up);
+                        yawed = clamp_seated_yaw(yawed, seat, seat_yaw, up);
+                    }
+
+                    match smoothing.as_mut() {
+                        Some(smoothing) => smoothing.target = Some(yawed),
+                        None => rotation.0 = yawed,
                     }
                 }
             }
+            let max_pitch = seat.map_or(pitch.max, |(seat, _)| pitch.max.min(seat.pitch_limit));
+
+            // Update pitch based on mouse Y movement
+            let pitch_delta = -delta.y * sensitivity.0;
+            pitch.angle += pitch_delta;
+            pitch.angle = pitch.angle.clamp(-max_pitch, max_pitch);
+
+            // Apply pitch to the camera pivot, or the camera if there is none
+            if let Some(target) = find_camera_pivot(entity, &children, &pivots) {
+                if let Ok((mut pivot_transform, _)) = pivots.get_mut(target) {
+                    pivot_transform.rotation = match view_yaw {
+                        Some(view_yaw) => orbit_rotation(rotation.0, view_yaw.0, pitch.angle).f32(),
+                        None => Quaternion::from_rotation_x(pitch.angle),
+                    };
+                }
+            }
+        }
+    }
+
+    span.record("look_actions", look_actions);
+}
+
+/// Eases the rotation of controllers with [`YawSmoothing`] towards their target yaw.
+fn smooth_yaw(
+    time: Res<Time<Virtual>>,
+    mut controllers: Query<(&mut Rotation, &mut YawSmoothing), With<CharacterController>>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (mut rotation, mut smoothing) in &mut controllers {
+        let Some(target) = smoothing.target else {
+            continue;
+        };
+
+        // Exponential smoothing is independent of the frame rate
+        let t = if smoothing.half_life > 0.0 {
+            1.0 - Scalar::powf(0.5, delta_time / smoothing.half_life)
+        } else {
+            1.0
+        };
+        rotation.0 = rotation.0.slerp(target, t);
+
+        // Stop steering once the target is reached, so other systems can rotate the controller
+        if rotation.0.angle_between(target) < 1e-4 {
+            rotation.0 = target;
+            smoothing.target = None;
+        }
+    }
+}
+
+/// Returns the descendant of the controller that pitch is applied to.
+///
+/// A [`CameraPivot`] anywhere in the hierarchy takes precedence over a [`Camera3d`].
+#[allow(clippy::type_complexity)]
+pub(crate) fn find_camera_pivot(
+    entity: Entity,
+    children: &Query<&Children>,
+    pivots: &Query<
+        (&mut Transform, Has<CameraPivot>),
+        (
+            Or<(With<CameraPivot>, With<Camera3d>)>,
+            Without<CharacterController>,
+        ),
+    >,
+) -> Option<Entity> {
+    let mut camera = None;
+    for descendant in children.iter_descendants(entity) {
+        match pivots.get(descendant) {
+            Ok((_, true)) => return Some(descendant),
+            Ok((_, false)) if camera.is_none() => camera = Some(descendant),
+            _ => {}
         }
     }
+    camera
 }
 
 /// Manages cursor grab mode and FPS controller input
 /// Right click to grab cursor and enable FPS controls
 /// Escape to release cursor and disable FPS controls
+///
+/// Only the cursor of the [`CursorWindow`] is changed, so apps with several windows,
+/// like editors and debug windows, keep their other cursors.
+///
+/// Grabbing the cursor only enables the [`PossessedController`], or the single
+/// [`FpsController`] if none was possessed, and releasing it disables all of them.
 fn manage_cursor(
     btn: Res<ButtonInput<MouseButton>>,
     key: Res<ButtonInput<KeyCode>>,
-    mut cursor_options: Single<&mut bevy::window::CursorOptions>,
+    cursor_window: Res<CursorWindow>,
+    possessed: Res<PossessedController>,
+    primary_window: Query<Entity, With<bevy::window::PrimaryWindow>>,
+    mut windows: Query<&mut bevy::window::CursorOptions>,
     mut controller_query: Query<&mut FpsController>,
 ) {
     let mut cursor_grabbed = false;
@@ -634,23 +2516,120 @@ fn manage_cursor(
         cursor_released = true;
     }
 
-    // Update cursor options
-    if cursor_grabbed {
-        cursor_options.grab_mode = bevy::window::CursorGrabMode::Locked;
-        cursor_options.visible = false;
-    }
-    if cursor_released {
-        cursor_options.grab_mode = bevy::window::CursorGrabMode::None;
-        cursor_options.visible = true;
+    // Update cursor options, if the window exists
+    let window = cursor_window.0.or_else(|| primary_window.single().ok());
+    if let Some(mut cursor_options) = window.and_then(|window| windows.get_mut(window).ok()) {
+        if cursor_grabbed {
+            cursor_options.grab_mode = bevy::window::CursorGrabMode::Locked;
+            cursor_options.visible = false;
+        }
+        if cursor_released {
+            cursor_options.grab_mode = bevy::window::CursorGrabMode::None;
+            cursor_options.visible = true;
+        }
     }
 
     // Update FPS controllers
-    for mut controller in &mut controller_query {
-        if cursor_grabbed {
+    if cursor_grabbed {
+        let active = match possessed.0 {
+            Some(entity) if controller_query.contains(entity) => {
+                controller_query.get_mut(entity).ok()
+            }
+            _ => controller_query.single_mut().ok(),
+        };
+        if let Some(mut controller) = active {
             controller.enable_input = true;
         }
-        if cursor_released {
+    }
+    if cursor_released {
+        for mut controller in &mut controller_query {
             controller.enable_input = false;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Scalar, expected: Scalar) {
+        assert!(
+            (actual - expected).abs() < 1e-5,
+            "expected {expected}, but got {actual}"
+        );
+    }
+
+    #[test]
+    fn response_curves_shape_deflection() {
+        assert_close(ResponseCurve::Linear.apply(0.5), 0.5);
+        assert_close(ResponseCurve::Squared.apply(0.5), 0.25);
+        assert_close(ResponseCurve::Power(3.0).apply(0.5), 0.125);
+    }
+
+    #[test]
+    fn response_curves_clamp_deflection() {
+        assert_close(ResponseCurve::Linear.apply(1.5), 1.0);
+        assert_close(ResponseCurve::Squared.apply(-0.5), 0.0);
+    }
+
+    #[test]
+    fn custom_response_curve_interpolates_between_points() {
+        let curve = ResponseCurve::Custom(vec![
+            Vector2::new(0.0, 0.0),
+            Vector2::new(0.5, 0.2),
+            Vector2::new(1.0, 1.0),
+        ]);
+        assert_close(curve.apply(0.25), 0.1);
+        assert_close(curve.apply(0.5), 0.2);
+        assert_close(curve.apply(0.75), 0.6);
+    }
+
+    #[test]
+    fn custom_response_curve_holds_outside_its_points() {
+        let curve = ResponseCurve::Custom(vec![Vector2::new(0.2, 0.1), Vector2::new(0.6, 1.0)]);
+        assert_close(curve.apply(0.1), 0.1);
+        assert_close(curve.apply(0.8), 1.0);
+
+        // Without points, the deflection is used linearly.
+        assert_close(ResponseCurve::Custom(Vec::new()).apply(0.3), 0.3);
+    }
+
+    #[test]
+    fn standing_long_jumps_are_boosted_along_facing() {
+        let long_jump = LongJump::default();
+
+        let standing = long_jump.horizontal_velocity(Vector::ZERO, Vector::NEG_Z, false);
+        assert!(standing.abs_diff_eq(Vector::ZERO, 1e-5), "{standing}");
+
+        let sprinting = long_jump.horizontal_velocity(Vector::ZERO, Vector::NEG_Z, true);
+        assert!(
+            sprinting.abs_diff_eq(Vector::NEG_Z * long_jump.sprint_boost, 1e-5),
+            "{sprinting}"
+        );
+    }
+
+    #[test]
+    fn running_long_jumps_scale_with_speed() {
+        let long_jump = LongJump::default();
+
+        // 5 m/s plus 20% of it, and the sprint boost.
+        let running = long_jump.horizontal_velocity(Vector::X * 5.0, Vector::NEG_Z, false);
+        assert!(running.abs_diff_eq(Vector::X * 6.0, 1e-5), "{running}");
+        let sprinting = long_jump.horizontal_velocity(Vector::X * 5.0, Vector::NEG_Z, true);
+        assert!(sprinting.abs_diff_eq(Vector::X * 8.0, 1e-5), "{sprinting}");
+    }
+
+    #[test]
+    fn long_jumps_are_capped_without_slowing_down() {
+        let long_jump = LongJump::default();
+
+        let capped = long_jump.horizontal_velocity(Vector::X * 14.0, Vector::NEG_Z, true);
+        assert!(
+            capped.abs_diff_eq(Vector::X * long_jump.max_speed, 1e-4),
+            "{capped}"
+        );
+
+        let fast = long_jump.horizontal_velocity(Vector::X * 20.0, Vector::NEG_Z, true);
+        assert!(fast.abs_diff_eq(Vector::X * 20.0, 1e-4), "{fast}");
+    }
+}
@@ -0,0 +1,143 @@
+//! Lock-on strafing for character controllers.
+//!
+//! A character controller with a [`LockOnTarget`] turns to face its target, so movement
+//! input strafes around it, like in souls-likes and brawlers. The camera can follow the
+//! target too. The character turns at a limited speed, so engaging and disengaging the
+//! lock-on is smooth.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    find_camera_pivot, orbit_rotation, CameraPivot, CharacterController, ControllerEnabled,
+    ControllerUp, Pitch, ViewYaw, WorldUp,
+};
+
+/// Locks a character controller onto the given target entity, making it face the target.
+///
+/// Configured with [`LockOnSettings`]. Removed when the target is despawned or out of range.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+#[require(LockOnSettings)]
+pub struct LockOnTarget(pub Entity);
+
+/// Settings for [`LockOnTarget`].
+#[derive(Component, Clone, Copy, Debug)]
+pub struct LockOnSettings {
+    /// The maximum turn speed of the character toward the target, in radians per second.
+    pub turn_speed: Scalar,
+    /// Whether the camera is turned toward the target as well.
+    pub lock_camera: bool,
+    /// The maximum turn speed of the camera toward the target, in radians per second.
+    pub camera_turn_speed: Scalar,
+    /// The distance at which the lock-on breaks, or `None` to never break it.
+    pub max_distance: Option<Scalar>,
+}
+
+impl Default for LockOnSettings {
+    fn default() -> Self {
+        Self {
+            turn_speed: 10.0,
+            lock_camera: true,
+            camera_turn_speed: 6.0,
+            max_distance: Some(30.0),
+        }
+    }
+}
+
+/// Returns the signed angle around `up` from `from` to `to`, limited to `max_angle`.
+fn limited_yaw(from: Vector, to: Vector, up: Vector, max_angle: Scalar) -> Scalar {
+    let angle = from.cross(to).dot(up).atan2(from.dot(to));
+    angle.clamp(-max_angle, max_angle)
+}
+
+/// Turns character controllers and their cameras toward their [`LockOnTarget`].
+#[allow(clippy::type_complexity)]
+pub(crate) fn face_lock_on_target(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    mut controllers: Query<
+        (
+            Entity,
+            &LockOnTarget,
+            &LockOnSettings,
+            &Position,
+            &mut Rotation,
+            Option<&mut Pitch>,
+            Option<&mut ViewYaw>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+    targets: Query<&GlobalTransform>,
+    mut pivots: Query<
+        (&mut Transform, Has<CameraPivot>),
+        (
+            Or<(With<CameraPivot>, With<Camera3d>)>,
+            Without<CharacterController>,
+        ),
+    >,
+    children: Query<&Children>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (entity, target, settings, position, mut rotation, pitch, view_yaw, controller_up) in
+        &mut controllers
+    {
+        let up = world_up.for_controller(controller_up);
+
+        let Ok(target_transform) = targets.get(target.0) else {
+            // The target was despawned.
+            commands.entity(entity).remove::<LockOnTarget>();
+            continue;
+        };
+        let offset = target_transform.translation().adjust_precision() - position.0;
+        if settings
+            .max_distance
+            .is_some_and(|max_distance| offset.length() > max_distance)
+        {
+            commands.entity(entity).remove::<LockOnTarget>();
+            continue;
+        }
+        let Some(direction) = offset.reject_from_normalized(up).try_normalize() else {
+            continue;
+        };
+
+        // Face the target, so movement input strafes around it
+        let forward = (rotation.0 * Vector::NEG_Z).reject_from_normalized(up);
+        let turn = limited_yaw(forward, direction, up, settings.turn_speed * delta_time);
+        rotation.0 = (Quaternion::from_axis_angle(up, turn) * rotation.0).normalize();
+
+        if !settings.lock_camera {
+            continue;
+        }
+        let max_camera_turn = settings.camera_turn_speed * delta_time;
+
+        // Cameras that orbit independently are turned on their own
+        let mut view_yaw = view_yaw;
+        if let Some(view_yaw) = view_yaw.as_mut() {
+            let view_forward = (view_yaw.0 * Vector::NEG_Z).reject_from_normalized(up);
+            let turn = limited_yaw(view_forward, direction, up, max_camera_turn);
+            view_yaw.0 = (Quaternion::from_axis_angle(up, turn) * view_yaw.0).normalize();
+        }
+
+        let Some(mut pitch) = pitch else {
+            continue;
+        };
+        let horizontal_distance = offset.reject_from_normalized(up).length();
+        let target_pitch = offset
+            .dot(up)
+            .atan2(horizontal_distance)
+            .clamp(-pitch.max, pitch.max);
+        pitch.angle += (target_pitch - pitch.angle).clamp(-max_camera_turn, max_camera_turn);
+
+        if let Some(pivot) = find_camera_pivot(entity, &children, &pivots) {
+            if let Ok((mut pivot_transform, _)) = pivots.get_mut(pivot) {
+                pivot_transform.rotation = match view_yaw {
+                    Some(view_yaw) => orbit_rotation(rotation.0, view_yaw.0, pitch.angle).f32(),
+                    None => Quaternion::from_rotation_x(pitch.angle).f32(),
+                };
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Continuous locomotion values for animation blend spaces.
+//!
+//! 2D blend spaces and 8-way locomotion blends are driven by the velocity of the character
+//! in its own space. [`LocomotionBlend`] is updated every frame with the forward and strafe
+//! speeds normalized by the top speed, and the turn rate, so animation graphs can use them
+//! directly.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    CharacterController, ControllerUp, ExternalVelocity, MovementAcceleration,
+    MovementDampingFactor, TargetVelocity, WorldUp, DAMPING_REFERENCE_RATE,
+};
+
+/// Locomotion values of a character controller for animation blend spaces, updated every
+/// frame.
+///
+/// Speeds are in the local space of the character and normalized by the top speed, so 1
+/// is running forward at full speed, and -1 is running backward or strafing left.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct LocomotionBlend {
+    /// The top speed the speeds are normalized by, in m/s.
+    ///
+    /// If `None`, the top speed is derived from [`TargetVelocity`], or from
+    /// [`MovementAcceleration`] and [`MovementDampingFactor`].
+    pub max_speed: Option<Scalar>,
+    /// The normalized speed along the facing direction.
+    pub forward_speed: Scalar,
+    /// The normalized speed to the right of the facing direction.
+    pub strafe_speed: Scalar,
+    /// The turn rate around the up axis in radians per second, positive to the left.
+    pub turn_rate: Scalar,
+    /// The facing direction in the last frame.
+    previous_forward: Option<Vector>,
+}
+
+impl LocomotionBlend {
+    /// Creates locomotion values normalized by the given top speed.
+    pub fn with_max_speed(max_speed: Scalar) -> Self {
+        Self {
+            max_speed: Some(max_speed),
+            ..default()
+        }
+    }
+
+    /// Returns the normalized planar speed, regardless of direction.
+    pub fn speed(&self) -> Scalar {
+        Vector2::new(self.strafe_speed, self.forward_speed).length()
+    }
+}
+
+/// Updates the [`LocomotionBlend`] of character controllers from their velocity.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_locomotion_blend(
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    mut controllers: Query<
+        (
+            &mut LocomotionBlend,
+            &Rotation,
+            &LinearVelocity,
+            Option<&ExternalVelocity>,
+            Option<&TargetVelocity>,
+            Option<(&MovementAcceleration, &MovementDampingFactor)>,
+            Option<&ControllerUp>,
+        ),
+        With<CharacterController>,
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (
+        mut blend,
+        rotation,
+        linear_velocity,
+        external,
+        target_velocity,
+        acceleration,
+        controller_up,
+    ) in &mut controllers
+    {
+        let up = world_up.for_controller(controller_up);
+
+        // The default model tops out at its steady state, like `TunedMovement::top_speed`
+        let max_speed = blend
+            .max_speed
+            .or(target_velocity.map(|target_velocity| target_velocity.max_speed))
+            .or(acceleration.map(|(acceleration, damping)| {
+                acceleration.0 * damping.0 / (DAMPING_REFERENCE_RATE * (1.0 - damping.0))
+            }))
+            .filter(|max_speed| max_speed.is_finite() && *max_speed > 0.0)
+            .unwrap_or(1.0);
+
+        // Knockbacks aren't locomotion
+        let external = external.map_or(Vector::ZERO, ExternalVelocity::applied);
+        let velocity = (linear_velocity.0 - external).reject_from_normalized(up);
+
+        let forward = (rotation.0 * Vector::NEG_Z)
+            .reject_from_normalized(up)
+            .normalize_or_zero();
+        let right = forward.cross(up);
+        blend.forward_speed = velocity.dot(forward) / max_speed;
+        blend.strafe_speed = velocity.dot(right) / max_speed;
+
+        blend.turn_rate = match blend.previous_forward {
+            Some(previous) if delta_time > 0.0 => {
+                previous.cross(forward).dot(up).atan2(previous.dot(forward)) / delta_time
+            }
+            _ => 0.0,
+        };
+        blend.previous_forward = Some(forward);
+    }
+}
@@ -0,0 +1,191 @@
+//! Magnet boots for walking up walls and across ceilings.
+//!
+//! A character controller with enabled [`MagnetBoots`] sticks to [`Magnetic`] surfaces. Its
+//! gravity points into the surface under its feet, and its body and [`ControllerUp`] turn
+//! toward the surface normal at a limited rate, so walking into a magnetic wall carries the
+//! character up onto it, and walking over the top edge of a wall carries it onto the ceiling.
+//! The camera rolls with the body, eased in and out so the transitions don't jolt the view.
+//!
+//! [`GravityField`](crate::GravityField)s take precedence over magnet boots.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    sync_controller_up, turn_toward_up, CharacterController, ControllerEnabled, ControllerGravity,
+    ControllerUp, InGravityField, ViewYaw, WorldUp,
+};
+
+/// A marker component for surfaces that [`MagnetBoots`] stick to.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Magnetic;
+
+/// Lets a character controller walk on [`Magnetic`] walls and ceilings.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct MagnetBoots {
+    /// Whether the boots are switched on.
+    pub enabled: bool,
+    /// How far below the feet and ahead of the collider magnetic surfaces are detected.
+    pub reach: Scalar,
+    /// The maximum rate at which the body turns toward the surface normal, in radians per
+    /// second.
+    pub max_rotation_speed: Scalar,
+    /// The half-life of easing the body toward the surface normal, in seconds, which keeps the
+    /// camera roll smooth at the start and end of a transition.
+    pub roll_half_life: Scalar,
+}
+
+impl Default for MagnetBoots {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            reach: 0.5,
+            max_rotation_speed: PI,
+            roll_half_life: 0.08,
+        }
+    }
+}
+
+/// A component on a [`MagnetBoots`] character controller that sticks to a [`Magnetic`]
+/// surface.
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct OnMagneticSurface {
+    /// The magnetic surface entity.
+    pub surface: Entity,
+    /// The normal of the surface under the character.
+    pub normal: Vector,
+    /// The gravity of the character before it stuck to the surface, restored when it leaves.
+    pub default_gravity: Vector,
+}
+
+/// Aligns the gravity and up direction of [`MagnetBoots`] characters to the [`Magnetic`]
+/// surfaces under them and ahead of them.
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_magnet_boots(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    spatial_query: SpatialQuery,
+    mut controllers: Query<
+        (
+            Entity,
+            &MagnetBoots,
+            &Position,
+            &mut Rotation,
+            &LinearVelocity,
+            &ColliderAabb,
+            &mut ControllerGravity,
+            Option<&OnMagneticSurface>,
+            Option<&mut ControllerUp>,
+            Option<&mut ViewYaw>,
+        ),
+        (
+            With<CharacterController>,
+            Without<InGravityField>,
+            ControllerEnabled,
+        ),
+    >,
+    magnetic: Query<(), With<Magnetic>>,
+    colliders: Query<&ColliderOf>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    let is_magnetic = |entity: Entity| {
+        magnetic.contains(entity)
+            || colliders
+                .get(entity)
+                .is_ok_and(|collider_of| magnetic.contains(collider_of.body))
+    };
+
+    for (
+        entity,
+        boots,
+        position,
+        mut rotation,
+        linear_velocity,
+        aabb,
+        mut gravity,
+        on_surface,
+        controller_up,
+        mut view_yaw,
+    ) in &mut controllers
+    {
+        let up = rotation.0 * Vector::Y;
+        let half_extents = (aabb.max - aabb.min) * 0.5;
+        let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+
+        // A magnetic surface ahead, like a wall, takes over from the one under the feet
+        let surface = if boots.enabled {
+            let ahead = linear_velocity.0.reject_from_normalized(up);
+            let ahead_hit = Dir3::new(ahead.f32()).ok().and_then(|direction| {
+                let radius = half_extents.dot(ahead.normalize().abs());
+                spatial_query
+                    .cast_ray(position.0, direction, radius + boots.reach, true, &filter)
+                    .filter(|hit| is_magnetic(hit.entity))
+            });
+            ahead_hit.or_else(|| {
+                let direction = Dir3::new((-up).f32()).ok()?;
+                let half_height = half_extents.dot(up.abs());
+                spatial_query
+                    .cast_ray(
+                        position.0,
+                        direction,
+                        half_height + boots.reach,
+                        true,
+                        &filter,
+                    )
+                    .filter(|hit| is_magnetic(hit.entity))
+            })
+        } else {
+            None
+        };
+
+        let target_up = match surface {
+            Some(hit) => {
+                let default_gravity =
+                    on_surface.map_or(gravity.0, |on_surface| on_surface.default_gravity);
+                commands.entity(entity).insert(OnMagneticSurface {
+                    surface: hit.entity,
+                    normal: hit.normal,
+                    default_gravity,
+                });
+                gravity.0 = -hit.normal * default_gravity.length();
+                hit.normal
+            }
+            None => {
+                // Leaving magnetic surfaces restores the gravity and turns back to the world up
+                if let Some(on_surface) = on_surface {
+                    gravity.0 = on_surface.default_gravity;
+                    commands.entity(entity).remove::<OnMagneticSurface>();
+                }
+                if controller_up.is_none() {
+                    continue;
+                }
+                world_up.0
+            }
+        };
+
+        // Ease toward the target up, limited by the max rotation speed
+        let angle = up.angle_between(target_up);
+        let t = if boots.roll_half_life > 0.0 {
+            1.0 - Scalar::powf(0.5, delta_time / boots.roll_half_life)
+        } else {
+            1.0
+        };
+        turn_toward_up(
+            &mut rotation,
+            view_yaw.as_deref_mut(),
+            target_up,
+            (angle * t).min(boots.max_rotation_speed * delta_time),
+        );
+        sync_controller_up(
+            &mut commands,
+            entity,
+            controller_up,
+            rotation.0 * Vector::Y,
+            world_up.0,
+            surface.is_some(),
+        );
+    }
+}
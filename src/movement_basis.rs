@@ -0,0 +1,336 @@
+//! Pluggable movement bases and abilities.
+//!
+//! Movement is split into a *basis*, which turns the movement input into velocity, and
+//! *abilities*, which stack on top of it. The built-in basis walks along the ground with
+//! [`MovementAcceleration`](crate::MovementAcceleration), and [`TargetVelocity`] is an
+//! alternative one. Custom bases, like swimming or flying, implement [`MovementBasis`],
+//! and custom abilities, like dashes or double jumps, implement [`MovementAbility`]:
+//!
+//! ```ignore
+//! #[derive(Component)]
+//! struct Dash { speed: Scalar }
+//!
+//! impl MovementAbility for Dash {
+//!     fn apply(&mut self, input: &MovementInput, context: &mut MovementContext) {
+//!         if input.sprint && context.is_grounded {
+//!             context.velocity += context.move_direction * self.speed;
+//!         }
+//!     }
+//! }
+//!
+//! app.add_movement_ability::<Dash>();
+//! ```
+//!
+//! Both run after the built-in movement, with the input gathered from the global and
+//! directed [`MovementAction`] streams, so they compose with the built-in jumps, stances,
+//! and speed modifiers.
+
+use avian3d::{math::*, prelude::*};
+use bevy::{ecs::component::Mutable, platform::collections::HashMap, prelude::*};
+
+use crate::{
+    ground_normal, input_rotation, CharacterController, ControllerEnabled, ControllerUp,
+    DirectedMovementAction, FpsController, Grounded, MovementAction, ViewYaw, WorldUp,
+};
+
+/// System sets for custom movement in [`Update`], run in order after the built-in
+/// movement system.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MovementBasisSystems {
+    /// Runs the [`MovementBasis`] of character controllers.
+    Basis,
+    /// Runs the [`MovementAbility`] components of character controllers.
+    Abilities,
+}
+
+/// The input of a character controller in a frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MovementInput {
+    /// The movement direction in local space, with `y` pointing forward.
+    pub direction: Vector2,
+    pub jump: bool,
+    pub jump_held: bool,
+    pub crouch: bool,
+    pub prone: bool,
+    pub interact: bool,
+    pub interact_held: bool,
+    pub throw: bool,
+    pub sprint: bool,
+}
+
+impl MovementInput {
+    /// Adds a [`MovementAction`] to the input.
+    pub fn add(&mut self, action: &MovementAction) {
+        match action {
+            MovementAction::Move(direction) => self.direction += *direction,
+            MovementAction::Jump => self.jump = true,
+            MovementAction::JumpHeld => self.jump_held = true,
+            MovementAction::Crouch => self.crouch = true,
+            MovementAction::Prone => self.prone = true,
+            MovementAction::Interact => self.interact = true,
+            MovementAction::InteractHeld => self.interact_held = true,
+            MovementAction::Throw => self.throw = true,
+            MovementAction::Sprint => self.sprint = true,
+        }
+    }
+
+    /// Combines two inputs, like the global input and the input directed at a character.
+    pub fn merge(&self, other: &Self) -> Self {
+        Self {
+            direction: self.direction + other.direction,
+            jump: self.jump || other.jump,
+            jump_held: self.jump_held || other.jump_held,
+            crouch: self.crouch || other.crouch,
+            prone: self.prone || other.prone,
+            interact: self.interact || other.interact,
+            interact_held: self.interact_held || other.interact_held,
+            throw: self.throw || other.throw,
+            sprint: self.sprint || other.sprint,
+        }
+    }
+}
+
+/// The state of a character controller passed to a [`MovementBasis`] or [`MovementAbility`].
+#[derive(Clone, Copy, Debug)]
+pub struct MovementContext {
+    pub entity: Entity,
+    /// The velocity of the character, written back to its [`LinearVelocity`].
+    pub velocity: Vector,
+    pub rotation: Quaternion,
+    /// The up direction of the character, see [`WorldUp`] and [`ControllerUp`].
+    pub up: Vector,
+    pub is_grounded: bool,
+    /// The normal of the ground, if the character is grounded.
+    pub ground_normal: Option<Vector>,
+    /// The world-space movement direction on the plane perpendicular to the up direction,
+    /// with a length of at most 1.
+    pub move_direction: Vector,
+    pub delta_time: Scalar,
+}
+
+/// A component that turns movement input into velocity, replacing the built-in walking.
+///
+/// Register it with [`MovementBasisAppExt::add_movement_basis`]. Jumps and other actions
+/// still apply. Remove [`MovementDampingFactor`](crate::MovementDampingFactor) if the
+/// basis handles deceleration itself.
+pub trait MovementBasis: Component<Mutability = Mutable> {
+    /// Updates the velocity in the context from the input.
+    fn apply(&mut self, input: &MovementInput, context: &mut MovementContext);
+}
+
+/// A component that adds an ability on top of the movement basis, like a dash.
+///
+/// Register it with [`MovementBasisAppExt::add_movement_ability`].
+pub trait MovementAbility: Component<Mutability = Mutable> {
+    /// Updates the velocity in the context from the input.
+    fn apply(&mut self, input: &MovementInput, context: &mut MovementContext);
+}
+
+/// A marker component for character controllers whose walking is replaced by a
+/// [`MovementBasis`], like [`TargetVelocity`](crate::TargetVelocity).
+///
+/// Required by registered bases, so it doesn't need to be added manually.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct CustomMovementBasis;
+
+/// Extension methods for registering movement bases and abilities.
+pub trait MovementBasisAppExt {
+    /// Registers a [`MovementBasis`], which replaces the built-in walking of the
+    /// character controllers it is added to.
+    fn add_movement_basis<B: MovementBasis>(&mut self) -> &mut Self;
+
+    /// Registers a [`MovementAbility`], which runs after the movement basis.
+    fn add_movement_ability<A: MovementAbility>(&mut self) -> &mut Self;
+}
+
+impl MovementBasisAppExt for App {
+    fn add_movement_basis<B: MovementBasis>(&mut self) -> &mut Self {
+        // Registering a basis twice is harmless.
+        let _ = self.try_register_required_components::<B, CustomMovementBasis>();
+        self.add_systems(
+            Update,
+            run_movement_basis::<B>.in_set(MovementBasisSystems::Basis),
+        )
+    }
+
+    fn add_movement_ability<A: MovementAbility>(&mut self) -> &mut Self {
+        self.add_systems(
+            Update,
+            run_movement_ability::<A>.in_set(MovementBasisSystems::Abilities),
+        )
+    }
+}
+
+/// The [`MovementInput`] gathered for the current frame.
+#[derive(Resource, Default)]
+pub(crate) struct GatheredMovementInput {
+    global: MovementInput,
+    directed: HashMap<Entity, MovementInput>,
+}
+
+impl GatheredMovementInput {
+    /// Returns the input of a character, including the global input if it is enabled.
+    pub(crate) fn get(
+        &self,
+        entity: Entity,
+        fps_controller: Option<&FpsController>,
+    ) -> MovementInput {
+        let directed = self.directed.get(&entity).copied().unwrap_or_default();
+        let mut input = if fps_controller.is_some_and(|controller| controller.enable_input) {
+            self.global.merge(&directed)
+        } else {
+            directed
+        };
+        input.direction = input.direction.clamp_length_max(1.0);
+        input
+    }
+}
+
+/// Gathers the [`MovementInput`] of the current frame for bases, abilities, and the built-in
+/// movement systems that read input per character.
+///
+/// This runs at the start of the movement systems, so directed actions written during them,
+/// like by [`AutoJump`](crate::AutoJump), are read by the regular movement system only.
+pub(crate) fn gather_movement_input(
+    mut gathered: ResMut<GatheredMovementInput>,
+    mut movement_reader: MessageReader<MovementAction>,
+    mut directed_reader: MessageReader<DirectedMovementAction>,
+) {
+    gathered.global = MovementInput::default();
+    gathered.directed.clear();
+
+    for action in movement_reader.read() {
+        gathered.global.add(action);
+    }
+    for directed in directed_reader.read() {
+        gathered
+            .directed
+            .entry(directed.entity)
+            .or_default()
+            .add(&directed.action);
+    }
+}
+
+/// The components of a character controller that a [`MovementContext`] is built from.
+type ContextData = (
+    Entity,
+    &'static mut LinearVelocity,
+    &'static Rotation,
+    Has<Grounded>,
+    Option<&'static ShapeHits>,
+    Option<&'static FpsController>,
+    Option<&'static ViewYaw>,
+    Option<&'static ControllerUp>,
+);
+
+/// Builds the [`MovementContext`] of a character controller.
+#[allow(clippy::too_many_arguments)]
+fn movement_context(
+    entity: Entity,
+    velocity: Vector,
+    rotation: &Rotation,
+    is_grounded: bool,
+    ground_hits: Option<&ShapeHits>,
+    view_yaw: Option<&ViewYaw>,
+    input: &MovementInput,
+    up: Vector,
+    delta_time: Scalar,
+) -> MovementContext {
+    let direction = Vector::new(input.direction.x, 0.0, -input.direction.y);
+    MovementContext {
+        entity,
+        velocity,
+        rotation: rotation.0,
+        up,
+        is_grounded,
+        ground_normal: ground_hits
+            .filter(|_| is_grounded)
+            .and_then(|hits| ground_normal(hits, rotation, up)),
+        move_direction: (input_rotation(rotation, view_yaw) * direction)
+            .reject_from_normalized(up)
+            .clamp_length_max(1.0),
+        delta_time,
+    }
+}
+
+/// Runs the [`MovementBasis`] `B` of character controllers.
+#[allow(clippy::type_complexity)]
+fn run_movement_basis<B: MovementBasis>(
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    gathered: Res<GatheredMovementInput>,
+    mut controllers: Query<(&mut B, ContextData), (With<CharacterController>, ControllerEnabled)>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (
+        mut basis,
+        (
+            entity,
+            mut linear_velocity,
+            rotation,
+            is_grounded,
+            ground_hits,
+            fps_controller,
+            view_yaw,
+            controller_up,
+        ),
+    ) in &mut controllers
+    {
+        let input = gathered.get(entity, fps_controller);
+        let mut context = movement_context(
+            entity,
+            linear_velocity.0,
+            rotation,
+            is_grounded,
+            ground_hits,
+            view_yaw,
+            &input,
+            world_up.for_controller(controller_up),
+            delta_time,
+        );
+        basis.apply(&input, &mut context);
+        linear_velocity.0 = context.velocity;
+    }
+}
+
+/// Runs the [`MovementAbility`] `A` of character controllers.
+#[allow(clippy::type_complexity)]
+fn run_movement_ability<A: MovementAbility>(
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    gathered: Res<GatheredMovementInput>,
+    mut controllers: Query<(&mut A, ContextData), (With<CharacterController>, ControllerEnabled)>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (
+        mut ability,
+        (
+            entity,
+            mut linear_velocity,
+            rotation,
+            is_grounded,
+            ground_hits,
+            fps_controller,
+            view_yaw,
+            controller_up,
+        ),
+    ) in &mut controllers
+    {
+        let input = gathered.get(entity, fps_controller);
+        let mut context = movement_context(
+            entity,
+            linear_velocity.0,
+            rotation,
+            is_grounded,
+            ground_hits,
+            view_yaw,
+            &input,
+            world_up.for_controller(controller_up),
+            delta_time,
+        );
+        ability.apply(&input, &mut context);
+        linear_velocity.0 = context.velocity;
+    }
+}
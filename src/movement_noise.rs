@@ -0,0 +1,140 @@
+//! Movement noise for stealth AI.
+//!
+//! Character controllers with a [`NoiseEmitter`] write a [`MovementNoise`] message for every
+//! [`Footstep`] and [`Landed`] message, with a loudness derived from the speed or impact, the
+//! [`Stance`], and the [`SurfaceMaterial`] of the ground. AI perception systems can read the
+//! noise directly and check whether listeners are within its radius, instead of deriving
+//! loudness from the movement state themselves.
+
+use avian3d::math::*;
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::{Footstep, Footsteps, Landed, Stance, SurfaceMaterial};
+
+/// A [`Message`] written when a [`NoiseEmitter`] character makes a noise while moving.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct MovementNoise {
+    pub entity: Entity,
+    /// Where the noise was made.
+    pub position: Vector,
+    /// How far the noise can be heard, in meters.
+    pub radius: Scalar,
+    /// How loud the noise is, where 1 is a footstep at the reference speed on a normal surface.
+    pub loudness: Scalar,
+}
+
+/// Enables [`MovementNoise`] messages for a character controller.
+///
+/// Noises are made on [`Footstep`]s and landings, so this requires [`Footsteps`].
+#[derive(Component, Clone, Debug)]
+#[require(Footsteps)]
+pub struct NoiseEmitter {
+    /// The radius of a noise with a loudness of 1, in meters.
+    pub radius: Scalar,
+    /// The horizontal speed at which footsteps have a loudness of 1, in m/s.
+    pub reference_speed: Scalar,
+    /// The fall speed at which landings have a loudness of 1, in m/s.
+    pub reference_landing_speed: Scalar,
+    /// The loudness multiplier while crouching.
+    pub crouch_multiplier: Scalar,
+    /// The loudness multiplier while prone.
+    pub prone_multiplier: Scalar,
+    /// Loudness multipliers keyed by the name of the [`SurfaceMaterial`], like 1.5 for metal
+    /// or 0.5 for carpet. Surfaces that aren't in the map have a multiplier of 1.
+    pub surface_multipliers: HashMap<String, Scalar>,
+    /// The maximum loudness of a single noise.
+    pub max_loudness: Scalar,
+}
+
+impl Default for NoiseEmitter {
+    fn default() -> Self {
+        Self {
+            radius: 15.0,
+            reference_speed: 7.0,
+            reference_landing_speed: 10.0,
+            crouch_multiplier: 0.3,
+            prone_multiplier: 0.1,
+            surface_multipliers: HashMap::default(),
+            max_loudness: 3.0,
+        }
+    }
+}
+
+impl NoiseEmitter {
+    /// Returns the emitter with a loudness multiplier for the given surface material.
+    pub fn with_surface(mut self, material: impl Into<String>, multiplier: Scalar) -> Self {
+        self.surface_multipliers.insert(material.into(), multiplier);
+        self
+    }
+
+    /// Returns the loudness of a noise with the given speed relative to the reference speed.
+    fn loudness(
+        &self,
+        speed: Scalar,
+        reference_speed: Scalar,
+        stance: Stance,
+        material: Option<&SurfaceMaterial>,
+    ) -> Scalar {
+        let stance_multiplier = match stance {
+            Stance::Standing => 1.0,
+            Stance::Crouching => self.crouch_multiplier,
+            Stance::Prone => self.prone_multiplier,
+        };
+        let surface_multiplier = material
+            .and_then(|material| self.surface_multipliers.get(&material.0))
+            .copied()
+            .unwrap_or(1.0);
+        let loudness = speed / reference_speed.max(Scalar::EPSILON);
+        (loudness * stance_multiplier * surface_multiplier).clamp(0.0, self.max_loudness)
+    }
+}
+
+/// Writes [`MovementNoise`] messages for the footsteps and landings of [`NoiseEmitter`]s.
+pub(crate) fn emit_movement_noise(
+    mut footstep_reader: MessageReader<Footstep>,
+    mut landed_reader: MessageReader<Landed>,
+    mut noise_writer: MessageWriter<MovementNoise>,
+    emitters: Query<(&NoiseEmitter, Option<&Stance>)>,
+) {
+    let footsteps = footstep_reader.read().map(|footstep| {
+        (
+            footstep.entity,
+            footstep.position,
+            footstep.speed,
+            footstep.material.as_ref(),
+            false,
+        )
+    });
+    let landings = landed_reader.read().map(|landed| {
+        (
+            landed.entity,
+            landed.position,
+            landed.impact_speed,
+            landed.material.as_ref(),
+            true,
+        )
+    });
+
+    for (entity, position, speed, material, is_landing) in footsteps.chain(landings) {
+        let Ok((emitter, stance)) = emitters.get(entity) else {
+            continue;
+        };
+        let reference_speed = if is_landing {
+            emitter.reference_landing_speed
+        } else {
+            emitter.reference_speed
+        };
+        let stance = stance.copied().unwrap_or_default();
+        let loudness = emitter.loudness(speed, reference_speed, stance, material);
+        if loudness <= 0.0 {
+            continue;
+        }
+
+        noise_writer.write(MovementNoise {
+            entity,
+            position,
+            radius: emitter.radius * loudness,
+            loudness,
+        });
+    }
+}
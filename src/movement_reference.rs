@@ -0,0 +1,57 @@
+//! Movement input relative to an external reference, like a camera rig.
+//!
+//! Top-down, isometric, and twin-stick games move characters relative to the camera,
+//! not to where the character faces. [`MovementReference`] points at the entity that
+//! movement input is relative to, and copies its yaw to the [`ViewYaw`] of the character
+//! every frame, so aiming and facing are decoupled from the travel direction.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{CharacterController, ControllerUp, ViewYaw, WorldUp};
+
+/// Makes the movement input of a character controller relative to the yaw of another
+/// entity, like a camera rig, instead of the rotation of the character.
+///
+/// Cameras looking straight down use their up direction as forward.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+#[require(ViewYaw)]
+pub struct MovementReference(pub Entity);
+
+/// Copies the yaw of the [`MovementReference`] of character controllers to their [`ViewYaw`].
+pub(crate) fn sync_movement_reference(
+    world_up: Res<WorldUp>,
+    mut controllers: Query<
+        (&MovementReference, &mut ViewYaw, Option<&ControllerUp>),
+        With<CharacterController>,
+    >,
+    references: Query<&GlobalTransform>,
+) {
+    for (reference, mut view_yaw, controller_up) in &mut controllers {
+        let up = world_up.for_controller(controller_up);
+        let Ok(transform) = references.get(reference.0) else {
+            continue;
+        };
+        let rotation = transform.rotation().adjust_precision();
+
+        // Top-down cameras look along the up axis, so their up direction is forward on screen
+        let Some(forward) = (rotation * Vector::NEG_Z)
+            .reject_from_normalized(up)
+            .try_normalize()
+            .or_else(|| {
+                (rotation * Vector::Y)
+                    .reject_from_normalized(up)
+                    .try_normalize()
+            })
+        else {
+            continue;
+        };
+
+        // Turn the view by the signed angle between the current and the reference forward
+        let current = (view_yaw.0 * Vector::NEG_Z).reject_from_normalized(up);
+        let angle = current.cross(forward).dot(up).atan2(current.dot(forward));
+        if angle.abs() > Scalar::EPSILON {
+            view_yaw.0 = (Quaternion::from_axis_angle(up, angle) * view_yaw.0).normalize();
+        }
+    }
+}
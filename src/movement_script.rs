@@ -0,0 +1,263 @@
+//! Scripted movement for patrols, cutscenes, and automated test levels.
+//!
+//! A [`MovementScript`] is a sequence of [`MovementStep`]s, loaded from `.movescript` files
+//! or built in code. A [`MovementScriptPlayer`] drives a character controller through it,
+//! walking with its [`PathFollower`] so scripted characters use the regular movement physics.
+//!
+//! The file format has one step per line, with `#` starting a comment:
+//!
+//! ```text
+//! # Patrol between two points
+//! move_to 4 0 -4
+//! wait 1.5
+//! face 90
+//! jump
+//! move_to -4 0 -4
+//! ```
+
+use std::fmt;
+
+use avian3d::{math::*, prelude::*};
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+
+use crate::{
+    yaw_rotation, CharacterController, ControllerEnabled, ControllerUp, DirectedMovementAction,
+    MovementAction, PathFollower, WorldUp,
+};
+
+/// A single step of a [`MovementScript`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MovementStep {
+    /// Walks to a position.
+    MoveTo(Vector),
+    /// Waits for a number of seconds.
+    Wait(Scalar),
+    /// Jumps once.
+    Jump,
+    /// Turns to face a yaw angle around the up axis of the character, in radians.
+    Face(Scalar),
+}
+
+/// An asset with a sequence of [`MovementStep`]s.
+#[derive(Asset, TypePath, Clone, Debug, Default)]
+pub struct MovementScript {
+    pub steps: Vec<MovementStep>,
+}
+
+impl MovementScript {
+    /// Parses a script from the `.movescript` text format.
+    pub fn parse(source: &str) -> Result<Self, MovementScriptError> {
+        let mut steps = Vec::new();
+
+        for (index, line) in source.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let error = |message: String| MovementScriptError::Parse {
+                line: index + 1,
+                message,
+            };
+            let mut parts = line.split_whitespace();
+            let command = parts.next().unwrap_or_default();
+            let args = parts
+                .map(|part| {
+                    part.parse::<Scalar>()
+                        .map_err(|_| error(format!("invalid number `{part}`")))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let step = match (command, args.as_slice()) {
+                ("move_to", &[x, y, z]) => MovementStep::MoveTo(Vector::new(x, y, z)),
+                ("wait", &[seconds]) => MovementStep::Wait(seconds),
+                ("jump", &[]) => MovementStep::Jump,
+                ("face", &[degrees]) => MovementStep::Face(degrees.to_radians()),
+                ("move_to" | "wait" | "jump" | "face", _) => {
+                    return Err(error(format!("wrong number of arguments for `{command}`")));
+                }
+                _ => return Err(error(format!("unknown step `{command}`"))),
+            };
+            steps.push(step);
+        }
+
+        Ok(Self { steps })
+    }
+}
+
+/// An error when loading a [`MovementScript`].
+#[derive(Debug)]
+pub enum MovementScriptError {
+    Io(std::io::Error),
+    Utf8(std::str::Utf8Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for MovementScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "could not read movement script: {error}"),
+            Self::Utf8(error) => write!(f, "movement script is not valid UTF-8: {error}"),
+            Self::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for MovementScriptError {}
+
+/// Loads [`MovementScript`]s from `.movescript` files.
+#[derive(Default)]
+pub struct MovementScriptLoader;
+
+impl AssetLoader for MovementScriptLoader {
+    type Asset = MovementScript;
+    type Settings = ();
+    type Error = MovementScriptError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(MovementScriptError::Io)?;
+        let source = std::str::from_utf8(&bytes).map_err(MovementScriptError::Utf8)?;
+        MovementScript::parse(source)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["movescript"]
+    }
+}
+
+/// Drives a character controller through a [`MovementScript`].
+#[derive(Component, Clone, Debug)]
+#[require(PathFollower)]
+pub struct MovementScriptPlayer {
+    pub script: Handle<MovementScript>,
+    /// Whether the script restarts from the beginning when it ends.
+    pub looping: bool,
+    /// The index of the current step.
+    step: usize,
+    /// Whether the current step has been started.
+    started: bool,
+    /// The remaining time of a [`MovementStep::Wait`].
+    wait_remaining: Scalar,
+}
+
+impl MovementScriptPlayer {
+    pub fn new(script: Handle<MovementScript>) -> Self {
+        Self {
+            script,
+            looping: false,
+            step: 0,
+            started: false,
+            wait_remaining: 0.0,
+        }
+    }
+
+    /// Makes the script restart from the beginning when it ends.
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    /// Returns the index of the current step.
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// Moves to the next step.
+    fn advance(&mut self) {
+        self.step += 1;
+        self.started = false;
+    }
+}
+
+/// A [`Message`] written when a [`MovementScriptPlayer`] reaches the end of a non-looping script.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct MovementScriptFinished {
+    pub entity: Entity,
+}
+
+/// Drives [`MovementScriptPlayer`]s through their scripts.
+#[allow(clippy::type_complexity)]
+pub(crate) fn play_movement_scripts(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    scripts: Res<Assets<MovementScript>>,
+    mut action_writer: MessageWriter<DirectedMovementAction>,
+    mut finished_writer: MessageWriter<MovementScriptFinished>,
+    mut players: Query<
+        (
+            Entity,
+            &mut MovementScriptPlayer,
+            &mut PathFollower,
+            &mut Rotation,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (entity, mut player, mut follower, mut rotation, controller_up) in &mut players {
+        // The script may still be loading.
+        let Some(script) = scripts.get(&player.script) else {
+            continue;
+        };
+
+        if player.step >= script.steps.len() {
+            if player.looping && !script.steps.is_empty() {
+                player.step = 0;
+                player.started = false;
+            } else {
+                commands.entity(entity).remove::<MovementScriptPlayer>();
+                finished_writer.write(MovementScriptFinished { entity });
+                continue;
+            }
+        }
+
+        let starting = !player.started;
+        player.started = true;
+
+        match script.steps[player.step] {
+            MovementStep::MoveTo(target) => {
+                if starting {
+                    follower.set_destination(target);
+                    follower.set_path([target]);
+                } else if !follower.is_following() {
+                    player.advance();
+                }
+            }
+            MovementStep::Wait(seconds) => {
+                if starting {
+                    player.wait_remaining = seconds;
+                }
+                player.wait_remaining -= delta_time;
+                if player.wait_remaining <= 0.0 {
+                    player.advance();
+                }
+            }
+            MovementStep::Jump => {
+                action_writer.write(DirectedMovementAction {
+                    entity,
+                    action: MovementAction::Jump,
+                });
+                player.advance();
+            }
+            MovementStep::Face(yaw) => {
+                rotation.0 = yaw_rotation(yaw, world_up.for_controller(controller_up));
+                player.advance();
+            }
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! Developer noclip mode.
+//!
+//! Writing [`ToggleNoclip`] disables the collider of a character controller, ignores gravity,
+//! and flies it in the direction the camera is facing. Toggling it again restores the collider
+//! and places the character at the nearest free position, like a teleport.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{aim_ray, CharacterController, FpsController, MovementAction, PendingPlacement};
+
+/// Configures the flying speed of a character controller in noclip mode.
+///
+/// Characters without this component use the default configuration.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct NoclipConfig {
+    /// The flying speed.
+    pub speed: Scalar,
+    /// The speed multiplier while [`MovementAction::Sprint`] is held.
+    pub sprint_multiplier: Scalar,
+}
+
+impl Default for NoclipConfig {
+    fn default() -> Self {
+        Self {
+            speed: 10.0,
+            sprint_multiplier: 3.0,
+        }
+    }
+}
+
+/// A component on a character controller in noclip mode.
+///
+/// Controller systems other than look skip characters in noclip mode.
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct Noclip {
+    /// Whether the collider was already disabled before entering noclip mode.
+    pub(crate) collider_was_disabled: bool,
+}
+
+/// A [`Message`] toggling noclip mode for a character controller.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct ToggleNoclip {
+    pub entity: Entity,
+}
+
+/// Enters and leaves noclip mode in response to [`ToggleNoclip`].
+pub(crate) fn toggle_noclip(
+    mut commands: Commands,
+    mut toggle_reader: MessageReader<ToggleNoclip>,
+    mut characters: Query<
+        (&mut LinearVelocity, Option<&Noclip>, Has<ColliderDisabled>),
+        With<CharacterController>,
+    >,
+) {
+    for &ToggleNoclip { entity } in toggle_reader.read() {
+        let Ok((mut linear_velocity, noclip, collider_disabled)) = characters.get_mut(entity)
+        else {
+            continue;
+        };
+
+        linear_velocity.0 = Vector::ZERO;
+
+        let mut entity_commands = commands.entity(entity);
+        match noclip {
+            Some(noclip) => {
+                entity_commands.remove::<Noclip>().insert(PendingPlacement);
+                if !noclip.collider_was_disabled {
+                    entity_commands.remove::<ColliderDisabled>();
+                }
+            }
+            None => {
+                entity_commands.insert((
+                    Noclip {
+                        collider_was_disabled: collider_disabled,
+                    },
+                    ColliderDisabled,
+                ));
+            }
+        }
+    }
+}
+
+/// Flies noclip characters in the direction of the camera.
+#[allow(clippy::type_complexity)]
+pub(crate) fn fly_noclip(
+    mut movement_reader: MessageReader<MovementAction>,
+    mut characters: Query<
+        (
+            Entity,
+            &Position,
+            &Rotation,
+            &mut LinearVelocity,
+            Option<&NoclipConfig>,
+            Option<&FpsController>,
+        ),
+        With<Noclip>,
+    >,
+    children: Query<&Children>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+) {
+    let mut input = Vector2::ZERO;
+    let mut sprint = false;
+    for event in movement_reader.read() {
+        match event {
+            MovementAction::Move(direction) => input += *direction,
+            MovementAction::Sprint => sprint = true,
+            _ => {}
+        }
+    }
+    let input = input.clamp_length_max(1.0);
+
+    for (entity, position, rotation, mut linear_velocity, config, fps_controller) in &mut characters
+    {
+        if !fps_controller.is_some_and(|controller| controller.enable_input) {
+            linear_velocity.0 = Vector::ZERO;
+            continue;
+        }
+
+        let config = config.copied().unwrap_or_default();
+        let (_, forward) = aim_ray(entity, position, rotation, &children, &cameras);
+        let right = rotation * Vector::X;
+        let direction = forward.as_vec3().adjust_precision() * input.y + right * input.x;
+
+        let speed = if sprint {
+            config.speed * config.sprint_multiplier
+        } else {
+            config.speed
+        };
+        linear_velocity.0 = direction * speed;
+    }
+}
@@ -0,0 +1,157 @@
+//! One-way platforms that character controllers can jump up through from below,
+//! and drop through by pressing crouch and jump at the same time.
+//!
+//! Contacts with one-way platforms are filtered in the kinematic collision response,
+//! and dropping through temporarily excludes the platform from the ground caster.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{CharacterController, ControllerEnabled, FpsController, JumpTiming, MovementAction};
+
+/// A component for platforms that character controllers only collide with from above.
+///
+/// Can be added to either the collider or its rigid body.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct OneWayPlatform {
+    /// Whether characters can drop through the platform by pressing crouch and jump.
+    pub allow_drop_through: bool,
+    /// How long the platform is ignored after dropping through it, in seconds.
+    pub drop_through_time: Scalar,
+}
+
+impl Default for OneWayPlatform {
+    fn default() -> Self {
+        Self {
+            allow_drop_through: true,
+            drop_through_time: 0.35,
+        }
+    }
+}
+
+/// A component indicating that a character controller is dropping through a [`OneWayPlatform`].
+///
+/// While present, the platform is ignored by the ground caster and the collision response.
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct DropThrough {
+    /// The collider entity of the platform being dropped through.
+    pub platform: Entity,
+    /// The remaining time during which the platform is ignored, in seconds.
+    pub remaining: Scalar,
+}
+
+/// The minimum upward component of a contact normal for a one-way platform
+/// to be treated as ground below the character.
+const ONE_WAY_MIN_NORMAL_UP: Scalar = 0.7;
+
+/// Returns `true` if the character should pass through a one-way platform
+/// instead of colliding with it.
+///
+/// Characters only collide with one-way platforms when they are moving downward
+/// onto the top surface, measured along the `up` direction of the character.
+pub(crate) fn passes_through_one_way(
+    contacts: &ContactPair,
+    is_first: bool,
+    linear_velocity: &LinearVelocity,
+    up: Vector,
+) -> bool {
+    if linear_velocity.dot(up) > 0.0 {
+        return true;
+    }
+
+    contacts.manifolds.iter().any(|manifold| {
+        let normal = if is_first {
+            -manifold.normal
+        } else {
+            manifold.normal
+        };
+        normal.dot(up) < ONE_WAY_MIN_NORMAL_UP
+    })
+}
+
+/// Starts dropping through one-way platforms when crouch and jump are pressed together,
+/// and ends the drop when its timer runs out.
+#[allow(clippy::type_complexity)]
+pub(crate) fn drop_through_platforms(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    mut movement_reader: MessageReader<MovementAction>,
+    mut controllers: Query<
+        (
+            Entity,
+            &ShapeHits,
+            &mut ShapeCaster,
+            Option<&mut DropThrough>,
+            Option<&FpsController>,
+            Option<&mut JumpTiming>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+    platforms: Query<&OneWayPlatform>,
+    collider_rbs: Query<&ColliderOf>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    let mut crouch = false;
+    let mut jump = false;
+    for event in movement_reader.read() {
+        match event {
+            MovementAction::Crouch => crouch = true,
+            MovementAction::Jump => jump = true,
+            _ => {}
+        }
+    }
+
+    // Platforms can be marked on the collider or on its rigid body.
+    let get_platform = |entity: Entity| {
+        platforms.get(entity).ok().or_else(|| {
+            collider_rbs
+                .get(entity)
+                .ok()
+                .and_then(|collider_of| platforms.get(collider_of.body).ok())
+        })
+    };
+
+    for (entity, hits, mut ground_caster, drop_through, fps_controller, jump_timing) in
+        &mut controllers
+    {
+        if let Some(mut drop_through) = drop_through {
+            drop_through.remaining -= delta_time;
+            if drop_through.remaining <= 0.0 {
+                ground_caster
+                    .query_filter
+                    .excluded_entities
+                    .remove(&drop_through.platform);
+                commands.entity(entity).remove::<DropThrough>();
+            }
+            continue;
+        }
+
+        if !(crouch && jump) || !fps_controller.is_some_and(|controller| controller.enable_input) {
+            continue;
+        }
+
+        let Some((platform_entity, platform)) = hits.iter().find_map(|hit| {
+            get_platform(hit.entity)
+                .filter(|platform| platform.allow_drop_through)
+                .map(|platform| (hit.entity, platform))
+        }) else {
+            continue;
+        };
+
+        ground_caster
+            .query_filter
+            .excluded_entities
+            .insert(platform_entity);
+        commands.entity(entity).insert(DropThrough {
+            platform: platform_entity,
+            remaining: platform.drop_through_time,
+        });
+
+        // The jump is used up by the drop, so it can't be performed as a coyote or buffered jump.
+        if let Some(mut timing) = jump_timing {
+            timing.consume();
+        }
+    }
+}
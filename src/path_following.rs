@@ -0,0 +1,412 @@
+//! Following paths with character controllers.
+//!
+//! A [`PathFollower`] converts a path of waypoints into [`DirectedMovementAction`]s,
+//! so NPCs and players share identical movement physics. Paths are provided by the game,
+//! for example from a navmesh: when the follower needs a new path, a [`RepathRequested`]
+//! message is written, and the answer is given with [`PathFollower::set_path`].
+//! With the `vleue_navigator` feature, paths can be computed automatically with a
+//! [`NavMeshAgent`](crate::NavMeshAgent).
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    input_rotation, CharacterController, ControllerEnabled, ControllerUp, DirectedMovementAction,
+    Grounded, MovementAction, ViewYaw, WorldUp,
+};
+
+/// Drives a character controller along a path of waypoints.
+#[derive(Component, Clone, Debug)]
+pub struct PathFollower {
+    /// The final destination, or `None` if the follower is idle.
+    pub destination: Option<Vector>,
+    /// The remaining waypoints, ending at the destination.
+    pub path: Vec<Vector>,
+    /// How close to the destination the character must get to arrive.
+    pub arrival_radius: Scalar,
+    /// How close to a waypoint the character must get before moving to the next one.
+    pub waypoint_radius: Scalar,
+    /// The character jumps when the next waypoint is this much higher than its feet.
+    pub jump_height: Scalar,
+    /// How often a new path is requested while following, in seconds.
+    /// If `None`, paths are only requested when needed.
+    pub repath_interval: Option<Scalar>,
+    /// A new path is requested when the character makes no progress for this long, in seconds.
+    pub stuck_time: Scalar,
+    /// The horizontal speed below which the character is making no progress.
+    pub min_progress_speed: Scalar,
+    /// The follower gives up and writes [`PathFailed`] when the character makes no progress
+    /// for this long, in seconds, even after requesting new paths. If `None`, it never gives up.
+    pub give_up_time: Option<Scalar>,
+    /// Whether a path was requested and not yet provided.
+    awaiting_path: bool,
+    /// The time since the last path request.
+    since_repath: Scalar,
+    /// How long the character has been making no progress since the last path request.
+    stalled: Scalar,
+    /// How long the character has been making no progress in total.
+    blocked: Scalar,
+}
+
+impl Default for PathFollower {
+    fn default() -> Self {
+        Self {
+            destination: None,
+            path: Vec::new(),
+            arrival_radius: 0.5,
+            waypoint_radius: 0.3,
+            jump_height: 0.6,
+            repath_interval: None,
+            stuck_time: 1.0,
+            min_progress_speed: 0.3,
+            give_up_time: None,
+            awaiting_path: false,
+            since_repath: 0.0,
+            stalled: 0.0,
+            blocked: 0.0,
+        }
+    }
+}
+
+impl PathFollower {
+    /// Sets a new destination, requesting a path to it.
+    pub fn set_destination(&mut self, destination: Vector) {
+        self.destination = Some(destination);
+        self.path.clear();
+        self.awaiting_path = false;
+        self.stalled = 0.0;
+        self.blocked = 0.0;
+    }
+
+    /// Sets the path to follow, for example in response to [`RepathRequested`].
+    ///
+    /// The destination is set to the last waypoint if it isn't set already.
+    pub fn set_path(&mut self, path: impl IntoIterator<Item = Vector>) {
+        self.path = path.into_iter().collect();
+        if self.destination.is_none() {
+            self.destination = self.path.last().copied();
+        }
+        self.awaiting_path = false;
+        self.since_repath = 0.0;
+        self.stalled = 0.0;
+    }
+
+    /// Stops following the path.
+    pub fn stop(&mut self) {
+        self.destination = None;
+        self.path.clear();
+        self.awaiting_path = false;
+    }
+
+    /// Returns `true` if the follower has a destination.
+    pub fn is_following(&self) -> bool {
+        self.destination.is_some()
+    }
+}
+
+/// A [`Message`] written when a [`PathFollower`] needs a new path to its destination.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct RepathRequested {
+    pub entity: Entity,
+    /// The position of the feet of the character.
+    pub from: Vector,
+    pub destination: Vector,
+}
+
+/// A [`Message`] written when a [`PathFollower`] arrives at its destination.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct PathCompleted {
+    pub entity: Entity,
+    pub destination: Vector,
+}
+
+/// A [`Message`] written when a [`PathFollower`] gives up on its destination.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct PathFailed {
+    pub entity: Entity,
+    pub destination: Vector,
+}
+
+/// Converts the paths of [`PathFollower`]s into [`DirectedMovementAction`]s.
+#[allow(clippy::type_complexity)]
+pub(crate) fn follow_paths(
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    mut action_writer: MessageWriter<DirectedMovementAction>,
+    mut repath_writer: MessageWriter<RepathRequested>,
+    mut completed_writer: MessageWriter<PathCompleted>,
+    mut failed_writer: MessageWriter<PathFailed>,
+    mut followers: Query<
+        (
+            Entity,
+            &mut PathFollower,
+            &Position,
+            &Rotation,
+            &LinearVelocity,
+            &ColliderAabb,
+            Has<Grounded>,
+            Option<&ControllerUp>,
+            Option<&ViewYaw>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (
+        entity,
+        mut follower,
+        position,
+        rotation,
+        linear_velocity,
+        aabb,
+        is_grounded,
+        controller_up,
+        view_yaw,
+    ) in &mut followers
+    {
+        let Some(destination) = follower.destination else {
+            continue;
+        };
+        let up = world_up.for_controller(controller_up);
+        let half_height = ((aabb.max - aabb.min) * 0.5).dot(up.abs());
+        let feet = position.0 - up * half_height;
+        let horizontal_distance =
+            |target: Vector| (target - feet).reject_from_normalized(up).length();
+
+        if horizontal_distance(destination) <= follower.arrival_radius {
+            follower.stop();
+            completed_writer.write(PathCompleted {
+                entity,
+                destination,
+            });
+            continue;
+        }
+
+        // Skip waypoints that have been reached.
+        let waypoint_radius = follower.waypoint_radius;
+        while follower
+            .path
+            .first()
+            .is_some_and(|&waypoint| horizontal_distance(waypoint) <= waypoint_radius)
+        {
+            follower.path.remove(0);
+        }
+
+        // Request a new path when there is none, periodically, or when the character is stuck.
+        follower.since_repath += delta_time;
+        let horizontal_speed = linear_velocity.reject_from_normalized(up).length();
+        if follower.path.is_empty() || horizontal_speed >= follower.min_progress_speed {
+            follower.stalled = 0.0;
+            follower.blocked = 0.0;
+        } else {
+            follower.stalled += delta_time;
+            follower.blocked += delta_time;
+        }
+
+        // Give up if the character stays blocked by an obstacle.
+        if follower
+            .give_up_time
+            .is_some_and(|give_up_time| follower.blocked >= give_up_time)
+        {
+            follower.stop();
+            failed_writer.write(PathFailed {
+                entity,
+                destination,
+            });
+            continue;
+        }
+
+        let needs_path = follower.path.is_empty() && !follower.awaiting_path;
+        let interval_elapsed = follower
+            .repath_interval
+            .is_some_and(|interval| follower.since_repath >= interval);
+        let is_stuck = follower.stalled >= follower.stuck_time;
+        if needs_path || interval_elapsed || is_stuck {
+            follower.awaiting_path = true;
+            follower.since_repath = 0.0;
+            follower.stalled = 0.0;
+            repath_writer.write(RepathRequested {
+                entity,
+                from: feet,
+                destination,
+            });
+        }
+
+        let Some(&waypoint) = follower.path.first() else {
+            continue;
+        };
+
+        // Convert the world-space direction into movement input, relative to the view yaw
+        // of characters that face their movement.
+        let offset = waypoint - feet;
+        let direction = offset.reject_from_normalized(up).normalize_or_zero();
+        let local = input_rotation(rotation, view_yaw).inverse() * direction;
+        action_writer.write(DirectedMovementAction {
+            entity,
+            action: MovementAction::Move(Vector2::new(local.x, -local.z)),
+        });
+
+        if is_grounded && offset.dot(up) > follower.jump_height {
+            action_writer.write(DirectedMovementAction {
+                entity,
+                action: MovementAction::Jump,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    /// Creates a world with a character standing at the origin and following the given path.
+    fn follower_world(follower: PathFollower, velocity: Vector) -> (World, Entity) {
+        let mut world = World::new();
+        world.init_resource::<Time<Virtual>>();
+        world.init_resource::<WorldUp>();
+        world.init_resource::<Messages<DirectedMovementAction>>();
+        world.init_resource::<Messages<RepathRequested>>();
+        world.init_resource::<Messages<PathCompleted>>();
+        world.init_resource::<Messages<PathFailed>>();
+
+        let half_extents = Vector::new(0.4, 0.9, 0.4);
+        let position = Vector::Y * half_extents.y;
+        let entity = world
+            .spawn((
+                CharacterController,
+                follower,
+                Position(position),
+                Rotation::default(),
+                LinearVelocity(velocity),
+                ColliderAabb {
+                    min: position - half_extents,
+                    max: position + half_extents,
+                },
+            ))
+            .id();
+        (world, entity)
+    }
+
+    /// Runs [`follow_paths`] once after advancing the time by `millis`.
+    fn step(world: &mut World, millis: u64) {
+        world
+            .resource_mut::<Time<Virtual>>()
+            .advance_by(Duration::from_millis(millis));
+        world.run_system_once(follow_paths).unwrap();
+    }
+
+    fn written<M: Message>(world: &World) -> usize {
+        world.resource::<Messages<M>>().len()
+    }
+
+    fn written_moves(world: &World) -> Vec<Vector2> {
+        world
+            .resource::<Messages<DirectedMovementAction>>()
+            .iter_current_update_messages()
+            .filter_map(|message| match message.action {
+                MovementAction::Move(direction) => Some(direction),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn follower_to(destination: Vector) -> PathFollower {
+        let mut follower = PathFollower::default();
+        follower.set_path([destination]);
+        follower
+    }
+
+    #[test]
+    fn arrives_within_the_arrival_radius() {
+        let (mut world, entity) = follower_world(follower_to(Vector::X * 0.3), Vector::ZERO);
+
+        step(&mut world, 100);
+        assert_eq!(written::<PathCompleted>(&world), 1);
+        assert!(!world.get::<PathFollower>(entity).unwrap().is_following());
+    }
+
+    #[test]
+    fn moves_toward_distant_waypoints() {
+        let (mut world, entity) = follower_world(follower_to(Vector::X * 5.0), Vector::ZERO);
+
+        step(&mut world, 100);
+        assert_eq!(written::<PathCompleted>(&world), 0);
+        assert!(world.get::<PathFollower>(entity).unwrap().is_following());
+        let moves = written_moves(&world);
+        assert_eq!(moves.len(), 1);
+        assert!(
+            moves[0].abs_diff_eq(Vector2::X, 1e-5),
+            "expected to move right, but moved {}",
+            moves[0]
+        );
+    }
+
+    #[test]
+    fn movement_input_is_relative_to_the_view_yaw() {
+        let (mut world, entity) = follower_world(follower_to(Vector::X * 5.0), Vector::ZERO);
+        // The view looks along -X, so the waypoint is behind it.
+        world
+            .entity_mut(entity)
+            .insert(ViewYaw(Quaternion::from_rotation_y(PI * 0.5)));
+
+        step(&mut world, 100);
+        let moves = written_moves(&world);
+        assert_eq!(moves.len(), 1);
+        assert!(
+            moves[0].abs_diff_eq(Vector2::NEG_Y, 1e-5),
+            "expected to move backward, but moved {}",
+            moves[0]
+        );
+    }
+
+    #[test]
+    fn requests_a_path_once_when_it_has_none() {
+        let mut follower = PathFollower::default();
+        follower.set_destination(Vector::X * 5.0);
+        let (mut world, _) = follower_world(follower, Vector::ZERO);
+
+        step(&mut world, 100);
+        step(&mut world, 100);
+        assert_eq!(written::<RepathRequested>(&world), 1);
+    }
+
+    #[test]
+    fn requests_a_new_path_when_stuck() {
+        let (mut world, _) = follower_world(follower_to(Vector::X * 5.0), Vector::ZERO);
+
+        step(&mut world, 500);
+        assert_eq!(written::<RepathRequested>(&world), 0);
+        step(&mut world, 500);
+        assert_eq!(written::<RepathRequested>(&world), 1);
+    }
+
+    #[test]
+    fn making_progress_is_not_stuck() {
+        let (mut world, _) = follower_world(follower_to(Vector::X * 5.0), Vector::X * 2.0);
+
+        for _ in 0..10 {
+            step(&mut world, 500);
+        }
+        assert_eq!(written::<RepathRequested>(&world), 0);
+    }
+
+    #[test]
+    fn gives_up_when_blocked() {
+        let mut follower = follower_to(Vector::X * 5.0);
+        follower.give_up_time = Some(2.0);
+        let (mut world, entity) = follower_world(follower, Vector::ZERO);
+
+        for _ in 0..3 {
+            step(&mut world, 500);
+        }
+        assert_eq!(written::<PathFailed>(&world), 0);
+        step(&mut world, 500);
+        assert_eq!(written::<PathFailed>(&world), 1);
+        assert!(!world.get::<PathFollower>(entity).unwrap().is_following());
+    }
+}
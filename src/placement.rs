@@ -0,0 +1,150 @@
+//! Safe spawning and teleporting.
+//!
+//! Character controllers with a [`SafeSpawn`] component, and characters moved with
+//! [`TeleportCharacter`], are placed at the nearest free position before their movement
+//! is enabled. If no free position exists within the search radius, a [`PlacementFailed`]
+//! message is written and the character is left where it is.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{find_free_position, is_free_position, CharacterController, ControllerUp, WorldUp};
+
+/// Resolves initial penetration when a character controller is spawned or teleported.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct SafeSpawn {
+    /// How far from the spawn position free space is searched for.
+    pub search_radius: Scalar,
+    /// The number of distance steps tried within the search radius.
+    pub search_steps: u32,
+}
+
+impl Default for SafeSpawn {
+    fn default() -> Self {
+        Self {
+            search_radius: 2.0,
+            search_steps: 8,
+        }
+    }
+}
+
+/// A marker component for character controllers waiting to be placed at a free position.
+///
+/// Movement systems skip characters with this component.
+#[derive(Component, Clone, Copy, Debug, Default)]
+#[component(storage = "SparseSet")]
+pub struct PendingPlacement;
+
+/// A [`Message`] requesting a character controller to be moved to the nearest
+/// free position around the given position.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct TeleportCharacter {
+    pub entity: Entity,
+    pub position: Vector,
+}
+
+/// A [`Message`] written when no free position was found for a character controller
+/// that was spawned or teleported into geometry.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct PlacementFailed {
+    pub entity: Entity,
+    pub position: Vector,
+}
+
+/// Marks newly spawned [`SafeSpawn`] characters for placement.
+pub(crate) fn place_safe_spawn(add: On<Add, SafeSpawn>, mut commands: Commands) {
+    commands.entity(add.entity).insert(PendingPlacement);
+}
+
+/// Moves character controllers in response to [`TeleportCharacter`] and marks them for placement.
+pub(crate) fn teleport_characters(
+    mut commands: Commands,
+    mut teleport_reader: MessageReader<TeleportCharacter>,
+    mut characters: Query<(&mut Position, &mut LinearVelocity), With<CharacterController>>,
+) {
+    for &TeleportCharacter { entity, position } in teleport_reader.read() {
+        let Ok((mut current_position, mut linear_velocity)) = characters.get_mut(entity) else {
+            continue;
+        };
+
+        current_position.0 = position;
+        linear_velocity.0 = Vector::ZERO;
+        commands.entity(entity).insert(PendingPlacement);
+    }
+}
+
+/// Places [`PendingPlacement`] characters at the nearest free position.
+#[allow(clippy::type_complexity)]
+pub(crate) fn resolve_placement(
+    mut commands: Commands,
+    physics_time: Res<Time<Physics>>,
+    mut failed_writer: MessageWriter<PlacementFailed>,
+    spatial_query: SpatialQuery,
+    sensors: Query<(), With<Sensor>>,
+    world_up: Res<WorldUp>,
+    mut query: Query<
+        (
+            Entity,
+            &Collider,
+            &mut Position,
+            &Rotation,
+            &mut LinearVelocity,
+            Option<&SafeSpawn>,
+            Option<&ControllerUp>,
+        ),
+        With<PendingPlacement>,
+    >,
+) {
+    // Spatial queries don't see any colliders before the first physics step.
+    if physics_time.elapsed().is_zero() {
+        return;
+    }
+
+    for (
+        entity,
+        collider,
+        mut position,
+        rotation,
+        mut linear_velocity,
+        safe_spawn,
+        controller_up,
+    ) in &mut query
+    {
+        commands.entity(entity).remove::<PendingPlacement>();
+
+        if is_free_position(
+            &spatial_query,
+            &sensors,
+            collider,
+            position.0,
+            rotation.0,
+            entity,
+        ) {
+            continue;
+        }
+
+        let config = safe_spawn.copied().unwrap_or_default();
+        match find_free_position(
+            &spatial_query,
+            &sensors,
+            collider,
+            position.0,
+            rotation.0,
+            entity,
+            world_up.for_controller(controller_up),
+            config.search_radius,
+            config.search_steps,
+        ) {
+            Some(free_position) => {
+                position.0 = free_position;
+                linear_velocity.0 = Vector::ZERO;
+            }
+            None => {
+                failed_writer.write(PlacementFailed {
+                    entity,
+                    position: position.0,
+                });
+            }
+        }
+    }
+}
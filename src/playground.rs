@@ -0,0 +1,292 @@
+//! A standard tuning environment for character controllers.
+//!
+//! Queueing the [`SpawnPlayground`] command builds staircases at several step heights,
+//! slopes at several angles, gaps of several widths, and moving platforms, so that the
+//! same geometry can back examples, tests, and tuning sessions.
+//!
+//! Meshes and materials are only added when their asset collections exist,
+//! so the playground also works in headless apps. The playground is opt-in: add the
+//! [`PlaygroundPlugin`] to move its platforms.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{redirect_vehicle_input, CharacterControllerSystems};
+
+/// A plugin that moves the platforms of playgrounds spawned with [`SpawnPlayground`].
+///
+/// Not part of the [`CharacterControllerPlugin`](crate::CharacterControllerPlugin), so apps
+/// that don't use the playground don't run its systems.
+pub struct PlaygroundPlugin;
+
+impl Plugin for PlaygroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            move_playground_platforms
+                .before(redirect_vehicle_input)
+                .in_set(CharacterControllerSystems::Movement),
+        );
+    }
+}
+
+/// The layout of a playground spawned with [`SpawnPlayground`].
+///
+/// Each kind of geometry is laid out in its own row along the -Z axis,
+/// with one lane per variation side by side along the X axis.
+#[derive(Clone, Debug)]
+pub struct PlaygroundConfig {
+    /// The position of the center of the ground plane.
+    pub origin: Vector,
+    /// The size of the square ground plane.
+    pub ground_size: Scalar,
+    /// The step heights of the staircases, one staircase per height.
+    pub step_heights: Vec<Scalar>,
+    /// The number of steps in each staircase.
+    pub step_count: u32,
+    /// The slope angles of the ramps in degrees, one ramp per angle.
+    pub slope_angles: Vec<Scalar>,
+    /// The widths of the gaps between raised platforms, one gap per width.
+    pub gap_widths: Vec<Scalar>,
+    /// Whether to spawn a horizontally and a vertically moving platform.
+    pub moving_platforms: bool,
+}
+
+impl Default for PlaygroundConfig {
+    fn default() -> Self {
+        Self {
+            origin: Vector::ZERO,
+            ground_size: 80.0,
+            step_heights: vec![0.1, 0.2, 0.3, 0.4, 0.5],
+            step_count: 6,
+            slope_angles: vec![10.0, 20.0, 30.0, 40.0, 50.0, 60.0],
+            gap_widths: vec![0.5, 1.0, 1.5, 2.0, 3.0, 4.0],
+            moving_platforms: true,
+        }
+    }
+}
+
+/// A marker component for the root entity of a playground.
+#[derive(Component)]
+pub struct Playground;
+
+/// A kinematic platform that moves back and forth between two positions.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PlaygroundMover {
+    /// The start position relative to the parent.
+    pub start: Vector,
+    /// The end position relative to the parent.
+    pub end: Vector,
+    /// The time for a full round trip, in seconds.
+    pub period: Scalar,
+}
+
+impl PlaygroundMover {
+    /// Returns the local position at the given time.
+    fn position_at(&self, time: Scalar) -> Vector {
+        let phase = (time * TAU / self.period).cos();
+        self.start.lerp(self.end, 0.5 - 0.5 * phase)
+    }
+}
+
+/// A [`Command`] that spawns a playground as described by its [`PlaygroundConfig`].
+///
+/// All pieces are children of an entity with the [`Playground`] marker,
+/// so the playground can be removed by despawning it. The moving platforms only move
+/// with the [`PlaygroundPlugin`].
+#[derive(Clone, Debug, Default)]
+pub struct SpawnPlayground(pub PlaygroundConfig);
+
+/// The spacing between the lanes of a row.
+const LANE_SPACING: Scalar = 3.0;
+/// The width of a lane.
+const LANE_WIDTH: Scalar = 2.0;
+/// The depth of a single stair step.
+const STEP_DEPTH: Scalar = 0.4;
+/// The length of a ramp along its slope.
+const RAMP_LENGTH: Scalar = 6.0;
+/// The height of the raised platforms around gaps.
+const GAP_PLATFORM_HEIGHT: Scalar = 1.0;
+/// The length of the raised platforms around gaps.
+const GAP_PLATFORM_LENGTH: Scalar = 3.0;
+
+impl Command for SpawnPlayground {
+    fn apply(self, world: &mut World) {
+        let config = self.0;
+        let root = world
+            .spawn((
+                Name::new("Playground"),
+                Playground,
+                Transform::from_translation(config.origin.f32()),
+                Visibility::default(),
+            ))
+            .id();
+        let mut builder = PlaygroundBuilder { world, root };
+
+        // Ground plane with its top surface at the origin.
+        builder.cuboid(
+            Vector::new(config.ground_size, 1.0, config.ground_size),
+            Vector::new(0.0, -0.5, 0.0),
+            Quaternion::IDENTITY,
+            Color::srgb(0.5, 0.5, 0.5),
+        );
+
+        // Staircases, each step a box reaching down to the ground.
+        let row_z = -5.0;
+        let first_lane = |count: usize| -(count.saturating_sub(1) as Scalar) * LANE_SPACING * 0.5;
+        let stairs_x = first_lane(config.step_heights.len());
+        for (lane, &step_height) in config.step_heights.iter().enumerate() {
+            let x = stairs_x + lane as Scalar * LANE_SPACING;
+            for step in 0..config.step_count {
+                let height = step_height * (step + 1) as Scalar;
+                let z = row_z - step as Scalar * STEP_DEPTH;
+                builder.cuboid(
+                    Vector::new(LANE_WIDTH, height, STEP_DEPTH),
+                    Vector::new(x, height * 0.5, z),
+                    Quaternion::IDENTITY,
+                    Color::srgb(0.6, 0.5, 0.4),
+                );
+            }
+        }
+
+        // Ramps rising along -Z, with their lower edge on the ground.
+        let row_z = -15.0;
+        let slopes_x = first_lane(config.slope_angles.len());
+        for (lane, &angle) in config.slope_angles.iter().enumerate() {
+            let angle = angle.to_radians();
+            let x = slopes_x + lane as Scalar * LANE_SPACING;
+            let thickness = 0.2;
+            let half_run = RAMP_LENGTH * 0.5 * angle.cos();
+            let half_rise = RAMP_LENGTH * 0.5 * angle.sin();
+            // Offset the center down by half the thickness along the ramp normal.
+            let normal = Vector::new(0.0, angle.cos(), angle.sin());
+            builder.cuboid(
+                Vector::new(LANE_WIDTH, thickness, RAMP_LENGTH),
+                Vector::new(x, half_rise, row_z - half_run) - normal * thickness * 0.5,
+                Quaternion::from_rotation_x(angle),
+                Color::srgb(0.4, 0.6, 0.4),
+            );
+        }
+
+        // Pairs of raised platforms with gaps between them, reached by a short staircase.
+        let row_z = -25.0;
+        let gaps_x = first_lane(config.gap_widths.len());
+        for (lane, &gap) in config.gap_widths.iter().enumerate() {
+            let x = gaps_x + lane as Scalar * LANE_SPACING;
+            let near_z = row_z - GAP_PLATFORM_LENGTH * 0.5;
+            let far_z = near_z - GAP_PLATFORM_LENGTH - gap;
+            for z in [near_z, far_z] {
+                builder.cuboid(
+                    Vector::new(LANE_WIDTH, GAP_PLATFORM_HEIGHT, GAP_PLATFORM_LENGTH),
+                    Vector::new(x, GAP_PLATFORM_HEIGHT * 0.5, z),
+                    Quaternion::IDENTITY,
+                    Color::srgb(0.4, 0.4, 0.7),
+                );
+            }
+            builder.cuboid(
+                Vector::new(LANE_WIDTH, GAP_PLATFORM_HEIGHT * 0.5, STEP_DEPTH * 2.0),
+                Vector::new(x, GAP_PLATFORM_HEIGHT * 0.25, row_z + STEP_DEPTH),
+                Quaternion::IDENTITY,
+                Color::srgb(0.4, 0.4, 0.7),
+            );
+        }
+
+        if config.moving_platforms {
+            let row_z = -33.0;
+            let size = Vector::new(3.0, 0.3, 3.0);
+            builder.mover(
+                size,
+                PlaygroundMover {
+                    start: Vector::new(-8.0, 1.0, row_z),
+                    end: Vector::new(-2.0, 1.0, row_z),
+                    period: 6.0,
+                },
+            );
+            builder.mover(
+                size,
+                PlaygroundMover {
+                    start: Vector::new(4.0, 0.15, row_z),
+                    end: Vector::new(4.0, 4.0, row_z),
+                    period: 8.0,
+                },
+            );
+        }
+    }
+}
+
+/// Spawns playground pieces as children of the root entity.
+struct PlaygroundBuilder<'w> {
+    world: &'w mut World,
+    root: Entity,
+}
+
+impl PlaygroundBuilder<'_> {
+    /// Spawns a static box.
+    fn cuboid(&mut self, size: Vector, position: Vector, rotation: Quaternion, color: Color) {
+        let transform = Transform::from_translation(position.f32()).with_rotation(rotation.f32());
+        let entity = self
+            .world
+            .spawn((
+                RigidBody::Static,
+                Collider::cuboid(size.x, size.y, size.z),
+                transform,
+                ChildOf(self.root),
+            ))
+            .id();
+        self.add_visuals(entity, size, color);
+    }
+
+    /// Spawns a kinematic box moved by a [`PlaygroundMover`].
+    fn mover(&mut self, size: Vector, mover: PlaygroundMover) {
+        let entity = self
+            .world
+            .spawn((
+                RigidBody::Kinematic,
+                Collider::cuboid(size.x, size.y, size.z),
+                Transform::from_translation(mover.start.f32()),
+                mover,
+                ChildOf(self.root),
+            ))
+            .id();
+        self.add_visuals(entity, size, Color::srgb(0.8, 0.5, 0.2));
+    }
+
+    /// Adds a mesh and material if the app has the assets for them.
+    fn add_visuals(&mut self, entity: Entity, size: Vector, color: Color) {
+        let Some(mesh) = self
+            .world
+            .get_resource_mut::<Assets<Mesh>>()
+            .map(|mut meshes| meshes.add(Cuboid::from_size(size.f32())))
+        else {
+            return;
+        };
+        let Some(material) = self
+            .world
+            .get_resource_mut::<Assets<StandardMaterial>>()
+            .map(|mut materials| materials.add(color))
+        else {
+            return;
+        };
+        self.world
+            .entity_mut(entity)
+            .insert((Mesh3d(mesh), MeshMaterial3d(material)));
+    }
+}
+
+/// Moves [`PlaygroundMover`] platforms with their velocity, so that they carry bodies.
+pub(crate) fn move_playground_platforms(
+    time: Res<Time<Virtual>>,
+    mut movers: Query<(&PlaygroundMover, &Transform, &mut LinearVelocity)>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+    if delta_time <= 0.0 {
+        return;
+    }
+
+    // Steer towards where the platform should be next frame, so errors don't accumulate.
+    let next_time = (time.elapsed_secs_f64() + time.delta_secs_f64()).adjust_precision();
+    for (mover, transform, mut linear_velocity) in &mut movers {
+        let target = mover.position_at(next_time);
+        linear_velocity.0 = (target - transform.translation.adjust_precision()) / delta_time;
+    }
+}
@@ -0,0 +1,127 @@
+//! Switching the local player between character controllers.
+//!
+//! Queueing the [`Possess`] command atomically moves the [`FpsController`] (and with it
+//! player input and the cursor grab state) and the first-person camera rig from the currently
+//! possessed character to another one. Possessing the previous character switches back.
+
+use avian3d::math::*;
+use bevy::prelude::*;
+
+use crate::{CameraPivot, CharacterController, FpsController, MouseSensitivity, Pitch};
+
+/// A [`Command`] that makes the local player control the given character controller.
+///
+/// The [`FpsController`] and the child holding the [`Camera3d`] or [`CameraPivot`] of the
+/// currently possessed character are moved to the target. Characters without
+/// [`MouseSensitivity`] or [`Pitch`], like NPCs, get them copied from the previous character.
+/// The released character stops reacting to player input, and can be driven with
+/// [`DirectedMovementAction`](crate::DirectedMovementAction)s like other NPCs. This assumes a
+/// single local player.
+#[derive(Clone, Copy, Debug)]
+pub struct Possess(pub Entity);
+
+/// The character controller the local player last possessed with [`Possess`].
+///
+/// Grabbing the cursor enables input only for this controller. If it is `None` or the
+/// character no longer has an [`FpsController`], the single [`FpsController`] is used.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PossessedController(pub Option<Entity>);
+
+/// A [`Message`] written when the local player possesses a character controller.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct Possessed {
+    /// The previously possessed character, if any.
+    pub previous: Option<Entity>,
+    /// The newly possessed character.
+    pub current: Entity,
+}
+
+impl Command for Possess {
+    fn apply(self, world: &mut World) {
+        let target = self.0;
+        if world.get::<CharacterController>(target).is_none() {
+            warn!("Cannot possess {target}, it is not a character controller");
+            return;
+        }
+
+        let mut possessed = world.query_filtered::<Entity, With<FpsController>>();
+        let previous = possessed.iter(world).find(|&entity| entity != target);
+        world.insert_resource(PossessedController(Some(target)));
+        if previous.is_none() && world.get::<FpsController>(target).is_some() {
+            // Already possessed.
+            return;
+        }
+
+        let enable_input = previous
+            .and_then(|previous| world.get::<FpsController>(previous))
+            .is_some_and(|controller| controller.enable_input);
+        let sensitivity = previous
+            .and_then(|previous| world.get::<MouseSensitivity>(previous))
+            .map_or(0.002, |sensitivity| sensitivity.0);
+        let max_pitch = previous
+            .and_then(|previous| world.get::<Pitch>(previous))
+            .map_or(PI * 0.5 - 0.1, |pitch| pitch.max);
+        let pitch_target = previous.and_then(|previous| find_pitch_target(world, previous));
+        // The direct child of the previous character that holds the camera rig.
+        let rig = pitch_target.and_then(|mut rig| loop {
+            let parent = world.get::<ChildOf>(rig)?.parent();
+            if Some(parent) == previous {
+                break Some(rig);
+            }
+            rig = parent;
+        });
+
+        if let Some(previous) = previous {
+            world.entity_mut(previous).remove::<FpsController>();
+        }
+
+        let mut target_entity = world.entity_mut(target);
+        target_entity.insert(FpsController { enable_input });
+        if !target_entity.contains::<MouseSensitivity>() {
+            target_entity.insert(MouseSensitivity(sensitivity));
+        }
+        if !target_entity.contains::<Pitch>() {
+            target_entity.insert(Pitch {
+                angle: 0.0,
+                max: max_pitch,
+            });
+        }
+        let pitch = target_entity
+            .get::<Pitch>()
+            .map_or(0.0, |pitch| pitch.angle);
+
+        // Move the camera rig over, keeping its local offset and applying the pitch of the target.
+        if let (Some(rig), Some(pitch_target)) = (rig, pitch_target) {
+            world.entity_mut(rig).insert(ChildOf(target));
+            if let Some(mut transform) = world.get_mut::<Transform>(pitch_target) {
+                transform.rotation = Quaternion::from_rotation_x(pitch).f32();
+            }
+        }
+
+        world.write_message(Possessed {
+            previous,
+            current: target,
+        });
+    }
+}
+
+/// Returns the descendant of the entity that pitch is applied to, preferring a
+/// [`CameraPivot`] over a [`Camera3d`], like the look system does.
+pub(crate) fn find_pitch_target(world: &World, entity: Entity) -> Option<Entity> {
+    let mut camera = None;
+    let mut stack: Vec<Entity> = world
+        .get::<Children>(entity)
+        .map_or(Vec::new(), |children| children.to_vec());
+    while let Some(descendant) = stack.pop() {
+        if world.get::<CameraPivot>(descendant).is_some() {
+            return Some(descendant);
+        }
+        if camera.is_none() && world.get::<Camera3d>(descendant).is_some() {
+            camera = Some(descendant);
+        }
+        if let Some(children) = world.get::<Children>(descendant) {
+            stack.extend(children.iter());
+        }
+    }
+    camera
+}
@@ -0,0 +1,269 @@
+//! Pushing and pulling heavy dynamic bodies, sokoban-style.
+//!
+//! A character with a [`Pusher`] grabs the [`Pushable`] body in front of it while
+//! [`MovementAction::InteractHeld`] is written. While [`Pushing`], the character is slowed
+//! down and only moves along the push axis, and the object follows it with spring forces
+//! along the same axis. The object is let go when interact is released, or when it gets
+//! blocked and falls too far behind.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    Carried, CharacterController, ControllerEnabled, ControllerUp, DirectedMovementAction,
+    FpsController, Grounded, MovementAction, SpeedModifier, SpeedModifiers, WorldUp,
+};
+
+/// The tag of the [`SpeedModifier`] that slows characters down while pushing.
+pub const PUSH_PULL_MODIFIER: &str = "push_pull";
+
+/// A dynamic body that characters with a [`Pusher`] can push and pull.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Pushable {
+    /// Whether the push axis snaps to the horizontal axes of the body, so crates move
+    /// along their faces like in sokoban puzzles, instead of along the grabbed normal.
+    pub snap_to_axes: bool,
+}
+
+impl Default for Pushable {
+    fn default() -> Self {
+        Self { snap_to_axes: true }
+    }
+}
+
+/// Enables pushing and pulling [`Pushable`] bodies for a character controller.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Pusher {
+    /// The maximum distance from the center of the character at which objects can be
+    /// grabbed.
+    pub reach: Scalar,
+    /// The speed multiplier while pushing.
+    pub speed_multiplier: Scalar,
+    /// The stiffness of the spring pulling the object along with the character.
+    pub strength: Scalar,
+    /// The damping of the spring, relative to the velocity of the character.
+    pub damping: Scalar,
+    /// The heaviest object that can be pushed.
+    pub max_mass: Scalar,
+    /// The object is let go if it gets further than this from where it should be, for
+    /// example when it is blocked by a wall.
+    pub break_distance: Scalar,
+}
+
+impl Default for Pusher {
+    fn default() -> Self {
+        Self {
+            reach: 1.2,
+            speed_multiplier: 0.4,
+            strength: 40.0,
+            damping: 10.0,
+            max_mass: 500.0,
+            break_distance: 0.5,
+        }
+    }
+}
+
+/// A component on a [`Pusher`] that is currently pushing or pulling an object.
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct Pushing {
+    /// The object being pushed.
+    pub object: Entity,
+    /// The horizontal axis the character and the object move along.
+    pub axis: Vector,
+    /// The offset from the character to the object when it was grabbed.
+    offset: Vector,
+}
+
+/// Grabs [`Pushable`] bodies while interact is held, and lets them go when it is released.
+#[allow(clippy::type_complexity)]
+pub(crate) fn grab_pushables(
+    mut commands: Commands,
+    world_up: Res<WorldUp>,
+    mut movement_reader: MessageReader<MovementAction>,
+    mut directed_reader: MessageReader<DirectedMovementAction>,
+    spatial_query: SpatialQuery,
+    mut controllers: Query<
+        (
+            Entity,
+            &Pusher,
+            &Position,
+            &Rotation,
+            Option<&Pushing>,
+            Option<&mut SpeedModifiers>,
+            Option<&FpsController>,
+            Has<Grounded>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+    objects: Query<(&Pushable, &RigidBody, &ComputedMass, &Position, &Rotation), Without<Carried>>,
+    colliders: Query<&ColliderOf>,
+) {
+    let global_held = movement_reader
+        .read()
+        .any(|action| matches!(action, MovementAction::InteractHeld));
+    let directed_held: Vec<Entity> = directed_reader
+        .read()
+        .filter(|directed| matches!(directed.action, MovementAction::InteractHeld))
+        .map(|directed| directed.entity)
+        .collect();
+
+    for (
+        entity,
+        pusher,
+        position,
+        rotation,
+        pushing,
+        speed_modifiers,
+        fps_controller,
+        is_grounded,
+        controller_up,
+    ) in &mut controllers
+    {
+        let up = world_up.for_controller(controller_up);
+        let input_enabled = fps_controller.is_some_and(|controller| controller.enable_input);
+        let held = (input_enabled && global_held) || directed_held.contains(&entity);
+
+        if let Some(pushing) = pushing {
+            if !held || !is_grounded || !objects.contains(pushing.object) {
+                commands.entity(entity).remove::<Pushing>();
+                if let Some(mut modifiers) = speed_modifiers {
+                    modifiers.remove(PUSH_PULL_MODIFIER);
+                }
+            }
+            continue;
+        }
+        if !held || !is_grounded {
+            continue;
+        }
+
+        let Ok(forward) = Dir3::new(
+            (rotation * Vector::NEG_Z)
+                .reject_from_normalized(up)
+                .normalize_or_zero()
+                .f32(),
+        ) else {
+            continue;
+        };
+        let Some(hit) = spatial_query.cast_ray(
+            position.0,
+            forward,
+            pusher.reach,
+            true,
+            &SpatialQueryFilter::from_excluded_entities([entity]),
+        ) else {
+            continue;
+        };
+
+        let object = colliders
+            .get(hit.entity)
+            .map_or(hit.entity, |collider_of| collider_of.body);
+        let Ok((pushable, rb, mass, object_position, object_rotation)) = objects.get(object) else {
+            continue;
+        };
+        if !rb.is_dynamic() || mass.value() > pusher.max_mass {
+            continue;
+        }
+
+        // Push into the grabbed face, optionally snapped to the closest face of the body
+        let normal = (-hit.normal).reject_from_normalized(up).normalize_or_zero();
+        let axis = if pushable.snap_to_axes {
+            [Vector::X, Vector::NEG_X, Vector::Z, Vector::NEG_Z]
+                .map(|axis| {
+                    (object_rotation * axis)
+                        .reject_from_normalized(up)
+                        .normalize_or_zero()
+                })
+                .into_iter()
+                .max_by(|a, b| a.dot(normal).total_cmp(&b.dot(normal)))
+                .unwrap_or(normal)
+        } else {
+            normal
+        };
+        if axis == Vector::ZERO {
+            continue;
+        }
+
+        commands.entity(entity).insert(Pushing {
+            object,
+            axis,
+            offset: object_position.0 - position.0,
+        });
+        let modifier = SpeedModifier::multiplier(pusher.speed_multiplier);
+        match speed_modifiers {
+            Some(mut modifiers) => modifiers.insert(PUSH_PULL_MODIFIER, modifier),
+            None => {
+                let mut modifiers = SpeedModifiers::default();
+                modifiers.insert(PUSH_PULL_MODIFIER, modifier);
+                commands.entity(entity).insert(modifiers);
+            }
+        }
+    }
+}
+
+/// Constrains [`Pushing`] characters to the push axis, and pulls the pushed objects along
+/// with them.
+#[allow(clippy::type_complexity)]
+pub(crate) fn push_pull_objects(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    mut controllers: Query<
+        (
+            Entity,
+            &Pusher,
+            &Pushing,
+            &Position,
+            &mut LinearVelocity,
+            Option<&mut SpeedModifiers>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+    mut objects: Query<
+        (&Position, &mut LinearVelocity, &mut AngularVelocity),
+        (With<Pushable>, Without<CharacterController>),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (entity, pusher, pushing, position, mut linear_velocity, speed_modifiers, controller_up) in
+        &mut controllers
+    {
+        let up = world_up.for_controller(controller_up);
+        let Ok((object_position, mut object_velocity, mut angular_velocity)) =
+            objects.get_mut(pushing.object)
+        else {
+            continue;
+        };
+
+        // Only movement along the axis is kept, like a character leaning on a crate
+        let vertical = linear_velocity.project_onto_normalized(up);
+        let speed = linear_velocity.dot(pushing.axis);
+        linear_velocity.0 = vertical + pushing.axis * speed;
+
+        // Let go if the object got stuck behind something
+        let error = position.0 + pushing.offset - object_position.0;
+        if error.reject_from_normalized(up).length() > pusher.break_distance {
+            commands.entity(entity).remove::<Pushing>();
+            if let Some(mut modifiers) = speed_modifiers {
+                modifiers.remove(PUSH_PULL_MODIFIER);
+            }
+            continue;
+        }
+
+        // Damped spring along the axis, relative to the velocity of the character
+        let relative_speed = (object_velocity.0 - linear_velocity.0).dot(pushing.axis);
+        let acceleration =
+            error.dot(pushing.axis) * pusher.strength - relative_speed * pusher.damping;
+        object_velocity.0 += pushing.axis * acceleration * delta_time;
+
+        // Keep the object from sliding sideways or spinning while pushed
+        let lateral = object_velocity
+            .reject_from_normalized(up)
+            .reject_from_normalized(pushing.axis);
+        object_velocity.0 -= lateral * (pusher.damping * delta_time).min(1.0);
+        angular_velocity.0 /= 1.0 + pusher.damping * delta_time;
+    }
+}
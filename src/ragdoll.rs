@@ -0,0 +1,118 @@
+//! Ragdoll mode for character controllers.
+//!
+//! Inserting [`Ragdoll`] on a character controller turns it into a tumbling dynamic body
+//! (or hands it over to a separate multi-body ragdoll) and disables the movement systems.
+//! Removing the component restores the upright controller state.
+//!
+//! ```ignore
+//! // Knock the character down...
+//! commands.entity(player).insert(Ragdoll::default());
+//! // ...and get back up.
+//! commands.entity(player).remove::<Ragdoll>();
+//! ```
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{yaw_of, yaw_rotation, CharacterController, ControllerUp, WorldUp};
+
+/// A component that switches a character controller into ragdoll mode while present.
+///
+/// All controller systems skip entities with this component.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Ragdoll {
+    /// An optional multi-body ragdoll to use instead of the controller body.
+    ///
+    /// The replacement is spawned and despawned by the game. While it is active,
+    /// the controller body is hidden and its collider disabled, and on exit the
+    /// controller is moved to the position of the replacement.
+    pub replacement: Option<Entity>,
+}
+
+impl Ragdoll {
+    /// Ragdoll mode using a separate multi-body ragdoll entity.
+    pub fn with_replacement(replacement: Entity) -> Self {
+        Self {
+            replacement: Some(replacement),
+        }
+    }
+}
+
+/// The controller state saved when entering ragdoll mode, restored on exit.
+#[derive(Component)]
+struct RagdollRestoreState {
+    body: RigidBody,
+    locked_axes: Option<LockedAxes>,
+}
+
+/// Switches a character controller into ragdoll mode when [`Ragdoll`] is added.
+pub(crate) fn enter_ragdoll(
+    add: On<Add, Ragdoll>,
+    mut commands: Commands,
+    controllers: Query<(&Ragdoll, &RigidBody, Option<&LockedAxes>), With<CharacterController>>,
+) {
+    let Ok((ragdoll, body, locked_axes)) = controllers.get(add.entity) else {
+        return;
+    };
+
+    let mut entity_commands = commands.entity(add.entity);
+    entity_commands.insert(RagdollRestoreState {
+        body: *body,
+        locked_axes: locked_axes.copied(),
+    });
+
+    if ragdoll.replacement.is_some() {
+        // The multi-body ragdoll takes over, so the controller body is parked.
+        entity_commands.insert((ColliderDisabled, RigidBodyDisabled, Visibility::Hidden));
+    } else {
+        // Let the controller body tumble freely.
+        entity_commands
+            .insert(RigidBody::Dynamic)
+            .remove::<LockedAxes>();
+    }
+}
+
+/// Restores the upright controller state when [`Ragdoll`] is removed.
+#[allow(clippy::type_complexity)]
+pub(crate) fn exit_ragdoll(
+    remove: On<Remove, Ragdoll>,
+    mut commands: Commands,
+    world_up: Res<WorldUp>,
+    mut controllers: Query<
+        (
+            &Ragdoll,
+            &RagdollRestoreState,
+            &mut Position,
+            &mut Rotation,
+            &mut AngularVelocity,
+            Option<&ControllerUp>,
+        ),
+        With<CharacterController>,
+    >,
+    positions: Query<&Position, Without<CharacterController>>,
+) {
+    let Ok((ragdoll, restore, mut position, mut rotation, mut angular_velocity, controller_up)) =
+        controllers.get_mut(remove.entity)
+    else {
+        return;
+    };
+
+    if let Some(replacement) = ragdoll.replacement {
+        if let Ok(replacement_position) = positions.get(replacement) {
+            position.0 = replacement_position.0;
+        }
+    }
+
+    // Stand back up, keeping only the yaw of the body.
+    let up = world_up.for_controller(controller_up);
+    rotation.0 = yaw_rotation(yaw_of(rotation.0, up), up);
+    angular_velocity.0 = Vector::ZERO;
+
+    let mut entity_commands = commands.entity(remove.entity);
+    entity_commands
+        .remove::<(RagdollRestoreState, ColliderDisabled, RigidBodyDisabled)>()
+        .insert((restore.body, Visibility::Inherited));
+    if let Some(locked_axes) = restore.locked_axes {
+        entity_commands.insert(locked_axes);
+    }
+}
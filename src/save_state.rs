@@ -0,0 +1,227 @@
+//! Saving and restoring the full movement state of a character controller.
+//!
+//! A [`ControllerSaveState`] captures more than the transform: the view pitch and yaw, the
+//! velocity, noclip mode, the stance and collider height, the [`AbilityCooldowns`], and the
+//! [`Stamina`]. With the `serde` feature, it can be written to save games, and restoring it
+//! with [`RestoreControllerState`] puts the character back exactly where and how it was.
+//!
+//! ```ignore
+//! // Saving
+//! let state = ControllerSaveState::capture(world, player).unwrap();
+//! let saved = ron::to_string(&state)?;
+//!
+//! // Loading
+//! let state: ControllerSaveState = ron::from_str(&saved)?;
+//! commands.queue(RestoreControllerState { entity: player, state });
+//! ```
+
+use std::collections::BTreeMap;
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    capsule_for_height, find_pitch_target, orbit_rotation, AbilityCooldowns, CharacterController,
+    GroundCastConfig, Noclip, Pitch, Stamina, Stance, StanceConfig, StanceHeight, ViewYaw,
+    YawSmoothing,
+};
+
+/// The saved charges and timers of an [`AbilityCooldown`](crate::AbilityCooldown).
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SavedCooldown {
+    pub charges: u32,
+    /// The time the next charge has been recharging for, in seconds.
+    pub recharge_progress: Scalar,
+    /// The remaining time before the ability can be used again, in seconds.
+    pub cooldown_remaining: Scalar,
+}
+
+/// The movement state of a character controller, for persisting it across save and load.
+///
+/// Settings like the speed and jump height aren't saved, since they come from the spawn code
+/// of the game. Only the state that changes while playing is.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ControllerSaveState {
+    pub position: Vector,
+    /// The rotation of the character, including its yaw.
+    pub rotation: Quaternion,
+    /// The [`ViewYaw`] of characters whose view is independent of their facing.
+    pub view_yaw: Option<Quaternion>,
+    /// The [`Pitch`] angle of the camera.
+    pub pitch: Scalar,
+    pub linear_velocity: Vector,
+    /// Whether the character is in [`Noclip`] mode.
+    pub noclip: bool,
+    pub stance: Option<Stance>,
+    /// The [`StanceHeight`] of the collider, which may be between two stances.
+    pub stance_height: Option<Scalar>,
+    /// The [`AbilityCooldowns`] state, keyed by tag.
+    pub cooldowns: BTreeMap<String, SavedCooldown>,
+    /// The current [`Stamina`].
+    pub stamina: Option<Scalar>,
+}
+
+impl ControllerSaveState {
+    /// Captures the state of a character controller.
+    ///
+    /// Returns `None` if the entity isn't a character controller.
+    pub fn capture(world: &World, entity: Entity) -> Option<Self> {
+        let entity_ref = world.get_entity(entity).ok()?;
+        if !entity_ref.contains::<CharacterController>() {
+            return None;
+        }
+
+        let cooldowns = entity_ref
+            .get::<AbilityCooldowns>()
+            .map(|cooldowns| {
+                cooldowns
+                    .iter()
+                    .map(|(tag, cooldown)| {
+                        let saved = SavedCooldown {
+                            charges: cooldown.charges(),
+                            recharge_progress: cooldown.recharge_progress(),
+                            cooldown_remaining: cooldown.cooldown_remaining(),
+                        };
+                        (tag.to_string(), saved)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Self {
+            position: entity_ref.get::<Position>()?.0,
+            rotation: entity_ref.get::<Rotation>()?.0,
+            view_yaw: entity_ref.get::<ViewYaw>().map(|view_yaw| view_yaw.0),
+            pitch: entity_ref.get::<Pitch>().map_or(0.0, |pitch| pitch.angle),
+            linear_velocity: entity_ref
+                .get::<LinearVelocity>()
+                .map_or(Vector::ZERO, |velocity| velocity.0),
+            noclip: entity_ref.contains::<Noclip>(),
+            stance: entity_ref.get::<Stance>().copied(),
+            stance_height: entity_ref.get::<StanceHeight>().map(|height| height.0),
+            cooldowns,
+            stamina: entity_ref.get::<Stamina>().map(|stamina| stamina.current),
+        })
+    }
+
+    /// Restores the state of a character controller.
+    ///
+    /// Abilities that the character doesn't have anymore are skipped, and charges are
+    /// limited to the current maximum.
+    pub fn apply(&self, world: &mut World, entity: Entity) {
+        let Ok(mut entity_mut) = world.get_entity_mut(entity) else {
+            return;
+        };
+        if !entity_mut.contains::<CharacterController>() {
+            warn!("Cannot restore the state of {entity}, it is not a character controller");
+            return;
+        }
+
+        // Write the transform too, so it isn't synced back over the restored position
+        entity_mut.insert((
+            Position(self.position),
+            Rotation(self.rotation),
+            LinearVelocity(self.linear_velocity),
+        ));
+        if let Some(mut transform) = entity_mut.get_mut::<Transform>() {
+            transform.translation = self.position.f32();
+            transform.rotation = self.rotation.f32();
+        }
+        if let Some(mut smoothing) = entity_mut.get_mut::<YawSmoothing>() {
+            smoothing.target = None;
+        }
+        if let (Some(view_yaw), Some(mut current)) =
+            (self.view_yaw, entity_mut.get_mut::<ViewYaw>())
+        {
+            current.0 = view_yaw;
+        }
+        if let Some(mut pitch) = entity_mut.get_mut::<Pitch>() {
+            pitch.angle = self.pitch.clamp(-pitch.max, pitch.max);
+        }
+
+        match (self.noclip, entity_mut.get::<Noclip>().copied()) {
+            (true, None) => {
+                let collider_was_disabled = entity_mut.contains::<ColliderDisabled>();
+                entity_mut.insert((
+                    Noclip {
+                        collider_was_disabled,
+                    },
+                    ColliderDisabled,
+                ));
+            }
+            (false, Some(noclip)) => {
+                entity_mut.remove::<Noclip>();
+                if !noclip.collider_was_disabled {
+                    entity_mut.remove::<ColliderDisabled>();
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(stance) = self.stance {
+            entity_mut.insert(stance);
+        }
+        // Resize the collider right away, since the stance system only resizes it toward
+        // the height of a new stance.
+        let config = entity_mut.get::<StanceConfig>().copied();
+        if let (Some(height), Some(config)) = (self.stance_height, config) {
+            let collider = capsule_for_height(config.radius, height);
+            let caster_shape = entity_mut
+                .get::<GroundCastConfig>()
+                .copied()
+                .unwrap_or_default()
+                .caster_shape(&collider);
+            if let Some(mut ground_caster) = entity_mut.get_mut::<ShapeCaster>() {
+                ground_caster.shape = caster_shape;
+            }
+            entity_mut.insert((StanceHeight(height), collider));
+        }
+
+        if let Some(mut cooldowns) = entity_mut.get_mut::<AbilityCooldowns>() {
+            for (tag, saved) in &self.cooldowns {
+                if let Some(cooldown) = cooldowns.get_mut(tag) {
+                    cooldown.restore(
+                        saved.charges,
+                        saved.recharge_progress,
+                        saved.cooldown_remaining,
+                    );
+                }
+            }
+        }
+
+        if let (Some(current), Some(mut stamina)) = (self.stamina, entity_mut.get_mut::<Stamina>())
+        {
+            stamina.current = current.clamp(0.0, stamina.max);
+        }
+
+        // The camera pitch is only written on look input, so apply it here
+        let rotation = self.rotation;
+        let Some(pitch) = world.get::<Pitch>(entity).map(|pitch| pitch.angle) else {
+            return;
+        };
+        let view_yaw = world.get::<ViewYaw>(entity).map(|view_yaw| view_yaw.0);
+        if let Some(target) = find_pitch_target(world, entity) {
+            if let Some(mut transform) = world.get_mut::<Transform>(target) {
+                transform.rotation = match view_yaw {
+                    Some(view_yaw) => orbit_rotation(rotation, view_yaw, pitch).f32(),
+                    None => Quaternion::from_rotation_x(pitch).f32(),
+                };
+            }
+        }
+    }
+}
+
+/// A [`Command`] that restores a [`ControllerSaveState`] on a character controller.
+#[derive(Clone, Debug)]
+pub struct RestoreControllerState {
+    pub entity: Entity,
+    pub state: ControllerSaveState,
+}
+
+impl Command for RestoreControllerState {
+    fn apply(self, world: &mut World) {
+        self.state.apply(world, self.entity);
+    }
+}
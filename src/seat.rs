@@ -0,0 +1,171 @@
+//! Sitting on seats.
+//!
+//! Writing [`SitDown`] anchors a character controller to a [`Seat`]: movement is disabled
+//! and the character follows the seat transform, but it can still look around within
+//! the yaw and pitch limits of the seat. Writing [`StandUp`] restores the controller
+//! at the exit point of the seat.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{CharacterController, ControllerUp, LookEnabled, WorldUp};
+
+/// A component for entities that character controllers can sit on.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Seat {
+    /// The position of the character relative to the seat while seated.
+    pub anchor: Vector,
+    /// The position of the character relative to the seat after standing up.
+    pub exit_offset: Vector,
+    /// The maximum yaw angle relative to the seat facing while seated.
+    pub yaw_limit: Scalar,
+    /// The maximum pitch angle while seated.
+    pub pitch_limit: Scalar,
+}
+
+impl Default for Seat {
+    fn default() -> Self {
+        Self {
+            anchor: Vector::new(0.0, 0.5, 0.0),
+            exit_offset: Vector::new(0.0, 1.0, -1.0),
+            yaw_limit: PI * 0.5,
+            pitch_limit: PI * 0.35,
+        }
+    }
+}
+
+/// A component on a character controller that is sitting on a [`Seat`].
+///
+/// Movement systems skip seated characters, but look input is still applied.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Seated {
+    pub seat: Entity,
+}
+
+/// A [`Message`] requesting a character controller to sit on a [`Seat`].
+#[derive(Message, Clone, Copy, Debug)]
+pub struct SitDown {
+    pub character: Entity,
+    pub seat: Entity,
+}
+
+/// A [`Message`] requesting a [`Seated`] character controller to stand up.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct StandUp {
+    pub character: Entity,
+}
+
+/// Returns the yaw angle of a rotation around the `up` axis.
+pub(crate) fn yaw_of(rotation: Quaternion, up: Vector) -> Scalar {
+    direction_yaw(rotation * Vector::NEG_Z, up)
+}
+
+/// Returns the yaw angle around the `up` axis of a forward direction.
+///
+/// Yaw is measured in the frame that rotates the Y axis onto `up`.
+pub(crate) fn direction_yaw(forward: Vector, up: Vector) -> Scalar {
+    let forward = Quaternion::from_rotation_arc(Vector::Y, up).inverse() * forward;
+    (-forward.x).atan2(-forward.z)
+}
+
+/// Returns the rotation with the given yaw angle around the `up` axis, the inverse of [`yaw_of`].
+pub(crate) fn yaw_rotation(yaw: Scalar, up: Vector) -> Quaternion {
+    Quaternion::from_rotation_arc(Vector::Y, up) * Quaternion::from_rotation_y(yaw)
+}
+
+/// Clamps the yaw of a seated character to the limits of its seat.
+pub(crate) fn clamp_seated_yaw(
+    rotation: Quaternion,
+    seat: &Seat,
+    seat_yaw: Scalar,
+    up: Vector,
+) -> Quaternion {
+    let relative_yaw = (yaw_of(rotation, up) - seat_yaw + PI).rem_euclid(TAU) - PI;
+    let clamped_yaw = relative_yaw.clamp(-seat.yaw_limit, seat.yaw_limit);
+    yaw_rotation(seat_yaw + clamped_yaw, up)
+}
+
+/// Seats character controllers in response to [`SitDown`], and restores them in response to [`StandUp`].
+#[allow(clippy::type_complexity)]
+pub(crate) fn sit_and_stand(
+    mut commands: Commands,
+    world_up: Res<WorldUp>,
+    mut sit_reader: MessageReader<SitDown>,
+    mut stand_reader: MessageReader<StandUp>,
+    mut characters: Query<
+        (
+            &mut Position,
+            &mut Rotation,
+            Option<&Seated>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, LookEnabled),
+    >,
+    seats: Query<(&Seat, &GlobalTransform)>,
+) {
+    for &SitDown { character, seat } in sit_reader.read() {
+        let Ok((_, mut rotation, None, controller_up)) = characters.get_mut(character) else {
+            continue;
+        };
+        let Ok((_, seat_transform)) = seats.get(seat) else {
+            continue;
+        };
+
+        // Face the same way as the seat.
+        let (_, seat_rotation, _) = seat_transform.to_scale_rotation_translation();
+        let up = world_up.for_controller(controller_up);
+        rotation.0 = yaw_rotation(yaw_of(seat_rotation.adjust_precision(), up), up);
+
+        commands.entity(character).insert((
+            Seated { seat },
+            ColliderDisabled,
+            LinearVelocity::ZERO,
+        ));
+    }
+
+    for &StandUp { character } in stand_reader.read() {
+        let Ok((mut position, _, Some(seated), _)) = characters.get_mut(character) else {
+            continue;
+        };
+
+        if let Ok((seat, seat_transform)) = seats.get(seated.seat) {
+            position.0 = seat_transform
+                .transform_point(seat.exit_offset.f32())
+                .adjust_precision();
+        }
+
+        commands
+            .entity(character)
+            .remove::<(Seated, ColliderDisabled)>();
+    }
+}
+
+/// Keeps seated character controllers anchored to their seats.
+pub(crate) fn anchor_seated(
+    mut characters: Query<(&Seated, &mut Position, &mut LinearVelocity)>,
+    seats: Query<(&Seat, &GlobalTransform)>,
+) {
+    for (seated, mut position, mut linear_velocity) in &mut characters {
+        let Ok((seat, seat_transform)) = seats.get(seated.seat) else {
+            continue;
+        };
+
+        position.0 = seat_transform
+            .transform_point(seat.anchor.f32())
+            .adjust_precision();
+        linear_velocity.0 = Vector::ZERO;
+    }
+}
+
+/// Makes seated characters stand up when their seat is despawned.
+pub(crate) fn stand_up_from_missing_seats(
+    mut stand_writer: MessageWriter<StandUp>,
+    characters: Query<(Entity, &Seated)>,
+    seats: Query<(), With<Seat>>,
+) {
+    for (entity, seated) in &characters {
+        if !seats.contains(seated.seat) {
+            stand_writer.write(StandUp { character: entity });
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Stackable speed modifiers for character controllers.
+//!
+//! Gameplay systems insert tagged multipliers and additives into [`SpeedModifiers`],
+//! for example a slow debuff, a haste buff, or the weight of a carried object.
+//! Modifiers are composed deterministically before the movement system applies acceleration:
+//! all multipliers are multiplied together, and all additives are summed.
+
+use std::collections::BTreeMap;
+
+use avian3d::math::*;
+use bevy::prelude::*;
+
+/// A single speed modifier.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpeedModifier {
+    /// The factor the movement acceleration is multiplied by.
+    pub multiplier: Scalar,
+    /// The amount added to the movement acceleration, after multipliers.
+    pub additive: Scalar,
+    /// The remaining duration of the modifier in seconds, or `None` if it doesn't expire.
+    pub remaining: Option<Scalar>,
+}
+
+impl SpeedModifier {
+    /// A modifier that multiplies the movement acceleration.
+    pub const fn multiplier(multiplier: Scalar) -> Self {
+        Self {
+            multiplier,
+            additive: 0.0,
+            remaining: None,
+        }
+    }
+
+    /// A modifier that adds to the movement acceleration.
+    pub const fn additive(additive: Scalar) -> Self {
+        Self {
+            multiplier: 1.0,
+            additive,
+            remaining: None,
+        }
+    }
+
+    /// Makes the modifier expire after the given duration in seconds.
+    pub const fn with_duration(mut self, duration: Scalar) -> Self {
+        self.remaining = Some(duration);
+        self
+    }
+}
+
+/// A component holding tagged [`SpeedModifier`]s for a character controller.
+///
+/// Inserting a modifier with an existing tag replaces it, so reapplying a buff
+/// refreshes it instead of stacking it.
+#[derive(Component, Clone, Debug, Default)]
+pub struct SpeedModifiers {
+    // A sorted map keeps the composition order deterministic.
+    modifiers: BTreeMap<String, SpeedModifier>,
+}
+
+impl SpeedModifiers {
+    /// Inserts a modifier with the given tag, replacing any existing modifier with the same tag.
+    pub fn insert(&mut self, tag: impl Into<String>, modifier: SpeedModifier) {
+        self.modifiers.insert(tag.into(), modifier);
+    }
+
+    /// Removes the modifier with the given tag, returning it if it existed.
+    pub fn remove(&mut self, tag: &str) -> Option<SpeedModifier> {
+        self.modifiers.remove(tag)
+    }
+
+    /// Returns the modifier with the given tag.
+    pub fn get(&self, tag: &str) -> Option<&SpeedModifier> {
+        self.modifiers.get(tag)
+    }
+
+    /// Returns `true` if a modifier with the given tag exists.
+    pub fn contains(&self, tag: &str) -> bool {
+        self.modifiers.contains_key(tag)
+    }
+
+    /// Removes all modifiers.
+    pub fn clear(&mut self) {
+        self.modifiers.clear();
+    }
+
+    /// Returns an iterator over the tags and modifiers, sorted by tag.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &SpeedModifier)> {
+        self.modifiers
+            .iter()
+            .map(|(tag, modifier)| (tag.as_str(), modifier))
+    }
+
+    /// Returns the product of all multipliers and the sum of all additives.
+    pub fn combined(&self) -> (Scalar, Scalar) {
+        self.modifiers
+            .values()
+            .fold((1.0, 0.0), |(multiplier, additive), modifier| {
+                (
+                    multiplier * modifier.multiplier,
+                    additive + modifier.additive,
+                )
+            })
+    }
+
+    /// Applies all modifiers to the given base value.
+    pub fn apply(&self, base: Scalar) -> Scalar {
+        let (multiplier, additive) = self.combined();
+        (base * multiplier + additive).max(0.0)
+    }
+}
+
+/// Counts down the durations of [`SpeedModifiers`] and removes expired modifiers.
+pub(crate) fn tick_speed_modifiers(
+    time: Res<Time<Virtual>>,
+    mut query: Query<&mut SpeedModifiers>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for mut speed_modifiers in &mut query {
+        // Avoid triggering change detection when nothing expires.
+        if speed_modifiers
+            .modifiers
+            .values()
+            .all(|modifier| modifier.remaining.is_none())
+        {
+            continue;
+        }
+
+        speed_modifiers.modifiers.retain(|_, modifier| {
+            let Some(remaining) = &mut modifier.remaining else {
+                return true;
+            };
+            *remaining -= delta_time;
+            *remaining > 0.0
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modifiers_stack_multipliers_then_additives() {
+        let mut modifiers = SpeedModifiers::default();
+        modifiers.insert("slow", SpeedModifier::multiplier(0.5));
+        modifiers.insert("haste", SpeedModifier::multiplier(1.5));
+        modifiers.insert("boost", SpeedModifier::additive(2.0));
+        modifiers.insert("weight", SpeedModifier::additive(-1.0));
+
+        assert_eq!(modifiers.combined(), (0.75, 1.0));
+        assert_eq!(modifiers.apply(10.0), 8.5);
+    }
+
+    #[test]
+    fn inserting_an_existing_tag_replaces_it() {
+        let mut modifiers = SpeedModifiers::default();
+        modifiers.insert("slow", SpeedModifier::multiplier(0.5));
+        modifiers.insert("slow", SpeedModifier::multiplier(0.8));
+
+        assert_eq!(modifiers.combined(), (0.8, 0.0));
+        assert_eq!(modifiers.iter().count(), 1);
+    }
+
+    #[test]
+    fn applied_speed_is_never_negative() {
+        let mut modifiers = SpeedModifiers::default();
+        assert_eq!(modifiers.apply(10.0), 10.0);
+
+        modifiers.insert("stun", SpeedModifier::additive(-20.0));
+        assert_eq!(modifiers.apply(10.0), 0.0);
+    }
+}
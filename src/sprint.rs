@@ -0,0 +1,294 @@
+//! Walk and sprint speeds with optional stamina.
+//!
+//! Character controllers with [`MovementSpeeds`] are marked [`Sprinting`] while sprint is
+//! held, they move, and they stand, and move at the sprint speed instead of the walk speed.
+//! [`Stamina`] drains while sprinting and regenerates after a short delay. Once it runs out,
+//! the character can't sprint again until it has recovered enough stamina, so tapping sprint
+//! doesn't keep an exhausted character at full speed.
+//!
+//! Speeds are applied as a [`SpeedModifier`], so they compose with the acceleration,
+//! damping, and other modifiers of the controller.
+
+use avian3d::math::*;
+use bevy::prelude::*;
+
+use crate::{
+    CharacterController, ControllerEnabled, CurrentSurface, FpsController, GatheredMovementInput,
+    SpeedModifier, SpeedModifiers, Stance,
+};
+
+/// The tag of the [`SpeedModifier`] that speeds characters up while sprinting.
+pub const SPRINT_SPEED_MODIFIER: &str = "sprint";
+
+/// The walk, sprint, and crouch speeds of a character controller, in m/s.
+///
+/// The walk speed is the speed the [`MovementAcceleration`](crate::MovementAcceleration)
+/// and [`MovementDampingFactor`](crate::MovementDampingFactor) of the controller reach on
+/// their own. The other speeds scale the acceleration relative to it.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct MovementSpeeds {
+    pub walk: Scalar,
+    pub sprint: Scalar,
+    /// Replaces the crouching speed multiplier of the [`StanceConfig`](crate::StanceConfig).
+    pub crouch: Scalar,
+}
+
+impl Default for MovementSpeeds {
+    fn default() -> Self {
+        Self {
+            walk: 7.0,
+            sprint: 11.0,
+            crouch: 3.5,
+        }
+    }
+}
+
+impl MovementSpeeds {
+    /// Returns the acceleration multiplier for sprinting.
+    pub fn sprint_multiplier(&self) -> Scalar {
+        self.sprint / self.walk.max(Scalar::EPSILON)
+    }
+
+    /// Returns the acceleration multiplier for crouching.
+    pub fn crouch_multiplier(&self) -> Scalar {
+        self.crouch / self.walk.max(Scalar::EPSILON)
+    }
+}
+
+/// A marker component for character controllers that are sprinting.
+#[derive(Component, Clone, Copy, Debug, Default)]
+#[component(storage = "SparseSet")]
+pub struct Sprinting;
+
+/// Stamina that drains while a character controller is [`Sprinting`].
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Stamina {
+    pub max: Scalar,
+    pub current: Scalar,
+    /// The stamina used per second of sprinting.
+    pub drain_rate: Scalar,
+    /// The stamina regenerated per second while not sprinting.
+    pub regen_rate: Scalar,
+    /// The time after sprinting before stamina starts to regenerate, in seconds.
+    pub regen_delay: Scalar,
+    /// The stamina needed to sprint again after running out.
+    pub min_to_sprint: Scalar,
+    /// Whether the stamina ran out and hasn't recovered to `min_to_sprint` yet.
+    exhausted: bool,
+    /// The remaining time before stamina starts to regenerate.
+    regen_remaining: Scalar,
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Self::new(100.0)
+    }
+}
+
+impl Stamina {
+    /// Creates full stamina with the given maximum.
+    pub fn new(max: Scalar) -> Self {
+        Self {
+            max,
+            current: max,
+            drain_rate: 20.0,
+            regen_rate: 15.0,
+            regen_delay: 1.0,
+            min_to_sprint: max * 0.2,
+            exhausted: false,
+            regen_remaining: 0.0,
+        }
+    }
+
+    /// Sets the stamina used per second of sprinting and regenerated per second of rest.
+    pub fn with_rates(mut self, drain_rate: Scalar, regen_rate: Scalar) -> Self {
+        self.drain_rate = drain_rate;
+        self.regen_rate = regen_rate;
+        self
+    }
+
+    /// Sets the time after sprinting before stamina starts to regenerate, in seconds.
+    pub fn with_regen_delay(mut self, regen_delay: Scalar) -> Self {
+        self.regen_delay = regen_delay;
+        self
+    }
+
+    /// Returns the stamina as a fraction of the maximum, for stamina bars.
+    pub fn fraction(&self) -> Scalar {
+        (self.current / self.max.max(Scalar::EPSILON)).clamp(0.0, 1.0)
+    }
+
+    /// Returns `true` if the stamina ran out and hasn't recovered enough to sprint yet.
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    /// Returns `true` if there is enough stamina to sprint.
+    pub fn can_sprint(&self) -> bool {
+        !self.exhausted && self.current > 0.0
+    }
+
+    /// Drains or regenerates the stamina.
+    fn tick(&mut self, sprinting: bool, delta_time: Scalar) {
+        if sprinting {
+            self.current -= self.drain_rate * delta_time;
+            self.regen_remaining = self.regen_delay;
+            if self.current <= 0.0 {
+                self.current = 0.0;
+                self.exhausted = true;
+            }
+            return;
+        }
+
+        self.regen_remaining -= delta_time;
+        if self.regen_remaining <= 0.0 {
+            self.current = (self.current + self.regen_rate * delta_time).min(self.max);
+        }
+        if self.exhausted && self.current >= self.min_to_sprint {
+            self.exhausted = false;
+        }
+    }
+}
+
+/// Updates [`Sprinting`] from sprint input, drains and regenerates [`Stamina`], and applies
+/// the sprint speed of [`MovementSpeeds`].
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_sprinting(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    gathered: Res<GatheredMovementInput>,
+    mut controllers: Query<
+        (
+            Entity,
+            Option<&MovementSpeeds>,
+            Option<&mut Stamina>,
+            Option<&mut SpeedModifiers>,
+            Has<Sprinting>,
+            Option<&Stance>,
+            Option<&CurrentSurface>,
+            Option<&FpsController>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (
+        entity,
+        speeds,
+        stamina,
+        speed_modifiers,
+        was_sprinting,
+        stance,
+        surface,
+        fps_controller,
+    ) in &mut controllers
+    {
+        let input = gathered.get(entity, fps_controller);
+        let (sprint, moving) = (input.sprint, input.direction != Vector2::ZERO);
+
+        // Surfaces like mud don't allow sprinting
+        let sprinting = speeds.is_some()
+            && sprint
+            && moving
+            && stance.is_none_or(|stance| *stance == Stance::Standing)
+            && surface.is_none_or(CurrentSurface::can_sprint)
+            && stamina.as_ref().is_none_or(|stamina| stamina.can_sprint());
+
+        if let Some(mut stamina) = stamina {
+            stamina.tick(sprinting, delta_time);
+        }
+
+        match (sprinting, was_sprinting) {
+            (true, false) => {
+                commands.entity(entity).insert(Sprinting);
+            }
+            (false, true) => {
+                commands.entity(entity).remove::<Sprinting>();
+            }
+            _ => {}
+        }
+
+        let Some(speeds) = speeds else {
+            continue;
+        };
+        match speed_modifiers {
+            Some(mut modifiers) if sprinting => {
+                // Only write changes, so the modifiers aren't marked as changed every frame
+                let modifier = SpeedModifier::multiplier(speeds.sprint_multiplier());
+                if modifiers.get(SPRINT_SPEED_MODIFIER) != Some(&modifier) {
+                    modifiers.insert(SPRINT_SPEED_MODIFIER, modifier);
+                }
+            }
+            Some(mut modifiers) => {
+                if modifiers.contains(SPRINT_SPEED_MODIFIER) {
+                    modifiers.remove(SPRINT_SPEED_MODIFIER);
+                }
+            }
+            None if sprinting => {
+                let mut modifiers = SpeedModifiers::default();
+                let modifier = SpeedModifier::multiplier(speeds.sprint_multiplier());
+                modifiers.insert(SPRINT_SPEED_MODIFIER, modifier);
+                commands.entity(entity).insert(modifiers);
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sprinting_drains_until_exhausted() {
+        let mut stamina = Stamina::new(100.0).with_rates(40.0, 15.0);
+
+        stamina.tick(true, 1.0);
+        assert_eq!(stamina.current, 60.0);
+        assert!(stamina.can_sprint());
+
+        stamina.tick(true, 2.0);
+        assert_eq!(stamina.current, 0.0);
+        assert!(stamina.is_exhausted());
+        assert!(!stamina.can_sprint());
+    }
+
+    #[test]
+    fn regeneration_waits_for_the_delay() {
+        let mut stamina = Stamina::new(100.0).with_regen_delay(1.0);
+        stamina.tick(true, 1.0);
+        let drained = stamina.current;
+
+        stamina.tick(false, 0.5);
+        assert_eq!(stamina.current, drained);
+        stamina.tick(false, 0.5);
+        assert!(stamina.current > drained);
+    }
+
+    #[test]
+    fn exhaustion_ends_at_the_minimum_to_sprint() {
+        let mut stamina = Stamina::new(100.0)
+            .with_rates(200.0, 15.0)
+            .with_regen_delay(0.0);
+        stamina.tick(true, 1.0);
+        assert!(stamina.is_exhausted());
+
+        // 15 is below the minimum of 20.
+        stamina.tick(false, 1.0);
+        assert!(stamina.is_exhausted());
+        stamina.tick(false, 1.0);
+        assert!(!stamina.is_exhausted());
+        assert!(stamina.can_sprint());
+    }
+
+    #[test]
+    fn regeneration_stops_at_the_maximum() {
+        let mut stamina = Stamina::new(100.0).with_regen_delay(0.0);
+        stamina.current = 95.0;
+
+        stamina.tick(false, 1.0);
+        assert_eq!(stamina.current, 100.0);
+        assert_eq!(stamina.fraction(), 1.0);
+    }
+}
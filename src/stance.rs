@@ -0,0 +1,269 @@
+//! Stance handling (standing, crouching, prone) for character controllers.
+//!
+//! Changing stance smoothly resizes the capsule collider and the ground caster shape.
+//! The feet stay planted while the height changes, so the character neither pops
+//! into the ground nor hovers above it. Characters with a [`StanceConfig`] also get
+//! [`CeilingDetection`], so they stay low under geometry that is too low to stand up in, and
+//! move slower while crouching or prone.
+//!
+//! Crouching is one of the stances rather than a separate subsystem: the crouching height and
+//! speed multiplier are part of the [`StanceConfig`], and [`Crouching`] marks crouching
+//! characters for queries. The config isn't part of the [`MovementBundle`](crate::MovementBundle),
+//! since resizing the capsule needs its radius and standing height. Add one that matches the
+//! collider, like `StanceConfig::capsule(0.4, 1.0)` for `Collider::capsule(0.4, 1.0)`.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    CeilingContact, CeilingDetection, CharacterController, ControllerEnabled, FpsController,
+    GroundCastConfig, InCrawlVolume, MovementAction, MovementSpeeds, SpeedModifier, SpeedModifiers,
+};
+
+/// The tag of the [`SpeedModifier`] that slows characters down while crouching or prone.
+pub const STANCE_SPEED_MODIFIER: &str = "stance";
+
+/// The stance of a character controller.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Stance {
+    #[default]
+    Standing,
+    Crouching,
+    Prone,
+}
+
+/// Capsule dimensions for each [`Stance`], and how fast the collider
+/// transitions between them.
+///
+/// Heights are the total heights of the capsule, including both hemispheres.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(Stance, CeilingDetection)]
+pub struct StanceConfig {
+    pub radius: Scalar,
+    pub standing_height: Scalar,
+    pub crouching_height: Scalar,
+    pub prone_height: Scalar,
+    /// How fast the height changes, in units per second.
+    pub transition_speed: Scalar,
+    /// The movement speed multiplier while crouching.
+    pub crouching_speed_multiplier: Scalar,
+    /// The movement speed multiplier while prone.
+    pub prone_speed_multiplier: Scalar,
+}
+
+impl StanceConfig {
+    /// Creates a stance configuration for a capsule with the given radius and segment length,
+    /// matching the arguments of [`Collider::capsule`].
+    pub fn capsule(radius: Scalar, length: Scalar) -> Self {
+        let standing_height = length + radius * 2.0;
+        Self {
+            radius,
+            standing_height,
+            crouching_height: (standing_height * 0.6).max(radius * 2.0),
+            prone_height: radius * 2.0,
+            transition_speed: 4.0,
+            crouching_speed_multiplier: 0.5,
+            prone_speed_multiplier: 0.25,
+        }
+    }
+
+    pub fn with_crouching_height(mut self, height: Scalar) -> Self {
+        self.crouching_height = height.max(self.radius * 2.0);
+        self
+    }
+
+    pub fn with_prone_height(mut self, height: Scalar) -> Self {
+        self.prone_height = height.max(self.radius * 2.0);
+        self
+    }
+
+    pub fn with_transition_speed(mut self, speed: Scalar) -> Self {
+        self.transition_speed = speed;
+        self
+    }
+
+    /// Sets the movement speed multipliers while crouching and prone.
+    pub fn with_speed_multipliers(mut self, crouching: Scalar, prone: Scalar) -> Self {
+        self.crouching_speed_multiplier = crouching;
+        self.prone_speed_multiplier = prone;
+        self
+    }
+
+    /// Returns the capsule height for the given stance.
+    pub fn height(&self, stance: Stance) -> Scalar {
+        match stance {
+            Stance::Standing => self.standing_height,
+            Stance::Crouching => self.crouching_height,
+            Stance::Prone => self.prone_height,
+        }
+    }
+
+    /// Returns the movement speed multiplier for the given stance.
+    pub fn speed_multiplier(&self, stance: Stance) -> Scalar {
+        match stance {
+            Stance::Standing => 1.0,
+            Stance::Crouching => self.crouching_speed_multiplier,
+            Stance::Prone => self.prone_speed_multiplier,
+        }
+    }
+}
+
+/// A marker component for character controllers in the [`Stance::Crouching`] stance.
+///
+/// Kept in sync with the [`Stance`] by the stance system, for filtering queries.
+#[derive(Component, Clone, Copy, Debug, Default)]
+#[component(storage = "SparseSet")]
+pub struct Crouching;
+
+/// The current height of the capsule collider, interpolated toward
+/// the height of the current [`Stance`].
+///
+/// Inserted automatically the first time the stance system sees a [`StanceConfig`].
+#[derive(Component, Clone, Copy, Debug)]
+pub struct StanceHeight(pub Scalar);
+
+/// Builds the capsule collider for the given total height.
+pub(crate) fn capsule_for_height(radius: Scalar, height: Scalar) -> Collider {
+    Collider::capsule(radius, (height - radius * 2.0).max(0.0))
+}
+
+/// Updates the [`Stance`] of character controllers from [`MovementAction`] events,
+/// and resizes their colliders and ground casters toward the stance height.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_stance(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    mut movement_reader: MessageReader<MovementAction>,
+    mut controllers: Query<
+        (
+            Entity,
+            &StanceConfig,
+            &mut Stance,
+            Option<&mut StanceHeight>,
+            &mut Collider,
+            &mut ShapeCaster,
+            &mut Position,
+            &Rotation,
+            Option<&GroundCastConfig>,
+            Option<&CeilingContact>,
+            Option<&FpsController>,
+            Option<&InCrawlVolume>,
+            Option<&mut SpeedModifiers>,
+            Has<Crouching>,
+            Option<&MovementSpeeds>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    // Stance input is held, so the character stands up again
+    // as soon as no crouch or prone action is written.
+    let mut requested = Stance::Standing;
+    for event in movement_reader.read() {
+        match event {
+            MovementAction::Prone => requested = Stance::Prone,
+            MovementAction::Crouch if requested != Stance::Prone => requested = Stance::Crouching,
+            _ => {}
+        }
+    }
+
+    for (
+        entity,
+        config,
+        mut stance,
+        height,
+        mut collider,
+        mut ground_caster,
+        mut position,
+        rotation,
+        ground_cast_config,
+        ceiling,
+        fps_controller,
+        crawl_volume,
+        speed_modifiers,
+        is_crouching,
+        speeds,
+    ) in &mut controllers
+    {
+        // Crawl volumes don't let the character stand up
+        let requested =
+            crawl_volume.map_or(requested, |crawl_volume| crawl_volume.limit(requested));
+        if fps_controller.is_some_and(|controller| controller.enable_input) && *stance != requested
+        {
+            *stance = requested;
+        }
+
+        match (*stance == Stance::Crouching, is_crouching) {
+            (true, false) => {
+                commands.entity(entity).insert(Crouching);
+            }
+            (false, true) => {
+                commands.entity(entity).remove::<Crouching>();
+            }
+            _ => {}
+        }
+
+        // Crouching and prone characters move slower
+        let multiplier = match (*stance, speeds) {
+            (Stance::Crouching, Some(speeds)) => speeds.crouch_multiplier(),
+            (stance, _) => config.speed_multiplier(stance),
+        };
+        match speed_modifiers {
+            Some(mut modifiers) if multiplier == 1.0 => {
+                if modifiers.contains(STANCE_SPEED_MODIFIER) {
+                    modifiers.remove(STANCE_SPEED_MODIFIER);
+                }
+            }
+            Some(mut modifiers) => {
+                // Only write changes, so the modifiers aren't marked as changed every frame
+                let modifier = SpeedModifier::multiplier(multiplier);
+                if modifiers.get(STANCE_SPEED_MODIFIER) != Some(&modifier) {
+                    modifiers.insert(STANCE_SPEED_MODIFIER, modifier);
+                }
+            }
+            None if multiplier != 1.0 => {
+                let mut modifiers = SpeedModifiers::default();
+                modifiers.insert(STANCE_SPEED_MODIFIER, SpeedModifier::multiplier(multiplier));
+                commands.entity(entity).insert(modifiers);
+            }
+            None => {}
+        }
+
+        let Some(mut height) = height else {
+            commands
+                .entity(entity)
+                .insert(StanceHeight(config.standing_height));
+            continue;
+        };
+
+        let target = config.height(*stance);
+        if height.0 == target {
+            continue;
+        }
+
+        let max_step = config.transition_speed * delta_time;
+        let mut new_height = height.0 + (target - height.0).clamp(-max_step, max_step);
+
+        // Don't grow into the ceiling. The character stays low until there is room to stand up.
+        if let Some(ceiling) = ceiling {
+            new_height = new_height.min(height.0 + ceiling.distance.max(0.0));
+        }
+        if new_height == height.0 {
+            continue;
+        }
+
+        // Keep the feet planted by moving the center by half of the height change.
+        let up = rotation * Vector::Y;
+        position.0 += up * (new_height - height.0) * 0.5;
+        height.0 = new_height;
+
+        *collider = capsule_for_height(config.radius, new_height);
+
+        ground_caster.shape = ground_cast_config
+            .copied()
+            .unwrap_or_default()
+            .caster_shape(&collider);
+    }
+}
@@ -0,0 +1,110 @@
+//! Movement statistics for achievements, analytics, and end-of-level summaries.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{CharacterController, ControllerEnabled, ControllerUp, Grounded, WorldUp};
+
+/// Accumulates movement statistics for a character controller.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct MovementStats {
+    /// The total distance traveled.
+    pub distance: Scalar,
+    /// The number of jumps.
+    pub jumps: u32,
+    /// The total time spent airborne, in seconds.
+    pub airtime: Scalar,
+    /// The longest single time spent airborne, in seconds.
+    pub longest_airtime: Scalar,
+    /// The highest speed reached.
+    pub top_speed: Scalar,
+    /// The number of landings at or above [`MovementStats::fall_speed_threshold`].
+    pub falls: u32,
+    /// The downward speed at which a landing counts as a fall.
+    pub fall_speed_threshold: Scalar,
+    previous_position: Option<Vector>,
+    was_grounded: bool,
+    current_airtime: Scalar,
+    fall_speed: Scalar,
+}
+
+impl Default for MovementStats {
+    fn default() -> Self {
+        Self {
+            distance: 0.0,
+            jumps: 0,
+            airtime: 0.0,
+            longest_airtime: 0.0,
+            top_speed: 0.0,
+            falls: 0,
+            fall_speed_threshold: 10.0,
+            previous_position: None,
+            was_grounded: true,
+            current_airtime: 0.0,
+            fall_speed: 0.0,
+        }
+    }
+}
+
+impl MovementStats {
+    /// Resets all statistics, keeping the configuration.
+    pub fn reset(&mut self) {
+        *self = Self {
+            fall_speed_threshold: self.fall_speed_threshold,
+            ..default()
+        };
+    }
+}
+
+/// Updates the [`MovementStats`] of character controllers.
+#[allow(clippy::type_complexity)]
+pub(crate) fn track_movement_stats(
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    mut query: Query<
+        (
+            &mut MovementStats,
+            &Position,
+            &LinearVelocity,
+            Has<Grounded>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (mut stats, position, linear_velocity, is_grounded, controller_up) in &mut query {
+        let vertical_speed = linear_velocity.dot(world_up.for_controller(controller_up));
+        if let Some(previous_position) = stats.previous_position {
+            stats.distance += position.distance(previous_position);
+        }
+        stats.previous_position = Some(position.0);
+        stats.top_speed = stats.top_speed.max(linear_velocity.length());
+
+        match (stats.was_grounded, is_grounded) {
+            // Leaving the ground with upward velocity is a jump.
+            (true, false) => {
+                if vertical_speed > 0.0 {
+                    stats.jumps += 1;
+                }
+                stats.current_airtime = delta_time;
+            }
+            (false, false) => {
+                stats.current_airtime += delta_time;
+                stats.fall_speed = (-vertical_speed).max(0.0);
+            }
+            (false, true) => {
+                stats.airtime += stats.current_airtime;
+                stats.longest_airtime = stats.longest_airtime.max(stats.current_airtime);
+                if stats.fall_speed >= stats.fall_speed_threshold {
+                    stats.falls += 1;
+                }
+                stats.current_airtime = 0.0;
+                stats.fall_speed = 0.0;
+            }
+            (true, true) => {}
+        }
+        stats.was_grounded = is_grounded;
+    }
+}
@@ -0,0 +1,237 @@
+//! Visual effect hooks for landings, slides, and skids.
+//!
+//! Character controllers with [`SurfaceEffects`] write [`SurfaceEffect`] messages with the
+//! position, surface normal, [`SurfaceMaterial`], and intensity of landings, slides down
+//! walls and steep slopes, and skids from sharp stops on the ground. They are meant for
+//! particle systems, like dust puffs and skid marks, and are separate from the
+//! [`Footstep`](crate::Footstep) and [`Landed`](crate::Landed) messages used for audio, so
+//! effects can be added without touching the movement systems.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    CharacterController, ControllerEnabled, ControllerUp, Grounded, MaxSlopeAngle, SurfaceMaterial,
+    WallSliding, WorldUp,
+};
+
+/// The kind of a [`SurfaceEffect`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SurfaceEffectKind {
+    /// The character landed after being airborne.
+    Landing,
+    /// The character is sliding down a wall or a slope that is too steep to stand on.
+    Slide,
+    /// The character is stopping or turning sharply on the ground.
+    Skid,
+}
+
+/// A [`Message`] for spawning visual effects where a [`SurfaceEffects`] character touches a
+/// surface.
+///
+/// Slides and skids are written repeatedly while they last, at the
+/// [`SurfaceEffects::interval`].
+#[derive(Message, Clone, Debug)]
+pub struct SurfaceEffect {
+    pub entity: Entity,
+    pub kind: SurfaceEffectKind,
+    /// The contact point on the surface.
+    pub position: Vector,
+    /// The normal of the surface at the contact point.
+    pub normal: Vector,
+    /// The collider entity of the surface.
+    pub surface: Entity,
+    /// The material of the surface, if it has a [`SurfaceMaterial`].
+    pub material: Option<SurfaceMaterial>,
+    /// The strength of the effect, from 0 to 1.
+    pub intensity: Scalar,
+}
+
+/// Enables [`SurfaceEffect`] messages for a character controller.
+///
+/// Each effect starts at a minimum speed and reaches full intensity at a maximum speed.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(SurfaceEffectsState)]
+pub struct SurfaceEffects {
+    /// The fall speeds of the weakest and strongest landings, in m/s.
+    pub landing_speed: (Scalar, Scalar),
+    /// The speeds of the weakest and strongest slides, in m/s.
+    pub slide_speed: (Scalar, Scalar),
+    /// The ground decelerations of the weakest and strongest skids, in m/s².
+    pub skid_deceleration: (Scalar, Scalar),
+    /// The minimum ground speed for skids, in m/s.
+    pub min_skid_speed: Scalar,
+    /// The time between repeated slide and skid effects, in seconds.
+    pub interval: Scalar,
+}
+
+impl Default for SurfaceEffects {
+    fn default() -> Self {
+        Self {
+            landing_speed: (2.0, 15.0),
+            slide_speed: (1.0, 10.0),
+            skid_deceleration: (20.0, 60.0),
+            min_skid_speed: 3.0,
+            interval: 0.1,
+        }
+    }
+}
+
+/// Returns the intensity of a value between the minimum and maximum of a range, or `None`
+/// below the minimum.
+fn intensity(value: Scalar, (min, max): (Scalar, Scalar)) -> Option<Scalar> {
+    if value < min {
+        return None;
+    }
+    Some(((value - min) / (max - min).max(Scalar::EPSILON)).clamp(0.0, 1.0))
+}
+
+/// Tracks the airborne state and ground speed of a [`SurfaceEffects`] character.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct SurfaceEffectsState {
+    /// Whether the character was grounded last frame.
+    was_grounded: bool,
+    /// The downward speed of the character while it was airborne.
+    fall_speed: Scalar,
+    /// The speed along the ground last frame.
+    ground_speed: Scalar,
+    /// The time until the next slide or skid effect.
+    cooldown: Scalar,
+}
+
+/// Writes [`SurfaceEffect`] messages for [`SurfaceEffects`] characters.
+#[allow(clippy::type_complexity)]
+pub(crate) fn emit_surface_effects(
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    mut effect_writer: MessageWriter<SurfaceEffect>,
+    mut controllers: Query<
+        (
+            Entity,
+            &SurfaceEffects,
+            &mut SurfaceEffectsState,
+            &Position,
+            &Rotation,
+            &LinearVelocity,
+            &ColliderAabb,
+            Option<&ShapeHits>,
+            Has<Grounded>,
+            Option<&WallSliding>,
+            Option<&MaxSlopeAngle>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+    materials: Query<&SurfaceMaterial>,
+    collider_rbs: Query<&ColliderOf>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+    if delta_time <= 0.0 {
+        return;
+    }
+
+    // Materials can be set on the collider or on its rigid body.
+    let get_material = |entity: Entity| {
+        materials
+            .get(entity)
+            .ok()
+            .or_else(|| {
+                collider_rbs
+                    .get(entity)
+                    .ok()
+                    .and_then(|collider_of| materials.get(collider_of.body).ok())
+            })
+            .cloned()
+    };
+
+    for (
+        entity,
+        effects,
+        mut state,
+        position,
+        rotation,
+        linear_velocity,
+        aabb,
+        hits,
+        is_grounded,
+        wall_sliding,
+        max_slope_angle,
+        controller_up,
+    ) in &mut controllers
+    {
+        let up = world_up.for_controller(controller_up);
+        let was_grounded = state.was_grounded;
+        state.was_grounded = is_grounded;
+        state.cooldown -= delta_time;
+
+        // The closest ground contact, with its world-space normal
+        let ground_hit = hits.and_then(|hits| hits.iter().next());
+        let ground = ground_hit.map(|hit| (hit.entity, hit.point1, rotation * -hit.normal2));
+
+        let mut write = |kind, (surface, position, normal): (Entity, Vector, Vector), intensity| {
+            effect_writer.write(SurfaceEffect {
+                entity,
+                kind,
+                position,
+                normal,
+                surface,
+                material: get_material(surface),
+                intensity,
+            });
+        };
+
+        let ground_velocity = linear_velocity.reject_from_normalized(up);
+        let ground_speed = ground_velocity.length();
+        let previous_ground_speed = state.ground_speed;
+        state.ground_speed = ground_speed;
+
+        if !is_grounded {
+            state.fall_speed = (-linear_velocity.dot(up)).max(0.0);
+
+            // Sliding down a wall, or along ground that is too steep to stand on
+            let slide = match (wall_sliding, ground) {
+                (Some(sliding), _) => {
+                    let half_extents = (aabb.max - aabb.min) * 0.5;
+                    let contact =
+                        position.0 - sliding.normal * half_extents.dot(sliding.normal.abs());
+                    Some((sliding.wall, contact, sliding.normal))
+                }
+                // Touching ground that is too steep, not just hovering above it after a jump
+                (None, Some(ground)) => {
+                    let max_slope = max_slope_angle.map_or(PI * 0.25, |angle| angle.0);
+                    let touching = ground_hit.is_some_and(|hit| hit.distance <= 0.05);
+                    let steep = ground.2.angle_between(up) > max_slope;
+                    (touching && steep && ground.2.dot(up) > 0.0).then_some(ground)
+                }
+                _ => None,
+            };
+            if let Some(slide) = slide.filter(|_| state.cooldown <= 0.0) {
+                if let Some(intensity) = intensity(linear_velocity.length(), effects.slide_speed) {
+                    write(SurfaceEffectKind::Slide, slide, intensity);
+                    state.cooldown = effects.interval;
+                }
+            }
+            continue;
+        }
+        let Some(ground) = ground else {
+            continue;
+        };
+
+        if !was_grounded {
+            if let Some(intensity) = intensity(state.fall_speed, effects.landing_speed) {
+                write(SurfaceEffectKind::Landing, ground, intensity);
+            }
+            state.fall_speed = 0.0;
+            continue;
+        }
+
+        // Skidding when slowing down sharply at speed
+        let deceleration = (previous_ground_speed - ground_speed) / delta_time;
+        if previous_ground_speed >= effects.min_skid_speed && state.cooldown <= 0.0 {
+            if let Some(intensity) = intensity(deceleration, effects.skid_deceleration) {
+                write(SurfaceEffectKind::Skid, ground, intensity);
+                state.cooldown = effects.interval;
+            }
+        }
+    }
+}
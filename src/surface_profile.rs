@@ -0,0 +1,245 @@
+//! Movement settings per surface, like mud, sand, ice, and metal grates.
+//!
+//! A [`SurfaceProfileMap`] asset maps [`SurfaceMaterial`]s to [`SurfaceProfile`]s, so
+//! movement and feedback change consistently from one data file. Character controllers with
+//! [`SurfaceProfiles`] look up the profile of the ground under them every frame and keep it
+//! in their [`CurrentSurface`]. The profile scales the movement acceleration, can override the
+//! damping on the ground, picks the footstep sounds, and can forbid sprinting.
+//!
+//! With the `serde` feature, maps are loaded from `.surfaces.ron` files:
+//!
+//! ```ron
+//! (
+//!     surfaces: {
+//!         "mud": (acceleration_multiplier: 0.6, damping: Some(0.8), footsteps: Some("mud"), can_sprint: false),
+//!         "ice": (acceleration_multiplier: 0.3, damping: Some(0.99)),
+//!     },
+//! )
+//! ```
+
+use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use std::fmt;
+
+use avian3d::{math::*, prelude::*};
+#[cfg(feature = "serde")]
+use bevy::asset::{io::Reader, AssetLoader, LoadContext};
+use bevy::prelude::*;
+
+use crate::{
+    CharacterController, ControllerEnabled, Grounded, SpeedModifier, SpeedModifiers,
+    SurfaceMaterial,
+};
+
+/// The tag of the [`SpeedModifier`] applied by the [`SurfaceProfile`] of the ground.
+pub const SURFACE_SPEED_MODIFIER: &str = "surface";
+
+/// The movement settings of a surface.
+#[derive(Clone, Debug, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SurfaceProfile {
+    /// The factor the movement acceleration is multiplied by on the surface.
+    pub acceleration_multiplier: Scalar,
+    /// The [`MovementDampingFactor`](crate::MovementDampingFactor) used on the surface, or
+    /// `None` to keep the damping of the character.
+    pub damping: Option<Scalar>,
+    /// The name of the footstep sounds played on the surface, or `None` to use the name of
+    /// the [`SurfaceMaterial`].
+    pub footsteps: Option<String>,
+    /// Whether the character can sprint on the surface.
+    pub can_sprint: bool,
+}
+
+impl Default for SurfaceProfile {
+    fn default() -> Self {
+        Self {
+            acceleration_multiplier: 1.0,
+            damping: None,
+            footsteps: None,
+            can_sprint: true,
+        }
+    }
+}
+
+/// An asset mapping [`SurfaceMaterial`]s to [`SurfaceProfile`]s.
+#[derive(Asset, Clone, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SurfaceProfileMap {
+    /// Profiles keyed by the name of the [`SurfaceMaterial`].
+    pub surfaces: HashMap<String, SurfaceProfile>,
+    /// The profile used for surfaces without a material, or with a material that isn't in
+    /// the map.
+    pub fallback: SurfaceProfile,
+}
+
+impl SurfaceProfileMap {
+    /// Returns the profile for the given material.
+    pub fn get(&self, material: Option<&SurfaceMaterial>) -> &SurfaceProfile {
+        material
+            .and_then(|material| self.surfaces.get(&material.0))
+            .unwrap_or(&self.fallback)
+    }
+}
+
+/// Applies the [`SurfaceProfile`]s of a [`SurfaceProfileMap`] to a character controller.
+#[derive(Component, Clone, Debug)]
+#[require(CurrentSurface)]
+pub struct SurfaceProfiles {
+    pub map: Handle<SurfaceProfileMap>,
+}
+
+impl SurfaceProfiles {
+    /// Creates surface profiles using the given [`SurfaceProfileMap`].
+    pub fn new(map: Handle<SurfaceProfileMap>) -> Self {
+        Self { map }
+    }
+}
+
+/// The surface a [`SurfaceProfiles`] character is standing on, and its profile.
+///
+/// The profile is the default one while the character is airborne.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct CurrentSurface {
+    /// The material of the ground, if it has a [`SurfaceMaterial`].
+    pub material: Option<SurfaceMaterial>,
+    pub profile: SurfaceProfile,
+}
+
+impl CurrentSurface {
+    /// Returns whether the character can sprint on the surface, for sprint systems.
+    pub fn can_sprint(&self) -> bool {
+        self.profile.can_sprint
+    }
+}
+
+/// An error when loading a [`SurfaceProfileMap`].
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum SurfaceProfileMapError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for SurfaceProfileMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "could not read surface profiles: {error}"),
+            Self::Ron(error) => write!(f, "invalid surface profiles: {error}"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl std::error::Error for SurfaceProfileMapError {}
+
+/// Loads [`SurfaceProfileMap`]s from `.surfaces.ron` files.
+#[cfg(feature = "serde")]
+#[derive(Default)]
+pub struct SurfaceProfileMapLoader;
+
+#[cfg(feature = "serde")]
+impl AssetLoader for SurfaceProfileMapLoader {
+    type Asset = SurfaceProfileMap;
+    type Settings = ();
+    type Error = SurfaceProfileMapError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(SurfaceProfileMapError::Io)?;
+        ron::de::from_bytes(&bytes).map_err(SurfaceProfileMapError::Ron)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["surfaces.ron"]
+    }
+}
+
+/// Updates the [`CurrentSurface`] of [`SurfaceProfiles`] characters from the ground under
+/// them, and applies the acceleration multiplier of its profile.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_current_surface(
+    mut commands: Commands,
+    profile_maps: Res<Assets<SurfaceProfileMap>>,
+    mut controllers: Query<
+        (
+            Entity,
+            &SurfaceProfiles,
+            &mut CurrentSurface,
+            &ShapeHits,
+            Has<Grounded>,
+            Option<&mut SpeedModifiers>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+    materials: Query<&SurfaceMaterial>,
+    collider_rbs: Query<&ColliderOf>,
+) {
+    // Materials can be set on the collider or on its rigid body.
+    let get_material = |entity: Entity| {
+        materials
+            .get(entity)
+            .ok()
+            .or_else(|| {
+                collider_rbs
+                    .get(entity)
+                    .ok()
+                    .and_then(|collider_of| materials.get(collider_of.body).ok())
+            })
+            .cloned()
+    };
+
+    for (entity, profiles, mut current, hits, is_grounded, speed_modifiers) in &mut controllers {
+        let Some(map) = profile_maps.get(&profiles.map) else {
+            continue;
+        };
+
+        let surface = match hits.iter().next().filter(|_| is_grounded) {
+            Some(hit) => {
+                let material = get_material(hit.entity);
+                CurrentSurface {
+                    profile: map.get(material.as_ref()).clone(),
+                    material,
+                }
+            }
+            None => CurrentSurface::default(),
+        };
+        current.set_if_neq(surface);
+
+        let multiplier = current.profile.acceleration_multiplier;
+        match speed_modifiers {
+            Some(mut modifiers) if multiplier == 1.0 => {
+                if modifiers.contains(SURFACE_SPEED_MODIFIER) {
+                    modifiers.remove(SURFACE_SPEED_MODIFIER);
+                }
+            }
+            Some(mut modifiers) => {
+                if modifiers.get(SURFACE_SPEED_MODIFIER).map(|m| m.multiplier) != Some(multiplier) {
+                    modifiers.insert(
+                        SURFACE_SPEED_MODIFIER,
+                        SpeedModifier::multiplier(multiplier),
+                    );
+                }
+            }
+            None if multiplier == 1.0 => {}
+            None => {
+                let mut modifiers = SpeedModifiers::default();
+                modifiers.insert(
+                    SURFACE_SPEED_MODIFIER,
+                    SpeedModifier::multiplier(multiplier),
+                );
+                commands.entity(entity).insert(modifiers);
+            }
+        }
+    }
+}
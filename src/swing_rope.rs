@@ -0,0 +1,207 @@
+//! Rope and vine swinging for character controllers.
+//!
+//! [`SwingRope`]s are level elements hanging from their anchor entity. Airborne characters
+//! with a [`RopeSwinger`] grab a rope when they get close to it, and while [`Swinging`], the
+//! regular movement systems are skipped: the character swings as a pendulum around the
+//! anchor, movement input pumps the swing along its direction, and jumping lets go while
+//! keeping the velocity of the swing.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    input_rotation, CharacterController, ControllerEnabled, ControllerGravity, ControllerUp,
+    FpsController, GatheredMovementInput, Grounded, Jumped, MovementInput, Noclip, Ragdoll,
+    ViewYaw, WorldUp,
+};
+
+/// A rope or vine hanging from the origin of its entity, that [`RopeSwinger`] characters
+/// can swing on.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct SwingRope {
+    /// The length of the rope below the anchor.
+    pub length: Scalar,
+    /// How close the character origin must get to the rope to grab it.
+    pub grab_radius: Scalar,
+}
+
+impl SwingRope {
+    /// Creates a rope with the given length.
+    pub fn new(length: Scalar) -> Self {
+        Self {
+            length,
+            grab_radius: 0.6,
+        }
+    }
+
+    /// Sets how close the character origin must get to the rope to grab it.
+    pub fn with_grab_radius(mut self, grab_radius: Scalar) -> Self {
+        self.grab_radius = grab_radius;
+        self
+    }
+}
+
+/// Lets a character controller grab [`SwingRope`]s.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct RopeSwinger {
+    /// The acceleration from full movement input along the swing, in m/s².
+    pub pump_acceleration: Scalar,
+    /// The upward speed added when jumping off a rope.
+    pub jump_up_speed: Scalar,
+    /// The time after letting go before a rope can be grabbed again, in seconds.
+    pub regrab_delay: Scalar,
+    /// The remaining time before a rope can be grabbed again.
+    regrab_remaining: Scalar,
+}
+
+impl Default for RopeSwinger {
+    fn default() -> Self {
+        Self {
+            pump_acceleration: 4.0,
+            jump_up_speed: 3.0,
+            regrab_delay: 0.4,
+            regrab_remaining: 0.0,
+        }
+    }
+}
+
+/// A component on a character controller that is swinging on a rope.
+///
+/// The regular movement systems skip swinging characters.
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct Swinging {
+    /// The [`SwingRope`] entity.
+    pub rope: Entity,
+    /// The distance between the anchor and the character origin.
+    pub length: Scalar,
+}
+
+/// Makes airborne [`RopeSwinger`] characters grab [`SwingRope`]s close to them.
+#[allow(clippy::type_complexity)]
+pub(crate) fn grab_swing_ropes(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    mut characters: Query<
+        (
+            Entity,
+            &mut RopeSwinger,
+            &Position,
+            Has<Grounded>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+    ropes: Query<(Entity, &SwingRope, &GlobalTransform)>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (entity, mut swinger, position, is_grounded, controller_up) in &mut characters {
+        let up = world_up.for_controller(controller_up);
+        swinger.regrab_remaining = (swinger.regrab_remaining - delta_time).max(0.0);
+        if is_grounded || swinger.regrab_remaining > 0.0 {
+            continue;
+        }
+
+        for (rope_entity, rope, transform) in &ropes {
+            let anchor = transform.translation().adjust_precision();
+
+            // The closest point on the rope, which hangs straight down while nobody holds it
+            let below_anchor = (anchor - position.0).dot(up).clamp(0.0, rope.length);
+            let closest = anchor - up * below_anchor;
+            if closest.distance(position.0) > rope.grab_radius {
+                continue;
+            }
+
+            commands.entity(entity).insert(Swinging {
+                rope: rope_entity,
+                length: anchor.distance(position.0).min(rope.length),
+            });
+            break;
+        }
+    }
+}
+
+/// Swings [`Swinging`] characters around the anchors of their ropes, and lets them go.
+#[allow(clippy::type_complexity)]
+pub(crate) fn swing_on_ropes(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    gathered: Res<GatheredMovementInput>,
+    mut characters: Query<
+        (
+            Entity,
+            &mut RopeSwinger,
+            &Swinging,
+            &mut Position,
+            &mut LinearVelocity,
+            &Rotation,
+            &ControllerGravity,
+            Option<&FpsController>,
+            Option<&ViewYaw>,
+            Option<&ControllerUp>,
+        ),
+        (Without<Ragdoll>, Without<Noclip>),
+    >,
+    ropes: Query<&GlobalTransform, With<SwingRope>>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (
+        entity,
+        mut swinger,
+        swinging,
+        mut position,
+        mut linear_velocity,
+        rotation,
+        gravity,
+        fps_controller,
+        view_yaw,
+        controller_up,
+    ) in &mut characters
+    {
+        let up = world_up.for_controller(controller_up);
+        let Ok(transform) = ropes.get(swinging.rope) else {
+            // The rope was despawned.
+            commands.entity(entity).remove::<Swinging>();
+            continue;
+        };
+        let anchor = transform.translation().adjust_precision();
+
+        let MovementInput {
+            direction: input,
+            jump,
+            ..
+        } = gathered.get(entity, fps_controller);
+
+        if jump {
+            // Let go, keeping the velocity of the swing.
+            linear_velocity.0 += up * swinger.jump_up_speed;
+            swinger.regrab_remaining = swinger.regrab_delay;
+            commands.entity(entity).remove::<Swinging>();
+            commands.trigger(Jumped { entity });
+            continue;
+        }
+
+        let offset = position.0 - anchor;
+        let Some(radial) = offset.try_normalize() else {
+            continue;
+        };
+
+        // Gravity drives the pendulum, and movement input pumps it along the swing.
+        let pump = (input_rotation(rotation, view_yaw) * Vector::new(input.x, 0.0, -input.y))
+            .reject_from_normalized(up)
+            .reject_from_normalized(radial);
+        linear_velocity.0 += (gravity.0 + pump * swinger.pump_acceleration) * delta_time;
+
+        // The rope can only pull, so a taut rope removes the outward velocity.
+        let distance = offset.length();
+        if distance >= swinging.length {
+            let outward = linear_velocity.dot(radial).max(0.0);
+            linear_velocity.0 -= radial * outward;
+            position.0 = anchor + radial * swinging.length;
+        }
+    }
+}
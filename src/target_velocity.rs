@@ -0,0 +1,157 @@
+//! An alternative movement model that moves toward a target velocity.
+//!
+//! The default model adds [`MovementAcceleration`](crate::MovementAcceleration) every
+//! frame and relies on [`MovementDampingFactor`](crate::MovementDampingFactor) to limit
+//! speed, so the top speed depends on both in unintuitive ways. With [`TargetVelocity`],
+//! the input sets a target velocity instead, and the character accelerates toward it and
+//! decelerates when there's no input, at configurable rates. The top speed is exactly
+//! [`TargetVelocity::max_speed`], which makes the movement easy to tune and clamp.
+//!
+//! Controllers with [`TargetVelocity`] ignore the acceleration and damping components.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    ground_normal, input_rotation, AvoidLedges, CharacterController, ControllerEnabled,
+    ControllerUp, CustomMovementBasis, ExternalVelocity, FpsController, GatheredMovementInput,
+    Grounded, SpeedModifiers, ViewYaw, WorldUp,
+};
+
+/// Moves a character controller toward a target velocity set by the movement input.
+///
+/// This is a movement basis, so it replaces the built-in walking.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(CustomMovementBasis)]
+pub struct TargetVelocity {
+    /// The speed at full input, in m/s. Scaled by [`SpeedModifiers`].
+    pub max_speed: Scalar,
+    /// The rate at which the character speeds up and turns toward the target, in m/s².
+    pub acceleration: Scalar,
+    /// The rate at which the character slows down without input, in m/s².
+    pub deceleration: Scalar,
+    /// The fraction of the acceleration and deceleration available while airborne.
+    pub air_control: Scalar,
+}
+
+impl Default for TargetVelocity {
+    fn default() -> Self {
+        Self {
+            max_speed: 6.0,
+            acceleration: 40.0,
+            deceleration: 50.0,
+            air_control: 0.3,
+        }
+    }
+}
+
+impl TargetVelocity {
+    /// Returns `current` moved toward `target` over the given time step.
+    pub fn step(
+        &self,
+        current: Vector,
+        target: Vector,
+        is_grounded: bool,
+        delta_time: Scalar,
+    ) -> Vector {
+        let rate = if target.length_squared() > 0.0 {
+            self.acceleration
+        } else {
+            self.deceleration
+        };
+        let control = if is_grounded { 1.0 } else { self.air_control };
+        let max_step = rate * control * delta_time;
+
+        let difference = target - current;
+        let distance = difference.length();
+        if distance <= max_step || distance <= Scalar::EPSILON {
+            target
+        } else {
+            current + difference / distance * max_step
+        }
+    }
+}
+
+/// Moves [`TargetVelocity`] character controllers toward the velocity set by their input.
+#[allow(clippy::type_complexity)]
+pub(crate) fn move_toward_target_velocity(
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    spatial_query: SpatialQuery,
+    gathered: Res<GatheredMovementInput>,
+    mut controllers: Query<
+        (
+            Entity,
+            &TargetVelocity,
+            &mut LinearVelocity,
+            &Rotation,
+            Has<Grounded>,
+            Option<&ShapeHits>,
+            Option<&FpsController>,
+            Option<&SpeedModifiers>,
+            Option<(&AvoidLedges, &Position, &ColliderAabb)>,
+            Option<&ExternalVelocity>,
+            Option<&ViewYaw>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (
+        entity,
+        target_velocity,
+        mut linear_velocity,
+        rotation,
+        is_grounded,
+        ground_hits,
+        fps_controller,
+        speed_modifiers,
+        ledge_avoidance,
+        external,
+        view_yaw,
+        controller_up,
+    ) in &mut controllers
+    {
+        let up = world_up.for_controller(controller_up);
+        let input = gathered.get(entity, fps_controller).direction;
+
+        let max_speed = speed_modifiers.map_or(target_velocity.max_speed, |modifiers| {
+            modifiers.apply(target_velocity.max_speed)
+        });
+        let direction = (input_rotation(rotation, view_yaw) * Vector::new(input.x, 0.0, -input.y))
+            .reject_from_normalized(up);
+        let mut target = direction * max_speed;
+
+        // Don't walk off drops that are too tall
+        if let Some((avoid_ledges, position, aabb)) = ledge_avoidance.filter(|_| is_grounded) {
+            if avoid_ledges.ledge_ahead(&spatial_query, entity, position, aabb, target, up) {
+                target = Vector::ZERO;
+            }
+        }
+
+        // Move along the ground plane when grounded, so slopes don't change the speed
+        let plane_normal = ground_hits
+            .filter(|_| is_grounded)
+            .and_then(|hits| ground_normal(hits, rotation, up))
+            .filter(|&normal| linear_velocity.dot(normal) <= 0.0)
+            .unwrap_or(up);
+        if plane_normal != up {
+            let length = target.length();
+            target = target
+                .reject_from_normalized(plane_normal)
+                .normalize_or_zero()
+                * length;
+        }
+
+        // External velocity decays at its own rate, so it isn't steered toward the target
+        let external = external.map_or(Vector::ZERO, ExternalVelocity::applied);
+        let velocity = linear_velocity.0 - external;
+
+        let planar = velocity.reject_from_normalized(plane_normal);
+        let normal = velocity - planar;
+        linear_velocity.0 =
+            normal + target_velocity.step(planar, target, is_grounded, delta_time) + external;
+    }
+}
@@ -0,0 +1,346 @@
+//! Helpers for integration tests of movement tuning, enabled with the `test_utils` feature.
+//!
+//! [`headless_movement_app`] creates an [`App`] with headless physics and a fixed tick of
+//! [`TEST_TICK`], so every [`App::update`] advances the simulation by exactly one tick.
+//! [`SimulatedInput`] queues [`MovementAction`]s per tick, and [`MovementTestExt`] adds helpers
+//! for spawning test geometry and inspecting controllers.
+//!
+//! [`record_trajectory`] and [`assert_matches_golden`] record the per-tick positions of a
+//! controller for a scripted input sequence and compare them against a golden file, catching
+//! changes to movement feel across crate updates.
+//!
+//! ```no_run
+//! use bevy_movement::test_utils::*;
+//! use bevy_movement::prelude::*;
+//! use avian3d::math::*;
+//!
+//! let mut app = headless_movement_app();
+//! app.spawn_test_ground();
+//! let player = app.spawn_test_controller(Vector::new(0.0, 1.5, 0.0));
+//!
+//! app.tick(60);
+//! assert_grounded(&app, player);
+//!
+//! app.simulated_input().push_ticks([MovementAction::Move(Vector2::Y)], 30);
+//! let position = app.position_after(player, 30);
+//! assert!(position.z < -1.0);
+//! ```
+
+use std::{collections::VecDeque, fmt::Write as _, fs, io, path::Path, time::Duration};
+
+use avian3d::{math::*, prelude::*};
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+use crate::{
+    CharacterController, CharacterControllerBundle, CharacterControllerPlugin,
+    CharacterControllerSystems, FpsController, Grounded, MovementAction,
+};
+
+/// The duration of a single simulation tick in apps created by [`headless_movement_app`].
+pub const TEST_TICK: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// A queue of [`MovementAction`]s written one tick at a time.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct SimulatedInput {
+    ticks: VecDeque<Vec<MovementAction>>,
+}
+
+impl SimulatedInput {
+    /// Queues actions for a single tick.
+    pub fn push_tick(&mut self, actions: impl IntoIterator<Item = MovementAction>) -> &mut Self {
+        self.ticks.push_back(actions.into_iter().collect());
+        self
+    }
+
+    /// Queues the same actions for several ticks, like holding a button.
+    pub fn push_ticks(
+        &mut self,
+        actions: impl IntoIterator<Item = MovementAction>,
+        ticks: u32,
+    ) -> &mut Self {
+        let actions: Vec<_> = actions.into_iter().collect();
+        for _ in 0..ticks {
+            self.ticks.push_back(actions.clone());
+        }
+        self
+    }
+
+    /// Queues ticks without any input.
+    pub fn wait(&mut self, ticks: u32) -> &mut Self {
+        self.push_ticks([], ticks)
+    }
+
+    /// Returns the number of queued ticks.
+    pub fn len(&self) -> usize {
+        self.ticks.len()
+    }
+
+    /// Returns `true` if no ticks are queued.
+    pub fn is_empty(&self) -> bool {
+        self.ticks.is_empty()
+    }
+
+    /// Removes all queued ticks.
+    pub fn clear(&mut self) {
+        self.ticks.clear();
+    }
+}
+
+/// Writes the [`MovementAction`]s queued for the current tick.
+fn write_simulated_input(
+    mut input: ResMut<SimulatedInput>,
+    mut movement_writer: MessageWriter<MovementAction>,
+) {
+    if let Some(actions) = input.ticks.pop_front() {
+        movement_writer.write_batch(actions);
+    }
+}
+
+/// Creates a headless [`App`] with physics, the [`CharacterControllerPlugin`],
+/// and [`SimulatedInput`], advancing by exactly [`TEST_TICK`] on every update.
+pub fn headless_movement_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        TransformPlugin,
+        bevy::input::InputPlugin,
+        PhysicsPlugins::default(),
+        CharacterControllerPlugin,
+    ))
+    .init_asset::<Mesh>()
+    .insert_resource(TimeUpdateStrategy::ManualDuration(TEST_TICK))
+    .insert_resource(Time::<Fixed>::from_duration(TEST_TICK))
+    .init_resource::<SimulatedInput>()
+    .add_systems(
+        Update,
+        write_simulated_input.in_set(CharacterControllerSystems::Input),
+    );
+
+    // Run startup systems and initialize the physics world.
+    app.update();
+    app
+}
+
+/// Extension methods for apps created by [`headless_movement_app`].
+pub trait MovementTestExt {
+    /// Spawns a large static ground plane with its top surface at `y = 0`.
+    fn spawn_test_ground(&mut self) -> Entity;
+
+    /// Spawns a character controller with default settings and input enabled.
+    fn spawn_test_controller(&mut self, position: Vector) -> Entity;
+
+    /// Returns the [`SimulatedInput`] queue.
+    fn simulated_input(&mut self) -> Mut<'_, SimulatedInput>;
+
+    /// Advances the simulation by the given number of ticks.
+    fn tick(&mut self, ticks: u32);
+
+    /// Advances the simulation and returns the position of the entity afterwards.
+    fn position_after(&mut self, entity: Entity, ticks: u32) -> Vector;
+}
+
+impl MovementTestExt for App {
+    fn spawn_test_ground(&mut self) -> Entity {
+        self.world_mut()
+            .spawn((
+                RigidBody::Static,
+                Collider::cuboid(200.0, 1.0, 200.0),
+                Transform::from_xyz(0.0, -0.5, 0.0),
+            ))
+            .id()
+    }
+
+    fn spawn_test_controller(&mut self, position: Vector) -> Entity {
+        self.world_mut()
+            .spawn((
+                Transform::from_translation(position.f32()),
+                CharacterControllerBundle::new(
+                    Collider::capsule(0.4, 1.0),
+                    Vector::NEG_Y * 9.81 * 2.0,
+                ),
+                FpsController { enable_input: true },
+            ))
+            .id()
+    }
+
+    fn simulated_input(&mut self) -> Mut<'_, SimulatedInput> {
+        self.world_mut().resource_mut::<SimulatedInput>()
+    }
+
+    fn tick(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            self.update();
+        }
+    }
+
+    fn position_after(&mut self, entity: Entity, ticks: u32) -> Vector {
+        self.tick(ticks);
+        position_of(self, entity)
+    }
+}
+
+/// Returns the [`Position`] of an entity.
+///
+/// # Panics
+///
+/// Panics if the entity has no [`Position`].
+#[track_caller]
+pub fn position_of(app: &App, entity: Entity) -> Vector {
+    app.world()
+        .get::<Position>(entity)
+        .unwrap_or_else(|| panic!("{entity} has no Position"))
+        .0
+}
+
+/// Asserts that a character controller is [`Grounded`].
+#[track_caller]
+pub fn assert_grounded(app: &App, entity: Entity) {
+    assert_controller(app, entity);
+    assert!(
+        app.world().get::<Grounded>(entity).is_some(),
+        "expected {entity} to be grounded at {}",
+        position_of(app, entity)
+    );
+}
+
+/// Asserts that a character controller is not [`Grounded`].
+#[track_caller]
+pub fn assert_airborne(app: &App, entity: Entity) {
+    assert_controller(app, entity);
+    assert!(
+        app.world().get::<Grounded>(entity).is_none(),
+        "expected {entity} to be airborne at {}",
+        position_of(app, entity)
+    );
+}
+
+/// Asserts that two positions are within `tolerance` of each other.
+#[track_caller]
+pub fn assert_near(actual: Vector, expected: Vector, tolerance: Scalar) {
+    let distance = actual.distance(expected);
+    assert!(
+        distance <= tolerance,
+        "expected {actual} to be within {tolerance} of {expected}, but it was {distance} away"
+    );
+}
+
+#[track_caller]
+fn assert_controller(app: &App, entity: Entity) {
+    assert!(
+        app.world().get::<CharacterController>(entity).is_some(),
+        "{entity} is not a character controller"
+    );
+}
+
+/// The per-tick positions of a character controller.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Trajectory {
+    pub positions: Vec<Vector>,
+}
+
+impl Trajectory {
+    /// Serializes the trajectory with one `x y z` line per tick.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for position in &self.positions {
+            let _ = writeln!(text, "{} {} {}", position.x, position.y, position.z);
+        }
+        text
+    }
+
+    /// Parses a trajectory from the format written by [`Trajectory::to_text`].
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut positions = Vec::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let components = line
+                .split_whitespace()
+                .map(|part| part.parse::<Scalar>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|error| format!("line {}: {error}", index + 1))?;
+            let &[x, y, z] = components.as_slice() else {
+                return Err(format!("line {}: expected 3 components", index + 1));
+            };
+            positions.push(Vector::new(x, y, z));
+        }
+
+        Ok(Self { positions })
+    }
+
+    /// Writes the trajectory to a file, creating parent directories as needed.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_text())
+    }
+
+    /// Reads a trajectory from a file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::from_text(&text).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Returns the first tick where the trajectories differ by more than `tolerance`,
+    /// with the distance between them, or `None` if they match.
+    ///
+    /// Trajectories of different lengths differ at the end of the shorter one.
+    pub fn first_divergence(&self, other: &Self, tolerance: Scalar) -> Option<(usize, Scalar)> {
+        let divergence = self
+            .positions
+            .iter()
+            .zip(&other.positions)
+            .map(|(a, b)| a.distance(*b))
+            .enumerate()
+            .find(|&(_, distance)| distance > tolerance);
+
+        divergence.or_else(|| {
+            (self.positions.len() != other.positions.len()).then(|| {
+                (
+                    self.positions.len().min(other.positions.len()),
+                    Scalar::INFINITY,
+                )
+            })
+        })
+    }
+}
+
+/// Queues `input` and records the position of the entity after each of its ticks.
+pub fn record_trajectory(app: &mut App, entity: Entity, input: SimulatedInput) -> Trajectory {
+    let ticks = input.len();
+    *app.simulated_input() = input;
+
+    let positions = (0..ticks).map(|_| app.position_after(entity, 1)).collect();
+    Trajectory { positions }
+}
+
+/// Asserts that a trajectory matches the golden file at `path` within `tolerance` per tick.
+///
+/// If the file does not exist, or the `UPDATE_GOLDEN` environment variable is set,
+/// the trajectory is written as the new golden file instead.
+#[track_caller]
+pub fn assert_matches_golden(actual: &Trajectory, path: impl AsRef<Path>, tolerance: Scalar) {
+    let path = path.as_ref();
+    if std::env::var_os("UPDATE_GOLDEN").is_some() || !path.exists() {
+        actual
+            .save(path)
+            .unwrap_or_else(|error| panic!("could not write {}: {error}", path.display()));
+        return;
+    }
+
+    let golden = Trajectory::load(path)
+        .unwrap_or_else(|error| panic!("could not read {}: {error}", path.display()));
+    if let Some((tick, distance)) = actual.first_divergence(&golden, tolerance) {
+        panic!(
+            "trajectory diverged from {} at tick {tick} by {distance} (tolerance {tolerance}); \
+             set UPDATE_GOLDEN=1 to accept the new trajectory",
+            path.display()
+        );
+    }
+}
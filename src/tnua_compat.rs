@@ -0,0 +1,144 @@
+//! An adapter for porting movement code written for [`bevy_tnua`](https://github.com/idanarye/bevy-tnua).
+//!
+//! Tnua controllers are driven by feeding a *basis* (like [`TnuaBuiltinWalk`]) and optional
+//! *actions* (like [`TnuaBuiltinJump`]) to a [`TnuaController`] every frame. This module
+//! provides look-alike types that translate those commands into [`DirectedMovementAction`]s
+//! for this crate's controllers, so existing input code can be ported incrementally.
+//!
+//! Only the concepts that map onto a kinematic controller are supported. Floating
+//! (`float_height`) and the other Tnua builtins have no equivalent here.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    direction_yaw, yaw_rotation, CharacterController, ControllerEnabled, ControllerGravity,
+    ControllerUp, DirectedMovementAction, Grounded, JumpImpulse, MovementAcceleration,
+    MovementAction, MovementDampingFactor, TunedMovement, WorldUp,
+};
+
+/// A walking basis, mirroring Tnua's `TnuaBuiltinWalk`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TnuaBuiltinWalk {
+    /// The desired world-space velocity. Only the part perpendicular to the up direction
+    /// of the controller is used.
+    ///
+    /// Speeds above the top speed of the controller are clamped to it.
+    pub desired_velocity: Vector,
+    /// The direction the character should face, or `None` to keep its rotation.
+    pub desired_forward: Option<Dir3>,
+}
+
+/// A jump action, mirroring Tnua's `TnuaBuiltinJump`.
+///
+/// The jump starts on the first grounded frame the action is fed, and feeding it
+/// continuously while the button is held does not jump again.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TnuaBuiltinJump {
+    /// The height of the jump. If `None`, the [`JumpImpulse`] of the controller is used.
+    pub height: Option<Scalar>,
+}
+
+/// Accepts Tnua-style movement commands for a character controller.
+///
+/// The basis and action must be fed every frame, for example from an input system
+/// that runs before [`CharacterControllerSystems::Input`](crate::CharacterControllerSystems::Input).
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct TnuaController {
+    basis: Option<TnuaBuiltinWalk>,
+    action: Option<TnuaBuiltinJump>,
+    /// Whether the jump action was fed in the previous frame.
+    jump_held: bool,
+}
+
+impl TnuaController {
+    /// Sets the walking basis for this frame.
+    pub fn basis(&mut self, walk: TnuaBuiltinWalk) {
+        self.basis = Some(walk);
+    }
+
+    /// Feeds the jump action for this frame.
+    pub fn action(&mut self, jump: TnuaBuiltinJump) {
+        self.action = Some(jump);
+    }
+}
+
+/// Translates the commands fed to [`TnuaController`]s into [`DirectedMovementAction`]s.
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_tnua_controllers(
+    world_up: Res<WorldUp>,
+    mut action_writer: MessageWriter<DirectedMovementAction>,
+    mut controllers: Query<
+        (
+            Entity,
+            &mut TnuaController,
+            &mut Rotation,
+            &mut JumpImpulse,
+            &MovementAcceleration,
+            &MovementDampingFactor,
+            &ControllerGravity,
+            Has<Grounded>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    for (
+        entity,
+        mut controller,
+        mut rotation,
+        mut jump_impulse,
+        acceleration,
+        damping,
+        gravity,
+        is_grounded,
+        controller_up,
+    ) in &mut controllers
+    {
+        let up = world_up.for_controller(controller_up);
+        if let Some(walk) = controller.basis.take() {
+            if let Some(forward) = walk.desired_forward {
+                let forward = forward
+                    .as_vec3()
+                    .adjust_precision()
+                    .reject_from_normalized(up);
+                if forward != Vector::ZERO {
+                    rotation.0 = yaw_rotation(direction_yaw(forward, up), up);
+                }
+            }
+
+            // The movement input is a fraction of the top speed of the controller.
+            let top_speed = TunedMovement {
+                acceleration: acceleration.0,
+                damping: damping.0,
+                jump_impulse: jump_impulse.0,
+                gravity: gravity.0.length(),
+            }
+            .top_speed();
+            let velocity = walk.desired_velocity.reject_from_normalized(up);
+            if velocity != Vector::ZERO && top_speed > 0.0 {
+                let local = rotation.0.inverse() * (velocity / top_speed).clamp_length_max(1.0);
+                action_writer.write(DirectedMovementAction {
+                    entity,
+                    action: MovementAction::Move(Vector2::new(local.x, -local.z)),
+                });
+            }
+        }
+
+        // A held jump only starts once, but feeding it in the air jumps on landing.
+        match controller.action.take() {
+            Some(jump) if is_grounded && !controller.jump_held => {
+                if let Some(height) = jump.height {
+                    jump_impulse.0 = (2.0 * gravity.0.length() * height).sqrt();
+                }
+                action_writer.write(DirectedMovementAction {
+                    entity,
+                    action: MovementAction::Jump,
+                });
+                controller.jump_held = true;
+            }
+            Some(_) => {}
+            None => controller.jump_held = false,
+        }
+    }
+}
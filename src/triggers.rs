@@ -0,0 +1,66 @@
+//! An entity-targeted trigger API for driving specific character controllers.
+//!
+//! As an alternative to the global [`MovementAction`] messages, [`Move`] and [`Jump`]
+//! can be triggered for a single controller:
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_movement::prelude::*;
+//! # use avian3d::math::*;
+//! fn drive(mut commands: Commands, npc: Single<Entity, With<CharacterController>>) {
+//!     commands.trigger(Move {
+//!         entity: *npc,
+//!         direction: Vector2::Y,
+//!     });
+//! }
+//! ```
+//!
+//! Triggers are forwarded as [`DirectedMovementAction`]s, so they use the same movement
+//! physics. [`Jumped`] is triggered on a controller when it jumps, so per-entity observers
+//! can react without filtering a global message stream.
+
+use avian3d::math::*;
+use bevy::prelude::*;
+
+use crate::{DirectedMovementAction, MovementAction};
+
+/// Moves a character controller in a local direction, like [`MovementAction::Move`].
+#[derive(EntityEvent, Clone, Copy, Debug)]
+pub struct Move {
+    pub entity: Entity,
+    pub direction: Vector2,
+}
+
+/// Makes a character controller jump if it is grounded, like [`MovementAction::Jump`].
+#[derive(EntityEvent, Clone, Copy, Debug)]
+pub struct Jump {
+    pub entity: Entity,
+}
+
+/// Triggered on a character controller when it jumps.
+#[derive(EntityEvent, Clone, Copy, Debug)]
+pub struct Jumped {
+    pub entity: Entity,
+}
+
+/// Forwards [`Move`] triggers to the movement system.
+pub(crate) fn forward_move_triggers(
+    event: On<Move>,
+    mut action_writer: MessageWriter<DirectedMovementAction>,
+) {
+    action_writer.write(DirectedMovementAction {
+        entity: event.entity,
+        action: MovementAction::Move(event.direction),
+    });
+}
+
+/// Forwards [`Jump`] triggers to the movement system.
+pub(crate) fn forward_jump_triggers(
+    event: On<Jump>,
+    mut action_writer: MessageWriter<DirectedMovementAction>,
+) {
+    action_writer.write(DirectedMovementAction {
+        entity: event.entity,
+        action: MovementAction::Jump,
+    });
+}
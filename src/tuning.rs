@@ -0,0 +1,218 @@
+//! Solving movement parameters from desired feel metrics.
+//!
+//! Instead of iterating on raw acceleration, damping, and impulse values, describe how movement
+//! should feel with a [`MovementFeel`] and [`solve`](MovementFeel::solve) for the parameters.
+//!
+//! ```
+//! use bevy_movement::prelude::*;
+//!
+//! let tuned = MovementFeel {
+//!     top_speed: 8.0,
+//!     time_to_top_speed: 0.25,
+//!     jump_height: 1.2,
+//!     jump_apex_time: 0.35,
+//! }
+//! .solve();
+//!
+//! assert!((tuned.top_speed() - 8.0).abs() < 1e-3);
+//! ```
+
+use avian3d::math::*;
+
+use crate::{
+    ControllerGravity, JumpImpulse, MovementAcceleration, MovementDampingFactor,
+    DAMPING_REFERENCE_RATE,
+};
+
+/// The fraction of the top speed that counts as having reached it.
+///
+/// Damped velocity approaches the top speed asymptotically, so it is never reached exactly.
+pub const TOP_SPEED_FRACTION: Scalar = 0.95;
+
+/// Standard gravity, used as the reference for [`TunedMovement::gravity_scale`].
+pub const STANDARD_GRAVITY: Scalar = 9.81;
+
+/// Desired movement feel in intuitive units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MovementFeel {
+    /// The maximum horizontal speed while holding a direction, in units per second.
+    pub top_speed: Scalar,
+    /// The time to accelerate from standstill to [`TOP_SPEED_FRACTION`] of the top speed, in seconds.
+    pub time_to_top_speed: Scalar,
+    /// The height of a jump from flat ground.
+    pub jump_height: Scalar,
+    /// The time from leaving the ground to the apex of a jump, in seconds.
+    pub jump_apex_time: Scalar,
+}
+
+impl Default for MovementFeel {
+    fn default() -> Self {
+        Self {
+            top_speed: 6.0,
+            time_to_top_speed: 0.3,
+            jump_height: 1.2,
+            jump_apex_time: 0.4,
+        }
+    }
+}
+
+impl MovementFeel {
+    /// Solves for the movement parameters that produce this feel.
+    ///
+    /// Acceleration and damping are solved for the discrete per-frame integration of the
+    /// movement systems, so they match the feel at 60 FPS exactly and closely at other rates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the metrics is not positive.
+    pub fn solve(&self) -> TunedMovement {
+        assert!(self.top_speed > 0.0, "top speed must be positive");
+        assert!(
+            self.time_to_top_speed > 0.0,
+            "time to top speed must be positive"
+        );
+        assert!(self.jump_height > 0.0, "jump height must be positive");
+        assert!(self.jump_apex_time > 0.0, "jump apex time must be positive");
+
+        // Each frame, velocity is accelerated and then damped: v' = (v + a * dt) * d.
+        // Starting from rest, v(n) = v_max * (1 - d^n), so reaching the top speed fraction
+        // after n frames requires d^n = 1 - fraction.
+        let dt = 1.0 / DAMPING_REFERENCE_RATE;
+        let frames = (self.time_to_top_speed * DAMPING_REFERENCE_RATE).max(1.0);
+        let damping = (1.0 - TOP_SPEED_FRACTION).powf(1.0 / frames);
+
+        // The steady state of v' = (v + a * dt) * d is v_max = a * dt * d / (1 - d).
+        let acceleration = self.top_speed * (1.0 - damping) / (dt * damping);
+
+        // A ballistic jump reaches h = v0 * t / 2 at its apex t = v0 / g.
+        let jump_impulse = 2.0 * self.jump_height / self.jump_apex_time;
+        let gravity = jump_impulse / self.jump_apex_time;
+
+        TunedMovement {
+            acceleration,
+            damping,
+            jump_impulse,
+            gravity,
+        }
+    }
+}
+
+/// Movement parameters solved from a [`MovementFeel`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TunedMovement {
+    /// The value for [`MovementAcceleration`].
+    pub acceleration: Scalar,
+    /// The value for [`MovementDampingFactor`].
+    pub damping: Scalar,
+    /// The value for [`JumpImpulse`].
+    pub jump_impulse: Scalar,
+    /// The magnitude of [`ControllerGravity`].
+    pub gravity: Scalar,
+}
+
+impl TunedMovement {
+    /// Returns the gravity relative to [`STANDARD_GRAVITY`].
+    pub fn gravity_scale(&self) -> Scalar {
+        self.gravity / STANDARD_GRAVITY
+    }
+
+    /// Returns the top speed these parameters reach at 60 FPS.
+    pub fn top_speed(&self) -> Scalar {
+        self.acceleration * self.damping / (DAMPING_REFERENCE_RATE * (1.0 - self.damping))
+    }
+
+    pub fn acceleration(&self) -> MovementAcceleration {
+        MovementAcceleration(self.acceleration)
+    }
+
+    pub fn damping(&self) -> MovementDampingFactor {
+        MovementDampingFactor(self.damping)
+    }
+
+    pub fn jump_impulse(&self) -> JumpImpulse {
+        JumpImpulse(self.jump_impulse)
+    }
+
+    /// Returns [`ControllerGravity`] with the solved magnitude, pulling along `direction`.
+    pub fn gravity(&self, direction: Vector) -> ControllerGravity {
+        ControllerGravity(direction.normalize_or(Vector::NEG_Y) * self.gravity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Scalar, expected: Scalar) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "expected {expected}, but got {actual}"
+        );
+    }
+
+    #[test]
+    fn solved_acceleration_reaches_top_speed_in_time() {
+        let feel = MovementFeel::default();
+        let tuned = feel.solve();
+        assert_close(tuned.top_speed(), feel.top_speed);
+
+        // Integrate the movement from rest at the reference rate.
+        let dt = 1.0 / DAMPING_REFERENCE_RATE;
+        let frames = (feel.time_to_top_speed * DAMPING_REFERENCE_RATE).round() as usize;
+        let mut velocity = 0.0;
+        for _ in 0..frames {
+            velocity = (velocity + tuned.acceleration * dt) * tuned.damping;
+        }
+        assert_close(velocity, feel.top_speed * TOP_SPEED_FRACTION);
+    }
+
+    #[test]
+    fn solved_jump_reaches_height_at_apex_time() {
+        let feel = MovementFeel::default();
+        let tuned = feel.solve();
+
+        assert_close(tuned.jump_impulse / tuned.gravity, feel.jump_apex_time);
+        assert_close(
+            tuned.jump_impulse * tuned.jump_impulse / (2.0 * tuned.gravity),
+            feel.jump_height,
+        );
+        assert_close(tuned.gravity_scale(), tuned.gravity / STANDARD_GRAVITY);
+    }
+
+    #[test]
+    fn instant_acceleration_takes_one_frame() {
+        let tuned = MovementFeel {
+            time_to_top_speed: 0.001,
+            ..MovementFeel::default()
+        }
+        .solve();
+
+        assert_close(tuned.damping, 1.0 - TOP_SPEED_FRACTION);
+    }
+
+    #[test]
+    fn solved_gravity_keeps_its_direction() {
+        let tuned = MovementFeel::default().solve();
+
+        let gravity = tuned.gravity(Vector::X * 2.0).0;
+        assert!(
+            gravity.abs_diff_eq(Vector::X * tuned.gravity, 1e-4),
+            "{gravity}"
+        );
+        let fallback = tuned.gravity(Vector::ZERO).0;
+        assert!(
+            fallback.abs_diff_eq(Vector::NEG_Y * tuned.gravity, 1e-4),
+            "{fallback}"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "jump height must be positive")]
+    fn non_positive_metrics_panic() {
+        MovementFeel {
+            jump_height: 0.0,
+            ..MovementFeel::default()
+        }
+        .solve();
+    }
+}
@@ -0,0 +1,135 @@
+//! Turn-in-place detection for third-person characters.
+//!
+//! When the [`ViewYaw`] of a standing character turns too far away from where its body
+//! faces, [`TurnInPlace`] triggers [`TurnStarted`] with the signed angle, so animation
+//! systems can play a 90° or 180° turn clip, and then turns the body to face the view.
+//! The character is [`TurningInPlace`] until it is aligned again.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    find_camera_pivot, orbit_rotation, CameraPivot, CharacterController, ControllerEnabled,
+    ControllerUp, LockOnTarget, Pitch, ViewYaw, WorldUp,
+};
+
+/// Turns a standing character controller in place when its view turns too far from its
+/// facing direction.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(ViewYaw)]
+pub struct TurnInPlace {
+    /// The angle between the view and the facing direction that starts a turn, in radians.
+    pub threshold: Scalar,
+    /// The maximum horizontal speed at which the character counts as standing, in m/s.
+    pub max_speed: Scalar,
+    /// The speed of the turn, in radians per second.
+    pub turn_speed: Scalar,
+}
+
+impl Default for TurnInPlace {
+    fn default() -> Self {
+        Self {
+            threshold: PI * 0.4,
+            max_speed: 0.2,
+            turn_speed: 6.0,
+        }
+    }
+}
+
+/// A component on a character controller that is turning in place.
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct TurningInPlace {
+    /// The signed angle of the turn when it started, in radians, positive to the left.
+    pub angle: Scalar,
+}
+
+/// Triggered on a [`TurnInPlace`] character controller when it starts turning in place.
+#[derive(EntityEvent, Clone, Copy, Debug)]
+pub struct TurnStarted {
+    pub entity: Entity,
+    /// The signed angle to turn, in radians, positive to the left.
+    pub angle: Scalar,
+}
+
+/// Starts, applies, and ends turns in place.
+#[allow(clippy::type_complexity)]
+pub(crate) fn turn_in_place(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    mut controllers: Query<
+        (
+            Entity,
+            &TurnInPlace,
+            &ViewYaw,
+            &mut Rotation,
+            &LinearVelocity,
+            Option<&Pitch>,
+            Has<TurningInPlace>,
+            Option<&ControllerUp>,
+        ),
+        (
+            With<CharacterController>,
+            Without<LockOnTarget>,
+            ControllerEnabled,
+        ),
+    >,
+    mut pivots: Query<
+        (&mut Transform, Has<CameraPivot>),
+        (
+            Or<(With<CameraPivot>, With<Camera3d>)>,
+            Without<CharacterController>,
+        ),
+    >,
+    children: Query<&Children>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (entity, turn, view_yaw, mut rotation, linear_velocity, pitch, is_turning, controller_up) in
+        &mut controllers
+    {
+        let up = world_up.for_controller(controller_up);
+
+        let forward = (rotation.0 * Vector::NEG_Z).reject_from_normalized(up);
+        let view_forward = (view_yaw.0 * Vector::NEG_Z).reject_from_normalized(up);
+        let angle = forward
+            .cross(view_forward)
+            .dot(up)
+            .atan2(forward.dot(view_forward));
+
+        // Moving characters are turned by their movement instead
+        let standing = linear_velocity.reject_from_normalized(up).length() <= turn.max_speed;
+
+        if !is_turning {
+            if standing && angle.abs() > turn.threshold {
+                commands.entity(entity).insert(TurningInPlace { angle });
+                commands.trigger(TurnStarted { entity, angle });
+            }
+            continue;
+        }
+
+        if !standing || angle.abs() <= Scalar::EPSILON {
+            commands.entity(entity).remove::<TurningInPlace>();
+            continue;
+        }
+
+        let max_turn = turn.turn_speed * delta_time;
+        let step = angle.clamp(-max_turn, max_turn);
+        rotation.0 = (Quaternion::from_axis_angle(up, step) * rotation.0).normalize();
+        if step == angle {
+            commands.entity(entity).remove::<TurningInPlace>();
+        }
+
+        // The camera is a child of the character, so counter its rotation
+        let Some(pitch) = pitch else {
+            continue;
+        };
+        if let Some(pivot) = find_camera_pivot(entity, &children, &pivots) {
+            if let Ok((mut pivot_transform, _)) = pivots.get_mut(pivot) {
+                pivot_transform.rotation =
+                    orbit_rotation(rotation.0, view_yaw.0, pitch.angle).f32();
+            }
+        }
+    }
+}
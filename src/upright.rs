@@ -0,0 +1,106 @@
+//! A springy alternative to keeping character controllers rigidly upright.
+//!
+//! [`UprightSpring`] steers the angular velocity of a character towards upright with a
+//! damped spring, and tilts the spring target with the horizontal acceleration of the
+//! character. Starting, stopping, and being knocked around by collisions make the character
+//! lean slightly and settle back, which looks more lively than a hard rotation lock.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{AccessibilityProfile, CharacterController, ControllerEnabled, ControllerUp, WorldUp};
+
+/// Keeps a character controller upright with a damped spring, letting it lean
+/// with acceleration and impacts.
+///
+/// Only the tilt of the character is affected, so yaw from look input is unchanged.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(UprightState)]
+pub struct UprightSpring {
+    /// The angular acceleration per radian of tilt, in 1/s².
+    pub stiffness: Scalar,
+    /// How quickly the tilting angular velocity is damped, in 1/s.
+    ///
+    /// `2.0 * stiffness.sqrt()` settles without overshooting, lower values wobble.
+    pub damping: Scalar,
+    /// The lean in radians per m/s² of horizontal acceleration.
+    ///
+    /// Positive values lean away from the acceleration like an inert body,
+    /// negative values lean into it like a runner.
+    pub lean: Scalar,
+    /// The maximum lean caused by acceleration, in radians.
+    pub max_lean: Scalar,
+}
+
+impl Default for UprightSpring {
+    fn default() -> Self {
+        Self {
+            stiffness: 200.0,
+            damping: 20.0,
+            lean: 0.005,
+            max_lean: 0.3,
+        }
+    }
+}
+
+/// Tracks the velocity of an [`UprightSpring`] character for estimating its acceleration.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct UprightState {
+    /// The velocity of the character in the previous frame.
+    previous_velocity: Option<Vector>,
+}
+
+/// Applies the [`UprightSpring`] of character controllers to their angular velocity.
+#[allow(clippy::type_complexity)]
+pub(crate) fn stabilize_upright(
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    mut controllers: Query<
+        (
+            &UprightSpring,
+            &mut UprightState,
+            &Rotation,
+            &LinearVelocity,
+            &mut AngularVelocity,
+            Option<&AccessibilityProfile>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+    if delta_time <= 0.0 {
+        return;
+    }
+
+    for (
+        spring,
+        mut state,
+        rotation,
+        linear_velocity,
+        mut angular_velocity,
+        profile,
+        controller_up,
+    ) in &mut controllers
+    {
+        let up = world_up.for_controller(controller_up);
+
+        let previous_velocity = state.previous_velocity.replace(linear_velocity.0);
+        let acceleration = previous_velocity.map_or(Vector::ZERO, |previous| {
+            (linear_velocity.0 - previous).reject_from_normalized(up) / delta_time
+        });
+
+        // The tilt as a rotation vector from upright to the current up axis of the body
+        let tilt = Quaternion::from_rotation_arc(up, rotation * Vector::Y).to_scaled_axis();
+        // Reduced camera motion scales the lean down, since the camera leans with the body
+        let motion = profile.map_or(1.0, |profile| profile.camera_motion.clamp(0.0, 1.0));
+        let target = (acceleration.cross(up) * spring.lean * motion)
+            .clamp_length_max(spring.max_lean * motion);
+
+        let yaw_velocity = angular_velocity.0.project_onto_normalized(up);
+        let mut tilt_velocity = angular_velocity.0 - yaw_velocity;
+        tilt_velocity +=
+            (spring.stiffness * (target - tilt) - spring.damping * tilt_velocity) * delta_time;
+        angular_velocity.0 = yaw_velocity + tilt_velocity;
+    }
+}
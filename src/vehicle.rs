@@ -0,0 +1,212 @@
+//! Handing a character controller over to a vehicle and back.
+//!
+//! Writing [`MountVehicle`] parks the character: its controller systems are disabled,
+//! its body is attached to the vehicle seat, and [`MovementAction`] input is redirected
+//! to the [`VehicleInput`] of the vehicle. Writing [`DismountVehicle`] places the character
+//! at the first free exit point of the vehicle and restores the controller. If every exit
+//! is blocked, the character stays in the vehicle and [`DismountBlocked`] is written.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    is_free_position, yaw_of, yaw_rotation, CharacterController, ControllerUp, LookAction,
+    MovementAction, WorldUp,
+};
+
+/// A component for entities that character controllers can mount.
+#[derive(Component, Clone, Debug)]
+#[require(VehicleInput)]
+pub struct Vehicle {
+    /// The entity the driver is attached to. If `None`, the vehicle itself is used.
+    pub seat: Option<Entity>,
+    /// Exit points relative to the vehicle, tried in order when dismounting.
+    pub exit_points: Vec<Vector>,
+    /// Whether the driver's body is hidden while mounted.
+    pub hide_driver: bool,
+}
+
+impl Default for Vehicle {
+    fn default() -> Self {
+        Self {
+            seat: None,
+            exit_points: vec![
+                Vector::new(-1.5, 0.5, 0.0),
+                Vector::new(1.5, 0.5, 0.0),
+                Vector::new(0.0, 2.0, 0.0),
+            ],
+            hide_driver: true,
+        }
+    }
+}
+
+/// Input redirected from the driver to a [`Vehicle`], reset every frame.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct VehicleInput {
+    /// The accumulated movement input.
+    pub movement: Vector2,
+    /// The accumulated look input.
+    pub look: Vector2,
+    /// Whether jump was pressed this frame.
+    pub jump: bool,
+}
+
+/// A component on a [`Vehicle`] with the character controller driving it.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct VehicleDriver(pub Entity);
+
+/// A component on a character controller that is parked in a vehicle.
+///
+/// All controller systems skip parked characters.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Parked {
+    pub vehicle: Entity,
+}
+
+/// A [`Message`] requesting a character controller to mount a [`Vehicle`].
+#[derive(Message, Clone, Copy, Debug)]
+pub struct MountVehicle {
+    pub character: Entity,
+    pub vehicle: Entity,
+}
+
+/// A [`Message`] requesting a [`Parked`] character controller to leave its vehicle.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct DismountVehicle {
+    pub character: Entity,
+}
+
+/// A [`Message`] written when a character can't leave a vehicle
+/// because all of its exit points are blocked.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct DismountBlocked {
+    pub character: Entity,
+    pub vehicle: Entity,
+}
+
+/// Parks character controllers in vehicles in response to [`MountVehicle`].
+pub(crate) fn mount_vehicles(
+    mut commands: Commands,
+    mut mount_reader: MessageReader<MountVehicle>,
+    characters: Query<(), (With<CharacterController>, Without<Parked>)>,
+    vehicles: Query<&Vehicle, Without<VehicleDriver>>,
+) {
+    for &MountVehicle { character, vehicle } in mount_reader.read() {
+        let Ok(vehicle_config) = vehicles.get(vehicle) else {
+            continue;
+        };
+        if !characters.contains(character) {
+            continue;
+        }
+
+        let mut character_commands = commands.entity(character);
+        character_commands.insert((
+            Parked { vehicle },
+            ColliderDisabled,
+            RigidBodyDisabled,
+            LinearVelocity::ZERO,
+            ChildOf(vehicle_config.seat.unwrap_or(vehicle)),
+            Transform::default(),
+        ));
+        if vehicle_config.hide_driver {
+            character_commands.insert(Visibility::Hidden);
+        }
+
+        commands.entity(vehicle).insert(VehicleDriver(character));
+    }
+}
+
+/// Restores parked character controllers at a free exit point in response to [`DismountVehicle`].
+#[allow(clippy::type_complexity)]
+pub(crate) fn dismount_vehicles(
+    mut commands: Commands,
+    world_up: Res<WorldUp>,
+    mut dismount_reader: MessageReader<DismountVehicle>,
+    mut blocked_writer: MessageWriter<DismountBlocked>,
+    spatial_query: SpatialQuery,
+    sensors: Query<(), With<Sensor>>,
+    mut characters: Query<
+        (
+            &Parked,
+            &Collider,
+            &mut Position,
+            &mut Rotation,
+            &mut Transform,
+            Option<&ControllerUp>,
+        ),
+        With<CharacterController>,
+    >,
+    vehicles: Query<(&Vehicle, &GlobalTransform)>,
+) {
+    for &DismountVehicle { character } in dismount_reader.read() {
+        let Ok((parked, collider, mut position, mut rotation, mut transform, controller_up)) =
+            characters.get_mut(character)
+        else {
+            continue;
+        };
+        let vehicle = parked.vehicle;
+        let Ok((vehicle_config, vehicle_transform)) = vehicles.get(vehicle) else {
+            continue;
+        };
+
+        // Exit upright, facing the same way as the vehicle.
+        let up = world_up.for_controller(controller_up);
+        let vehicle_rotation = vehicle_transform.rotation().adjust_precision();
+        let exit_rotation = yaw_rotation(yaw_of(vehicle_rotation, up), up);
+
+        let exit = vehicle_config.exit_points.iter().find_map(|&offset| {
+            let exit_position = vehicle_transform
+                .transform_point(offset.f32())
+                .adjust_precision();
+            is_free_position(
+                &spatial_query,
+                &sensors,
+                collider,
+                exit_position,
+                exit_rotation,
+                character,
+            )
+            .then_some(exit_position)
+        });
+
+        let Some(exit_position) = exit else {
+            blocked_writer.write(DismountBlocked { character, vehicle });
+            continue;
+        };
+
+        position.0 = exit_position;
+        rotation.0 = exit_rotation;
+        *transform =
+            Transform::from_translation(exit_position.f32()).with_rotation(exit_rotation.f32());
+
+        commands
+            .entity(character)
+            .remove::<(Parked, ChildOf, ColliderDisabled, RigidBodyDisabled)>()
+            .insert(Visibility::Inherited);
+        commands.entity(vehicle).remove::<VehicleDriver>();
+    }
+}
+
+/// Redirects [`MovementAction`] and [`LookAction`] input to the [`VehicleInput`] of driven vehicles.
+pub(crate) fn redirect_vehicle_input(
+    mut movement_reader: MessageReader<MovementAction>,
+    mut look_reader: MessageReader<LookAction>,
+    mut vehicles: Query<&mut VehicleInput, With<VehicleDriver>>,
+) {
+    let mut input = VehicleInput::default();
+    for event in movement_reader.read() {
+        match event {
+            MovementAction::Move(direction) => input.movement += *direction,
+            MovementAction::Jump => input.jump = true,
+            _ => {}
+        }
+    }
+    for LookAction(delta) in look_reader.read() {
+        input.look += *delta;
+    }
+    input.movement = input.movement.clamp_length_max(1.0);
+
+    for mut vehicle_input in &mut vehicles {
+        *vehicle_input = input;
+    }
+}
@@ -0,0 +1,80 @@
+//! Hook points for custom velocity modifiers.
+//!
+//! Games often need to add their own forces, like wind, magnets, or scripted pushes,
+//! without replacing the movement systems. [`VelocityHooks`] are system sets at fixed
+//! points of the movement pipeline, where systems can modify the [`DesiredVelocity`] of
+//! character controllers. Before each hook set runs, [`DesiredVelocity`] is loaded from the
+//! [`LinearVelocity`], and afterwards it is written back.
+//!
+//! ```ignore
+//! fn apply_wind(mut query: Query<&mut DesiredVelocity>) {
+//!     for mut velocity in &mut query {
+//!         velocity.0.x += 0.1;
+//!     }
+//! }
+//!
+//! app.add_systems(Update, apply_wind.in_set(VelocityHooks::PostMovement));
+//! ```
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::ControllerEnabled;
+
+/// System sets in [`Update`] for systems that modify the [`DesiredVelocity`] of
+/// character controllers, run in order.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VelocityHooks {
+    /// After detection, before gravity and movement input are applied.
+    PreMovement,
+    /// After gravity, movement input, and jumps are applied.
+    PostMovement,
+    /// Right before movement damping, for velocity that should still be damped.
+    PreDamping,
+    /// After damping and all other velocity adjustments, before physics runs.
+    Finalize,
+}
+
+impl VelocityHooks {
+    /// All hook sets, in the order they run.
+    pub const ALL: [Self; 4] = [
+        Self::PreMovement,
+        Self::PostMovement,
+        Self::PreDamping,
+        Self::Finalize,
+    ];
+}
+
+/// Internal sets that load and store the [`DesiredVelocity`] around each hook set.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum VelocitySync {
+    Load(VelocityHooks),
+    Store(VelocityHooks),
+}
+
+/// The velocity of a character controller in the movement pipeline, for systems in
+/// [`VelocityHooks`] to modify.
+///
+/// Only character controllers with this component take part in the hooks.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct DesiredVelocity(pub Vector);
+
+/// Loads the [`DesiredVelocity`] of character controllers from their [`LinearVelocity`].
+pub(crate) fn load_desired_velocity(
+    mut query: Query<(&LinearVelocity, &mut DesiredVelocity), ControllerEnabled>,
+) {
+    for (linear_velocity, mut desired_velocity) in &mut query {
+        desired_velocity.set_if_neq(DesiredVelocity(linear_velocity.0));
+    }
+}
+
+/// Writes the [`DesiredVelocity`] of character controllers back to their [`LinearVelocity`].
+pub(crate) fn store_desired_velocity(
+    mut query: Query<(&DesiredVelocity, &mut LinearVelocity), ControllerEnabled>,
+) {
+    for (desired_velocity, mut linear_velocity) in &mut query {
+        if linear_velocity.0 != desired_velocity.0 {
+            linear_velocity.0 = desired_velocity.0;
+        }
+    }
+}
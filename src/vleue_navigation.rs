@@ -0,0 +1,46 @@
+//! Navmesh pathfinding for [`PathFollower`]s, enabled with the `vleue_navigator` feature.
+//!
+//! Followers with a [`NavMeshAgent`] get their [`RepathRequested`] messages answered
+//! automatically with a path computed on the agent's navmesh.
+
+use avian3d::math::*;
+use bevy::prelude::*;
+use vleue_navigator::NavMesh;
+
+use crate::{PathFailed, PathFollower, RepathRequested};
+
+/// Computes paths for a [`PathFollower`] on a navmesh.
+#[derive(Component, Clone, Debug)]
+#[require(PathFollower)]
+pub struct NavMeshAgent(pub Handle<NavMesh>);
+
+/// Answers [`RepathRequested`] messages for [`NavMeshAgent`]s using their navmesh.
+pub(crate) fn compute_navmesh_paths(
+    mut repath_reader: MessageReader<RepathRequested>,
+    mut failed_writer: MessageWriter<PathFailed>,
+    mut agents: Query<(&NavMeshAgent, &mut PathFollower)>,
+    navmeshes: Res<Assets<NavMesh>>,
+) {
+    for request in repath_reader.read() {
+        let Ok((agent, mut follower)) = agents.get_mut(request.entity) else {
+            continue;
+        };
+        // The navmesh may still be building.
+        let Some(navmesh) = navmeshes.get(&agent.0) else {
+            continue;
+        };
+
+        match navmesh.transformed_path(request.from.f32(), request.destination.f32()) {
+            Some(path) => {
+                follower.set_path(path.path.into_iter().map(|point| point.adjust_precision()));
+            }
+            None => {
+                follower.stop();
+                failed_writer.write(PathFailed {
+                    entity: request.entity,
+                    destination: request.destination,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Wading through shallow water.
+//!
+//! Character controllers with a [`Wading`] component are slowed down while partially
+//! submerged in a [`WaterVolume`](crate::WaterVolume), below the depth at which they start
+//! swimming. Movement acceleration and jump impulse are scaled by the submersion depth,
+//! so wading through knee-deep water feels heavy. A [`WadingSplash`] message is written
+//! whenever the depth crosses one of the splash thresholds.
+
+use avian3d::math::*;
+use bevy::prelude::*;
+
+use crate::{CharacterController, ControllerEnabled, SpeedModifier, SpeedModifiers, Submerged};
+
+/// The tag of the [`SpeedModifier`] applied while wading.
+pub const WADING_SPEED_MODIFIER: &str = "wading";
+
+/// Slows down a character controller in shallow water.
+#[derive(Component, Clone, Debug)]
+#[require(SpeedModifiers, WadingState)]
+pub struct Wading {
+    /// The submerged fraction of the character at which it starts swimming
+    /// instead of wading. No wading slowdown is applied above it.
+    pub swim_fraction: Scalar,
+    /// The fraction of movement acceleration lost at the swim depth.
+    pub max_speed_penalty: Scalar,
+    /// The fraction of jump impulse lost at the swim depth.
+    pub max_jump_penalty: Scalar,
+    /// Submerged fractions at which [`WadingSplash`] messages are written, in ascending order.
+    pub splash_thresholds: Vec<Scalar>,
+}
+
+impl Default for Wading {
+    fn default() -> Self {
+        Self {
+            swim_fraction: 0.6,
+            max_speed_penalty: 0.6,
+            max_jump_penalty: 0.5,
+            splash_thresholds: vec![0.1, 0.3, 0.5],
+        }
+    }
+}
+
+/// The current wading state of a [`Wading`] character.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct WadingState {
+    /// The factor the jump impulse is multiplied by.
+    pub jump_multiplier: Scalar,
+    /// The number of splash thresholds below the current submerged fraction.
+    level: usize,
+}
+
+impl Default for WadingState {
+    fn default() -> Self {
+        Self {
+            jump_multiplier: 1.0,
+            level: 0,
+        }
+    }
+}
+
+/// A [`Message`] written when a wading character crosses a splash threshold.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct WadingSplash {
+    pub entity: Entity,
+    /// The water volume entity, or `None` if the character is no longer submerged.
+    pub water: Option<Entity>,
+    /// The threshold that was crossed.
+    pub threshold: Scalar,
+    /// `true` if the character went deeper, `false` if it got shallower.
+    pub entering: bool,
+}
+
+/// Updates the speed and jump penalties of [`Wading`] characters from their submersion,
+/// and writes [`WadingSplash`] messages at depth thresholds.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_wading(
+    mut splash_writer: MessageWriter<WadingSplash>,
+    mut query: Query<
+        (
+            Entity,
+            &Wading,
+            &mut WadingState,
+            &mut SpeedModifiers,
+            Option<&Submerged>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    for (entity, wading, mut state, mut speed_modifiers, submerged) in &mut query {
+        let fraction = submerged.map_or(0.0, |submerged| submerged.fraction);
+
+        // Write a splash for every threshold crossed since the last frame.
+        let level = wading
+            .splash_thresholds
+            .iter()
+            .filter(|&&threshold| fraction >= threshold)
+            .count();
+        if level != state.level {
+            let entering = level > state.level;
+            let crossed = if entering {
+                state.level..level
+            } else {
+                level..state.level
+            };
+            for index in crossed {
+                splash_writer.write(WadingSplash {
+                    entity,
+                    water: submerged.map(|submerged| submerged.water),
+                    threshold: wading.splash_thresholds[index],
+                    entering,
+                });
+            }
+            state.level = level;
+        }
+
+        // Only wade below the swim depth.
+        if fraction <= 0.0 || fraction >= wading.swim_fraction {
+            if speed_modifiers.contains(WADING_SPEED_MODIFIER) {
+                speed_modifiers.remove(WADING_SPEED_MODIFIER);
+            }
+            state.jump_multiplier = 1.0;
+            continue;
+        }
+
+        let depth = fraction / wading.swim_fraction.max(Scalar::EPSILON);
+        speed_modifiers.insert(
+            WADING_SPEED_MODIFIER,
+            SpeedModifier::multiplier(1.0 - wading.max_speed_penalty * depth),
+        );
+        state.jump_multiplier = 1.0 - wading.max_jump_penalty * depth;
+    }
+}
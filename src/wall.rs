@@ -0,0 +1,131 @@
+//! Wall detection for character controllers.
+//!
+//! Lateral shape casts maintain a [`WallContact`] component while a character is next to a wall.
+//! Wall movement abilities build on it, and gameplay code can use it for things like cover systems.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{CharacterController, ControllerEnabled, ControllerUp, WorldUp};
+
+/// Enables wall detection for a character controller.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct WallDetection {
+    /// The maximum distance from the character at which walls are detected.
+    pub max_distance: Scalar,
+    /// The number of horizontal directions cast around the character.
+    pub directions: u32,
+    /// The minimum angle between a surface normal and the up axis
+    /// for the surface to count as a wall.
+    pub min_wall_angle: Scalar,
+    /// The scale of the cast shape relative to the collider.
+    /// Smaller than the collider so that the ground isn't detected as a wall.
+    pub shrink_factor: Scalar,
+}
+
+impl Default for WallDetection {
+    fn default() -> Self {
+        Self {
+            max_distance: 0.15,
+            directions: 8,
+            min_wall_angle: PI * 0.4,
+            shrink_factor: 0.9,
+        }
+    }
+}
+
+/// A component describing the nearest wall next to a character controller.
+///
+/// Present only while a wall is within [`WallDetection::max_distance`].
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct WallContact {
+    /// The surface normal of the wall, pointing toward the character.
+    pub normal: Vector,
+    /// The entity of the wall collider.
+    pub entity: Entity,
+    /// The distance between the character and the wall.
+    pub distance: Scalar,
+}
+
+/// Updates the [`WallContact`] of character controllers.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_wall_contact(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    world_up: Res<WorldUp>,
+    controllers: Query<
+        (
+            Entity,
+            &WallDetection,
+            &Collider,
+            &Position,
+            &Rotation,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    for (entity, detection, collider, position, rotation, controller_up) in &controllers {
+        let up = world_up.for_controller(controller_up);
+        let mut caster_shape = collider.clone();
+        caster_shape.set_scale(Vector::ONE * detection.shrink_factor, 10);
+
+        // Account for the shrunken shape so that walls are detected at the configured distance
+        // from the actual collider.
+        let shrink_offset = collider.aabb(Vector::ZERO, Quaternion::IDENTITY).size().x
+            * 0.5
+            * (1.0 - detection.shrink_factor);
+
+        let config = ShapeCastConfig {
+            ignore_origin_penetration: true,
+            ..ShapeCastConfig::from_max_distance(detection.max_distance + shrink_offset)
+        };
+        let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+
+        let mut nearest: Option<WallContact> = None;
+        let directions = detection.directions.max(1);
+
+        for i in 0..directions {
+            let angle = TAU * i as Scalar / directions as Scalar;
+            let local_direction = Vector::new(angle.sin(), 0.0, -angle.cos());
+            let Ok(direction) = Dir3::new((rotation * local_direction).f32()) else {
+                continue;
+            };
+
+            let Some(hit) = spatial_query.cast_shape(
+                &caster_shape,
+                position.0,
+                rotation.0,
+                direction,
+                &config,
+                &filter,
+            ) else {
+                continue;
+            };
+
+            let normal = rotation * -hit.normal2;
+            if normal.angle_between(up).abs() < detection.min_wall_angle {
+                continue;
+            }
+
+            let distance = (hit.distance - shrink_offset).max(0.0);
+            if nearest.is_none_or(|nearest| distance < nearest.distance) {
+                nearest = Some(WallContact {
+                    normal,
+                    entity: hit.entity,
+                    distance,
+                });
+            }
+        }
+
+        match nearest {
+            Some(contact) => {
+                commands.entity(entity).insert(contact);
+            }
+            None => {
+                commands.entity(entity).remove::<WallContact>();
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Wall sliding and wall jumping for character controllers.
+//!
+//! A [`WallSlide`] character that is airborne, falling, and moving into a wall it is in
+//! [`WallContact`] with starts [`WallSliding`]: gravity is reduced and the fall speed is
+//! capped. Jumping while sliding kicks the character off the wall. Letting go of the wall
+//! keeps the grip for a short while before the character falls freely again.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    input_rotation, CharacterController, ControllerEnabled, ControllerGravity, ControllerUp,
+    FpsController, GatheredMovementInput, Grounded, Jumped, MovementInput, ViewYaw, WallContact,
+    WorldUp,
+};
+
+/// Enables wall sliding and wall jumping for a character controller.
+///
+/// Walls are found with [`WallDetection`](crate::WallDetection), which is needed as well.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct WallSlide {
+    /// The maximum fall speed while sliding, in m/s.
+    pub max_slide_speed: Scalar,
+    /// The fraction of gravity applied while sliding.
+    pub gravity_scale: Scalar,
+    /// How long the character keeps sliding after the movement input stops pushing into the
+    /// wall, in seconds.
+    pub grip_duration: Scalar,
+    /// The speed away from the wall of a wall jump.
+    pub jump_away_speed: Scalar,
+    /// The upward speed of a wall jump.
+    pub jump_up_speed: Scalar,
+}
+
+impl Default for WallSlide {
+    fn default() -> Self {
+        Self {
+            max_slide_speed: 2.0,
+            gravity_scale: 0.3,
+            grip_duration: 0.2,
+            jump_away_speed: 6.0,
+            jump_up_speed: 7.0,
+        }
+    }
+}
+
+/// A component on a character controller that is sliding down a wall.
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct WallSliding {
+    /// The surface normal of the wall, pointing toward the character.
+    pub normal: Vector,
+    /// The entity of the wall collider.
+    pub wall: Entity,
+    /// The time since the movement input last pushed into the wall, in seconds.
+    pub released_time: Scalar,
+}
+
+/// Starts, applies, and ends wall slides, and performs wall jumps.
+#[allow(clippy::type_complexity)]
+pub(crate) fn wall_slide(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    gathered: Res<GatheredMovementInput>,
+    mut controllers: Query<
+        (
+            Entity,
+            &WallSlide,
+            Option<&mut WallSliding>,
+            Option<&WallContact>,
+            &Rotation,
+            &mut LinearVelocity,
+            &ControllerGravity,
+            Has<Grounded>,
+            Option<&FpsController>,
+            Option<&ViewYaw>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (
+        entity,
+        slide,
+        sliding,
+        wall_contact,
+        rotation,
+        mut linear_velocity,
+        gravity,
+        is_grounded,
+        fps_controller,
+        view_yaw,
+        controller_up,
+    ) in &mut controllers
+    {
+        let up = world_up.for_controller(controller_up);
+        let MovementInput {
+            direction: input,
+            jump,
+            ..
+        } = gathered.get(entity, fps_controller);
+        let input_direction = (input_rotation(rotation, view_yaw)
+            * Vector::new(input.x, 0.0, -input.y))
+        .reject_from_normalized(up);
+
+        let falling = linear_velocity.dot(up) < 0.0;
+        let pushing = wall_contact
+            .is_some_and(|contact| input_direction.dot(contact.normal) < -Scalar::EPSILON);
+
+        let Some(mut sliding) = sliding else {
+            // Start sliding when falling into a wall.
+            if let Some(contact) = wall_contact.filter(|_| !is_grounded && falling && pushing) {
+                commands.entity(entity).insert(WallSliding {
+                    normal: contact.normal,
+                    wall: contact.entity,
+                    released_time: 0.0,
+                });
+            }
+            continue;
+        };
+
+        if let Some(contact) = wall_contact {
+            sliding.normal = contact.normal;
+            sliding.wall = contact.entity;
+        }
+
+        if jump {
+            // Kick off the wall.
+            let away = sliding
+                .normal
+                .reject_from_normalized(up)
+                .normalize_or_zero();
+            linear_velocity.0 = linear_velocity.reject_from_normalized(up).reject_from(away)
+                + away * slide.jump_away_speed
+                + up * slide.jump_up_speed;
+            commands.entity(entity).remove::<WallSliding>();
+            commands.trigger(Jumped { entity });
+            continue;
+        }
+
+        sliding.released_time = if pushing {
+            0.0
+        } else {
+            sliding.released_time + delta_time
+        };
+
+        // Stop sliding on landing, when the wall ends, or when the grip runs out.
+        if is_grounded || wall_contact.is_none() || sliding.released_time > slide.grip_duration {
+            commands.entity(entity).remove::<WallSliding>();
+            continue;
+        }
+
+        // Gravity was already applied in full, so take back the part that doesn't apply.
+        linear_velocity.0 -= gravity.0 * (1.0 - slide.gravity_scale) * delta_time;
+
+        let fall_speed = -linear_velocity.dot(up);
+        if fall_speed > slide.max_slide_speed {
+            linear_velocity.0 += up * (fall_speed - slide.max_slide_speed);
+        }
+    }
+}
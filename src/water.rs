@@ -0,0 +1,187 @@
+//! Water volumes and buoyancy.
+//!
+//! A [`WaterVolume`] is a sensor collider with a surface height. Bodies overlapping it
+//! get a [`Submerged`] component describing how deep they are, and buoyancy pushes them
+//! up proportionally to their submersion. This is the physical foundation for swimming,
+//! and for objects the character carries into water.
+
+use avian3d::{math::*, prelude::*};
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::{CharacterController, ControllerGravity, ControllerUp, Ragdoll, WorldUp};
+
+/// A component for a body of water. Should be added to a [`Sensor`] collider
+/// that covers the water.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(Sensor)]
+pub struct WaterVolume {
+    /// The height of the water surface along the up direction, see [`WorldUp`].
+    ///
+    /// Character controllers measure it along their [`ControllerUp`] instead.
+    pub surface_height: Scalar,
+    /// The density of the water, used for the buoyancy of dynamic bodies.
+    pub density: Scalar,
+    /// How quickly submerged bodies are slowed down.
+    pub drag: Scalar,
+}
+
+impl Default for WaterVolume {
+    fn default() -> Self {
+        Self {
+            surface_height: 0.0,
+            density: 1.0,
+            drag: 2.0,
+        }
+    }
+}
+
+impl WaterVolume {
+    pub fn new(surface_height: Scalar) -> Self {
+        Self {
+            surface_height,
+            ..default()
+        }
+    }
+}
+
+/// A component describing how deep a dynamic body or character controller is in a [`WaterVolume`].
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Submerged {
+    /// The water volume entity.
+    pub water: Entity,
+    /// The depth of the lowest point of the body below the water surface.
+    pub depth: Scalar,
+    /// The submerged fraction of the body height, from 0 to 1.
+    pub fraction: Scalar,
+    /// The approximate submerged volume of the body, based on its bounding box.
+    pub volume: Scalar,
+}
+
+/// The buoyancy of a character controller, as a fraction of its gravity.
+///
+/// At `1.0`, a fully submerged character floats neutrally. Character controllers
+/// without this component are not affected by buoyancy.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Buoyancy(pub Scalar);
+
+impl Default for Buoyancy {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Updates the [`Submerged`] state of dynamic bodies and character controllers
+/// overlapping [`WaterVolume`]s.
+pub(crate) fn update_submersion(
+    mut commands: Commands,
+    world_up: Res<WorldUp>,
+    spatial_query: SpatialQuery,
+    waters: Query<(Entity, &WaterVolume, &Collider, &Position, &Rotation)>,
+    colliders: Query<(&ColliderOf, &ColliderAabb), Without<Sensor>>,
+    bodies: Query<(&RigidBody, Has<CharacterController>, Option<&ControllerUp>)>,
+    submerged: Query<Entity, With<Submerged>>,
+) {
+    let mut submersions = HashMap::<Entity, Submerged>::default();
+
+    for (water_entity, water, collider, position, rotation) in &waters {
+        let intersections = spatial_query.shape_intersections(
+            collider,
+            position.0,
+            rotation.0,
+            &SpatialQueryFilter::from_excluded_entities([water_entity]),
+        );
+
+        for collider_entity in intersections {
+            let Ok((&ColliderOf { body }, aabb)) = colliders.get(collider_entity) else {
+                continue;
+            };
+            let Ok((rb, is_character, controller_up)) = bodies.get(body) else {
+                continue;
+            };
+            if !rb.is_dynamic() && !is_character {
+                continue;
+            }
+
+            let up = world_up.for_controller(controller_up);
+            let size = aabb.max - aabb.min;
+            let height = size.dot(up.abs());
+            let bottom = ((aabb.min + aabb.max) * 0.5).dot(up) - height * 0.5;
+            let depth = water.surface_height - bottom;
+            if depth <= 0.0 || height <= 0.0 {
+                continue;
+            }
+            let fraction = (depth / height).min(1.0);
+
+            // Bodies in several volumes (or with several colliders) use the deepest submersion.
+            let submersion = Submerged {
+                water: water_entity,
+                depth,
+                fraction,
+                volume: size.x * size.y * size.z * fraction,
+            };
+            submersions
+                .entry(body)
+                .and_modify(|existing| {
+                    if submersion.fraction > existing.fraction {
+                        *existing = submersion;
+                    }
+                })
+                .or_insert(submersion);
+        }
+    }
+
+    for entity in &submerged {
+        if !submersions.contains_key(&entity) {
+            commands.entity(entity).remove::<Submerged>();
+        }
+    }
+
+    for (entity, submersion) in submersions {
+        commands.entity(entity).insert(submersion);
+    }
+}
+
+/// Applies buoyancy and water drag to submerged bodies.
+///
+/// Dynamic bodies use Archimedes' principle with the water density and their mass.
+/// Character controllers use their [`Buoyancy`] relative to their [`ControllerGravity`].
+#[allow(clippy::type_complexity)]
+pub(crate) fn apply_buoyancy(
+    time: Res<Time<Virtual>>,
+    gravity: Res<Gravity>,
+    waters: Query<&WaterVolume>,
+    mut bodies: Query<
+        (
+            &Submerged,
+            &RigidBody,
+            &mut LinearVelocity,
+            Option<&ComputedMass>,
+            Option<&ControllerGravity>,
+            Option<&Buoyancy>,
+        ),
+        Without<Ragdoll>,
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for (submerged, rb, mut linear_velocity, mass, controller_gravity, buoyancy) in &mut bodies {
+        let Ok(water) = waters.get(submerged.water) else {
+            continue;
+        };
+
+        let acceleration = match (controller_gravity, buoyancy) {
+            (Some(controller_gravity), Some(buoyancy)) => {
+                -controller_gravity.0 * buoyancy.0 * submerged.fraction
+            }
+            (Some(_), None) => continue,
+            (None, _) if rb.is_dynamic() => {
+                let inverse_mass = mass.map_or(0.0, |mass| mass.inverse());
+                -gravity.0 * water.density * submerged.volume * inverse_mass
+            }
+            (None, _) => continue,
+        };
+
+        linear_velocity.0 += acceleration * delta_time;
+        linear_velocity.0 /= 1.0 + water.drag * submerged.fraction * delta_time;
+    }
+}
@@ -0,0 +1,257 @@
+//! Climbing out of water at low edges, like banks and pool ledges.
+//!
+//! A swimming character that moves against an edge only slightly above the water would
+//! otherwise bump against it endlessly. With [`WaterClimbOut`], the edge is checked for a
+//! walkable lip with room to stand on it, and the character mantles onto it in a short
+//! scripted motion while it is [`ClimbingOutOfWater`].
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    input_rotation, is_free_position, CharacterController, ControllerEnabled, ControllerUp,
+    DirectedMovementAction, FpsController, MaxSlopeAngle, MovementAction, Submerged, ViewYaw,
+    WalkableSurfaces, WaterVolume, WorldUp,
+};
+
+/// Lets a character controller climb out of water onto low edges.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct WaterClimbOut {
+    /// How far ahead of the edge of the collider edges are detected.
+    pub check_distance: Scalar,
+    /// The maximum height of the lip above the water surface.
+    pub max_lip_height: Scalar,
+    /// The minimum submerged fraction of the character for climbing out.
+    pub min_submersion: Scalar,
+    /// How long the climb takes, in seconds.
+    pub duration: Scalar,
+}
+
+impl Default for WaterClimbOut {
+    fn default() -> Self {
+        Self {
+            check_distance: 0.4,
+            max_lip_height: 0.9,
+            min_submersion: 0.3,
+            duration: 0.45,
+        }
+    }
+}
+
+/// A component on a character controller that is climbing out of water.
+///
+/// Movement, gravity, and buoyancy are suspended while this is present.
+#[derive(Component, Clone, Copy, Debug)]
+#[component(storage = "SparseSet")]
+pub struct ClimbingOutOfWater {
+    /// The position where the climb started.
+    pub start: Vector,
+    /// The position on the lip where the climb ends.
+    pub end: Vector,
+    /// The time since the climb started, in seconds.
+    pub elapsed: Scalar,
+    /// The duration of the climb, in seconds.
+    pub duration: Scalar,
+}
+
+impl ClimbingOutOfWater {
+    /// Returns the progress of the climb, from 0 to 1.
+    pub fn progress(&self) -> Scalar {
+        (self.elapsed / self.duration.max(Scalar::EPSILON)).min(1.0)
+    }
+
+    /// Returns the position along the climb at the given progress: up first, then forward
+    /// onto the lip, along the `up` direction of the character.
+    fn position_at(&self, progress: Scalar, up: Vector) -> Vector {
+        let smooth = |t: Scalar| {
+            let t = t.clamp(0.0, 1.0);
+            t * t * (3.0 - 2.0 * t)
+        };
+        let offset = self.end - self.start;
+        let rise = offset.dot(up) * smooth(progress / 0.6);
+        let forward = offset.reject_from_normalized(up) * smooth((progress - 0.5) / 0.5);
+        self.start + up * rise + forward
+    }
+}
+
+/// Triggered on a [`WaterClimbOut`] character controller when it starts climbing out of
+/// water, for example to play a mantle animation.
+#[derive(EntityEvent, Clone, Copy, Debug)]
+pub struct WaterClimbStarted {
+    pub entity: Entity,
+    /// The position on the lip where the climb ends.
+    pub end: Vector,
+}
+
+/// Starts climbing out of water when swimming [`WaterClimbOut`] characters move against a
+/// low edge with a walkable lip.
+#[allow(clippy::type_complexity)]
+pub(crate) fn start_water_climb(
+    mut commands: Commands,
+    world_up: Res<WorldUp>,
+    spatial_query: SpatialQuery,
+    walkable: WalkableSurfaces,
+    mut movement_reader: MessageReader<MovementAction>,
+    mut directed_reader: MessageReader<DirectedMovementAction>,
+    controllers: Query<
+        (
+            Entity,
+            &WaterClimbOut,
+            &Submerged,
+            &Collider,
+            &Position,
+            &Rotation,
+            &ColliderAabb,
+            Option<&MaxSlopeAngle>,
+            Option<&FpsController>,
+            Option<&ViewYaw>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+    waters: Query<&WaterVolume>,
+    sensors: Query<(), With<Sensor>>,
+) {
+    let global_input: Vector2 = movement_reader
+        .read()
+        .filter_map(|action| match action {
+            MovementAction::Move(direction) => Some(*direction),
+            _ => None,
+        })
+        .sum();
+    let directed_input: Vec<(Entity, Vector2)> = directed_reader
+        .read()
+        .filter_map(|directed| match directed.action {
+            MovementAction::Move(direction) => Some((directed.entity, direction)),
+            _ => None,
+        })
+        .collect();
+
+    for (
+        entity,
+        climb,
+        submerged,
+        collider,
+        position,
+        rotation,
+        aabb,
+        max_slope_angle,
+        fps_controller,
+        view_yaw,
+        controller_up,
+    ) in &controllers
+    {
+        if submerged.fraction < climb.min_submersion {
+            continue;
+        }
+        let Ok(water) = waters.get(submerged.water) else {
+            continue;
+        };
+
+        let mut input: Vector2 = directed_input
+            .iter()
+            .filter(|(target, _)| *target == entity)
+            .map(|(_, direction)| *direction)
+            .sum();
+        if fps_controller.is_some_and(|controller| controller.enable_input) {
+            input += global_input;
+        }
+        let up = world_up.for_controller(controller_up);
+        let Ok(direction) = Dir3::new(
+            (input_rotation(rotation, view_yaw) * Vector::new(input.x, 0.0, -input.y))
+                .reject_from_normalized(up)
+                .f32(),
+        ) else {
+            continue;
+        };
+        let Ok(down) = Dir3::new(-up.f32()) else {
+            continue;
+        };
+        let horizontal = direction.as_vec3().adjust_precision();
+        // Moves a point along the up direction to the given height.
+        let at_height = |point: Vector, height: Scalar| point + up * (height - point.dot(up));
+
+        // A steep edge blocks the way at the water surface...
+        let half_extents = (aabb.max - aabb.min) * 0.5;
+        let radius = half_extents.reject_from_normalized(up.abs()).max_element();
+        let distance = radius + climb.check_distance;
+        let filter = SpatialQueryFilter::from_excluded_entities([entity]);
+        let surface = at_height(position.0, water.surface_height - 0.1);
+        let Some(edge) = spatial_query.cast_ray(surface, direction, distance, true, &filter) else {
+            continue;
+        };
+        let max_slope = max_slope_angle.map_or(PI * 0.25, |angle| angle.0);
+        if edge.normal.angle_between(up) <= max_slope {
+            continue;
+        }
+
+        // ...with a walkable lip low enough to climb onto just behind it...
+        let lip_origin = at_height(
+            surface + horizontal * (edge.distance + radius),
+            water.surface_height + climb.max_lip_height,
+        );
+        let Some(lip) =
+            spatial_query.cast_ray(lip_origin, down, climb.max_lip_height + 0.2, true, &filter)
+        else {
+            continue;
+        };
+        // Slopes are measured against the up direction of the controller
+        let align = controller_up.map_or(Quaternion::IDENTITY, |controller_up| {
+            Quaternion::from_rotation_arc(controller_up.0, world_up.0)
+        });
+        if !walkable.is_walkable(lip.entity, align * lip.normal, max_slope_angle, None) {
+            continue;
+        }
+
+        // ...and room to stand on it.
+        let lip_height = lip_origin.dot(up) - lip.distance;
+        let half_height = half_extents.dot(up.abs());
+        let end = at_height(lip_origin, lip_height + half_height + 0.05);
+        if !is_free_position(&spatial_query, &sensors, collider, end, rotation.0, entity) {
+            continue;
+        }
+
+        commands.entity(entity).insert(ClimbingOutOfWater {
+            start: position.0,
+            end,
+            elapsed: 0.0,
+            duration: climb.duration,
+        });
+        commands.trigger(WaterClimbStarted { entity, end });
+    }
+}
+
+/// Moves [`ClimbingOutOfWater`] characters along their climb, and ends it on the lip.
+pub(crate) fn climb_out_of_water(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    mut controllers: Query<
+        (
+            Entity,
+            &mut ClimbingOutOfWater,
+            &Position,
+            &mut LinearVelocity,
+            Option<&ControllerUp>,
+        ),
+        With<CharacterController>,
+    >,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+    if delta_time <= 0.0 {
+        return;
+    }
+
+    for (entity, mut climb, position, mut linear_velocity, controller_up) in &mut controllers {
+        climb.elapsed += delta_time;
+        let progress = climb.progress();
+
+        // Move with velocity so the character still collides and interpolates.
+        let target = climb.position_at(progress, world_up.for_controller(controller_up));
+        linear_velocity.0 = (target - position.0) / delta_time;
+
+        if progress >= 1.0 {
+            commands.entity(entity).remove::<ClimbingOutOfWater>();
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! Locomotion for VR/XR character controllers.
+//!
+//! The XR runtime integration, like `bevy_mod_openxr`, writes an [`XrInput`] message every
+//! frame with the controller sticks and the teleport aim of a character with
+//! [`XrLocomotion`]. The movement stick moves the character relative to where the head
+//! looks, the turn stick snap turns or smoothly turns the body, and aiming shows a
+//! teleport arc that is validated against walkable ground like the grounded checks, and
+//! teleports the character when the aim is released.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    input_rotation, CharacterController, ControllerEnabled, ControllerGravity, ControllerUp,
+    DirectedMovementAction, MaxSlopeAngle, MovementAction, TeleportCharacter, ViewYaw,
+    WalkableSurfaces, WorldUp,
+};
+
+/// How an [`XrLocomotion`] character turns with the turn stick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum XrTurn {
+    /// Turns by a fixed angle in radians each time the stick is flicked, which is more
+    /// comfortable for most players.
+    Snap(Scalar),
+    /// Turns continuously at the given speed in radians per second.
+    Smooth(Scalar),
+}
+
+impl Default for XrTurn {
+    fn default() -> Self {
+        Self::Snap(PI / 4.0)
+    }
+}
+
+/// Enables XR locomotion for a character controller, driven by [`XrInput`].
+#[derive(Component, Clone, Copy, Debug)]
+#[require(XrLocomotionState)]
+pub struct XrLocomotion {
+    /// The entity tracking the head-mounted display, which movement is relative to.
+    pub head: Entity,
+    pub turn: XrTurn,
+    /// How far the turn stick has to be pushed to turn.
+    pub turn_deadzone: Scalar,
+    /// The launch speed of the teleport arc, in m/s, or `None` to disable teleports.
+    pub teleport_speed: Option<Scalar>,
+    /// How far ahead the teleport arc is traced, in seconds.
+    pub teleport_duration: Scalar,
+    /// The time step of the teleport arc, in seconds.
+    pub teleport_time_step: Scalar,
+}
+
+impl XrLocomotion {
+    /// Creates XR locomotion relative to the given head entity.
+    pub fn new(head: Entity) -> Self {
+        Self {
+            head,
+            turn: XrTurn::default(),
+            turn_deadzone: 0.6,
+            teleport_speed: Some(8.0),
+            teleport_duration: 2.0,
+            teleport_time_step: 1.0 / 30.0,
+        }
+    }
+}
+
+/// The turn and teleport state of an [`XrLocomotion`] character controller.
+#[derive(Component, Clone, Debug, Default)]
+pub struct XrLocomotionState {
+    /// Whether the turn stick returned to the center since the last snap turn.
+    turn_released: bool,
+    teleport_target: Option<Vector>,
+    teleport_arc: Vec<Vector>,
+}
+
+impl XrLocomotionState {
+    /// Returns the valid teleport destination on the ground while aiming, if any.
+    pub fn teleport_target(&self) -> Option<Vector> {
+        self.teleport_target
+    }
+
+    /// Returns the points of the teleport arc while aiming, for drawing it.
+    pub fn teleport_arc(&self) -> &[Vector] {
+        &self.teleport_arc
+    }
+}
+
+/// A [`Message`] with the XR controller input of an [`XrLocomotion`] character controller,
+/// written every frame by the XR runtime integration.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct XrInput {
+    pub entity: Entity,
+    /// The movement stick, with `y` pointing forward.
+    pub movement: Vector2,
+    /// The horizontal axis of the turn stick, positive to the right.
+    pub turn: Scalar,
+    /// The origin and direction of the teleport aim while it is held, usually the pose of
+    /// a hand controller.
+    pub teleport_aim: Option<(Vector, Vector)>,
+}
+
+/// Turns [`XrInput`] into head-relative movement, turns, and teleports.
+#[allow(clippy::type_complexity)]
+pub(crate) fn xr_locomotion(
+    time: Res<Time<Virtual>>,
+    world_up: Res<WorldUp>,
+    spatial_query: SpatialQuery,
+    walkable: WalkableSurfaces,
+    mut input_reader: MessageReader<XrInput>,
+    mut directed_writer: MessageWriter<DirectedMovementAction>,
+    mut teleport_writer: MessageWriter<TeleportCharacter>,
+    mut controllers: Query<
+        (
+            &XrLocomotion,
+            &mut XrLocomotionState,
+            &mut Rotation,
+            &ColliderAabb,
+            &ControllerGravity,
+            Option<&mut ViewYaw>,
+            Option<&MaxSlopeAngle>,
+            Option<&ControllerUp>,
+        ),
+        (With<CharacterController>, ControllerEnabled),
+    >,
+    heads: Query<&GlobalTransform>,
+) {
+    let delta_time = time.delta_secs_f64().adjust_precision();
+
+    for input in input_reader.read() {
+        let Ok((
+            locomotion,
+            mut state,
+            mut rotation,
+            aabb,
+            gravity,
+            mut view_yaw,
+            max_slope_angle,
+            controller_up,
+        )) = controllers.get_mut(input.entity)
+        else {
+            continue;
+        };
+        let up = world_up.for_controller(controller_up);
+
+        // Movement is relative to the head, so rotate the stick by the yaw of the head
+        // relative to the movement basis of the body.
+        if input.movement != Vector2::ZERO {
+            let body_forward = (input_rotation(&rotation, view_yaw.as_deref()) * Vector::NEG_Z)
+                .reject_from_normalized(up);
+            let angle = heads.get(locomotion.head).map_or(0.0, |head| {
+                let head_forward =
+                    (head.rotation().adjust_precision() * Vector::NEG_Z).reject_from_normalized(up);
+                body_forward
+                    .cross(head_forward)
+                    .dot(up)
+                    .atan2(body_forward.dot(head_forward))
+            });
+            directed_writer.write(DirectedMovementAction {
+                entity: input.entity,
+                action: MovementAction::Move(
+                    Vector2::from_angle(angle)
+                        .rotate(input.movement)
+                        .clamp_length_max(1.0),
+                ),
+            });
+        }
+
+        // Turns are to the left around the up axis for negative stick values
+        let turn = match locomotion.turn {
+            XrTurn::Snap(angle) => {
+                if input.turn.abs() < locomotion.turn_deadzone * 0.5 {
+                    state.turn_released = true;
+                }
+                if state.turn_released && input.turn.abs() >= locomotion.turn_deadzone {
+                    state.turn_released = false;
+                    -angle * input.turn.signum()
+                } else {
+                    0.0
+                }
+            }
+            XrTurn::Smooth(speed) if input.turn.abs() >= locomotion.turn_deadzone => {
+                -speed * input.turn * delta_time
+            }
+            XrTurn::Smooth(_) => 0.0,
+        };
+        if turn != 0.0 {
+            let turn = Quaternion::from_axis_angle(up, turn);
+            rotation.0 = (turn * rotation.0).normalize();
+            if let Some(view_yaw) = view_yaw.as_mut() {
+                view_yaw.0 = (turn * view_yaw.0).normalize();
+            }
+        }
+
+        let Some(teleport_speed) = locomotion.teleport_speed else {
+            continue;
+        };
+        let Some((origin, direction)) = input.teleport_aim else {
+            // Releasing the aim teleports to the last valid target, standing on the ground
+            state.teleport_arc.clear();
+            if let Some(target) = state.teleport_target.take() {
+                let half_height = ((aabb.max - aabb.min) * 0.5).dot(up.abs());
+                teleport_writer.write(TeleportCharacter {
+                    entity: input.entity,
+                    position: target + up * (half_height + 0.05),
+                });
+            }
+            continue;
+        };
+
+        // Trace the arc of a projectile under the gravity of the character
+        let filter = SpatialQueryFilter::from_excluded_entities([input.entity]);
+        let mut point = origin;
+        let mut velocity = direction.normalize_or_zero() * teleport_speed;
+        let steps = (locomotion.teleport_duration / locomotion.teleport_time_step.max(0.001)).ceil()
+            as usize;
+        state.teleport_arc.clear();
+        state.teleport_arc.push(point);
+        state.teleport_target = None;
+
+        // Slopes are measured against the up direction of the controller
+        let align = controller_up.map_or(Quaternion::IDENTITY, |controller_up| {
+            Quaternion::from_rotation_arc(controller_up.0, world_up.0)
+        });
+
+        for _ in 0..steps {
+            velocity += gravity.0 * locomotion.teleport_time_step;
+            let step = velocity * locomotion.teleport_time_step;
+            let Ok((step_direction, distance)) = Dir3::new_and_length(step.f32()) else {
+                continue;
+            };
+
+            if let Some(hit) = spatial_query.cast_ray(
+                point,
+                step_direction,
+                distance.adjust_precision(),
+                true,
+                &filter,
+            ) {
+                point += step.normalize() * hit.distance;
+                state.teleport_arc.push(point);
+                if walkable.is_walkable(hit.entity, align * hit.normal, max_slope_angle, None) {
+                    state.teleport_target = Some(point);
+                }
+                break;
+            }
+
+            point += step;
+            state.teleport_arc.push(point);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Integration tests for recording and comparing golden trajectories.
+//!
+//! Run with `cargo test --features test_utils`.
+
+use std::path::PathBuf;
+
+use avian3d::math::*;
+use bevy::prelude::*;
+use bevy_movement::{prelude::*, test_utils::*};
+
+/// The distance per tick that recorded trajectories may differ from their golden files.
+const TOLERANCE: Scalar = 1e-3;
+
+/// Returns a path for a golden file in a temporary directory, removing any previous file.
+fn temp_golden(name: &str) -> PathBuf {
+    let path = std::env::temp_dir()
+        .join(format!("bevy_movement_golden_{}", std::process::id()))
+        .join(format!("{name}.txt"));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// Walks forward, jumps, and turns to strafe, covering ground and air movement.
+fn scripted_input() -> SimulatedInput {
+    let mut input = SimulatedInput::default();
+    input
+        .push_ticks([MovementAction::Move(Vector2::Y)], 30)
+        .push_tick([MovementAction::Move(Vector2::Y), MovementAction::Jump])
+        .push_ticks([MovementAction::Move(Vector2::Y)], 40)
+        .push_ticks([MovementAction::Move(Vector2::X)], 30)
+        .wait(30);
+    input
+}
+
+/// Records the trajectory of a controller for the scripted input in a fresh app.
+fn record_scripted_trajectory() -> Trajectory {
+    let mut app = headless_movement_app();
+    app.spawn_test_ground();
+    let player = app.spawn_test_controller(Vector::new(0.0, 1.5, 0.0));
+    app.tick(60);
+
+    record_trajectory(&mut app, player, scripted_input())
+}
+
+#[test]
+fn records_one_position_per_tick() {
+    let trajectory = record_scripted_trajectory();
+
+    assert_eq!(trajectory.positions.len(), scripted_input().len());
+    let (first, last) = (
+        trajectory.positions[0],
+        *trajectory.positions.last().unwrap(),
+    );
+    assert!(
+        last.z < first.z - 1.0 && last.x > first.x + 1.0,
+        "expected the controller to walk forward and strafe from {first}, but it is at {last}"
+    );
+}
+
+#[test]
+fn recorded_trajectory_matches_golden() {
+    let path = temp_golden("walk_jump_strafe");
+
+    // The first run records the golden file, and the second compares against it.
+    assert_matches_golden(&record_scripted_trajectory(), &path, TOLERANCE);
+    assert!(path.exists(), "expected {} to be recorded", path.display());
+    assert_matches_golden(&record_scripted_trajectory(), &path, TOLERANCE);
+
+    let golden = Trajectory::load(&path).unwrap();
+    assert_eq!(golden.positions.len(), scripted_input().len());
+}
+
+#[test]
+fn changed_trajectory_diverges_from_golden() {
+    let path = temp_golden("diverged");
+    record_scripted_trajectory().save(&path).unwrap();
+    let golden = Trajectory::load(&path).unwrap();
+
+    let mut changed = golden.clone();
+    changed.positions[90].x += 0.5;
+    assert_eq!(
+        changed
+            .first_divergence(&golden, TOLERANCE)
+            .map(|(tick, _)| tick),
+        Some(90)
+    );
+}
+
+#[test]
+fn trajectory_text_round_trips() {
+    let trajectory = record_scripted_trajectory();
+    let parsed = Trajectory::from_text(&trajectory.to_text()).unwrap();
+
+    assert_eq!(trajectory.first_divergence(&parsed, TOLERANCE), None);
+    assert_eq!(
+        trajectory.first_divergence(
+            &Trajectory {
+                positions: trajectory.positions[..10].to_vec(),
+            },
+            TOLERANCE,
+        ),
+        Some((10, Scalar::INFINITY))
+    );
+}
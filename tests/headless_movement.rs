@@ -0,0 +1,172 @@
+//! Integration tests for movement with the headless test harness.
+//!
+//! Run with `cargo test --features test_utils`.
+
+use avian3d::{math::*, prelude::*};
+use bevy::{
+    input::{mouse::MouseButtonInput, ButtonState},
+    prelude::*,
+};
+use bevy_movement::{prelude::*, test_utils::*};
+
+/// Creates an app with a controller that has settled on the ground.
+fn grounded_controller() -> (App, Entity) {
+    let mut app = headless_movement_app();
+    app.spawn_test_ground();
+    let player = app.spawn_test_controller(Vector::new(0.0, 1.5, 0.0));
+    app.tick(60);
+    (app, player)
+}
+
+#[test]
+fn controller_lands_on_ground() {
+    let (app, player) = grounded_controller();
+
+    assert_grounded(&app, player);
+    let position = position_of(&app, player);
+    assert!(
+        position.y > 0.0 && position.y < 1.5,
+        "expected the controller to rest on the ground, but it is at {position}"
+    );
+}
+
+#[test]
+fn controller_falls_without_ground() {
+    let mut app = headless_movement_app();
+    let player = app.spawn_test_controller(Vector::new(0.0, 10.0, 0.0));
+
+    let position = app.position_after(player, 30);
+    assert_airborne(&app, player);
+    assert!(
+        position.y < 10.0,
+        "expected the controller to fall, but it is at {position}"
+    );
+}
+
+#[test]
+fn move_input_walks_forward() {
+    let (mut app, player) = grounded_controller();
+    let start = position_of(&app, player);
+
+    app.simulated_input()
+        .push_ticks([MovementAction::Move(Vector2::Y)], 30);
+    let position = app.position_after(player, 30);
+
+    assert!(
+        position.z < start.z - 1.0,
+        "expected the controller to walk forward from {start}, but it is at {position}"
+    );
+    assert!(
+        (position.x - start.x).abs() < 0.1,
+        "expected the controller to walk straight from {start}, but it is at {position}"
+    );
+    assert_grounded(&app, player);
+}
+
+#[test]
+fn controller_stops_without_input() {
+    let (mut app, player) = grounded_controller();
+
+    app.simulated_input()
+        .push_ticks([MovementAction::Move(Vector2::X)], 30);
+    let stopped = app.position_after(player, 90);
+
+    assert_near(app.position_after(player, 10), stopped, 0.01);
+}
+
+#[test]
+fn jump_leaves_ground_and_lands() {
+    let (mut app, player) = grounded_controller();
+    let start = position_of(&app, player);
+
+    app.simulated_input().push_tick([MovementAction::Jump]);
+    let position = app.position_after(player, 10);
+    assert_airborne(&app, player);
+    assert!(
+        position.y > start.y + 0.1,
+        "expected the controller to jump from {start}, but it is at {position}"
+    );
+
+    app.tick(120);
+    assert_grounded(&app, player);
+    assert_near(position_of(&app, player), start, 0.05);
+}
+
+#[test]
+fn input_is_ignored_when_disabled() {
+    let (mut app, player) = grounded_controller();
+    app.world_mut()
+        .get_mut::<FpsController>(player)
+        .unwrap()
+        .enable_input = false;
+    let start = position_of(&app, player);
+
+    app.simulated_input()
+        .push_ticks([MovementAction::Move(Vector2::Y)], 30);
+    assert_near(app.position_after(player, 30), start, 0.01);
+}
+
+#[test]
+fn crouch_jump_drops_through_one_way_platform() {
+    let mut app = headless_movement_app();
+    app.spawn_test_ground();
+    app.world_mut().spawn((
+        RigidBody::Static,
+        Collider::cuboid(10.0, 0.2, 10.0),
+        Transform::from_xyz(0.0, 3.0, 0.0),
+        OneWayPlatform::default(),
+    ));
+    let player = app.spawn_test_controller(Vector::new(0.0, 4.5, 0.0));
+    app.world_mut()
+        .entity_mut(player)
+        .insert((CoyoteTime::default(), JumpBuffer::default()));
+    app.tick(60);
+    assert_grounded(&app, player);
+    let start = position_of(&app, player);
+
+    // Dropping through loses the ground, which must not allow a coyote jump.
+    app.simulated_input()
+        .push_tick([MovementAction::Crouch, MovementAction::Jump]);
+    for _ in 0..20 {
+        let position = app.position_after(player, 1);
+        assert!(
+            position.y < start.y + 0.01,
+            "expected the controller to drop from {start}, but it rose to {position}"
+        );
+    }
+
+    let position = position_of(&app, player);
+    assert!(
+        position.y < start.y - 0.5,
+        "expected the controller to drop through the platform from {start}, but it is at {position}"
+    );
+}
+
+#[test]
+fn grabbing_the_cursor_only_enables_the_possessed_controller() {
+    let (mut app, player) = grounded_controller();
+    let other = app.spawn_test_controller(Vector::new(3.0, 1.5, 0.0));
+    for entity in [player, other] {
+        app.world_mut()
+            .get_mut::<FpsController>(entity)
+            .unwrap()
+            .enable_input = false;
+    }
+    app.insert_resource(PossessedController(Some(other)));
+
+    app.world_mut().write_message(MouseButtonInput {
+        button: MouseButton::Right,
+        state: ButtonState::Pressed,
+        window: Entity::PLACEHOLDER,
+    });
+    app.tick(1);
+
+    let enabled = |app: &App, entity: Entity| {
+        app.world()
+            .get::<FpsController>(entity)
+            .unwrap()
+            .enable_input
+    };
+    assert!(enabled(&app, other));
+    assert!(!enabled(&app, player));
+}
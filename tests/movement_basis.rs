@@ -0,0 +1,54 @@
+//! Integration tests for custom movement bases and abilities.
+//!
+//! Run with `cargo test --features test_utils`.
+
+use avian3d::{math::*, prelude::*};
+use bevy::prelude::*;
+use bevy_movement::{prelude::*, test_utils::*};
+
+/// An ability that cancels all horizontal velocity, like a character rooted in place.
+#[derive(Component)]
+struct Rooted;
+
+impl MovementAbility for Rooted {
+    fn apply(&mut self, _input: &MovementInput, context: &mut MovementContext) {
+        context.velocity = context.velocity.project_onto_normalized(context.up);
+    }
+}
+
+#[test]
+fn abilities_run_after_built_in_movement() {
+    let mut app = headless_movement_app();
+    app.add_movement_ability::<Rooted>();
+    app.spawn_test_ground();
+    let player = app.spawn_test_controller(Vector::new(0.0, 1.5, 0.0));
+    app.world_mut().entity_mut(player).insert(Rooted);
+    app.tick(60);
+    let start = position_of(&app, player);
+
+    // The built-in walking accelerates the character, but the ability cancels it every frame.
+    app.simulated_input()
+        .push_ticks([MovementAction::Move(Vector2::Y)], 30);
+    assert_near(app.position_after(player, 30), start, 0.01);
+}
+
+#[test]
+fn abilities_see_the_controller_up() {
+    let mut app = headless_movement_app();
+    app.add_movement_ability::<Rooted>();
+    let player = app.spawn_test_controller(Vector::new(0.0, 10.0, 0.0));
+    app.world_mut().entity_mut(player).insert((
+        Rooted,
+        ControllerUp(Vector::X),
+        ControllerGravity(Vector::NEG_X),
+    ));
+    app.world_mut().get_mut::<LinearVelocity>(player).unwrap().0 = Vector::new(2.0, 3.0, 0.0);
+    app.tick(1);
+
+    // Only the velocity along the controller up is kept, so the upward velocity is removed.
+    let velocity = app.world().get::<LinearVelocity>(player).unwrap().0;
+    assert!(
+        velocity.y.abs() < 1e-3 && velocity.x > 0.0,
+        "expected only velocity along the controller up to be kept, but it is {velocity}"
+    );
+}